@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+
+/// Something the user can do with their gremlin, earned once and kept for good.
+/// A gremlin can hold animations and accessories back until one is earned, see `Gremlin::unlocks`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Achievement {
+    pub id: &'static str,
+    pub title: &'static str,
+    pub description: &'static str,
+}
+
+pub const PETTED: Achievement = Achievement {
+    id: "petted",
+    title: "Good gremlin",
+    description: "pet it 100 times",
+};
+pub const FULL_NIGHT: Achievement = Achievement {
+    id: "full_night",
+    title: "Sweet dreams",
+    description: "let it sleep through a whole night",
+};
+pub const ACHIEVEMENTS: &[Achievement] = &[PETTED, FULL_NIGHT];
+pub const PETS_FOR_PETTED: u32 = 100;
+
+pub fn find(id: &str) -> Option<&'static Achievement> {
+    ACHIEVEMENTS.iter().find(|achievement| achievement.id == id)
+}
+
+/// Progress towards the achievements and the ones earned so far, saved with the `AppState`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Achievements {
+    pub pets: u32,
+    // ids of the earned `ACHIEVEMENTS`, oldest first
+    pub earned: Vec<String>,
+}
+
+impl Achievements {
+    pub fn has(&self, id: &str) -> bool {
+        self.earned.iter().any(|earned| earned == id)
+    }
+
+    /// true when it wasn't earned before
+    pub fn earn(&mut self, achievement: &Achievement) -> bool {
+        if self.has(achievement.id) {
+            return false;
+        }
+        self.earned.push(achievement.id.to_string());
+        true
+    }
+}
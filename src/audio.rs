@@ -0,0 +1,86 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use sdl3::{
+    AudioSubsystem, Sdl,
+    audio::{AudioDevice, AudioSpec, AudioSpecWAV, AudioStreamOwner},
+};
+
+/// Plays the short sound effects mapped to animations in the gremlin manifest.
+/// Sounds are decoded once and kept around, every playback gets its own stream
+/// so overlapping sounds mix instead of cutting each other off.
+pub struct GremlinAudio {
+    subsystem: AudioSubsystem,
+    device: AudioDevice,
+    sounds: HashMap<PathBuf, AudioSpecWAV>,
+    playing: Vec<AudioStreamOwner>,
+    volume: f32,
+    is_muted: bool,
+}
+
+impl GremlinAudio {
+    pub fn new(sdl: &Sdl) -> Result<Self> {
+        let subsystem = sdl.audio()?;
+        let device = subsystem.open_playback_device(&AudioSpec::default())?;
+        Ok(Self {
+            subsystem,
+            device,
+            sounds: Default::default(),
+            playing: Default::default(),
+            volume: 1.0,
+            is_muted: false,
+        })
+    }
+
+    pub fn play(&mut self, path: &Path) -> Result<()> {
+        // streams that have drained everything can go
+        self.playing
+            .retain(|stream| stream.queued_bytes().is_ok_and(|queued| queued > 0));
+
+        if self.is_muted {
+            return Ok(());
+        }
+
+        if !self.sounds.contains_key(path) {
+            self.sounds
+                .insert(path.to_path_buf(), AudioSpecWAV::load_wav(path)?);
+        }
+        // unwrap safety: inserted right above
+        let wav = self.sounds.get(path).unwrap();
+
+        let spec = AudioSpec::new(Some(wav.freq), Some(wav.channels as i32), Some(wav.format));
+        let stream = self.subsystem.new_playback_stream(&spec, None)?;
+        stream.set_gain(self.volume)?;
+        stream.put_data(wav.buffer())?;
+        stream.flush()?;
+        self.device.bind_stream(&stream)?;
+        self.playing.push(stream);
+        Ok(())
+    }
+
+    pub fn volume(&self) -> f32 {
+        self.volume
+    }
+
+    /// `0.0` is silent, `1.0` is the sound's original loudness
+    pub fn set_volume(&mut self, volume: f32) {
+        self.volume = volume.max(0.0);
+        for stream in &self.playing {
+            let _ = stream.set_gain(self.volume);
+        }
+    }
+
+    pub fn is_muted(&self) -> bool {
+        self.is_muted
+    }
+
+    pub fn set_muted(&mut self, is_muted: bool) {
+        self.is_muted = is_muted;
+        if is_muted {
+            self.playing.clear();
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use std::{env, io, path::PathBuf, process::ExitCode};
+
+#[cfg(not(target_os = "windows"))]
+use std::fs;
+
+const APP_NAME: &str = "desktop_gremlin";
+
+/// registers the current executable to start with the user's session
+pub fn install_autostart() -> io::Result<()> {
+    let executable = env::current_exe()?;
+    platform::install(&executable)
+}
+
+pub fn remove_autostart() -> io::Result<()> {
+    platform::remove()
+}
+
+pub fn is_autostart_installed() -> bool {
+    platform::is_installed()
+}
+
+/// entry point for `desktop_gremlin autostart <on|off|status>`
+pub fn run_cli(args: &[String]) -> ExitCode {
+    let result = match args.first().map(String::as_str) {
+        Some("on") => install_autostart().map(|_| "autostart enabled"),
+        Some("off") => remove_autostart().map(|_| "autostart disabled"),
+        Some("status") => Ok(if is_autostart_installed() {
+            "autostart is enabled"
+        } else {
+            "autostart is disabled"
+        }),
+        _ => {
+            eprintln!("usage: desktop_gremlin autostart <on|off|status>");
+            return ExitCode::FAILURE;
+        }
+    };
+    match result {
+        Ok(message) => {
+            println!("{message}");
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("couldn't change autostart: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn home_dir() -> io::Result<PathBuf> {
+    env::var_os("HOME")
+        .map(PathBuf::from)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "HOME is not set"))
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use std::{io, os::windows::ffi::OsStrExt, path::Path};
+
+    use windows::{
+        Win32::{
+            Foundation::ERROR_FILE_NOT_FOUND,
+            System::Registry::{
+                HKEY, HKEY_CURRENT_USER, KEY_SET_VALUE, REG_SZ, RRF_RT_REG_SZ, RegCloseKey,
+                RegDeleteValueW, RegGetValueW, RegOpenKeyExW, RegSetValueExW,
+            },
+        },
+        core::{HSTRING, w},
+    };
+
+    use super::APP_NAME;
+
+    const RUN_KEY: windows::core::PCWSTR = w!(r"Software\Microsoft\Windows\CurrentVersion\Run");
+
+    fn with_run_key<T>(action: impl FnOnce(HKEY) -> io::Result<T>) -> io::Result<T> {
+        let mut key = HKEY::default();
+        unsafe { RegOpenKeyExW(HKEY_CURRENT_USER, RUN_KEY, None, KEY_SET_VALUE, &mut key) }
+            .ok()
+            .map_err(io::Error::other)?;
+        let result = action(key);
+        let _ = unsafe { RegCloseKey(key) };
+        result
+    }
+
+    pub fn install(executable: &Path) -> io::Result<()> {
+        // REG_SZ wants the quoted path as a null terminated utf-16 string
+        let command = format!("\"{}\"", executable.display());
+        let data = std::ffi::OsStr::new(&command)
+            .encode_wide()
+            .chain(Some(0))
+            .flat_map(u16::to_le_bytes)
+            .collect::<Vec<u8>>();
+        with_run_key(|key| {
+            unsafe { RegSetValueExW(key, &HSTRING::from(APP_NAME), None, REG_SZ, Some(&data)) }
+                .ok()
+                .map_err(io::Error::other)
+        })
+    }
+
+    pub fn remove() -> io::Result<()> {
+        with_run_key(|key| {
+            let result = unsafe { RegDeleteValueW(key, &HSTRING::from(APP_NAME)) };
+            if result == ERROR_FILE_NOT_FOUND {
+                return Ok(());
+            }
+            result.ok().map_err(io::Error::other)
+        })
+    }
+
+    pub fn is_installed() -> bool {
+        unsafe {
+            RegGetValueW(
+                HKEY_CURRENT_USER,
+                RUN_KEY,
+                &HSTRING::from(APP_NAME),
+                RRF_RT_REG_SZ,
+                None,
+                None,
+                None,
+            )
+        }
+        .is_ok()
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::{io, path::Path, path::PathBuf};
+
+    use super::{APP_NAME, fs, home_dir};
+
+    fn plist_path() -> io::Result<PathBuf> {
+        Ok(home_dir()?
+            .join("Library/LaunchAgents")
+            .join(format!("com.{APP_NAME}.plist")))
+    }
+
+    pub fn install(executable: &Path) -> io::Result<()> {
+        let path = plist_path()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let working_dir = executable.parent().unwrap_or(Path::new("/"));
+        fs::write(
+            path,
+            format!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>com.{APP_NAME}</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>{}</string>
+    </array>
+    <key>WorkingDirectory</key>
+    <string>{}</string>
+    <key>RunAtLoad</key>
+    <true/>
+</dict>
+</plist>
+"#,
+                executable.display(),
+                working_dir.display()
+            ),
+        )
+    }
+
+    pub fn remove() -> io::Result<()> {
+        match fs::remove_file(plist_path()?) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn is_installed() -> bool {
+        plist_path().is_ok_and(|path| path.exists())
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+mod platform {
+    use std::{env, io, path::Path, path::PathBuf};
+
+    use super::{APP_NAME, fs, home_dir};
+
+    /// freedesktop autostart entry, respected by pretty much every linux desktop
+    fn desktop_entry_path() -> io::Result<PathBuf> {
+        let config_dir = match env::var_os("XDG_CONFIG_HOME") {
+            Some(dir) => PathBuf::from(dir),
+            None => home_dir()?.join(".config"),
+        };
+        Ok(config_dir
+            .join("autostart")
+            .join(format!("{APP_NAME}.desktop")))
+    }
+
+    pub fn install(executable: &Path) -> io::Result<()> {
+        let path = desktop_entry_path()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let working_dir = executable.parent().unwrap_or(Path::new("/"));
+        fs::write(
+            path,
+            format!(
+                "[Desktop Entry]\nType=Application\nName=Desktop Gremlin\nExec=\"{}\"\nPath={}\nTerminal=false\nX-GNOME-Autostart-enabled=true\n",
+                executable.display(),
+                working_dir.display()
+            ),
+        )
+    }
+
+    pub fn remove() -> io::Result<()> {
+        match fs::remove_file(desktop_entry_path()?) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn is_installed() -> bool {
+        desktop_entry_path().is_ok_and(|path| path.exists())
+    }
+}
@@ -0,0 +1,612 @@
+use std::any::Any;
+
+use sdl3::{
+    pixels::Color,
+    rect::Rect,
+    render::{Canvas, FRect, Texture},
+    sys::render::SDL_SetTextureAlphaMod,
+    video::{Window, WindowPos},
+};
+
+use crate::utils::{get_window_pos, win_to_rect};
+
+/// Whether windows can be see-through. Only a bare X11 session can say no,
+/// windows, macos and wayland always composite.
+pub fn has_compositor() -> bool {
+    #[cfg(target_os = "linux")]
+    {
+        use x11rb::protocol::xproto::ConnectionExt;
+
+        // no X11 display means wayland
+        let Ok((connection, screen_number)) = x11rb::connect(None) else {
+            return true;
+        };
+        // compositing managers own this selection, see the EWMH spec
+        let selection = format!("_NET_WM_CM_S{screen_number}");
+        let owner = connection
+            .intern_atom(false, selection.as_bytes())
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .and_then(|reply| connection.get_selection_owner(reply.atom).ok())
+            .and_then(|cookie| cookie.reply().ok())
+            .map(|reply| reply.owner);
+        // can't tell, don't take transparency away over it
+        owner.is_none_or(|owner| owner != x11rb::NONE)
+    }
+    #[cfg(not(target_os = "linux"))]
+    true
+}
+
+/// Where the gremlin's window sits relative to everything else on screen.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WindowLayer {
+    /// above every other window
+    #[default]
+    OnTop,
+    /// behind the desktop icons, like an animated wallpaper, windows only
+    Wallpaper,
+}
+
+/// An earlier frame of the same sheet, drawn `offset` away from the current one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ghost {
+    pub frame: Rect,
+    pub offset: (i32, i32),
+    pub alpha: u8,
+}
+
+/// Everything behaviors get to do with the gremlin's window.
+/// `SdlBackend` is the real window, `HeadlessBackend` only records what it was asked to do.
+pub trait WindowBackend: Any {
+    fn position(&self) -> (i32, i32);
+    fn set_position(&mut self, x: i32, y: i32);
+    fn size(&self) -> (u32, u32);
+    fn set_size(&mut self, width: u32, height: u32);
+    fn set_visible(&mut self, is_visible: bool);
+
+    fn rect(&self) -> Rect {
+        let (x, y) = self.position();
+        let (width, height) = self.size();
+        Rect::new(x, y, width, height)
+    }
+
+    /// usable bounds of the display the window is on
+    fn usable_bounds(&self) -> Option<Rect>;
+
+    /// usable bounds of every connected display
+    fn all_usable_bounds(&self) -> Vec<Rect>;
+
+    /// the canvas to create textures with, `None` when there's nothing to draw on
+    fn canvas(&mut self) -> Option<&mut Canvas<Window>>;
+
+    /// clears the window and draws `frame` of the sprite sheet over all of it, with `trail` faded
+    /// in underneath, oldest first. Nothing shows up until `present()`
+    fn draw_frame(&mut self, texture: Option<&Texture>, frame: Rect, trail: &[Ghost]);
+
+    /// draws `frame` of another sheet (all of it when `None`) over `target` (the whole window when
+    /// `None`), on top of whatever was drawn since `draw_frame`, see `Gremlin::layers`
+    fn draw_layer(&mut self, texture: &Texture, frame: Option<Rect>, target: Option<Rect>);
+
+    fn present(&mut self);
+
+    /// fails where the platform has no such layer
+    fn set_layer(&mut self, layer: WindowLayer) -> anyhow::Result<()>;
+}
+
+pub struct SdlBackend {
+    pub canvas: Canvas<Window>,
+}
+
+impl WindowBackend for SdlBackend {
+    fn position(&self) -> (i32, i32) {
+        get_window_pos(&self.canvas)
+    }
+
+    fn set_position(&mut self, x: i32, y: i32) {
+        self.canvas
+            .window_mut()
+            .set_position(WindowPos::Positioned(x), WindowPos::Positioned(y));
+    }
+
+    fn size(&self) -> (u32, u32) {
+        self.canvas.window().size()
+    }
+
+    fn set_size(&mut self, width: u32, height: u32) {
+        let _ = self.canvas.window_mut().set_size(width, height);
+    }
+
+    fn set_visible(&mut self, is_visible: bool) {
+        if is_visible {
+            self.canvas.window_mut().show();
+        } else {
+            self.canvas.window_mut().hide();
+        }
+    }
+
+    fn rect(&self) -> Rect {
+        win_to_rect(self.canvas.window())
+    }
+
+    fn usable_bounds(&self) -> Option<Rect> {
+        self.canvas
+            .window()
+            .get_display()
+            .ok()?
+            .get_usable_bounds()
+            .ok()
+    }
+
+    fn all_usable_bounds(&self) -> Vec<Rect> {
+        self.canvas
+            .window()
+            .subsystem()
+            .displays()
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|display| display.get_usable_bounds().ok())
+            .collect()
+    }
+
+    fn canvas(&mut self) -> Option<&mut Canvas<Window>> {
+        Some(&mut self.canvas)
+    }
+
+    fn draw_frame(&mut self, texture: Option<&Texture>, frame: Rect, trail: &[Ghost]) {
+        let Some(texture) = texture else {
+            return;
+        };
+        // fully transparent where there's no sprite, and black for the colorkey where there's no alpha
+        self.canvas.set_draw_color(Color::RGBA(0, 0, 0, 0));
+        self.canvas.clear();
+        if !trail.is_empty() {
+            let (width, height) = self.size();
+            for ghost in trail {
+                // the sheet is shared through an `Rc`, so no `&mut` for `set_alpha_mod`
+                unsafe { SDL_SetTextureAlphaMod(texture.raw(), ghost.alpha) };
+                let target = Rect::new(ghost.offset.0, ghost.offset.1, width, height);
+                let _ = self.canvas.copy(texture, ghost.frame, target);
+            }
+            unsafe { SDL_SetTextureAlphaMod(texture.raw(), u8::MAX) };
+        }
+        self.canvas.copy(texture, frame, None).unwrap();
+    }
+
+    fn draw_layer(&mut self, texture: &Texture, frame: Option<Rect>, target: Option<Rect>) {
+        let _ = self
+            .canvas
+            .copy(texture, frame.map(FRect::from), target.map(FRect::from));
+    }
+
+    fn present(&mut self) {
+        self.canvas.present();
+    }
+
+    fn set_layer(&mut self, layer: WindowLayer) -> anyhow::Result<()> {
+        #[cfg(target_os = "windows")]
+        return windows_layer::set_layer(self.canvas.window(), layer);
+        #[cfg(not(target_os = "windows"))]
+        match layer {
+            WindowLayer::OnTop => Ok(()),
+            WindowLayer::Wallpaper => anyhow::bail!("wallpaper mode only works on windows"),
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod windows_layer {
+    use anyhow::bail;
+    use sdl3::{
+        sys::{
+            properties::SDL_GetPointerProperty,
+            video::{SDL_GetWindowProperties, SDL_PROP_WINDOW_WIN32_HWND_POINTER},
+        },
+        video::Window,
+    };
+    use windows::{
+        Win32::{
+            Foundation::{HWND, LPARAM, WPARAM},
+            UI::WindowsAndMessaging::{
+                EnumWindows, FindWindowExW, FindWindowW, HWND_TOPMOST, SMTO_NORMAL, SWP_NOACTIVATE,
+                SWP_NOMOVE, SWP_NOSIZE, SendMessageTimeoutW, SetParent, SetWindowPos,
+            },
+        },
+        core::{BOOL, PCWSTR, w},
+    };
+
+    use super::WindowLayer;
+
+    // undocumented, asks explorer to put a WorkerW between the wallpaper and the desktop icons
+    const SPAWN_WORKER_W: u32 = 0x052C;
+
+    pub fn set_layer(window: &Window, layer: WindowLayer) -> anyhow::Result<()> {
+        unsafe {
+            let hwnd = HWND(SDL_GetPointerProperty(
+                SDL_GetWindowProperties(window.raw()),
+                SDL_PROP_WINDOW_WIN32_HWND_POINTER,
+                std::ptr::null_mut(),
+            ));
+            match layer {
+                WindowLayer::Wallpaper => {
+                    let progman = FindWindowW(w!("Progman"), PCWSTR::null())?;
+                    SendMessageTimeoutW(
+                        progman,
+                        SPAWN_WORKER_W,
+                        WPARAM(0),
+                        LPARAM(0),
+                        SMTO_NORMAL,
+                        1000,
+                        None,
+                    );
+                    let mut worker_w = HWND::default();
+                    // stops early once it's found, which comes back as an error
+                    let _ = EnumWindows(
+                        Some(find_worker_w),
+                        LPARAM(&mut worker_w as *mut HWND as isize),
+                    );
+                    // newer windows 11 builds keep it inside Progman instead
+                    if worker_w.is_invalid() {
+                        worker_w =
+                            FindWindowExW(Some(progman), None, w!("WorkerW"), PCWSTR::null())
+                                .unwrap_or_default();
+                    }
+                    if worker_w.is_invalid() {
+                        bail!("couldn't find the wallpaper layer");
+                    }
+                    // the WorkerW spans the whole desktop from its top left corner, so the
+                    // screen coordinates SDL keeps using line up as long as that's at 0, 0
+                    SetParent(hwnd, Some(worker_w))?;
+                }
+                WindowLayer::OnTop => {
+                    // fails when there was no parent to begin with, which is fine
+                    let _ = SetParent(hwnd, None);
+                    SetWindowPos(
+                        hwnd,
+                        Some(HWND_TOPMOST),
+                        0,
+                        0,
+                        0,
+                        0,
+                        SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE,
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// the WorkerW right after the top level window holding the desktop icons
+    unsafe extern "system" fn find_worker_w(hwnd: HWND, worker_w: LPARAM) -> BOOL {
+        unsafe {
+            if FindWindowExW(Some(hwnd), None, w!("SHELLDLL_DefView"), PCWSTR::null()).is_err() {
+                return true.into();
+            }
+            *(worker_w.0 as *mut HWND) =
+                FindWindowExW(None, Some(hwnd), w!("WorkerW"), PCWSTR::null()).unwrap_or_default();
+        }
+        false.into()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BackendCall {
+    Move {
+        x: i32,
+        y: i32,
+    },
+    Resize {
+        width: u32,
+        height: u32,
+    },
+    SetVisible {
+        is_visible: bool,
+    },
+    DrawFrame {
+        frame: Rect,
+    },
+    DrawLayer {
+        frame: Option<Rect>,
+        target: Option<Rect>,
+    },
+    SetLayer {
+        layer: WindowLayer,
+    },
+}
+
+/// A pretend window on a pretend 1920x1080 display, for running behaviors without SDL (e.g. in tests).
+/// Every move, resize and drawn frame ends up in `calls`.
+#[derive(Debug, Clone)]
+pub struct HeadlessBackend {
+    pub position: (i32, i32),
+    pub size: (u32, u32),
+    pub display_bounds: Rect,
+    pub calls: Vec<BackendCall>,
+}
+
+impl Default for HeadlessBackend {
+    fn default() -> Self {
+        Self {
+            position: (0, 0),
+            size: (150, 150),
+            display_bounds: Rect::new(0, 0, 1920, 1080),
+            calls: Vec::new(),
+        }
+    }
+}
+
+impl WindowBackend for HeadlessBackend {
+    fn position(&self) -> (i32, i32) {
+        self.position
+    }
+
+    fn set_position(&mut self, x: i32, y: i32) {
+        self.position = (x, y);
+        self.calls.push(BackendCall::Move { x, y });
+    }
+
+    fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    fn set_size(&mut self, width: u32, height: u32) {
+        self.size = (width, height);
+        self.calls.push(BackendCall::Resize { width, height });
+    }
+
+    fn set_visible(&mut self, is_visible: bool) {
+        self.calls.push(BackendCall::SetVisible { is_visible });
+    }
+
+    fn usable_bounds(&self) -> Option<Rect> {
+        Some(self.display_bounds)
+    }
+
+    fn all_usable_bounds(&self) -> Vec<Rect> {
+        vec![self.display_bounds]
+    }
+
+    fn canvas(&mut self) -> Option<&mut Canvas<Window>> {
+        None
+    }
+
+    fn draw_frame(&mut self, _: Option<&Texture>, frame: Rect, _: &[Ghost]) {
+        self.calls.push(BackendCall::DrawFrame { frame });
+    }
+
+    fn draw_layer(&mut self, _: &Texture, frame: Option<Rect>, target: Option<Rect>) {
+        self.calls.push(BackendCall::DrawLayer { frame, target });
+    }
+
+    fn present(&mut self) {}
+
+    fn set_layer(&mut self, layer: WindowLayer) -> anyhow::Result<()> {
+        self.calls.push(BackendCall::SetLayer { layer });
+        Ok(())
+    }
+}
+
+/// The X11 counterpart of the colorkey `see_through` falls back to on windows, for when there's
+/// no compositor to make the window see-through: after every frame the window gets cut down to
+/// the pixels that aren't black, with the shape extension. Edges come out hard, but it beats a
+/// black square.
+#[cfg(target_os = "linux")]
+pub mod x11_shape {
+    use sdl3::{
+        pixels::PixelFormat,
+        rect::Rect,
+        render::{Canvas, Texture},
+        sys::{
+            properties::SDL_GetNumberProperty,
+            video::{SDL_GetWindowProperties, SDL_PROP_WINDOW_X11_WINDOW_NUMBER},
+        },
+        video::Window,
+    };
+    use tracing::warn;
+    use x11rb::{
+        connection::RequestConnection,
+        protocol::{
+            shape::{self, ConnectionExt, SK, SO},
+            xproto::{ClipOrdering, Rectangle},
+        },
+        rust_connection::RustConnection,
+    };
+
+    use super::{Ghost, SdlBackend, WindowBackend, WindowLayer};
+
+    /// An `SdlBackend` whose window gets cut to shape every time it's presented.
+    pub struct ShapedBackend {
+        inner: SdlBackend,
+        connection: RustConnection,
+        x11_window: u32,
+        // what it was cut to last, frames that come out the same don't go to the server again
+        shape: Option<Vec<Rect>>,
+    }
+
+    impl ShapedBackend {
+        /// `None` when SDL isn't running on X11 or the server has no shape extension
+        fn connect(canvas: &Canvas<Window>) -> Option<(RustConnection, u32)> {
+            let x11_window = unsafe {
+                SDL_GetNumberProperty(
+                    SDL_GetWindowProperties(canvas.window().raw()),
+                    SDL_PROP_WINDOW_X11_WINDOW_NUMBER,
+                    0,
+                )
+            } as u32;
+            if x11_window == 0 {
+                return None;
+            }
+            let (connection, _) = x11rb::connect(None).ok()?;
+            matches!(
+                connection.extension_information(shape::X11_EXTENSION_NAME),
+                Ok(Some(_))
+            )
+            .then_some((connection, x11_window))
+        }
+
+        /// `backend` cut to shape, or as it was where that can't be done
+        pub fn wrap(backend: SdlBackend) -> Result<Self, SdlBackend> {
+            match Self::connect(&backend.canvas) {
+                Some((connection, x11_window)) => Ok(Self {
+                    inner: backend,
+                    connection,
+                    x11_window,
+                    shape: None,
+                }),
+                None => Err(backend),
+            }
+        }
+
+        fn cut(&mut self) -> anyhow::Result<()> {
+            let surface = self
+                .inner
+                .canvas
+                .read_pixels(None)?
+                .convert_format(PixelFormat::RGBA32)?;
+            let (width, pitch) = (surface.width() as usize, surface.pitch() as usize);
+            let shape = surface.with_lock(|pixels| opaque_rectangles(pixels, width, pitch));
+            if self.shape.as_ref() == Some(&shape) {
+                return Ok(());
+            }
+            self.connection
+                .shape_rectangles(
+                    SO::SET,
+                    SK::BOUNDING,
+                    ClipOrdering::YX_BANDED,
+                    self.x11_window,
+                    0,
+                    0,
+                    &shape
+                        .iter()
+                        .map(|rect| Rectangle {
+                            x: rect.x() as i16,
+                            y: rect.y() as i16,
+                            width: rect.width() as u16,
+                            height: rect.height() as u16,
+                        })
+                        .collect::<Vec<_>>(),
+                )?
+                .check()?;
+            self.shape = Some(shape);
+            Ok(())
+        }
+    }
+
+    impl WindowBackend for ShapedBackend {
+        fn position(&self) -> (i32, i32) {
+            self.inner.position()
+        }
+
+        fn set_position(&mut self, x: i32, y: i32) {
+            self.inner.set_position(x, y);
+        }
+
+        fn size(&self) -> (u32, u32) {
+            self.inner.size()
+        }
+
+        fn set_size(&mut self, width: u32, height: u32) {
+            self.inner.set_size(width, height);
+        }
+
+        fn set_visible(&mut self, is_visible: bool) {
+            self.inner.set_visible(is_visible);
+        }
+
+        fn rect(&self) -> Rect {
+            self.inner.rect()
+        }
+
+        fn usable_bounds(&self) -> Option<Rect> {
+            self.inner.usable_bounds()
+        }
+
+        fn all_usable_bounds(&self) -> Vec<Rect> {
+            self.inner.all_usable_bounds()
+        }
+
+        fn canvas(&mut self) -> Option<&mut Canvas<Window>> {
+            self.inner.canvas()
+        }
+
+        fn draw_frame(&mut self, texture: Option<&Texture>, frame: Rect, trail: &[Ghost]) {
+            self.inner.draw_frame(texture, frame, trail);
+        }
+
+        fn draw_layer(&mut self, texture: &Texture, frame: Option<Rect>, target: Option<Rect>) {
+            self.inner.draw_layer(texture, frame, target);
+        }
+
+        fn present(&mut self) {
+            // what's drawn can still be read back until it's presented
+            if let Err(err) = self.cut() {
+                warn!("couldn't cut the window to the gremlin's shape: {err:#}");
+            }
+            self.inner.present();
+        }
+
+        fn set_layer(&mut self, layer: WindowLayer) -> anyhow::Result<()> {
+            self.inner.set_layer(layer)
+        }
+    }
+
+    /// the runs of pixels that aren't black in `pixels` (RGBA32 rows `pitch` bytes apart),
+    /// rows that come out the same merged into one band
+    fn opaque_rectangles(pixels: &[u8], width: usize, pitch: usize) -> Vec<Rect> {
+        let mut rectangles: Vec<Rect> = Vec::new();
+        // where the previous row's runs start in `rectangles`
+        let mut band = 0;
+        for (y, row) in pixels.chunks(pitch).enumerate() {
+            let mut runs = Vec::new();
+            let mut start = None;
+            for x in 0..=width {
+                let is_opaque = x < width
+                    && row
+                        .get(x * 4..x * 4 + 3)
+                        .is_some_and(|rgb| rgb != [0, 0, 0]);
+                match (is_opaque, start) {
+                    (true, None) => start = Some(x),
+                    (false, Some(from)) => {
+                        runs.push(Rect::new(from as i32, y as i32, (x - from) as u32, 1));
+                        start = None;
+                    }
+                    _ => {}
+                }
+            }
+            let previous = &mut rectangles[band..];
+            let is_same = !runs.is_empty()
+                && previous.len() == runs.len()
+                && previous.iter().zip(&runs).all(|(above, run)| {
+                    above.x() == run.x()
+                        && above.width() == run.width()
+                        && above.bottom() == run.y()
+                });
+            if is_same {
+                previous
+                    .iter_mut()
+                    .for_each(|above| above.set_height(above.height() + 1));
+            } else {
+                band = rectangles.len();
+                rectangles.extend(runs);
+            }
+        }
+        rectangles
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn cuts_out_the_black() {
+            let (o, k) = ([0xff, 0x80, 0x00, 0xff], [0, 0, 0, 0]);
+            // 3x3 with a padded row: a column down the middle, the bottom row full
+            let rows = [[k, o, k, k], [k, o, k, k], [o, o, o, o]];
+            let pixels = rows.concat().concat();
+            assert_eq!(
+                opaque_rectangles(&pixels, 3, 16),
+                [Rect::new(1, 0, 1, 2), Rect::new(0, 2, 3, 1)]
+            );
+            assert!(opaque_rectangles(&[0; 16], 2, 8).is_empty());
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Local, TimeDelta, Timelike};
+
+use crate::{
+    achievements::{Achievement, FULL_NIGHT, PETS_FOR_PETTED, PETTED},
+    behavior::{Behavior, ContextData},
+    events::{
+        Event, MouseButton,
+        kinds::{Click, DragStart},
+    },
+    gremlin::DesktopGremlin,
+};
+
+const NIGHT_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+const NIGHT_CHECK_EVENT: &str = "achievements:night_check";
+// from 22:00 to 08:00
+const NIGHT_STARTS_AT: u32 = 22;
+const NIGHT_ENDS_AT: u32 = 8;
+// left alone this long at night counts as sleeping through it
+const FULL_NIGHT_SLEEP: TimeDelta = TimeDelta::hours(6);
+
+/// Keeps track of `DesktopGremlin::achievements`, shows a toast for every new one and unlocks
+/// what the gremlin's `[unlocks]` held back for it.
+#[derive(Default)]
+pub struct GremlinAchievements {
+    // since when nobody touched the gremlin, only counted at night
+    left_alone_since: Option<DateTime<Local>>,
+}
+
+impl GremlinAchievements {
+    pub fn new() -> Box<Self> {
+        Default::default()
+    }
+
+    fn earn(application: &mut DesktopGremlin, achievement: &Achievement) {
+        if !application.achievements.earn(achievement) {
+            return;
+        }
+        let unlocked: Vec<String> = application
+            .current_gremlin
+            .as_ref()
+            .map(|gremlin| {
+                gremlin
+                    .unlocks
+                    .iter()
+                    .filter(|(_, id)| *id == achievement.id)
+                    .map(|(name, _)| name.to_lowercase())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let body = if unlocked.is_empty() {
+            achievement.description.to_string()
+        } else {
+            format!(
+                "{}, unlocked {}",
+                achievement.description,
+                unlocked.join(", ")
+            )
+        };
+        application.notify(format!("Achievement: {}", achievement.title), body);
+    }
+
+    fn check_night(&mut self, application: &mut DesktopGremlin) {
+        let now = Local::now();
+        if (NIGHT_ENDS_AT..NIGHT_STARTS_AT).contains(&now.hour()) {
+            self.left_alone_since = None;
+            return;
+        }
+        let since = *self.left_alone_since.get_or_insert(now);
+        if now - since >= FULL_NIGHT_SLEEP {
+            Self::earn(application, &FULL_NIGHT);
+        }
+    }
+}
+
+impl Behavior for GremlinAchievements {
+    fn setup(&mut self, application: &mut DesktopGremlin) {
+        application.schedule_every(
+            NIGHT_CHECK_INTERVAL,
+            Event::Custom(NIGHT_CHECK_EVENT.to_string()),
+        );
+    }
+
+    fn update(&mut self, application: &mut DesktopGremlin, context: &ContextData) {
+        let mut pets = 0;
+        let mut was_touched = false;
+        context.on::<Click>(|click| {
+            was_touched = true;
+            if click.mouse_btn == MouseButton::Left {
+                pets += 1;
+            }
+        });
+        context.on::<DragStart>(|_| was_touched = true);
+        if was_touched {
+            // woken up
+            self.left_alone_since = None;
+        }
+        if pets > 0 {
+            application.achievements.pets += pets;
+            if application.achievements.pets >= PETS_FOR_PETTED {
+                Self::earn(application, &PETTED);
+            }
+        }
+        if context
+            .events
+            .contains_key(&Event::Custom(NIGHT_CHECK_EVENT.to_string()))
+        {
+            self.check_night(application);
+        }
+
+        // a gremlin loaded since, or something just earned
+        let achievements = &application.achievements;
+        if let Some(gremlin) = &mut application.current_gremlin
+            && !gremlin.locked.is_empty()
+        {
+            let unlocks = &gremlin.unlocks;
+            gremlin
+                .locked
+                .retain(|name| unlocks.get(name).is_some_and(|id| !achievements.has(id)));
+        }
+    }
+}
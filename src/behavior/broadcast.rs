@@ -0,0 +1,86 @@
+use tracing::warn;
+
+use crate::{
+    behavior::{Behavior, ContextData},
+    events::Event,
+    gremlin::DesktopGremlin,
+    stream::{DEFAULT_STREAM_PORT, EventStream, StreamEvent},
+};
+
+/// Mirrors the gremlin's state (animation, position, clicks and drags) to websocket clients.
+pub struct GremlinBroadcast {
+    port: u16,
+    stream: Option<EventStream>,
+    last_animation: Option<String>,
+    last_position: Option<(i32, i32)>,
+}
+
+impl Default for GremlinBroadcast {
+    fn default() -> Self {
+        Self {
+            port: DEFAULT_STREAM_PORT,
+            stream: None,
+            last_animation: None,
+            last_position: None,
+        }
+    }
+}
+
+impl GremlinBroadcast {
+    pub fn new() -> Box<Self> {
+        Default::default()
+    }
+
+    #[allow(unused)]
+    pub fn with_port(port: u16) -> Box<Self> {
+        Box::new(Self {
+            port,
+            ..Default::default()
+        })
+    }
+}
+
+impl Behavior for GremlinBroadcast {
+    fn setup(&mut self, _: &mut DesktopGremlin) {
+        self.stream = EventStream::start(self.port)
+            .inspect_err(|err| warn!("event stream unavailable on port {}: {err}", self.port))
+            .ok();
+    }
+
+    fn update(&mut self, application: &mut DesktopGremlin, context: &ContextData) {
+        let Some(stream) = &self.stream else {
+            return;
+        };
+
+        for event in context.events.keys() {
+            let stream_event = match event {
+                Event::Click { mouse_btn } => StreamEvent::Click { button: *mouse_btn },
+                Event::DragStart { mouse_btn } => StreamEvent::DragStart { button: *mouse_btn },
+                Event::DragEnd { mouse_btn } => StreamEvent::DragEnd { button: *mouse_btn },
+                _ => continue,
+            };
+            stream.publish(stream_event);
+        }
+
+        let animation = application
+            .current_gremlin
+            .as_ref()
+            .and_then(|gremlin| gremlin.animator.as_ref())
+            .map(|animator| animator.animation_properties.animation_name.clone());
+        if animation != self.last_animation {
+            if let Some(name) = &animation {
+                stream.publish(StreamEvent::Animation { name: name.clone() });
+            }
+            self.last_animation = animation;
+        }
+
+        let position = application.window.position();
+        if self.last_position != Some(position) {
+            stream.publish(StreamEvent::Position {
+                x: position.0,
+                y: position.1,
+            });
+            self.last_position = Some(position);
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use tracing::warn;
+
+use crate::{
+    behavior::{Behavior, ContextData},
+    gremlin::DesktopGremlin,
+    ui::bubble::SpeechBubble,
+};
+
+/// Shows whatever the gremlin was asked to `say()`, one bubble at a time, following the gremlin around.
+#[derive(Default)]
+pub struct GremlinBubble {
+    bubble: Option<SpeechBubble>,
+}
+
+impl GremlinBubble {
+    pub fn new() -> Box<Self> {
+        Default::default()
+    }
+}
+
+impl Behavior for GremlinBubble {
+    fn setup(&mut self, _: &mut DesktopGremlin) {}
+
+    fn update(&mut self, application: &mut DesktopGremlin, _: &ContextData) {
+        if self
+            .bubble
+            .as_ref()
+            .is_some_and(|bubble| bubble.is_expired())
+        {
+            self.bubble = None;
+        }
+
+        // headless there's nowhere to show bubbles, speech just piles up in the queue
+        if self.bubble.is_none()
+            && let Some(sdl) = &application.sdl
+            && let Some(speech) = application.speech_queue.pop_front()
+        {
+            match sdl
+                .video()
+                .map_err(anyhow::Error::from)
+                .and_then(|video| SpeechBubble::new(&video, &speech.text, speech.duration))
+            {
+                Ok(bubble) => self.bubble = Some(bubble),
+                Err(err) => warn!("couldn't show speech bubble: {err}"),
+            }
+        }
+
+        if let Some(bubble) = &mut self.bubble {
+            bubble.follow(application.window.rect());
+        }
+    }
+}
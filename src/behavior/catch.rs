@@ -0,0 +1,264 @@
+use std::{
+    ops::Range,
+    time::{Duration, Instant},
+};
+
+use rand::Rng;
+use sdl3::{pixels::Color, rect::Rect};
+use tracing::warn;
+
+use crate::{
+    backend::{SdlBackend, WindowBackend},
+    behavior::{Behavior, ContextData},
+    events::Event,
+    gremlin::{DesktopGremlin, GremlinTask, LaunchArguments, click_through, see_through},
+};
+
+/// starts a round or ends the one going, raised by the `catch` ipc command
+pub const CATCH_TOGGLE_EVENT: &str = "catch:toggle";
+const DEFAULT_ROUND_LENGTH: Duration = Duration::from_secs(60);
+const SPAWN_INTERVAL: Duration = Duration::from_millis(1200);
+const MAX_ITEMS: usize = 6;
+const ITEM_SIZE: u32 = 28;
+// pixels per second
+const FALL_SPEED: Range<f32> = 120.0..320.0;
+const BUBBLE_DURATION: Duration = Duration::from_secs(3);
+// short, catches can come one right after another
+const SCORE_BUBBLE_DURATION: Duration = Duration::from_secs(1);
+// these would walk the gremlin off or play something over the game
+const PAUSED_BEHAVIORS: &[&str] = &[
+    "GremlinMovement",
+    "RandomEvents",
+    "IdleVariation",
+    "GremlinNeighbors",
+    "GremlinPresenter",
+];
+const TREAT_COLOR: Color = Color::RGB(0xc8, 0x8a, 0x4a);
+const CHIP_COLOR: Color = Color::RGB(0x4a, 0x2c, 0x1a);
+
+/// A treat falling down the screen in a window of its own.
+struct Item {
+    window: SdlBackend,
+    // fractional so slow items still move every frame
+    y: f32,
+    speed: f32,
+}
+
+/// A round of the game that's being played.
+struct Round {
+    started_at: Instant,
+    last_spawn: Instant,
+    items: Vec<Item>,
+    score: u32,
+    missed: u32,
+    // the behaviors this round paused, to turn back on after
+    paused: Vec<String>,
+}
+
+/// Catch: treats fall from the top of the screen and the user drags the gremlin under them.
+/// A round lasts `round_length`, the score shows in a bubble with every catch. While a round is on,
+/// the behaviors that move or animate the gremlin by themselves are paused.
+pub struct GremlinCatch {
+    round_length: Duration,
+    round: Option<Round>,
+    is_quitting: bool,
+}
+
+impl Default for GremlinCatch {
+    fn default() -> Self {
+        Self {
+            round_length: DEFAULT_ROUND_LENGTH,
+            round: None,
+            is_quitting: false,
+        }
+    }
+}
+
+impl GremlinCatch {
+    pub fn new() -> Box<Self> {
+        Default::default()
+    }
+
+    #[allow(unused)]
+    pub fn with_round_length(round_length: Duration) -> Box<Self> {
+        Box::new(Self {
+            round_length,
+            ..Default::default()
+        })
+    }
+
+    fn start(&mut self, application: &mut DesktopGremlin) {
+        let paused = PAUSED_BEHAVIORS
+            .iter()
+            .filter(|name| application.disabled_behaviors.insert(name.to_string()))
+            .map(|name| name.to_string())
+            .collect();
+        let now = Instant::now();
+        self.round = Some(Round {
+            started_at: now,
+            last_spawn: now,
+            items: Vec::new(),
+            score: 0,
+            missed: 0,
+            paused,
+        });
+        application.say("catch the treats!", BUBBLE_DURATION);
+    }
+
+    fn stop(&mut self, application: &mut DesktopGremlin) {
+        // dropping the items closes their windows
+        let Some(round) = self.round.take() else {
+            return;
+        };
+        for name in &round.paused {
+            application.disabled_behaviors.remove(name);
+        }
+        application.say(
+            format!(
+                "caught {}, missed {}!",
+                round.score,
+                round.missed + round.items.len() as u32
+            ),
+            BUBBLE_DURATION,
+        );
+    }
+
+    fn drop_item(application: &mut DesktopGremlin) -> Option<Item> {
+        let video = application.sdl.as_ref()?.video().ok()?;
+        let bounds = application.window.usable_bounds()?;
+        let launch_arguments = LaunchArguments::default();
+        let window = video
+            .window(&launch_arguments.title, ITEM_SIZE, ITEM_SIZE)
+            .set_window_flags(launch_arguments.window_flags())
+            .build()
+            .inspect_err(|err| warn!("couldn't open a window for a treat: {err}"))
+            .ok()?;
+        see_through(&window, true);
+        // grabbing the gremlin right under a treat shouldn't grab the treat
+        click_through(&window, true);
+
+        let mut window = SdlBackend {
+            canvas: window.into_canvas(),
+        };
+        let max_x = bounds.right() - ITEM_SIZE as i32;
+        let x = application
+            .rng
+            .random_range(bounds.x()..=max_x.max(bounds.x()));
+        let y = bounds.y() - ITEM_SIZE as i32;
+        window.set_position(x, y);
+        draw_treat(&mut window);
+        Some(Item {
+            window,
+            y: y as f32,
+            speed: application.rng.random_range(FALL_SPEED),
+        })
+    }
+
+    fn play(application: &mut DesktopGremlin, animation: &str) {
+        if application
+            .current_gremlin
+            .as_ref()
+            .is_some_and(|gremlin| gremlin.has_animation(animation))
+        {
+            let _ = application
+                .task_channel
+                .0
+                .send(GremlinTask::PlayInterrupt(animation.to_string()));
+            let _ = application.task_channel.0.send(GremlinTask::Play(
+                application.default_animation().to_string(),
+            ));
+        }
+    }
+}
+
+impl Behavior for GremlinCatch {
+    fn setup(&mut self, _: &mut DesktopGremlin) {}
+
+    fn update(&mut self, application: &mut DesktopGremlin, context: &ContextData) {
+        // the saved state shouldn't remember what the round paused as turned off
+        self.is_quitting |= context.events.contains_key(&Event::Quit);
+        if self.is_quitting {
+            self.stop(application);
+            return;
+        }
+        if context
+            .events
+            .contains_key(&Event::Custom(CATCH_TOGGLE_EVENT.to_string()))
+        {
+            if self.round.is_some() {
+                self.stop(application);
+            } else {
+                self.start(application);
+            }
+        }
+        let Some(round) = &mut self.round else {
+            return;
+        };
+        if round.started_at.elapsed() >= self.round_length {
+            self.stop(application);
+            return;
+        }
+
+        if round.items.len() < MAX_ITEMS && round.last_spawn.elapsed() >= SPAWN_INTERVAL {
+            round.last_spawn = Instant::now();
+            round.items.extend(Self::drop_item(application));
+        }
+
+        // the treats fall a little every frame
+        application.stay_awake();
+        let elapsed = application.frame_tick().as_secs_f32();
+        let gremlin = application.window.rect();
+        let bottom = application
+            .window
+            .usable_bounds()
+            .map_or(i32::MAX, |bounds| bounds.bottom());
+        let (mut caught, mut missed) = (0, 0);
+        round.items.retain_mut(|item| {
+            item.y += item.speed * elapsed;
+            let (x, _) = item.window.position();
+            item.window.set_position(x, item.y.round() as i32);
+            if item.window.rect().has_intersection(gremlin) {
+                caught += 1;
+                return false;
+            }
+            if item.y as i32 > bottom {
+                missed += 1;
+                return false;
+            }
+            true
+        });
+        round.score += caught;
+        round.missed += missed;
+        if caught > 0 {
+            let score = round.score;
+            // an older score is no news anymore
+            application.speech_queue.clear();
+            application.say(format!("{score}!"), SCORE_BUBBLE_DURATION);
+            Self::play(application, "EAT");
+        }
+    }
+}
+
+/// a round cookie with a few chips in it
+fn draw_treat(window: &mut SdlBackend) {
+    let canvas = &mut window.canvas;
+    canvas.set_draw_color(Color::RGBA(0, 0, 0, 0));
+    canvas.clear();
+    let radius = ITEM_SIZE as i32 / 2 - 1;
+    let center = ITEM_SIZE as i32 / 2;
+    canvas.set_draw_color(TREAT_COLOR);
+    for dy in -radius..=radius {
+        let half_width = ((radius * radius - dy * dy) as f32).sqrt() as i32;
+        let _ = canvas.fill_rect(Rect::new(
+            center - half_width,
+            center + dy,
+            (half_width * 2).max(1) as u32,
+            1,
+        ));
+    }
+    canvas.set_draw_color(CHIP_COLOR);
+    for (x, y) in [(-5, -4), (4, -6), (-2, 5), (6, 3), (0, -1)] {
+        let _ = canvas.fill_rect(Rect::new(center + x - 1, center + y - 1, 3, 3));
+    }
+    window.present();
+}
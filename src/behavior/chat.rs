@@ -0,0 +1,67 @@
+use std::{
+    sync::{
+        Arc,
+        mpsc::{self, Receiver, Sender},
+    },
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    behavior::{Behavior, ContextData},
+    brain::{Brain, HttpBrain},
+    gremlin::{DesktopGremlin, GremlinTask},
+};
+
+// long enough to read a couple of sentences
+const SECONDS_PER_WORD: f32 = 0.4;
+const MIN_REPLY_DURATION: Duration = Duration::from_secs(3);
+
+/// Answers whatever ends up in `DesktopGremlin::chat_prompts` (`desktop_gremlin send chat ...`)
+/// with a speech bubble, asking its `Brain` on a separate thread so the gremlin keeps moving meanwhile.
+pub struct GremlinChat {
+    brain: Arc<dyn Brain>,
+    reply_channel: (Sender<String>, Receiver<String>),
+}
+
+impl GremlinChat {
+    pub fn new() -> Box<Self> {
+        Self::with_brain(HttpBrain::from_env())
+    }
+
+    pub fn with_brain(brain: impl Brain + 'static) -> Box<Self> {
+        Box::new(Self {
+            brain: Arc::new(brain),
+            reply_channel: mpsc::channel(),
+        })
+    }
+}
+
+impl Behavior for GremlinChat {
+    fn setup(&mut self, _: &mut DesktopGremlin) {}
+
+    fn update(&mut self, application: &mut DesktopGremlin, _: &ContextData) {
+        while let Some(prompt) = application.chat_prompts.pop_front() {
+            let brain = Arc::clone(&self.brain);
+            let reply_tx = self.reply_channel.0.clone();
+            thread::spawn(move || {
+                let _ = reply_tx.send(brain.respond(&prompt));
+            });
+        }
+
+        while let Ok(reply) = self.reply_channel.1.try_recv() {
+            let words = reply.split_whitespace().count() as f32;
+            let duration =
+                Duration::from_secs_f32(words * SECONDS_PER_WORD).max(MIN_REPLY_DURATION);
+            application.say(reply, duration);
+            if let Some(gremlin) = &application.current_gremlin
+                && gremlin.has_animation("TALK")
+            {
+                let _ = application
+                    .task_channel
+                    .0
+                    .send(GremlinTask::Play("TALK".to_string()));
+            }
+        }
+    }
+}
@@ -1,31 +1,216 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use rand::seq::IndexedRandom;
+
 use crate::{
-    behavior::Behavior,
-    gremlin::{DesktopGremlin, GremlinTask},
+    behavior::{Behavior, ContextData, MENU_TOGGLE_EVENT},
+    events::{Event, MouseButton, kinds::Click},
+    gremlin::{DesktopGremlin, Gremlin, GremlinTask},
 };
-#[derive(Default)]
-pub struct GremlinClick {}
+
+const DEFAULT_COOLDOWN: Duration = Duration::from_millis(600);
+
+/// What a click with a given button does. In a manifest's `[clicks]` that's `left=CLICK,PAT`,
+/// `middle=tag:reaction,SPIN` (the tag, then what to play without it) or `right=event:menu`.
+#[derive(Debug, Clone)]
+pub enum ClickAction {
+    /// plays one of these at random, then goes back to IDLE
+    Animations(Vec<String>),
+    /// plays one of the animations with this tag at random like `Animations`, or one of
+    /// `otherwise` when the gremlin hasn't tagged any, see `Gremlin::animations_with_tag`
+    Tagged { tag: String, otherwise: Vec<String> },
+    /// raises `Event::Custom(name)` for some other behavior to pick up
+    Emit(String),
+}
+
+impl ClickAction {
+    /// what there is to pick from for `gremlin`, nothing for `Emit`
+    fn animations<'a>(&'a self, gremlin: &'a Gremlin) -> Vec<&'a str> {
+        match self {
+            Self::Animations(animations) => animations.iter().map(String::as_str).collect(),
+            Self::Tagged { tag, otherwise } => {
+                let tagged = gremlin.animations_with_tag(tag);
+                if tagged.is_empty() {
+                    otherwise.iter().map(String::as_str).collect()
+                } else {
+                    tagged
+                }
+            }
+            Self::Emit(_) => Vec::new(),
+        }
+    }
+}
+
+impl FromStr for ClickAction {
+    type Err = ();
+
+    fn from_str(action: &str) -> Result<Self, Self::Err> {
+        let action = action.trim();
+        if let Some(name) = action.strip_prefix("event:") {
+            let name = name.trim();
+            return (!name.is_empty())
+                .then(|| Self::Emit(name.to_string()))
+                .ok_or(());
+        }
+        let (tag, animations) = match action.strip_prefix("tag:") {
+            Some(tagged) => {
+                let (tag, otherwise) = tagged.split_once(',').unwrap_or((tagged, ""));
+                (Some(tag.trim().to_lowercase()), otherwise)
+            }
+            None => (None, action),
+        };
+        let animations: Vec<String> = animations
+            .split(',')
+            .map(|animation| animation.trim().to_uppercase())
+            .filter(|animation| !animation.is_empty())
+            .collect();
+        match tag {
+            Some(tag) if !tag.is_empty() => Ok(Self::Tagged {
+                tag,
+                otherwise: animations,
+            }),
+            Some(_) => Err(()),
+            None if animations.is_empty() => Err(()),
+            None => Ok(Self::Animations(animations)),
+        }
+    }
+}
+
+/// Plays an animation or raises an event on a click, per mouse button. The gremlin's `[clicks]`
+/// go over these bindings button by button.
+pub struct GremlinClick {
+    bindings: HashMap<MouseButton, ClickAction>,
+    // clicks with a button that played something less than this long ago are dropped, so
+    // mashing the gremlin doesn't queue up minutes of CLICK -> IDLE
+    cooldown: Duration,
+    last_played: HashMap<MouseButton, Instant>,
+}
+
+impl Default for GremlinClick {
+    fn default() -> Self {
+        Self {
+            bindings: HashMap::from([
+                (
+                    MouseButton::Left,
+                    ClickAction::Tagged {
+                        tag: "reaction".to_string(),
+                        otherwise: vec!["CLICK".to_string()],
+                    },
+                ),
+                (
+                    MouseButton::Middle,
+                    ClickAction::Animations(vec!["SPIN".to_string()]),
+                ),
+                (
+                    MouseButton::Right,
+                    ClickAction::Emit(MENU_TOGGLE_EVENT.to_string()),
+                ),
+            ]),
+            cooldown: DEFAULT_COOLDOWN,
+            last_played: HashMap::new(),
+        }
+    }
+}
 
 impl GremlinClick {
     pub fn new() -> Box<Self> {
         Default::default()
     }
+
+    /// replaces the default bindings, buttons left out of `bindings` do nothing
+    #[allow(unused)]
+    pub fn with_bindings(bindings: HashMap<MouseButton, ClickAction>) -> Box<Self> {
+        Box::new(Self {
+            bindings,
+            ..Default::default()
+        })
+    }
+
+    /// default bindings, but with `cooldown` between two plays off the same button
+    #[allow(unused)]
+    pub fn with_cooldown(cooldown: Duration) -> Box<Self> {
+        Box::new(Self {
+            cooldown,
+            ..Default::default()
+        })
+    }
 }
 
 impl Behavior for GremlinClick {
-    fn setup(&mut self, _: &mut crate::gremlin::DesktopGremlin) {}
-
-    fn update(&mut self, application: &mut DesktopGremlin, context: &super::ContextData) {
-        if let Some(_) = context.events.get(&crate::events::Event::Click {
-            mouse_btn: crate::events::MouseButton::Left,
-        }) {
-            let _ = application
-                .task_channel
-                .0
-                .send(GremlinTask::PlayInterrupt("CLICK".to_string()));
-            let _ = application
-                .task_channel
-                .0
-                .send(GremlinTask::Play("IDLE".to_string()));
+    fn setup(&mut self, _: &mut DesktopGremlin) {}
+
+    fn update(&mut self, application: &mut DesktopGremlin, context: &ContextData) {
+        // every click counts, not just one per button per frame
+        context.on::<Click>(|click| {
+            let action = application
+                .current_gremlin
+                .as_ref()
+                .and_then(|gremlin| gremlin.clicks.get(&click.mouse_btn))
+                .or_else(|| self.bindings.get(&click.mouse_btn))
+                .cloned();
+            let Some(action) = action else {
+                return;
+            };
+            match action {
+                ClickAction::Animations(_) | ClickAction::Tagged { .. } => {
+                    if self
+                        .last_played
+                        .get(&click.mouse_btn)
+                        .is_some_and(|played_at| played_at.elapsed() < self.cooldown)
+                    {
+                        return;
+                    }
+                    let Some(gremlin) = &application.current_gremlin else {
+                        return;
+                    };
+                    // only pick between animations this gremlin actually ships with
+                    let candidates: Vec<&str> = action
+                        .animations(gremlin)
+                        .into_iter()
+                        .filter(|name| gremlin.has_animation(name))
+                        .collect();
+                    let Some(animation) = candidates.choose(&mut application.rng) else {
+                        return;
+                    };
+                    self.last_played.insert(click.mouse_btn, Instant::now());
+                    let _ = application
+                        .task_channel
+                        .0
+                        .send(GremlinTask::PlayInterrupt(animation.to_string()));
+                    let _ = application.task_channel.0.send(GremlinTask::Play(
+                        application.default_animation().to_string(),
+                    ));
+                }
+                ClickAction::Emit(name) => {
+                    application.emit(Event::Custom(name), None);
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_click_actions() {
+        assert!(matches!(
+            "click, pat".parse(),
+            Ok(ClickAction::Animations(animations)) if animations == ["CLICK", "PAT"]
+        ));
+        assert!(matches!(
+            "tag:Reaction,SPIN".parse(),
+            Ok(ClickAction::Tagged { tag, otherwise }) if tag == "reaction" && otherwise == ["SPIN"]
+        ));
+        assert!(matches!(
+            "event:menu".parse(),
+            Ok(ClickAction::Emit(name)) if name == "menu"
+        ));
+        for malformed in ["", " , ", "tag:", "event: "] {
+            assert!(malformed.parse::<ClickAction>().is_err());
         }
     }
 }
@@ -0,0 +1,226 @@
+use std::{
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use rand::Rng;
+use sdl3::{rect::Rect, render::Texture};
+use tracing::{error, warn};
+
+use crate::{
+    backend::{SdlBackend, WindowBackend},
+    behavior::{Behavior, ContextData, create_sheet_texture},
+    events::{MouseButton, kinds::Click},
+    gremlin::{
+        Animation, AnimationProperties, Animator, DesktopGremlin, GremlinTask, LaunchArguments,
+        Scaling, see_through,
+    },
+};
+
+const DEFAULT_MAX_CLONES: usize = 8;
+const DEFAULT_LIFETIME: Duration = Duration::from_secs(20);
+// of the original's size
+const CLONE_SCALE: f32 = 0.5;
+// this many clicks in a row, each within `CLICK_STREAK_GAP` of the last, and the gremlin multiplies
+const CLICKS_TO_MULTIPLY: u32 = 7;
+const CLICK_STREAK_GAP: Duration = Duration::from_millis(400);
+
+/// A smaller copy of a gremlin in a window of its own, tagging along with the original.
+struct MiniClone {
+    window: SdlBackend,
+    animator: Animator,
+    texture: Rc<Texture>,
+    offset: (i32, i32),
+    spawned_at: Instant,
+    last_frame: Option<Rect>,
+}
+
+/// Runs the mini clones `GremlinTask::Spawn` asks for, at most `max_clones` at a time, each
+/// despawning on its own after `lifetime`. Clicking the gremlin fast enough spawns one too.
+pub struct GremlinClones {
+    max_clones: usize,
+    lifetime: Duration,
+    clones: Vec<MiniClone>,
+    click_streak: u32,
+    last_click: Option<Instant>,
+}
+
+impl Default for GremlinClones {
+    fn default() -> Self {
+        Self {
+            max_clones: DEFAULT_MAX_CLONES,
+            lifetime: DEFAULT_LIFETIME,
+            clones: Vec::new(),
+            click_streak: 0,
+            last_click: None,
+        }
+    }
+}
+
+impl GremlinClones {
+    pub fn new() -> Box<Self> {
+        Default::default()
+    }
+
+    #[allow(unused)]
+    pub fn with_limits(max_clones: usize, lifetime: Duration) -> Box<Self> {
+        Box::new(Self {
+            max_clones,
+            lifetime,
+            ..Default::default()
+        })
+    }
+
+    /// IDLE of `gremlin_path` or the current gremlin, decoded right away, clones are rare enough
+    fn idle_animation(
+        application: &mut DesktopGremlin,
+        gremlin_path: Option<&str>,
+    ) -> Option<(Animation, Scaling)> {
+        let loaded;
+        let gremlin = match gremlin_path {
+            Some(path) => {
+                loaded = application
+                    .load_gremlin(path.to_string())
+                    .inspect_err(|err| error!("couldn't load {path} to clone: {err}"))
+                    .ok()?;
+                &loaded
+            }
+            None => application.current_gremlin.as_ref()?,
+        };
+        let resolved_name = gremlin.resolve_animation(gremlin.metadata.default_animation())?;
+        let props = gremlin.animation_map.get(&resolved_name)?;
+        let animation = <&AnimationProperties as TryInto<Animation>>::try_into(props)
+            .inspect_err(|err| error!("couldn't load {resolved_name} to clone: {err}"))
+            .ok()?;
+        Some((animation, gremlin.scaling))
+    }
+
+    fn spawn(
+        &mut self,
+        application: &mut DesktopGremlin,
+        gremlin_path: Option<String>,
+        offset: (i32, i32),
+    ) {
+        if self.clones.len() >= self.max_clones {
+            return;
+        }
+        let Some(video) = application.sdl.as_ref().and_then(|sdl| sdl.video().ok()) else {
+            return;
+        };
+        let Some((animation, scaling)) = Self::idle_animation(application, gremlin_path.as_deref())
+        else {
+            return;
+        };
+
+        let launch_arguments = LaunchArguments::default();
+        let (width, height) = application.window.size();
+        let size = (
+            ((width as f32 * CLONE_SCALE) as u32).max(1),
+            ((height as f32 * CLONE_SCALE) as u32).max(1),
+        );
+        let window = match video
+            .window(&launch_arguments.title, size.0, size.1)
+            .set_window_flags(launch_arguments.window_flags())
+            .build()
+        {
+            Ok(window) => window,
+            Err(err) => {
+                warn!("couldn't open a window for a clone: {err}");
+                return;
+            }
+        };
+        see_through(&window, true);
+
+        let mut window = SdlBackend {
+            canvas: window.into_canvas(),
+        };
+        let mut animator: Animator = (&animation).into();
+        let Some(texture) = create_sheet_texture(&mut window, &animation, &mut animator, scaling)
+        else {
+            return;
+        };
+        let (x, y) = application.window.position();
+        window.set_position(x + offset.0, y + offset.1);
+        self.clones.push(MiniClone {
+            window,
+            animator,
+            texture,
+            offset,
+            spawned_at: Instant::now(),
+            last_frame: None,
+        });
+    }
+
+    /// counts fast clicks on the gremlin, the streak breaks on a pause
+    fn count_clicks(&mut self, application: &mut DesktopGremlin, context: &ContextData) {
+        let mut clicks = 0;
+        context.on::<Click>(|click| {
+            if click.mouse_btn == MouseButton::Left {
+                clicks += 1;
+            }
+        });
+        if clicks == 0 {
+            return;
+        }
+        if self
+            .last_click
+            .is_none_or(|last_click| last_click.elapsed() > CLICK_STREAK_GAP)
+        {
+            self.click_streak = 0;
+        }
+        self.last_click = Some(Instant::now());
+        self.click_streak += clicks;
+        if self.click_streak < CLICKS_TO_MULTIPLY {
+            return;
+        }
+
+        self.click_streak = 0;
+        let (width, height) = application.window.size();
+        let offset = (
+            application.rng.random_range(-(width as i32)..=width as i32),
+            application.rng.random_range(0..=height as i32 / 2),
+        );
+        let _ = application.task_channel.0.send(GremlinTask::Spawn {
+            gremlin: None,
+            offset,
+        });
+    }
+}
+
+impl Behavior for GremlinClones {
+    fn setup(&mut self, _: &mut DesktopGremlin) {}
+
+    fn update(&mut self, application: &mut DesktopGremlin, context: &ContextData) {
+        self.count_clicks(application, context);
+        while let Some((gremlin_path, offset)) = application.spawn_requests.pop_front() {
+            self.spawn(application, gremlin_path, offset);
+        }
+
+        // dropping a clone closes its window
+        self.clones
+            .retain(|clone| clone.spawned_at.elapsed() < self.lifetime);
+
+        let (x, y) = application.window.position();
+        let frame_tick = application.frame_tick().mul_f32(application.speed);
+        for clone in &mut self.clones {
+            let target = (x + clone.offset.0, y + clone.offset.1);
+            if clone.window.position() != target {
+                clone.window.set_position(target.0, target.1);
+            }
+            let frame = clone.animator.get_frame_rect();
+            if clone.last_frame != Some(frame) {
+                clone.window.draw_frame(Some(&clone.texture), frame, &[]);
+                clone.window.present();
+                clone.last_frame = Some(frame);
+            }
+            if clone.animator.advance(frame_tick) {
+                clone.animator.restart();
+            }
+            if let Some(until_next_frame) = clone.animator.until_next_frame()
+                && application.speed > 0.0
+            {
+                application.idle_for(until_next_frame.div_f32(application.speed));
+            }
+        }
+    }
+}
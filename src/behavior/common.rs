@@ -1,25 +1,69 @@
+use std::time::{Duration, Instant};
+
+use tracing::{error, info, warn};
+
 use super::Behavior;
+use crate::{events::kinds::Quit, gremlin::Gremlin};
+
+const DEFAULT_GREMLIN_PATH: &str =
+    r"C:\Users\ASUS\Documents\Projects\desktop_gremlin\assets\Gremlins\Mambo\config.txt";
+
+const FALLBACK_MESSAGE_DURATION: Duration = Duration::from_secs(10);
+// an OUTRO holding a frame for good would otherwise keep the gremlin from ever quitting
+const OUTRO_TIMEOUT: Duration = Duration::from_secs(10);
 
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, Default)]
-pub struct CommonBehavior {}
+pub struct CommonBehavior {
+    quit_at: Option<Instant>,
+}
 
 impl Behavior for CommonBehavior {
     fn setup(&mut self, application: &mut crate::gremlin::DesktopGremlin) {
-        application.current_gremlin = application
-            .load_gremlin(
-                r"C:\Users\ASUS\Documents\Projects\desktop_gremlin\assets\Gremlins\Mambo\config.txt".to_string()
-            )
-            .ok();
+        // the restored gremlin might have been deleted since, fall back to the default one
+        let candidates = application
+            .gremlin_path
+            .clone()
+            .into_iter()
+            .chain(Some(DEFAULT_GREMLIN_PATH.to_string()));
+        for gremlin_path in candidates {
+            match application.load_gremlin(gremlin_path.clone()) {
+                Ok(gremlin) => {
+                    match gremlin.metadata.byline() {
+                        Some(byline) => info!("loaded {} {byline}", gremlin.name),
+                        None => info!("loaded {}", gremlin.name),
+                    }
+                    application.current_gremlin = Some(gremlin);
+                    application.gremlin_path = Some(gremlin_path);
+                    application.fit_window_to_gremlin();
+                    application.schedule_routine();
+                    break;
+                }
+                Err(err) => error!("couldn't load {gremlin_path}: {err}"),
+            }
+        }
+        if application.current_gremlin.is_none() {
+            application.current_gremlin = Some(Gremlin::fallback());
+            application.fit_window_to_gremlin();
+            application.say(
+                "couldn't load a gremlin, check the logs",
+                FALLBACK_MESSAGE_DURATION,
+            );
+        }
 
         let _ = application
             .task_channel
             .0
             .send(crate::gremlin::GremlinTask::Play("INTRO".to_string()));
 
+        let default_animation = application.default_animation().to_string();
         let _ = application
             .task_channel
             .0
-            .send(crate::gremlin::GremlinTask::Play("IDLE".to_string()));
+            .send(crate::gremlin::GremlinTask::Play(default_animation));
+    }
+
+    fn runs_while_paused(&self) -> bool {
+        true
     }
 
     fn update(
@@ -27,8 +71,18 @@ impl Behavior for CommonBehavior {
         application: &mut crate::gremlin::DesktopGremlin,
         context: &super::ContextData,
     ) {
-        if let Some(_) = context.events.get(&crate::events::Event::Quit) {
-            application.task_queue.clear();
+        let mut is_quitting = false;
+        context.on::<Quit>(|_| is_quitting = true);
+        if is_quitting {
+            self.quit_at.get_or_insert_with(Instant::now);
+            // slowed way down the goodbye could take forever
+            application.speed = 1.0;
+            application.cancel_animations();
+            // the goodbye can't play while frozen
+            let _ = application
+                .task_channel
+                .0
+                .send(crate::gremlin::GremlinTask::Resume);
             let _ = application
                 .task_channel
                 .0
@@ -36,6 +90,15 @@ impl Behavior for CommonBehavior {
                     "OUTRO".to_string(),
                 ));
         }
+        if self
+            .quit_at
+            .is_some_and(|quit_at| quit_at.elapsed() >= OUTRO_TIMEOUT)
+            && let Ok(mut should_exit) = application.should_exit.lock()
+            && !*should_exit
+        {
+            warn!("OUTRO didn't finish in time, quitting anyway");
+            *should_exit = true;
+        }
     }
 }
 
@@ -1,67 +1,134 @@
+use std::time::{Duration, Instant};
+
 use super::Behavior;
 use crate::behavior::ContextData;
-use crate::events::{Event, EventData, MouseButton};
+use crate::events::{
+    MouseButton,
+    kinds::{DragEnd, DragStart},
+};
 use crate::gremlin::{DesktopGremlin, GremlinTask};
-use crate::utils::get_window_pos;
+use crate::utils::{Easing, get_cursor_position};
+
+const SNAP_DURATION: Duration = Duration::from_millis(250);
 
 #[derive(Default, Debug, Clone)]
 pub struct GremlinDrag {
-    should_move: bool,
-    drag_start_x: i32,
-    drag_start_y: i32,
+    // where in the window the gremlin was grabbed, `None` when it isn't being dragged
+    grab_offset: Option<(f32, f32)>,
+    // `DesktopGremlin::snap_distance` to start with when nothing else set one
+    snap_distance: Option<u32>,
+    snap: Option<Snap>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Snap {
+    from: (i32, i32),
+    to: (i32, i32),
+    started_at: Instant,
 }
 
 impl GremlinDrag {
     pub fn new() -> Box<Self> {
         Box::new(Default::default())
     }
+
+    /// snaps to the nearest screen edge or corner when let go within `snap_distance` pixels of it,
+    /// unless the user picked another distance with `send snap`
+    #[allow(unused)]
+    pub fn with_snap(snap_distance: u32) -> Box<Self> {
+        Box::new(Self {
+            snap_distance: Some(snap_distance),
+            ..Default::default()
+        })
+    }
+
+    /// where the window ends up when stuck to every edge of the display it's within reach of
+    fn snap_target(&self, application: &DesktopGremlin) -> Option<(i32, i32)> {
+        let snap_distance = application.snap_distance? as i32;
+        let bounds = application.window.usable_bounds()?;
+        let window = application.window.rect();
+
+        let snap_axis = |start: i32, end: i32, min: i32, max: i32| {
+            if (start - min).abs() <= snap_distance {
+                min
+            } else if (max - end).abs() <= snap_distance {
+                max - (end - start)
+            } else {
+                start
+            }
+        };
+        let target = (
+            snap_axis(window.left(), window.right(), bounds.left(), bounds.right()),
+            snap_axis(window.top(), window.bottom(), bounds.top(), bounds.bottom()),
+        );
+        (target != (window.x(), window.y())).then_some(target)
+    }
 }
 
 impl Behavior for GremlinDrag {
     fn update(&mut self, application: &mut DesktopGremlin, context: &ContextData) {
-        if let Some(Some(EventData::FCoordinate { x, y })) = context.events.get(&Event::DragStart {
-            mouse_btn: MouseButton::Left,
-        }) {
+        let mut grabbed_at = None;
+        context.on::<DragStart>(|drag| {
+            if drag.mouse_btn == MouseButton::Left {
+                grabbed_at = Some((drag.x, drag.y));
+            }
+        });
+        if let Some((x, y)) = grabbed_at {
             let _ = application
                 .task_channel
                 .0
                 .send(GremlinTask::PlayInterrupt("GRAB".to_string()));
 
-            application.task_queue.clear();
+            application.cancel_animations();
 
-            (self.drag_start_x, self.drag_start_y) = (x.round() as i32, y.round() as i32);
+            self.grab_offset = Some((x, y));
+            self.snap = None;
         }
 
-        if let Some(Some(EventData::Difference { x, y, .. })) = context.events.get(&Event::Drag {
-            mouse_btn: MouseButton::Left,
-        }) {
-            if self.should_move {
-                let (gremlin_x, gremlin_y) = get_window_pos(&application.canvas);
-                application.canvas.window_mut().set_position(
-                    sdl3::video::WindowPos::Positioned(
-                        gremlin_x.saturating_add(((x.round() as i32) - self.drag_start_x) as i32),
-                    ),
-                    sdl3::video::WindowPos::Positioned(
-                        gremlin_y.saturating_add(((y.round() as i32) - self.drag_start_y) as i32),
-                    ),
-                );
-            }
-            self.should_move = !self.should_move;
+        // keep the grabbed spot right under the cursor
+        if let Some((offset_x, offset_y)) = self.grab_offset {
+            let (cursor_x, cursor_y) = get_cursor_position();
+            application.window.set_position(
+                (cursor_x - offset_x).round() as i32,
+                (cursor_y - offset_y).round() as i32,
+            );
         }
 
-        if let Some(_) = context.events.get(&Event::DragEnd {
-            mouse_btn: MouseButton::Left,
-        }) {
+        let mut is_dropped = false;
+        context.on::<DragEnd>(|drag| is_dropped |= drag.mouse_btn == MouseButton::Left);
+        if is_dropped {
+            self.grab_offset = None;
             let _ = application
                 .task_channel
                 .0
                 .send(GremlinTask::PlayInterrupt("PAT".to_string()));
-            let _ = application
-                .task_channel
-                .0
-                .send(GremlinTask::Play("IDLE".to_string()));
+            let _ = application.task_channel.0.send(GremlinTask::Play(
+                application.default_animation().to_string(),
+            ));
+
+            self.snap = self.snap_target(application).map(|to| Snap {
+                from: application.window.position(),
+                to,
+                started_at: Instant::now(),
+            });
+        }
+
+        if let Some(snap) = self.snap {
+            let progress = snap.started_at.elapsed().as_secs_f32() / SNAP_DURATION.as_secs_f32();
+            let eased = Easing::EaseOut.apply(progress);
+            application.window.set_position(
+                snap.from.0 + ((snap.to.0 - snap.from.0) as f32 * eased).round() as i32,
+                snap.from.1 + ((snap.to.1 - snap.from.1) as f32 * eased).round() as i32,
+            );
+            if progress >= 1.0 {
+                self.snap = None;
+            }
         }
     }
 
-    fn setup(&mut self, _: &mut DesktopGremlin) {}
+    fn setup(&mut self, application: &mut DesktopGremlin) {
+        if application.snap_distance.is_none() {
+            application.snap_distance = self.snap_distance;
+        }
+    }
 }
@@ -0,0 +1,588 @@
+use std::{
+    fs,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use anyhow::anyhow;
+use image::DynamicImage;
+use sdl3::{
+    pixels::Color,
+    rect::Rect,
+    render::{FRect, Texture},
+};
+use tracing::warn;
+
+use crate::{
+    backend::SdlBackend,
+    behavior::{Behavior, ContextData},
+    events::{Event, kinds},
+    gremlin::{
+        AnimationProperties, DEFAULT_COLUMN_COUNT, DEFAULT_FRAMERATE, DesktopGremlin, FRAME_TICK,
+        Scaling,
+    },
+    pack,
+    ui::{Render, text::Text},
+    utils::{sdl_resize, set_manifest_value},
+};
+
+/// opens the animation editor or closes it, raised by the `editor` ipc command
+pub const EDITOR_TOGGLE_EVENT: &str = "editor:toggle";
+const WINDOW_SIZE: (u32, u32) = (760, 520);
+const MARGIN: f32 = 8.0;
+const LIST_WIDTH: f32 = 200.0;
+const LINE_HEIGHT: f32 = 18.0;
+const TEXT_SIZE: f32 = 14.0;
+const PREVIEW_SIZE: f32 = 256.0;
+const BACKGROUND: Color = Color::RGB(0xf4, 0xf4, 0xf4);
+const PREVIEW_BACKGROUND: Color = Color::RGB(0xff, 0xff, 0xff);
+const SELECTED: Color = Color::RGB(0xc8, 0xdc, 0xff);
+const GRID: Color = Color::RGBA(0x30, 0x60, 0xff, 0x80);
+const CURRENT_FRAME: Color = Color::RGB(0xff, 0x40, 0x40);
+const WARNING: Color = Color::RGB(0xc0, 0x20, 0x20);
+const MAX_FPS: f32 = 240.0;
+const HELP: &str = "up/down: animation   left/right: frames   [ ]: columns   - =: fps   \
+    L: loop   space: restart   S: save to the manifest   esc: close";
+
+/// How the animation being previewed gets cut up and played, starting from what the manifest says.
+#[derive(Debug, Clone, PartialEq)]
+struct Settings {
+    frame_count: u32,
+    columns: u32,
+    fps: f32,
+    is_looping: bool,
+}
+
+struct Sheet {
+    texture: Texture,
+    size: (u32, u32),
+}
+
+/// The editor's own window, see `GremlinEditor`.
+struct Editor {
+    window: SdlBackend,
+    // the gremlin's animations, sorted
+    names: Vec<String>,
+    selected: usize,
+    sheet: Option<Sheet>,
+    frame_durations: Vec<Duration>,
+    // what the manifest says, to tell what changed
+    saved: Settings,
+    settings: Settings,
+    frame: u32,
+    next_frame_at: Instant,
+    // how loading or saving went
+    status: Option<(String, Color)>,
+    // the list and the numbers, only drawn again when they change
+    panel: Option<Texture>,
+}
+
+impl Editor {
+    fn open(application: &DesktopGremlin) -> Option<Self> {
+        let gremlin = application.current_gremlin.as_ref()?;
+        let mut names = gremlin.animation_map.keys().cloned().collect::<Vec<_>>();
+        if names.is_empty() {
+            warn!("{} has no animations to edit", gremlin.name);
+            return None;
+        }
+        names.sort();
+        let video = application.sdl.as_ref()?.video().ok()?;
+        let window = video
+            .window(
+                &format!("{} animations", gremlin.name),
+                WINDOW_SIZE.0,
+                WINDOW_SIZE.1,
+            )
+            .position_centered()
+            .build()
+            .inspect_err(|err| warn!("couldn't open the editor: {err}"))
+            .ok()?;
+        let settings = Settings {
+            frame_count: 1,
+            columns: DEFAULT_COLUMN_COUNT,
+            fps: DEFAULT_FRAMERATE as f32,
+            is_looping: true,
+        };
+        let mut editor = Self {
+            window: SdlBackend {
+                canvas: window.into_canvas(),
+            },
+            names,
+            selected: 0,
+            sheet: None,
+            frame_durations: Vec::new(),
+            saved: settings.clone(),
+            settings,
+            frame: 0,
+            next_frame_at: Instant::now(),
+            status: None,
+            panel: None,
+        };
+        editor.select(application, 0);
+        Some(editor)
+    }
+
+    fn window_id(&self) -> u32 {
+        self.window.canvas.window().id()
+    }
+
+    fn select(&mut self, application: &DesktopGremlin, index: usize) {
+        let Some(gremlin) = &application.current_gremlin else {
+            return;
+        };
+        self.selected = index.min(self.names.len() - 1);
+        self.status = None;
+        if let Some(sheet) = self.sheet.take() {
+            unsafe { sheet.texture.destroy() };
+        }
+        let name = &self.names[self.selected];
+        let Some(properties) = gremlin.animation_map.get(name) else {
+            return;
+        };
+        let speed = gremlin.speeds.get(name).copied().unwrap_or(1.0);
+        self.saved = Settings {
+            frame_count: properties.sprite_count.max(1),
+            columns: DEFAULT_COLUMN_COUNT,
+            fps: (DEFAULT_FRAMERATE as f32 * speed).clamp(1.0, MAX_FPS),
+            is_looping: true,
+        };
+        self.settings = self.saved.clone();
+        self.frame_durations = properties.frame_durations.clone();
+        match self.load_sheet(properties, gremlin.scaling) {
+            Ok(sheet) => self.sheet = Some(sheet),
+            Err(err) => self.status = Some((format!("couldn't load the sheet: {err:#}"), WARNING)),
+        }
+        self.restart();
+    }
+
+    /// the sheet as it is on disk, without the checks `Animation` makes, those are what the
+    /// editor is for
+    fn load_sheet(
+        &mut self,
+        properties: &AnimationProperties,
+        scaling: Scaling,
+    ) -> anyhow::Result<Sheet> {
+        let image = match (properties.sprite_bytes, &properties.sprite_path) {
+            (Some(bytes), _) => image::load_from_memory(bytes)?,
+            (None, Some(path)) => image::open(path)?,
+            (None, None) => return Err(anyhow!("there's no png for it next to the manifest")),
+        };
+        // img_get_bytes_global hands the raw buffer straight to sdl
+        let image = DynamicImage::from(image.into_rgba8());
+        let size = (image.width(), image.height());
+        let texture = sdl_resize(&image, size, scaling, &mut self.window.canvas)?;
+        Ok(Sheet { texture, size })
+    }
+
+    fn restart(&mut self) {
+        self.frame = 0;
+        self.next_frame_at = Instant::now() + self.frame_duration();
+        self.redraw_panel();
+    }
+
+    fn redraw_panel(&mut self) {
+        if let Some(panel) = self.panel.take() {
+            unsafe { panel.destroy() };
+        }
+    }
+
+    /// how long the current frame stays up at the fps being previewed
+    fn frame_duration(&self) -> Duration {
+        self.frame_durations
+            .get(self.frame as usize)
+            .or(self.frame_durations.last())
+            .copied()
+            .unwrap_or(FRAME_TICK)
+            .mul_f32(DEFAULT_FRAMERATE as f32 / self.settings.fps)
+    }
+
+    fn step(&mut self, now: Instant) {
+        if now < self.next_frame_at {
+            return;
+        }
+        if self.frame + 1 < self.settings.frame_count {
+            self.frame += 1;
+        } else if self.settings.is_looping {
+            self.frame = 0;
+        } else {
+            // stays on the last frame until restarted
+            self.next_frame_at = now + Duration::from_secs(60 * 60);
+            return;
+        }
+        self.next_frame_at = now + self.frame_duration();
+    }
+
+    fn press(&mut self, application: &mut DesktopGremlin, key: &str) {
+        let settings = &mut self.settings;
+        match key {
+            "Up" => return self.select(application, self.selected.saturating_sub(1)),
+            "Down" => return self.select(application, self.selected + 1),
+            "Left" => settings.frame_count = settings.frame_count.saturating_sub(1).max(1),
+            "Right" => settings.frame_count += 1,
+            "[" => settings.columns = settings.columns.saturating_sub(1).max(1),
+            "]" => settings.columns += 1,
+            "-" => settings.fps = (settings.fps.round() - 1.0).max(1.0),
+            "=" => settings.fps = (settings.fps.round() + 1.0).min(MAX_FPS),
+            "L" => settings.is_looping = !settings.is_looping,
+            "Space" => {}
+            "S" => self.save(application),
+            _ => return,
+        }
+        self.restart();
+    }
+
+    /// writes the frame count and fps (as a `[speeds]` entry) into the manifest and the loaded
+    /// gremlin, columns and looping are only for previewing
+    fn save(&mut self, application: &mut DesktopGremlin) {
+        let name = self.names[self.selected].clone();
+        self.status = Some(match self.write_manifest(application, &name) {
+            Ok(()) => {
+                if let Some(gremlin) = &mut application.current_gremlin {
+                    if let Some(properties) = gremlin.animation_map.get_mut(&name) {
+                        properties.sprite_count = self.settings.frame_count;
+                    }
+                    if self.settings.fps != self.saved.fps {
+                        gremlin.speeds.insert(name.to_uppercase(), self.speed());
+                    }
+                }
+                // the cached sheets were cut up for the old frame count
+                if let Ok(mut cache) = application.texture_cache.lock() {
+                    cache.clear();
+                }
+                self.saved.frame_count = self.settings.frame_count;
+                self.saved.fps = self.settings.fps;
+                (
+                    "saved, it plays like this from the next time it starts".to_string(),
+                    Color::RGB(0x20, 0x80, 0x20),
+                )
+            }
+            Err(err) => (format!("couldn't save: {err:#}"), WARNING),
+        });
+    }
+
+    fn speed(&self) -> f32 {
+        // two decimals is plenty and keeps the manifest readable
+        (self.settings.fps / DEFAULT_FRAMERATE as f32 * 100.0).round() / 100.0
+    }
+
+    fn write_manifest(&self, application: &DesktopGremlin, name: &str) -> anyhow::Result<()> {
+        let path = application
+            .gremlin_path
+            .as_ref()
+            .ok_or_else(|| anyhow!("this gremlin is built in, there's no manifest"))?;
+        if pack::is_pack(Path::new(path)) {
+            return Err(anyhow!(
+                "packs are read only, edit the manifest it was packed from"
+            ));
+        }
+        let mut manifest = fs::read_to_string(path)?;
+        if self.settings.frame_count != self.saved.frame_count {
+            manifest =
+                set_manifest_value(&manifest, "", name, &self.settings.frame_count.to_string());
+        }
+        if self.settings.fps != self.saved.fps {
+            manifest = set_manifest_value(&manifest, "speeds", name, &self.speed().to_string());
+        }
+        fs::write(path, manifest)?;
+        Ok(())
+    }
+
+    /// where frame `frame` is on the sheet, cut up the way it's being previewed
+    fn frame_rect(&self, sheet: &Sheet, frame: u32) -> Rect {
+        let columns = self.settings.columns.max(1);
+        let rows = self.settings.frame_count.div_ceil(columns).max(1);
+        let (width, height) = (
+            (sheet.size.0 / columns).max(1),
+            (sheet.size.1 / rows).max(1),
+        );
+        Rect::new(
+            ((frame % columns) * width) as i32,
+            ((frame / columns) * height) as i32,
+            width,
+            height,
+        )
+    }
+
+    /// why the frames might come out wrong
+    fn problems(&self) -> Vec<String> {
+        let Some(sheet) = &self.sheet else {
+            return Vec::new();
+        };
+        let columns = self.settings.columns.max(1);
+        let rows = self.settings.frame_count.div_ceil(columns).max(1);
+        let mut problems = Vec::new();
+        if sheet.size.0 % columns != 0 {
+            problems.push(format!(
+                "{}px wide doesn't split into {columns} columns",
+                sheet.size.0
+            ));
+        }
+        if sheet.size.1 % rows != 0 {
+            problems.push(format!(
+                "{}px high doesn't split into {rows} rows",
+                sheet.size.1
+            ));
+        }
+        if columns != DEFAULT_COLUMN_COUNT {
+            problems.push(format!(
+                "only {DEFAULT_COLUMN_COUNT} column sheets load, re-export it with {DEFAULT_COLUMN_COUNT}"
+            ));
+        }
+        problems
+    }
+
+    fn draw_panel(&mut self) -> anyhow::Result<Texture> {
+        let lines = {
+            let settings = &self.settings;
+            let changed = |is_changed: bool| if is_changed { " *" } else { "" };
+            let mut lines = vec![
+                (self.names[self.selected].clone(), None),
+                (
+                    format!(
+                        "frames: {}{}",
+                        settings.frame_count,
+                        changed(settings.frame_count != self.saved.frame_count)
+                    ),
+                    None,
+                ),
+                (format!("columns: {}", settings.columns), None),
+                (
+                    format!(
+                        "fps: {:.0}{}",
+                        settings.fps,
+                        changed(settings.fps != self.saved.fps)
+                    ),
+                    None,
+                ),
+                (
+                    format!("loop: {}", if settings.is_looping { "on" } else { "off" }),
+                    None,
+                ),
+            ];
+            if let Some(sheet) = &self.sheet {
+                let frame = self.frame_rect(sheet, 0);
+                lines.push((
+                    format!(
+                        "sheet {}x{}, frames {}x{}",
+                        sheet.size.0,
+                        sheet.size.1,
+                        frame.width(),
+                        frame.height()
+                    ),
+                    None,
+                ));
+            }
+            lines.extend(
+                self.problems()
+                    .into_iter()
+                    .map(|problem| (problem, Some(WARNING))),
+            );
+            lines.extend(
+                self.status
+                    .clone()
+                    .map(|(status, color)| (status, Some(color))),
+            );
+            lines
+        };
+
+        let canvas = &mut self.window.canvas;
+        let (width, height) = WINDOW_SIZE;
+        let mut panel = canvas
+            .texture_creator()
+            .create_texture_target(None, width, height)?;
+        let visible = ((height as f32 - 2.0 * MARGIN) / LINE_HEIGHT) as usize;
+        // keeps the selected animation in view
+        let first = self
+            .selected
+            .saturating_sub(visible / 2)
+            .min(self.names.len().saturating_sub(visible));
+        let (names, selected) = (&self.names, self.selected);
+        canvas.with_texture_canvas(&mut panel, |canvas| {
+            canvas.set_draw_color(BACKGROUND);
+            canvas.clear();
+            for (row, name) in names.iter().enumerate().skip(first).take(visible) {
+                let y = MARGIN + (row - first) as f32 * LINE_HEIGHT;
+                if row == selected {
+                    canvas.set_draw_color(SELECTED);
+                    let _ = canvas.fill_rect(FRect::new(MARGIN, y, LIST_WIDTH, LINE_HEIGHT));
+                }
+                let _ = Text::new(name.as_str())
+                    .size(TEXT_SIZE)
+                    .render_canvas(canvas, Some(FRect::new(MARGIN * 2.0, y, 0.0, 0.0)));
+            }
+            let x = LIST_WIDTH + PREVIEW_SIZE + MARGIN * 3.0;
+            let max_width = width as f32 - x - MARGIN;
+            let mut y = MARGIN;
+            for (line, color) in lines {
+                let mut text = Text::new(line).size(TEXT_SIZE).max_width(max_width);
+                if let Some(color) = color {
+                    text.color = color;
+                }
+                let _ = text.render_canvas(canvas, Some(FRect::new(x, y, 0.0, 0.0)));
+                y += text.rasterize().map_or(LINE_HEIGHT, |(_, height, _)| {
+                    height as f32 + LINE_HEIGHT - TEXT_SIZE
+                });
+            }
+            let _ = Text::new(HELP)
+                .size(TEXT_SIZE)
+                .max_width(width as f32 - LIST_WIDTH - MARGIN * 3.0)
+                .render_canvas(
+                    canvas,
+                    Some(FRect::new(
+                        LIST_WIDTH + MARGIN * 2.0,
+                        height as f32 - MARGIN - LINE_HEIGHT * 2.0,
+                        0.0,
+                        0.0,
+                    )),
+                );
+        })?;
+        Ok(panel)
+    }
+
+    fn draw(&mut self) {
+        if self.panel.is_none() {
+            self.panel = self
+                .draw_panel()
+                .inspect_err(|err| warn!("couldn't draw the editor: {err:#}"))
+                .ok();
+        }
+        let preview_area = FRect::new(
+            LIST_WIDTH + MARGIN * 2.0,
+            MARGIN,
+            PREVIEW_SIZE,
+            PREVIEW_SIZE,
+        );
+        let sheet_area = FRect::new(
+            LIST_WIDTH + MARGIN * 2.0,
+            PREVIEW_SIZE + MARGIN * 2.0,
+            WINDOW_SIZE.0 as f32 - LIST_WIDTH - MARGIN * 3.0,
+            WINDOW_SIZE.1 as f32 - PREVIEW_SIZE - MARGIN * 4.0 - LINE_HEIGHT * 2.0,
+        );
+        let frames = self.sheet.as_ref().map(|sheet| {
+            (0..self.settings.frame_count)
+                .map(|frame| self.frame_rect(sheet, frame))
+                .collect::<Vec<_>>()
+        });
+
+        let canvas = &mut self.window.canvas;
+        canvas.set_draw_color(BACKGROUND);
+        canvas.clear();
+        if let Some(panel) = &self.panel {
+            let _ = canvas.copy(panel, None, None);
+        }
+        canvas.set_draw_color(PREVIEW_BACKGROUND);
+        let _ = canvas.fill_rect(preview_area);
+        if let Some(sheet) = &self.sheet
+            && let Some(frames) = frames
+        {
+            let current = frames[self.frame as usize % frames.len()];
+            let _ = canvas.copy(
+                &sheet.texture,
+                current,
+                fit((current.width(), current.height()), preview_area),
+            );
+            // the whole sheet with how it's being cut up on top
+            let thumbnail = fit(sheet.size, sheet_area);
+            let _ = canvas.copy(&sheet.texture, None, thumbnail);
+            let scale = thumbnail.w / sheet.size.0 as f32;
+            let on_thumbnail = |frame: Rect| {
+                FRect::new(
+                    thumbnail.x + frame.x() as f32 * scale,
+                    thumbnail.y + frame.y() as f32 * scale,
+                    frame.width() as f32 * scale,
+                    frame.height() as f32 * scale,
+                )
+            };
+            canvas.set_draw_color(GRID);
+            for frame in &frames {
+                let _ = canvas.draw_rect(on_thumbnail(*frame));
+            }
+            canvas.set_draw_color(CURRENT_FRAME);
+            let _ = canvas.draw_rect(on_thumbnail(current));
+        }
+        canvas.present();
+    }
+}
+
+impl Drop for Editor {
+    fn drop(&mut self) {
+        if let Some(sheet) = self.sheet.take() {
+            unsafe { sheet.texture.destroy() };
+        }
+        self.redraw_panel();
+    }
+}
+
+/// `size` scaled to fit in `area` and centered there
+fn fit(size: (u32, u32), area: FRect) -> FRect {
+    let scale = (area.w / size.0.max(1) as f32).min(area.h / size.1.max(1) as f32);
+    let (width, height) = (size.0 as f32 * scale, size.1 as f32 * scale);
+    FRect::new(
+        area.x + (area.w - width) / 2.0,
+        area.y + (area.h - height) / 2.0,
+        width,
+        height,
+    )
+}
+
+/// A window listing the loaded gremlin's animations that plays the picked one and lets its
+/// frame count, columns, fps and looping be tried out, showing how the sheet gets cut up.
+/// The frame count and fps can be saved back into the manifest. Opened and closed with the
+/// `editor` ipc command, driven from the keyboard, see `HELP`.
+#[derive(Default)]
+pub struct GremlinEditor {
+    editor: Option<Editor>,
+}
+
+impl GremlinEditor {
+    pub fn new() -> Box<Self> {
+        Default::default()
+    }
+}
+
+impl Behavior for GremlinEditor {
+    fn setup(&mut self, _: &mut DesktopGremlin) {}
+
+    // authors pause the gremlin to look at it
+    fn runs_while_paused(&self) -> bool {
+        true
+    }
+
+    fn update(&mut self, application: &mut DesktopGremlin, context: &ContextData) {
+        if context
+            .events
+            .contains_key(&Event::Custom(EDITOR_TOGGLE_EVENT.to_string()))
+        {
+            self.editor = match self.editor.take() {
+                Some(_) => None,
+                None => Editor::open(application),
+            };
+        }
+        let Some(editor) = &mut self.editor else {
+            return;
+        };
+
+        let window_id = editor.window_id();
+        let mut keys = Vec::new();
+        let mut is_closed = false;
+        context.on::<kinds::OtherWindowKeyDown>(|(id, key)| {
+            if id == window_id {
+                is_closed |= key == "Escape";
+                keys.push(key);
+            }
+        });
+        context.on::<kinds::OtherWindowClosed>(|id| is_closed |= id == window_id);
+        if is_closed {
+            self.editor = None;
+            return;
+        }
+        for key in keys {
+            editor.press(application, &key);
+        }
+
+        let now = Instant::now();
+        editor.step(now);
+        editor.draw();
+        application.idle_for(editor.next_frame_at.saturating_duration_since(now));
+    }
+}
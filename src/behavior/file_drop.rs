@@ -0,0 +1,187 @@
+use std::{
+    env,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::Command,
+    str::FromStr,
+    time::Duration,
+};
+
+use tracing::warn;
+
+use crate::{
+    behavior::{Behavior, ContextData},
+    events::kinds::FileDropped,
+    gremlin::{DesktopGremlin, GremlinTask},
+    utils::data_dir,
+};
+
+/// "open", "favorite" or "move:<folder>", see `DropAction`
+const DROP_ACTION_ENV: &str = "GREMLIN_DROP_ACTION";
+const BUBBLE_DURATION: Duration = Duration::from_secs(3);
+
+/// What happens to a file dropped on the gremlin, once it's been eaten.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DropAction {
+    /// opens it with whatever the system opens that kind of file with
+    Open,
+    /// moves it into this folder, never over a file already there
+    MoveTo(PathBuf),
+    /// adds it to `favorites_path()`, one path per line
+    Favorite,
+}
+
+impl FromStr for DropAction {
+    type Err = ();
+
+    fn from_str(action: &str) -> Result<Self, Self::Err> {
+        match action.split_once(':') {
+            Some(("move", folder)) if !folder.is_empty() => Ok(DropAction::MoveTo(folder.into())),
+            None if action == "open" => Ok(DropAction::Open),
+            None if action == "favorite" => Ok(DropAction::Favorite),
+            _ => Err(()),
+        }
+    }
+}
+
+/// where `DropAction::Favorite` keeps the dropped files
+pub fn favorites_path() -> PathBuf {
+    data_dir().join("favorites.txt")
+}
+
+/// Feeds files dropped on the gremlin to it: it plays EAT and then does `action` with the file.
+/// The action comes from `with_action` or `GREMLIN_DROP_ACTION`, and is `Favorite` otherwise.
+pub struct GremlinFileDrop {
+    action: DropAction,
+}
+
+impl Default for GremlinFileDrop {
+    fn default() -> Self {
+        Self {
+            action: env::var(DROP_ACTION_ENV)
+                .ok()
+                .and_then(|action| {
+                    action
+                        .parse()
+                        .inspect_err(|_| {
+                            warn!(
+                                "{DROP_ACTION_ENV} should be open, favorite or move:<folder>, got {action:?}"
+                            )
+                        })
+                        .ok()
+                })
+                .unwrap_or(DropAction::Favorite),
+        }
+    }
+}
+
+impl GremlinFileDrop {
+    pub fn new() -> Box<Self> {
+        Default::default()
+    }
+
+    #[allow(unused)]
+    pub fn with_action(action: DropAction) -> Box<Self> {
+        Box::new(Self { action })
+    }
+
+    /// what the gremlin says about it afterwards
+    fn handle(&self, path: &Path) -> io::Result<String> {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string());
+        match &self.action {
+            DropAction::Open => {
+                open_with_default_app(path)?;
+                Ok(format!("opened {name}"))
+            }
+            DropAction::MoveTo(folder) => {
+                let target = folder.join(path.file_name().ok_or(io::ErrorKind::InvalidInput)?);
+                fs::create_dir_all(folder)?;
+                // claims the name first so nothing that shows up there meanwhile gets replaced,
+                // the rename or copy then goes over this empty file
+                OpenOptions::new()
+                    .write(true)
+                    .create_new(true)
+                    .open(&target)
+                    .map_err(|err| match err.kind() {
+                        io::ErrorKind::AlreadyExists => io::Error::new(
+                            io::ErrorKind::AlreadyExists,
+                            format!("{} already has a {name}", folder.display()),
+                        ),
+                        _ => err,
+                    })?;
+                // a rename can't cross drives, copying can
+                if fs::rename(path, &target).is_err() {
+                    let copied = fs::copy(path, &target).and_then(|_| fs::remove_file(path));
+                    if let Err(err) = copied {
+                        let _ = fs::remove_file(&target);
+                        return Err(err);
+                    }
+                }
+                Ok(format!("put {name} away"))
+            }
+            DropAction::Favorite => {
+                let favorites = favorites_path();
+                if let Some(parent) = favorites.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                let mut file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(favorites)?;
+                writeln!(file, "{}", path.display())?;
+                Ok(format!("{name} is a favorite now"))
+            }
+        }
+    }
+}
+
+impl Behavior for GremlinFileDrop {
+    fn setup(&mut self, _: &mut DesktopGremlin) {}
+
+    fn update(&mut self, application: &mut DesktopGremlin, context: &ContextData) {
+        let mut dropped = Vec::new();
+        context.on::<FileDropped>(|path| dropped.push(path));
+        if dropped.is_empty() {
+            return;
+        }
+
+        if application
+            .current_gremlin
+            .as_ref()
+            .is_some_and(|gremlin| gremlin.has_animation("EAT"))
+        {
+            let _ = application
+                .task_channel
+                .0
+                .send(GremlinTask::PlayInterrupt("EAT".to_string()));
+            let _ = application.task_channel.0.send(GremlinTask::Play(
+                application.default_animation().to_string(),
+            ));
+        }
+        for path in dropped {
+            match self.handle(&path) {
+                Ok(said) => application.say(said, BUBBLE_DURATION),
+                Err(err) => {
+                    warn!("couldn't deal with dropped {}: {err}", path.display());
+                    application.say(format!("can't eat that: {err}"), BUBBLE_DURATION);
+                }
+            }
+        }
+    }
+}
+
+fn open_with_default_app(path: &Path) -> io::Result<()> {
+    let mut command = if cfg!(target_os = "windows") {
+        // never through cmd's start, cmd would parse the file name and run whatever's after a &
+        Command::new("explorer.exe")
+    } else if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else {
+        Command::new("xdg-open")
+    };
+    command.arg(path).spawn().map(|_| ())
+}
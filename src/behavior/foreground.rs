@@ -0,0 +1,67 @@
+use crate::{
+    behavior::{Behavior, ContextData},
+    events::Event,
+    gremlin::{DesktopGremlin, GremlinTask},
+};
+
+/// Plays an animation when the user switches to a certain app, e.g. putting on GLASSES
+/// when an IDE gets focused. Apps are matched on `DesktopGremlin::foreground_app`'s name.
+pub struct ForegroundReactions {
+    // (part of the app name, animation name)
+    reactions: Vec<(String, String)>,
+}
+
+impl Default for ForegroundReactions {
+    fn default() -> Self {
+        Self {
+            reactions: ["code", "devenv", "idea", "rustrover", "zed"]
+                .into_iter()
+                .map(|app| (app.to_string(), "GLASSES".to_string()))
+                .collect(),
+        }
+    }
+}
+
+impl ForegroundReactions {
+    pub fn new() -> Box<Self> {
+        Default::default()
+    }
+
+    #[allow(unused)]
+    pub fn with_reactions(reactions: Vec<(String, String)>) -> Box<Self> {
+        Box::new(Self { reactions })
+    }
+}
+
+impl Behavior for ForegroundReactions {
+    fn setup(&mut self, _: &mut DesktopGremlin) {}
+
+    fn update(&mut self, application: &mut DesktopGremlin, context: &ContextData) {
+        if !context
+            .events
+            .contains_key(&Event::Custom("foreground_changed".to_string()))
+        {
+            return;
+        }
+        let (Some(gremlin), Some(app)) =
+            (&application.current_gremlin, &application.foreground_app)
+        else {
+            return;
+        };
+        let reaction = self
+            .reactions
+            .iter()
+            .find(|(app_name, _)| app.name.contains(app_name.as_str()));
+        if let Some((_, animation_name)) = reaction
+            && gremlin.has_animation(animation_name)
+        {
+            let _ = application
+                .task_channel
+                .0
+                .send(GremlinTask::Play(animation_name.clone()));
+            let _ = application.task_channel.0.send(GremlinTask::Play(
+                application.default_animation().to_string(),
+            ));
+        }
+    }
+}
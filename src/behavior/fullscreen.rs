@@ -0,0 +1,56 @@
+use crate::{
+    behavior::{Behavior, ContextData},
+    events::Event,
+    gremlin::{DesktopGremlin, GremlinTask},
+};
+
+/// Hides and pauses the gremlin while a fullscreen app (a game, a presentation, a video) is in front,
+/// and brings it back once that app leaves fullscreen or loses focus.
+#[derive(Default)]
+pub struct FullscreenHide {
+    is_hidden: bool,
+    // the user had paused the gremlin already, so coming back shouldn't resume it
+    was_paused: bool,
+}
+
+impl FullscreenHide {
+    pub fn new() -> Box<Self> {
+        Default::default()
+    }
+}
+
+impl Behavior for FullscreenHide {
+    fn setup(&mut self, _: &mut DesktopGremlin) {}
+
+    fn runs_while_paused(&self) -> bool {
+        true
+    }
+
+    fn update(&mut self, application: &mut DesktopGremlin, context: &ContextData) {
+        if !context
+            .events
+            .contains_key(&Event::Custom("foreground_changed".to_string()))
+        {
+            return;
+        }
+        let is_fullscreen = application
+            .foreground_app
+            .as_ref()
+            .is_some_and(|app| app.is_fullscreen);
+        if is_fullscreen == self.is_hidden {
+            return;
+        }
+        self.is_hidden = is_fullscreen;
+
+        if is_fullscreen {
+            self.was_paused = application.is_paused();
+            application.window.set_visible(false);
+            let _ = application.task_channel.0.send(GremlinTask::Pause);
+        } else {
+            application.window.set_visible(true);
+            if !self.was_paused {
+                let _ = application.task_channel.0.send(GremlinTask::Resume);
+            }
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use std::time::Instant;
+
+use crate::{
+    behavior::{Behavior, ContextData},
+    events::kinds::{GamepadPress, GamepadStick},
+    gremlin::{DesktopGremlin, GremlinTask},
+};
+
+// pixels per second with the stick all the way over
+const NUDGE_SPEED: f32 = 600.0;
+
+/// Lets a gamepad play with the gremlin: the left stick nudges its window around its display
+/// and buttons play what the manifest's `[gamepad]` says, e.g. `south=JUMP`. Buttons that
+/// aren't in there do nothing. See `Gamepads` for how the pads get opened.
+#[derive(Default)]
+pub struct GremlinGamepad {
+    // where the left stick is, -1 to 1 on each axis
+    stick: (f32, f32),
+    // the window's position with what didn't add up to a whole pixel yet
+    position: Option<(f32, f32)>,
+    last_nudged_at: Option<Instant>,
+}
+
+impl GremlinGamepad {
+    pub fn new() -> Box<Self> {
+        Default::default()
+    }
+
+    fn nudge(&mut self, application: &mut DesktopGremlin) {
+        let now = Instant::now();
+        let elapsed = self
+            .last_nudged_at
+            .replace(now)
+            .map_or(0.0, |last_nudged_at| (now - last_nudged_at).as_secs_f32());
+        let (window_x, window_y) = application.window.position();
+        let (x, y) = self
+            .position
+            .get_or_insert((window_x as f32, window_y as f32));
+        *x += self.stick.0 * NUDGE_SPEED * elapsed;
+        *y += self.stick.1 * NUDGE_SPEED * elapsed;
+        if let Some(bounds) = application.window.usable_bounds() {
+            let (width, height) = application.window.size();
+            *x = x.clamp(
+                bounds.left() as f32,
+                (bounds.right() - width as i32).max(bounds.left()) as f32,
+            );
+            *y = y.clamp(
+                bounds.top() as f32,
+                (bounds.bottom() - height as i32).max(bounds.top()) as f32,
+            );
+        }
+        // the stick takes over from wherever it was walking to
+        application.goto_target = None;
+        application
+            .window
+            .set_position(x.round() as i32, y.round() as i32);
+    }
+}
+
+impl Behavior for GremlinGamepad {
+    fn setup(&mut self, _: &mut DesktopGremlin) {}
+
+    fn update(&mut self, application: &mut DesktopGremlin, context: &ContextData) {
+        context.on::<GamepadStick>(|stick| self.stick = stick);
+        if self.stick != (0.0, 0.0) {
+            // the stick doesn't send anything while it's held still
+            application.stay_awake();
+            self.nudge(application);
+        } else {
+            self.position = None;
+            self.last_nudged_at = None;
+        }
+
+        let mut pressed = Vec::new();
+        context.on::<GamepadPress>(|button| pressed.push(button));
+        let Some(gremlin) = &application.current_gremlin else {
+            return;
+        };
+        for button in pressed {
+            let Some(animation_name) = gremlin.gamepad.get(&button) else {
+                continue;
+            };
+            if !gremlin.has_animation(animation_name) {
+                continue;
+            }
+            let task_tx = &application.task_channel.0;
+            let _ = task_tx.send(GremlinTask::PlayInterrupt(animation_name.clone()));
+            let _ = task_tx.send(GremlinTask::Play(
+                gremlin.metadata.default_animation().to_string(),
+            ));
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use std::{
+    collections::VecDeque,
+    f32::consts::TAU,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    behavior::{Behavior, ContextData},
+    events::{
+        Event, MouseButton,
+        kinds::{Drag, DragEnd, DragStart},
+    },
+    gremlin::{DesktopGremlin, GremlinTask},
+    utils::get_cursor_position,
+};
+
+/// raised when the gremlin gets shaken back and forth while dragged
+pub const SHAKEN_EVENT: &str = "shaken";
+/// raised when the cursor goes all the way around the gremlin
+pub const CIRCLED_EVENT: &str = "circled";
+// this many turnarounds within `SHAKE_WINDOW` make a shake, it has to fit in `EventHistory`
+const SHAKE_REVERSALS: usize = 6;
+const SHAKE_WINDOW: Duration = Duration::from_millis(1200);
+// frames dragging it less than this don't count as a turnaround, hands jitter
+const MIN_SHAKE_STEP: f32 = 12.0;
+// a full turn within `CIRCLE_WINDOW`, no farther out than `CIRCLE_REACH` window sizes
+const CIRCLE_WINDOW: Duration = Duration::from_millis(2500);
+const CIRCLE_REACH: f32 = 2.5;
+// after either gesture, so one long shake doesn't count over and over
+const COOLDOWN: Duration = Duration::from_secs(3);
+const DIZZY_ANIMATION: &str = "DIZZY";
+
+/// Spots shaking the gremlin while it's dragged (going by the drags in `EventHistory`) and
+/// drawing a circle around it with the cursor, raising `SHAKEN_EVENT` and `CIRCLED_EVENT` for
+/// other behaviors. Either one makes the gremlin play DIZZY when it has it.
+#[derive(Default)]
+pub struct GremlinGestures {
+    is_dragged: bool,
+    // the cursor's angle around the gremlin and how far it turned since the circle started
+    last_angle: Option<f32>,
+    turned: VecDeque<(Instant, f32)>,
+    cooldown_until: Option<Instant>,
+}
+
+impl GremlinGestures {
+    pub fn new() -> Box<Self> {
+        Default::default()
+    }
+
+    /// counts the drag turning around on either axis over the last `SHAKE_WINDOW`, a shake is
+    /// lots of them in a row
+    fn is_shaken(context: &ContextData) -> bool {
+        // how far each frame dragged it, newest first
+        let mut steps: Vec<(Instant, (f32, f32))> = Vec::new();
+        context.history.on::<Drag>(SHAKE_WINDOW, |at, step| {
+            if step.mouse_btn != MouseButton::Left {
+                return;
+            }
+            match steps.last_mut() {
+                Some((last_at, (x, y))) if *last_at == at => {
+                    *x += step.x_rel;
+                    *y += step.y_rel;
+                }
+                _ => steps.push((at, (step.x_rel, step.y_rel))),
+            }
+        });
+        // which way it was going, -1, 0 or 1 on each axis
+        let mut direction = (0.0, 0.0);
+        let turn = |step: f32, direction: &mut f32| {
+            if step.abs() < MIN_SHAKE_STEP {
+                return false;
+            }
+            let is_reversal = *direction != 0.0 && step.signum() != *direction;
+            *direction = step.signum();
+            is_reversal
+        };
+        steps
+            .iter()
+            .rev()
+            .filter(|(_, step)| {
+                let turned_x = turn(step.0, &mut direction.0);
+                let turned_y = turn(step.1, &mut direction.1);
+                turned_x || turned_y
+            })
+            .count()
+            >= SHAKE_REVERSALS
+    }
+
+    /// adds up how far the cursor went around the gremlin's middle, a circle is a full turn
+    fn track_circle(
+        &mut self,
+        application: &DesktopGremlin,
+        cursor: (f32, f32),
+        now: Instant,
+    ) -> bool {
+        let window = application.window.rect();
+        let center = window.center();
+        let (x, y) = (cursor.0 - center.x() as f32, cursor.1 - center.y() as f32);
+        let reach = window.width().max(window.height()) as f32 * CIRCLE_REACH;
+        // going over the gremlin or wandering off doesn't go around it
+        if x.hypot(y) > reach || window.contains_point((cursor.0 as i32, cursor.1 as i32)) {
+            self.last_angle = None;
+            self.turned.clear();
+            return false;
+        }
+        let angle = y.atan2(x);
+        if let Some(last_angle) = self.last_angle.replace(angle) {
+            // the short way round, across the -pi/pi seam too
+            let delta = (angle - last_angle + TAU / 2.0).rem_euclid(TAU) - TAU / 2.0;
+            self.turned.push_back((now, delta));
+        }
+        while self
+            .turned
+            .front()
+            .is_some_and(|(at, _)| now.duration_since(*at) > CIRCLE_WINDOW)
+        {
+            self.turned.pop_front();
+        }
+        self.turned
+            .iter()
+            .map(|(_, delta)| delta)
+            .sum::<f32>()
+            .abs()
+            >= TAU
+    }
+
+    fn reset(&mut self) {
+        self.last_angle = None;
+        self.turned.clear();
+    }
+}
+
+impl Behavior for GremlinGestures {
+    fn setup(&mut self, _: &mut DesktopGremlin) {}
+
+    fn update(&mut self, application: &mut DesktopGremlin, context: &ContextData) {
+        let is_gesture = [SHAKEN_EVENT, CIRCLED_EVENT].iter().any(|name| {
+            context
+                .events
+                .contains_key(&Event::Custom(name.to_string()))
+        });
+        if is_gesture
+            && let Some(gremlin) = &application.current_gremlin
+            && gremlin.has_animation(DIZZY_ANIMATION)
+        {
+            let default_animation = gremlin.metadata.default_animation().to_string();
+            let task_tx = &application.task_channel.0;
+            let _ = task_tx.send(GremlinTask::PlayInterrupt(DIZZY_ANIMATION.to_string()));
+            let _ = task_tx.send(GremlinTask::Play(default_animation));
+        }
+
+        let was_dragged = self.is_dragged;
+        context.on::<DragStart>(|drag| self.is_dragged |= drag.mouse_btn == MouseButton::Left);
+        context.on::<DragEnd>(|drag| self.is_dragged &= drag.mouse_btn != MouseButton::Left);
+        if self.is_dragged != was_dragged {
+            self.reset();
+        }
+
+        let now = Instant::now();
+        if self.cooldown_until.is_some_and(|until| now < until) {
+            return;
+        }
+        let gesture = if self.is_dragged {
+            Self::is_shaken(context).then_some(SHAKEN_EVENT)
+        } else {
+            let is_circled = self.track_circle(application, get_cursor_position(), now);
+            // the cursor doesn't send anything while it's off the gremlin, keep polling it
+            // while it's on its way round
+            if self.turned.iter().any(|(_, delta)| *delta != 0.0) {
+                application.stay_awake();
+            }
+            is_circled.then_some(CIRCLED_EVENT)
+        };
+        if let Some(gesture) = gesture {
+            application.emit(Event::Custom(gesture.to_string()), None);
+            self.cooldown_until = Some(now + COOLDOWN);
+            self.reset();
+        }
+    }
+}
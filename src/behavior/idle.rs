@@ -0,0 +1,89 @@
+use std::time::{Duration, Instant};
+
+use rand::distr::{Distribution, weighted::WeightedIndex};
+
+use crate::{
+    behavior::{Behavior, ContextData},
+    gremlin::{DesktopGremlin, GremlinTask},
+};
+
+const DEFAULT_IDLE_AFTER: Duration = Duration::from_secs(10);
+
+/// Sprinkles "flavor" animations (stretch, yawn, dance...) in between IDLE loops
+/// so the gremlin doesn't play the same animation forever.
+pub struct IdleVariation {
+    idle_after: Duration,
+    // (animation name, weight)
+    flavors: Vec<(String, u32)>,
+    idle_since: Option<Instant>,
+}
+
+impl Default for IdleVariation {
+    fn default() -> Self {
+        Self {
+            idle_after: DEFAULT_IDLE_AFTER,
+            flavors: vec![
+                ("STRETCH".to_string(), 3),
+                ("YAWN".to_string(), 2),
+                ("DANCE".to_string(), 1),
+            ],
+            idle_since: None,
+        }
+    }
+}
+
+impl IdleVariation {
+    pub fn new() -> Box<Self> {
+        Default::default()
+    }
+
+    #[allow(unused)]
+    pub fn with_flavors(idle_after: Duration, flavors: Vec<(String, u32)>) -> Box<Self> {
+        Box::new(Self {
+            idle_after,
+            flavors,
+            idle_since: None,
+        })
+    }
+}
+
+impl Behavior for IdleVariation {
+    fn setup(&mut self, _: &mut DesktopGremlin) {}
+
+    fn update(&mut self, application: &mut DesktopGremlin, _: &ContextData) {
+        let Some(gremlin) = &application.current_gremlin else {
+            return;
+        };
+        let is_idle = gremlin.animator.as_ref().is_some_and(|animator| {
+            animator.animation_properties.animation_name == gremlin.metadata.default_animation()
+        }) && application.task_queue.is_empty();
+
+        if !is_idle {
+            self.idle_since = None;
+            return;
+        }
+
+        let idle_since = *self.idle_since.get_or_insert_with(Instant::now);
+        if idle_since.elapsed() < self.idle_after.mul_f32(gremlin.personality.idle_patience()) {
+            return;
+        }
+        self.idle_since = Some(Instant::now());
+
+        // only roll between flavors that this gremlin actually ships with
+        let candidates: Vec<&(String, u32)> = self
+            .flavors
+            .iter()
+            .filter(|(name, weight)| *weight > 0 && gremlin.has_animation(name))
+            .collect();
+        if let Ok(distribution) = WeightedIndex::new(candidates.iter().map(|(_, weight)| *weight)) {
+            let (flavor, _) = candidates[distribution.sample(&mut application.rng)];
+            let _ = application
+                .task_channel
+                .0
+                .send(GremlinTask::Play(flavor.clone()));
+            let _ = application.task_channel.0.send(GremlinTask::Play(
+                application.default_animation().to_string(),
+            ));
+        }
+    }
+}
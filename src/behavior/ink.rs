@@ -0,0 +1,246 @@
+use std::time::{Duration, Instant};
+
+use sdl3::{
+    pixels::Color,
+    rect::Rect,
+    render::{BlendMode, FPoint},
+    sys::mouse::{SDL_BUTTON_LMASK, SDL_GetGlobalMouseState},
+};
+use tracing::warn;
+
+use crate::{
+    backend::{SdlBackend, WindowBackend},
+    behavior::{Behavior, ContextData},
+    events::{Event, kinds},
+    gremlin::{DesktopGremlin, LaunchArguments, click_through, see_through},
+};
+
+const DEFAULT_HOTKEY: &str = "ctrl+alt+KeyD";
+/// lets the user draw on the screen or stops it, raised by the `ink` ipc command
+pub const INK_TOGGLE_EVENT: &str = "ink:toggle";
+const DEFAULT_FADE: Duration = Duration::from_secs(3);
+const INK_COLOR: (u8, u8, u8) = (255, 72, 72);
+// the line is drawn once per offset, one pixel lines are hard to spot
+const PEN_OFFSETS: [(f32, f32); 5] = [(0.0, 0.0), (1.0, 0.0), (-1.0, 0.0), (0.0, 1.0), (0.0, -1.0)];
+// short walks aren't worth pointing out
+const MIN_ARROW_LENGTH: f32 = 96.0;
+
+struct Stroke {
+    points: Vec<(i32, i32)>,
+    drawn_at: Instant,
+}
+
+/// A transparent window over the gremlin's display that only exists while there's ink to show.
+struct Overlay {
+    window: SdlBackend,
+    bounds: Rect,
+    is_click_through: bool,
+    is_blank: bool,
+}
+
+/// Temporary ink on top of everything: lines behaviors draw with `DesktopGremlin::scribble` and
+/// `draw_arrow` (an arrow to wherever a `GremlinTask::Goto` sends the gremlin, by default), and the
+/// user's own scribbles while ink mode is on. Every stroke fades out after `fade`.
+/// The overlay lets clicks through except in ink mode, where it takes them for the pen.
+pub struct GremlinInk {
+    hotkey: String,
+    fade: Duration,
+    draws_goto_arrows: bool,
+    hotkey_id: Option<u32>,
+    strokes: Vec<Stroke>,
+    is_inking: bool,
+    // what the user is drawing right now, it starts fading once the button comes up
+    pen: Option<Vec<(i32, i32)>>,
+    overlay: Option<Overlay>,
+    last_goto: Option<(i32, i32)>,
+}
+
+impl Default for GremlinInk {
+    fn default() -> Self {
+        Self {
+            hotkey: DEFAULT_HOTKEY.to_string(),
+            fade: DEFAULT_FADE,
+            draws_goto_arrows: true,
+            hotkey_id: None,
+            strokes: Vec::new(),
+            is_inking: false,
+            pen: None,
+            overlay: None,
+            last_goto: None,
+        }
+    }
+}
+
+impl GremlinInk {
+    pub fn new() -> Box<Self> {
+        Default::default()
+    }
+
+    /// `hotkey` is written like "ctrl+shift+KeyD" or "alt+F11"
+    #[allow(unused)]
+    pub fn with_options(hotkey: &str, fade: Duration, draws_goto_arrows: bool) -> Box<Self> {
+        Box::new(Self {
+            hotkey: hotkey.to_string(),
+            fade,
+            draws_goto_arrows,
+            ..Default::default()
+        })
+    }
+
+    fn point_at_goto(&mut self, application: &mut DesktopGremlin) {
+        if application.goto_target == self.last_goto {
+            return;
+        }
+        self.last_goto = application.goto_target;
+        let Some((x, y)) = application.goto_target else {
+            return;
+        };
+        let rect = application.window.rect();
+        let (half_width, half_height) = (rect.width() as i32 / 2, rect.height() as i32 / 2);
+        let from = (rect.x() + half_width, rect.y() + half_height);
+        let to = (x + half_width, y + half_height);
+        let length = ((to.0 - from.0) as f32).hypot((to.1 - from.1) as f32);
+        if length >= MIN_ARROW_LENGTH {
+            application.draw_arrow(from, to);
+        }
+    }
+
+    fn follow_pen(&mut self) {
+        let (mut x, mut y) = (0.0, 0.0);
+        // SAFETY: behaviors only run while the SDL context is alive
+        let buttons = unsafe { SDL_GetGlobalMouseState(&mut x, &mut y) };
+        if buttons & SDL_BUTTON_LMASK != 0 {
+            let point = (x.round() as i32, y.round() as i32);
+            let pen = self.pen.get_or_insert_default();
+            if pen.last() != Some(&point) {
+                pen.push(point);
+            }
+        } else if let Some(points) = self.pen.take()
+            && points.len() > 1
+        {
+            self.strokes.push(Stroke {
+                points,
+                drawn_at: Instant::now(),
+            });
+        }
+    }
+
+    fn open_overlay(&self, application: &mut DesktopGremlin) -> Option<Overlay> {
+        let video = application.sdl.as_ref()?.video().ok()?;
+        let bounds = application.window.usable_bounds()?;
+        let launch_arguments = LaunchArguments::default();
+        let window = video
+            .window(&launch_arguments.title, bounds.width(), bounds.height())
+            .set_window_flags(launch_arguments.window_flags())
+            .build()
+            .inspect_err(|err| warn!("couldn't open the ink overlay: {err}"))
+            .ok()?;
+        see_through(&window, true);
+        click_through(&window, !self.is_inking);
+
+        let mut window = SdlBackend {
+            canvas: window.into_canvas(),
+        };
+        window.set_position(bounds.x(), bounds.y());
+        window.canvas.set_blend_mode(BlendMode::Blend);
+        Some(Overlay {
+            window,
+            bounds,
+            is_click_through: !self.is_inking,
+            is_blank: false,
+        })
+    }
+
+    fn draw(&mut self) {
+        let Some(overlay) = self.overlay.as_mut() else {
+            return;
+        };
+        let is_blank = self.strokes.is_empty() && self.pen.is_none();
+        if is_blank && overlay.is_blank {
+            return;
+        }
+
+        let canvas = &mut overlay.window.canvas;
+        canvas.set_draw_color(Color::RGBA(0, 0, 0, 0));
+        canvas.clear();
+        let (r, g, b) = INK_COLOR;
+        let pen = self.pen.iter().map(|points| (points, 1.0));
+        let strokes = self.strokes.iter().map(|stroke| {
+            // solid for the first half, then gone by the end
+            let left = 1.0 - stroke.drawn_at.elapsed().as_secs_f32() / self.fade.as_secs_f32();
+            (&stroke.points, (left * 2.0).clamp(0.0, 1.0))
+        });
+        for (points, opacity) in strokes.chain(pen) {
+            canvas.set_draw_color(Color::RGBA(r, g, b, (opacity * 255.0) as u8));
+            for (offset_x, offset_y) in PEN_OFFSETS {
+                let points: Vec<FPoint> = points
+                    .iter()
+                    .map(|(x, y)| {
+                        FPoint::new(
+                            (x - overlay.bounds.x()) as f32 + offset_x,
+                            (y - overlay.bounds.y()) as f32 + offset_y,
+                        )
+                    })
+                    .collect();
+                let _ = canvas.draw_lines(points.as_slice());
+            }
+        }
+        overlay.window.present();
+        overlay.is_blank = is_blank;
+    }
+}
+
+impl Behavior for GremlinInk {
+    fn setup(&mut self, application: &mut DesktopGremlin) {
+        match application.register_hotkey(&self.hotkey) {
+            Ok(id) => self.hotkey_id = Some(id),
+            Err(err) => warn!("couldn't register ink hotkey {:?}: {err:#}", self.hotkey),
+        }
+    }
+
+    fn update(&mut self, application: &mut DesktopGremlin, context: &ContextData) {
+        let mut is_toggled = context
+            .events
+            .contains_key(&Event::Custom(INK_TOGGLE_EVENT.to_string()));
+        if let Some(hotkey_id) = self.hotkey_id {
+            context.on::<kinds::HotKey>(|id| is_toggled |= id == hotkey_id);
+        }
+        if is_toggled {
+            self.is_inking = !self.is_inking;
+            self.pen = None;
+        }
+
+        if self.draws_goto_arrows {
+            self.point_at_goto(application);
+        }
+        let now = Instant::now();
+        self.strokes
+            .extend(application.ink.drain(..).map(|points| Stroke {
+                points,
+                drawn_at: now,
+            }));
+        if self.is_inking {
+            self.follow_pen();
+        }
+        self.strokes
+            .retain(|stroke| stroke.drawn_at.elapsed() < self.fade);
+
+        // dropping the overlay closes its window
+        if !self.is_inking && self.strokes.is_empty() && self.pen.is_none() {
+            self.overlay = None;
+            return;
+        }
+        // the pen and the fading both need every frame
+        application.stay_awake();
+        if self.overlay.is_none() {
+            self.overlay = self.open_overlay(application);
+        }
+        if let Some(overlay) = self.overlay.as_mut()
+            && overlay.is_click_through == self.is_inking
+        {
+            overlay.is_click_through = !self.is_inking;
+            click_through(overlay.window.canvas.window(), overlay.is_click_through);
+        }
+        self.draw();
+    }
+}
@@ -0,0 +1,87 @@
+use std::time::Duration;
+
+use crate::{
+    behavior::{Behavior, ContextData},
+    events::{Event, EventSource},
+    gremlin::{DesktopGremlin, GremlinTask},
+    media::MediaMonitor,
+};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// DANCEs while some media player is playing and stops once it's paused.
+/// Turned off like any other behavior, through `disabled_behaviors` in the saved state.
+pub struct MediaReactions {
+    animation_name: String,
+    monitor: Option<(MediaMonitor, EventSource)>,
+    is_playing: bool,
+}
+
+impl Default for MediaReactions {
+    fn default() -> Self {
+        Self {
+            animation_name: "DANCE".to_string(),
+            monitor: None,
+            is_playing: false,
+        }
+    }
+}
+
+impl MediaReactions {
+    pub fn new() -> Box<Self> {
+        Default::default()
+    }
+
+    #[allow(unused)]
+    pub fn with_animation(animation_name: &str) -> Box<Self> {
+        Box::new(Self {
+            animation_name: animation_name.to_string(),
+            ..Default::default()
+        })
+    }
+}
+
+impl Behavior for MediaReactions {
+    fn setup(&mut self, _: &mut DesktopGremlin) {
+        self.monitor = Some(MediaMonitor::spawn(POLL_INTERVAL));
+    }
+
+    fn update(&mut self, application: &mut DesktopGremlin, _: &ContextData) {
+        if let Some((_, media_events)) = &self.monitor {
+            for (event, _) in media_events.try_iter() {
+                match event {
+                    Event::Custom(name) if name == "media_playing" => self.is_playing = true,
+                    Event::Custom(name) if name == "media_paused" => self.is_playing = false,
+                    _ => {}
+                }
+            }
+        }
+
+        let Some(gremlin) = &application.current_gremlin else {
+            return;
+        };
+        let current_animation = gremlin
+            .animator
+            .as_ref()
+            .map(|animator| animator.animation_properties.animation_name.as_str());
+
+        if self.is_playing {
+            // wait for whatever the gremlin is up to, nothing queued after it keeps the dance looping
+            if current_animation == Some(gremlin.metadata.default_animation())
+                && application.task_queue.is_empty()
+                && gremlin.has_animation(&self.animation_name)
+            {
+                let _ = application
+                    .task_channel
+                    .0
+                    .send(GremlinTask::Play(self.animation_name.clone()));
+            }
+        } else if current_animation == Some(gremlin.alias(&self.animation_name))
+            && application.task_queue.is_empty()
+        {
+            let _ = application.task_channel.0.send(GremlinTask::PlayInterrupt(
+                application.default_animation().to_string(),
+            ));
+        }
+    }
+}
@@ -0,0 +1,289 @@
+use sdl3::{pixels::Color, render::FRect};
+use tracing::warn;
+
+use crate::{
+    backend::SdlBackend,
+    behavior::{Behavior, ContextData, POMODORO_START_EVENT, POMODORO_STOP_EVENT},
+    events::{Event, kinds},
+    gremlin::{DesktopGremlin, GremlinTask, MAX_FRAMERATE, MAX_SCALE, MIN_FRAMERATE, MIN_SCALE},
+    ui::{Render, text::Text},
+};
+
+/// opens the menu or closes it, what right clicking the gremlin raises, see `GremlinClick`
+pub const MENU_TOGGLE_EVENT: &str = "menu";
+const WIDTH: u32 = 260;
+const MARGIN: f32 = 8.0;
+const LINE_HEIGHT: f32 = 22.0;
+const TEXT_SIZE: f32 = 14.0;
+const BACKGROUND: Color = Color::RGB(0xf4, 0xf4, 0xf4);
+const SELECTED: Color = Color::RGB(0xc8, 0xdc, 0xff);
+const SLIDER: Color = Color::RGB(0xa0, 0xa0, 0xa0);
+const SLIDER_FILL: Color = Color::RGB(0x30, 0x60, 0xff);
+const SLIDER_WIDTH: f32 = 90.0;
+const SCALE_STEP: f32 = 0.25;
+const FRAMERATE_STEP: u32 = 5;
+const HELP: &str = "up/down: pick   left/right: slide   enter: do it   esc: close";
+
+/// A line of the menu.
+#[derive(Debug, Clone, PartialEq)]
+enum Item {
+    Scale,
+    Framerate,
+    PomodoroStart,
+    PomodoroStop,
+    // puts it on or takes it off, see `Gremlin::accessories`
+    Accessory(String),
+}
+
+impl Item {
+    fn label(&self, application: &DesktopGremlin) -> String {
+        match self {
+            Item::Scale => format!("size: {:.2}x", application.scale),
+            Item::Framerate => format!("fps: {}", application.target_framerate),
+            Item::PomodoroStart => "start a pomodoro".to_string(),
+            Item::PomodoroStop => "stop the pomodoro".to_string(),
+            Item::Accessory(name) if application.accessories.contains(name) => {
+                format!("take off {}", name.to_lowercase())
+            }
+            Item::Accessory(name) => format!("wear {}", name.to_lowercase()),
+        }
+    }
+
+    /// how far along its slider is, 0 to 1, `None` for the ones without one
+    fn slider(&self, application: &DesktopGremlin) -> Option<f32> {
+        match self {
+            Item::Scale => Some((application.scale - MIN_SCALE) / (MAX_SCALE - MIN_SCALE)),
+            Item::Framerate => Some(
+                (application.target_framerate - MIN_FRAMERATE) as f32
+                    / (MAX_FRAMERATE - MIN_FRAMERATE) as f32,
+            ),
+            _ => None,
+        }
+    }
+
+    /// moves its slider `steps` notches, left is negative
+    fn slide(&self, application: &mut DesktopGremlin, steps: i32) {
+        // both snap to their notches so sliding back lands where it started
+        match self {
+            Item::Scale => {
+                let scale = (application.scale / SCALE_STEP).round() + steps as f32;
+                let _ = application
+                    .task_channel
+                    .0
+                    .send(GremlinTask::SetScale(scale * SCALE_STEP));
+            }
+            Item::Framerate => {
+                let notch = (application.target_framerate / FRAMERATE_STEP) as i32 + steps;
+                application.set_target_framerate(notch.max(0) as u32 * FRAMERATE_STEP);
+            }
+            _ => {}
+        }
+    }
+
+    /// what enter on it does, true when the menu is done after
+    fn activate(&self, application: &mut DesktopGremlin) -> bool {
+        match self {
+            Item::Scale | Item::Framerate => false,
+            Item::PomodoroStart => {
+                application.emit(Event::Custom(POMODORO_START_EVENT.to_string()), None);
+                true
+            }
+            Item::PomodoroStop => {
+                application.emit(Event::Custom(POMODORO_STOP_EVENT.to_string()), None);
+                true
+            }
+            Item::Accessory(name) => {
+                match application.accessories.iter().position(|worn| worn == name) {
+                    Some(index) => {
+                        application.accessories.remove(index);
+                    }
+                    None => application.accessories.push(name.clone()),
+                }
+                false
+            }
+        }
+    }
+}
+
+/// A line the way it's on screen.
+#[derive(Debug, Clone, PartialEq)]
+struct Line {
+    label: String,
+    slider: Option<f32>,
+}
+
+/// The menu's own window, see `GremlinMenu`.
+struct Menu {
+    window: SdlBackend,
+    items: Vec<Item>,
+    selected: usize,
+    // what's on screen, only drawn again when it changes
+    drawn: Option<(usize, Vec<Line>)>,
+}
+
+impl Menu {
+    fn open(application: &DesktopGremlin) -> Option<Self> {
+        let mut items = vec![
+            Item::Scale,
+            Item::Framerate,
+            Item::PomodoroStart,
+            Item::PomodoroStop,
+        ];
+        if let Some(gremlin) = &application.current_gremlin {
+            items.extend(
+                gremlin
+                    .accessories
+                    .iter()
+                    .filter(|accessory| !gremlin.locked.contains(&accessory.name))
+                    .map(|accessory| Item::Accessory(accessory.name.clone())),
+            );
+        }
+        let video = application.sdl.as_ref()?.video().ok()?;
+        let height = MARGIN * 3.0 + LINE_HEIGHT * (items.len() + 1) as f32;
+        // right next to the gremlin
+        let (x, y) = application.window.position();
+        let (width, _) = application.window.size();
+        let window = video
+            .window("menu", WIDTH, height as u32)
+            .position(x + width as i32, y)
+            .build()
+            .inspect_err(|err| warn!("couldn't open the menu: {err}"))
+            .ok()?;
+        Some(Self {
+            window: SdlBackend {
+                canvas: window.into_canvas(),
+            },
+            items,
+            selected: 0,
+            drawn: None,
+        })
+    }
+
+    fn window_id(&self) -> u32 {
+        self.window.canvas.window().id()
+    }
+
+    /// true when the menu is done
+    fn press(&mut self, application: &mut DesktopGremlin, key: &str) -> bool {
+        match key {
+            "Up" => self.selected = self.selected.saturating_sub(1),
+            "Down" => self.selected = (self.selected + 1).min(self.items.len() - 1),
+            "Left" => self.items[self.selected].slide(application, -1),
+            "Right" => self.items[self.selected].slide(application, 1),
+            "Return" | "Space" => return self.items[self.selected].activate(application),
+            "Escape" => return true,
+            _ => {}
+        }
+        false
+    }
+
+    fn draw(&mut self, application: &DesktopGremlin) {
+        let lines = self
+            .items
+            .iter()
+            .map(|item| Line {
+                label: item.label(application),
+                slider: item.slider(application),
+            })
+            .collect::<Vec<_>>();
+        let drawn = Some((self.selected, lines));
+        if self.drawn == drawn {
+            return;
+        }
+        self.drawn = drawn;
+        let Some((_, lines)) = &self.drawn else {
+            return;
+        };
+        let canvas = &mut self.window.canvas;
+        canvas.set_draw_color(BACKGROUND);
+        canvas.clear();
+        for (row, line) in lines.iter().enumerate() {
+            let y = MARGIN + row as f32 * LINE_HEIGHT;
+            if row == self.selected {
+                canvas.set_draw_color(SELECTED);
+                let _ = canvas.fill_rect(FRect::new(
+                    MARGIN,
+                    y,
+                    WIDTH as f32 - MARGIN * 2.0,
+                    LINE_HEIGHT,
+                ));
+            }
+            if let Some(slider) = line.slider {
+                let x = WIDTH as f32 - MARGIN * 2.0 - SLIDER_WIDTH;
+                let y = y + LINE_HEIGHT / 2.0 - 2.0;
+                canvas.set_draw_color(SLIDER);
+                let _ = canvas.fill_rect(FRect::new(x, y, SLIDER_WIDTH, 4.0));
+                canvas.set_draw_color(SLIDER_FILL);
+                let _ = canvas.fill_rect(FRect::new(
+                    x,
+                    y - 2.0,
+                    SLIDER_WIDTH * slider.clamp(0.0, 1.0),
+                    8.0,
+                ));
+            }
+            let _ = Text::new(line.label.as_str())
+                .size(TEXT_SIZE)
+                .render_canvas(canvas, Some(FRect::new(MARGIN * 2.0, y + 3.0, 0.0, 0.0)));
+        }
+        let _ = Text::new(HELP).size(TEXT_SIZE * 0.8).render_canvas(
+            canvas,
+            Some(FRect::new(
+                MARGIN,
+                MARGIN * 2.0 + self.items.len() as f32 * LINE_HEIGHT,
+                0.0,
+                0.0,
+            )),
+        );
+        canvas.present();
+    }
+}
+
+/// A small window next to the gremlin with things to do with it, opened and closed by right
+/// clicking it (`MENU_TOGGLE_EVENT`). Driven from the keyboard, see `HELP`.
+#[derive(Default)]
+pub struct GremlinMenu {
+    menu: Option<Menu>,
+}
+
+impl GremlinMenu {
+    pub fn new() -> Box<Self> {
+        Default::default()
+    }
+}
+
+impl Behavior for GremlinMenu {
+    fn setup(&mut self, _: &mut DesktopGremlin) {}
+
+    fn update(&mut self, application: &mut DesktopGremlin, context: &ContextData) {
+        if context
+            .events
+            .contains_key(&Event::Custom(MENU_TOGGLE_EVENT.to_string()))
+        {
+            self.menu = match self.menu.take() {
+                Some(_) => None,
+                None => Menu::open(application),
+            };
+        }
+        let Some(menu) = &mut self.menu else {
+            return;
+        };
+
+        let window_id = menu.window_id();
+        let mut keys = Vec::new();
+        let mut is_closed = false;
+        context.on::<kinds::OtherWindowKeyDown>(|(id, key)| {
+            if id == window_id {
+                keys.push(key);
+            }
+        });
+        context.on::<kinds::OtherWindowClosed>(|id| is_closed |= id == window_id);
+        for key in keys {
+            is_closed |= menu.press(application, &key);
+        }
+        if is_closed {
+            self.menu = None;
+            return;
+        }
+        menu.draw(application);
+    }
+}
@@ -1,18 +1,88 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, VecDeque},
+    rc::Rc,
+    time::{Duration, Instant},
+};
 
-use crate::events::{Event, EventData};
+use crate::events::{Event, EventData, kinds::EventKind};
 use crate::gremlin::DesktopGremlin;
+mod achievements;
+mod broadcast;
+mod bubble;
+mod catch;
+#[cfg(feature = "chat")]
+mod chat;
 mod click;
+mod clones;
 mod common;
 mod drag;
+mod editor;
+mod file_drop;
+mod foreground;
+mod fullscreen;
+mod gamepad;
+mod gestures;
+mod idle;
+mod ink;
+mod media;
+mod menu;
 mod movement;
+mod neighbors;
+mod notify;
+mod pomodoro;
+mod power;
+mod presenter;
+mod random;
 mod render;
+mod scale;
+mod stats;
+mod summon;
+mod suspend;
+mod system;
+mod tree;
+mod typing;
+mod utility;
+mod visits;
+mod weather;
 
+pub use achievements::*;
+pub use broadcast::*;
+pub use bubble::*;
+pub use catch::*;
+#[cfg(feature = "chat")]
+pub use chat::*;
 pub use click::*;
+pub use clones::*;
 pub use common::*;
 pub use drag::*;
+pub use editor::*;
+pub use file_drop::*;
+pub use foreground::*;
+pub use fullscreen::*;
+pub use gamepad::*;
+pub use gestures::*;
+pub use idle::*;
+pub use ink::*;
+pub use media::*;
+pub use menu::*;
 pub use movement::*;
+pub use neighbors::*;
+pub use notify::*;
+pub use pomodoro::*;
+pub use power::*;
+pub use presenter::*;
+pub use random::*;
 pub use render::*;
+pub use scale::*;
+pub use stats::*;
+pub use summon::*;
+pub use suspend::*;
+pub use system::*;
+pub use tree::*;
+pub use typing::*;
+pub use utility::*;
+pub use visits::*;
+pub use weather::*;
 /// Behaviors define actions that the gremlins/application can take and can modify the state of the application/gremlin.<br>
 /// This is heavily inspired by Unity's **`MonoBehavior`** superclass. <br>
 /// Their lifecycle is as follows:
@@ -27,9 +97,184 @@ pub trait Behavior {
     /// Called every frame and passes the whole execution ctx mutably,
     /// with collected events from the last time the behavior was executed.
     fn update(&mut self, application: &mut DesktopGremlin, context: &ContextData);
+
+    /// Whether `update()` keeps getting called while the runtime is paused, only for the
+    /// behaviors that need to keep the window alive.
+    fn runs_while_paused(&self) -> bool {
+        false
+    }
+
+    /// Identifies the behavior in the saved state, defaults to the struct's name.
+    fn name(&self) -> &'static str {
+        let type_name = std::any::type_name::<Self>();
+        type_name.rsplit("::").next().unwrap_or(type_name)
+    }
+}
+
+/// Builds a `Vec<Box<dyn Behavior>>` out of behavior types, calling each one's `new()`, in the
+/// order they run. Attributes like `#[cfg(...)]` go in front of a type, e.g.
+///
+/// ```no_run
+/// # use desktop_gremlin::{behavior::*, register_behaviors};
+/// let behaviors = register_behaviors![
+///     CommonBehavior,
+///     GremlinDrag,
+///     #[cfg(feature = "chat")]
+///     GremlinChat,
+/// ];
+/// ```
+///
+/// Their names come from `Behavior::name`.
+#[macro_export]
+macro_rules! register_behaviors {
+    ($($(#[$attribute:meta])* $behavior:ty),* $(,)?) => {{
+        let behaviors: Vec<Box<dyn $crate::behavior::Behavior>> =
+            vec![$($(#[$attribute])* <$behavior>::new()),*];
+        behaviors
+    }};
+}
+
+/// Every behavior the `desktop_gremlin` binary runs, in the order it runs them. Embedders can
+/// start from this and add or drop behaviors before handing it to `DGRuntime::register_behaviors`.
+pub fn default_behaviors() -> Vec<Box<dyn Behavior>> {
+    crate::register_behaviors![
+        CommonBehavior,
+        GremlinDrag,
+        GremlinGestures,
+        GremlinGamepad,
+        GremlinMovement,
+        GremlinRender,
+        GremlinClick,
+        IdleVariation,
+        GremlinTree,
+        GremlinUtility,
+        GremlinStats,
+        SystemReactions,
+        GremlinNotifier,
+        GremlinBubble,
+        GremlinBroadcast,
+        GremlinScale,
+        GremlinSummon,
+        RandomEvents,
+        GremlinPomodoro,
+        GremlinWeather,
+        MediaReactions,
+        ForegroundReactions,
+        FullscreenHide,
+        PowerSaver,
+        SuspendAwareness,
+        GremlinNeighbors,
+        GremlinVisits,
+        GremlinClones,
+        GremlinPresenter,
+        GremlinInk,
+        GremlinAchievements,
+        GremlinCatch,
+        GremlinFileDrop,
+        GremlinTyping,
+        GremlinEditor,
+        GremlinMenu,
+        #[cfg(feature = "chat")]
+        GremlinChat,
+    ]
+}
+
+// frames with events kept around for `EventHistory`, a few seconds of busy input
+pub const EVENT_HISTORY_LEN: usize = 64;
+
+/// The events of the last `EVENT_HISTORY_LEN` frames that had any, the current one included,
+/// for spotting gestures and double actions that span frames, e.g. two drags in quick
+/// succession or `GremlinGestures` telling a shake from the drags. Frames without events aren't
+/// kept, so this reaches further back while the gremlin is left alone.
+#[derive(Debug, Clone, Default)]
+pub struct EventHistory {
+    frames: VecDeque<PastFrame>,
+}
+
+// when the frame started and what happened in it
+type PastFrame = (Instant, Vec<(Event, Option<EventData>)>);
+
+impl EventHistory {
+    pub(crate) fn push(&mut self, at: Instant, sequence: &[(Event, Option<EventData>)]) {
+        if sequence.is_empty() {
+            return;
+        }
+        if self.frames.len() == EVENT_HISTORY_LEN {
+            self.frames.pop_back();
+        }
+        self.frames.push_front((at, sequence.to_vec()));
+    }
+
+    /// every kept event that happened at most `within` ago with when its frame started,
+    /// newest first
+    pub fn within(
+        &self,
+        within: Duration,
+    ) -> impl Iterator<Item = (Instant, &Event, Option<&EventData>)> {
+        let now = Instant::now();
+        self.frames
+            .iter()
+            .take_while(move |(at, _)| now.duration_since(*at) <= within)
+            .flat_map(|(at, sequence)| {
+                sequence
+                    .iter()
+                    .rev()
+                    .map(|(event, data)| (*at, event, data.as_ref()))
+            })
+    }
+
+    /// calls `handler` for every `E` at most `within` ago, newest first
+    pub fn on<E: EventKind>(&self, within: Duration, mut handler: impl FnMut(Instant, E::Data)) {
+        for (at, event, data) in self.within(within) {
+            if let Some(event_data) = E::extract(event, data) {
+                handler(at, event_data);
+            }
+        }
+    }
+
+    /// how many `E`s happened at most `within` ago
+    pub fn count<E: EventKind>(&self, within: Duration) -> usize {
+        self.within(within)
+            .filter(|(_, event, data)| E::extract(event, *data).is_some())
+            .count()
+    }
 }
 
 #[derive(Debug, Default)]
 pub struct ContextData {
+    /// the latest of each event this frame, for checking whether something happened at all
     pub events: HashMap<Event, Option<EventData>>,
+    /// every event this frame in the order it happened, repeats included
+    pub sequence: Vec<(Event, Option<EventData>)>,
+    /// this frame's events and the ones before it, see `EventHistory`
+    pub history: Rc<EventHistory>,
+}
+
+impl ContextData {
+    pub fn new(sequence: Vec<(Event, Option<EventData>)>, history: Rc<EventHistory>) -> Self {
+        Self {
+            events: sequence.iter().cloned().collect(),
+            sequence,
+            history,
+        }
+    }
+
+    /// calls `handler` for every `E` this frame, in order
+    pub fn on<E: EventKind>(&self, mut handler: impl FnMut(E::Data)) {
+        for (event, data) in &self.sequence {
+            if let Some(event_data) = E::extract(event, data.as_ref()) {
+                handler(event_data);
+            }
+        }
+    }
+
+    /// calls `handler` with the data of every `Event::Custom(name)` this frame, in order
+    #[allow(unused)]
+    pub fn on_custom(&self, name: &str, mut handler: impl FnMut(Option<&EventData>)) {
+        for (event, data) in &self.sequence {
+            if matches!(event, Event::Custom(custom) if custom == name) {
+                handler(data.as_ref());
+            }
+        }
+    }
 }
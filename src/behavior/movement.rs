@@ -1,131 +1,315 @@
-use std::time::Instant;
+use std::collections::VecDeque;
 
-use sdl3::rect::Point;
+use sdl3::rect::{Point, Rect};
 
 use crate::{
     behavior::ContextData,
     events::{Event, EventData, MouseButton},
     gremlin::{DesktopGremlin, GremlinTask},
-    utils::{DirectionX, DirectionY, get_cursor_position, get_move_direction, win_to_rect},
+    utils::{
+        DirectionX, DirectionY, Easing, directional_animation_name, get_cursor_position,
+        get_move_direction, plan_path,
+    },
 };
 
 const DEFAULT_VELOCITY: f32 = 300.0;
+const DEFAULT_ACCELERATION: f32 = 1200.0;
+const DEFAULT_EASING_DISTANCE: f32 = 120.0;
+// eased speed never drops under this before the cursor is reached, or it would never get there
+const MIN_CHASE_SPEED: f32 = 40.0;
+const DEFAULT_STOP_DISTANCE: u32 = 50;
+const DEFAULT_PANIC_RADIUS: f32 = 200.0;
+// close enough to a waypoint to call it reached, in pixels
+const ARRIVAL_DISTANCE: f32 = 1.0;
+// below about 22.5 degrees off an axis the gremlin runs straight instead of diagonally
+const DIAGONAL_THRESHOLD: f32 = 0.38;
+
+/// Chase follows the cursor once the gremlin is clicked, flee keeps away from it
+/// whenever it gets closer than the panic radius.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Default)]
+pub enum MovementMode {
+    #[default]
+    Chase,
+    Flee,
+}
+
+/// Tunes how the gremlin chases the cursor, lazy gremlins want a low `max_speed`
+/// and a long `easing_distance`, frantic ones a high `acceleration`.
+#[derive(Debug, Clone, Copy)]
+pub struct FollowConfig {
+    /// top speed in pixels per second
+    pub max_speed: f32,
+    /// pixels per second squared, `0.0` jumps straight to the target speed
+    pub acceleration: f32,
+    /// margin around the window in which the gremlin considers the cursor reached
+    pub stop_distance: u32,
+    /// distance between the cursor and the stop margin under which the gremlin starts
+    /// slowing down, `0.0` disables it
+    pub easing_distance: f32,
+    pub easing: Easing,
+}
+
+impl Default for FollowConfig {
+    fn default() -> Self {
+        Self {
+            max_speed: DEFAULT_VELOCITY,
+            acceleration: DEFAULT_ACCELERATION,
+            stop_distance: DEFAULT_STOP_DISTANCE,
+            easing_distance: DEFAULT_EASING_DISTANCE,
+            easing: Easing::EaseOut,
+        }
+    }
+}
+
+impl FollowConfig {
+    /// speed the gremlin should be heading towards when `distance` pixels away from where it stops,
+    /// easing down from `max_speed`, usually `self.max_speed`
+    fn target_speed(&self, distance: f32, max_speed: f32) -> f32 {
+        if self.easing_distance <= 0.0 {
+            return max_speed;
+        }
+        max_speed
+            * self
+                .easing
+                .apply((distance / self.easing_distance).clamp(0.0, 1.0))
+    }
+
+    fn accelerate(&self, current_speed: f32, target_speed: f32, elapsed: f32) -> f32 {
+        if self.acceleration <= 0.0 {
+            return target_speed;
+        }
+        let step = self.acceleration * elapsed;
+        if current_speed < target_speed {
+            (current_speed + step).min(target_speed)
+        } else {
+            (current_speed - step).max(target_speed)
+        }
+    }
+}
 
 pub struct GremlinMovement {
-    velocity: f32,
-    is_active: bool,
+    follow: FollowConfig,
+    speed: f32,
+    // `DesktopGremlin::is_chasing` last frame, to pick up where the gremlin is when it starts
+    was_chasing: bool,
     is_dragging: bool,
     current_position: (i32, i32),
-    last_moved_at: Instant,
+    // the next frame it moves on is the first in a while, see `elapsed`
+    is_setting_off: bool,
     should_check_position: bool,
     is_window_inflated: bool,
+    initial_mode: MovementMode,
+    panic_radius: f32,
+    scared_animation: String,
+    is_fleeing: bool,
+    // waypoints left on the way to a `GremlinTask::Goto` target
+    path: VecDeque<Point>,
+    // sub-pixel position while following the path
+    path_position: (f32, f32),
+    // sub-pixel position while chasing, `None` until the next chase frame picks it up
+    chase_position: Option<(f32, f32)>,
+    // sub-pixel position while fleeing, `None` until the next flee frame picks it up
+    flee_position: Option<(f32, f32)>,
+    // unit vector the gremlin is running along, kept while it slows down
+    heading: (f32, f32),
+    heading_direction: (DirectionX, DirectionY),
 }
 
 impl Default for GremlinMovement {
     fn default() -> Self {
         Self {
-            velocity: DEFAULT_VELOCITY,
-            is_active: Default::default(),
+            follow: Default::default(),
+            speed: 0.0,
+            was_chasing: false,
             is_dragging: Default::default(),
             current_position: Default::default(),
-            last_moved_at: Instant::now(),
+            is_setting_off: true,
             should_check_position: true,
             is_window_inflated: false,
+            initial_mode: MovementMode::Chase,
+            panic_radius: DEFAULT_PANIC_RADIUS,
+            scared_animation: "SCARED".to_string(),
+            is_fleeing: false,
+            path: VecDeque::new(),
+            path_position: (0.0, 0.0),
+            chase_position: None,
+            flee_position: None,
+            heading: (0.0, 0.0),
+            heading_direction: (DirectionX::None, DirectionY::None),
         }
     }
 }
 impl super::Behavior for GremlinMovement {
-    fn setup(&mut self, _: &mut DesktopGremlin) {}
+    fn setup(&mut self, application: &mut DesktopGremlin) {
+        application.movement_mode = self.initial_mode;
+    }
 
     fn update(&mut self, application: &mut DesktopGremlin, context: &ContextData) {
-        if let Some(_) = context.events.get(&Event::Click {
+        // a gremlin with a `[tree]` or `[utility]` decides for itself when to chase, see
+        // `GremlinTree` and `GremlinUtility`
+        if context.events.contains_key(&Event::Click {
             mouse_btn: MouseButton::Left,
-        }) {
-            if !self.is_active {
-                self.last_moved_at = Instant::now();
-                self.current_position = application.canvas.window().position();
-            }
-
-            self.is_active = !self.is_active;
+        }) && application
+            .current_gremlin
+            .as_ref()
+            .is_some_and(|gremlin| gremlin.tree.is_none() && gremlin.utility.is_empty())
+        {
+            application.is_chasing = !application.is_chasing;
         }
+        if application.is_chasing && !self.was_chasing {
+            self.is_setting_off = true;
+            self.current_position = application.window.position();
+            self.chase_position = None;
+            self.speed = 0.0;
+        }
+        self.was_chasing = application.is_chasing;
         if let Some(_) = context.events.get(&Event::DragStart {
             mouse_btn: MouseButton::Left,
         }) {
             self.is_dragging = true;
+            // picked up on the way, it's not going there anymore
+            self.path.clear();
         }
         if let Some(Some(EventData::FCoordinate { .. })) = context.events.get(&Event::DragEnd {
             mouse_btn: MouseButton::Left,
         }) {
             self.is_dragging = false;
+            // it's somewhere else now
+            self.chase_position = None;
+            self.flee_position = None;
+            self.speed = 0.0;
+        }
+
+        if let Some((x, y)) = application.goto_target.take() {
+            let (from_x, from_y) = application.window.position();
+            self.path = plan_path(
+                Point::new(from_x, from_y),
+                Point::new(x, y),
+                &application.window.all_usable_bounds(),
+            )
+            .into();
+            self.path_position = (from_x as f32, from_y as f32);
+            self.chase_position = None;
+            self.flee_position = None;
+            self.speed = 0.0;
+            self.is_setting_off = true;
         }
 
-        if self.is_active
+        if !self.path.is_empty() || application.movement_mode != MovementMode::Flee {
+            self.flee_position = None;
+        }
+        if !self.path.is_empty() {
+            if !self.is_dragging {
+                self.follow_path(application);
+            }
+        } else if application.movement_mode == MovementMode::Flee {
+            self.chase_position = None;
+            if !self.is_dragging {
+                self.flee(application);
+            }
+        } else if application.is_chasing
             && !self.is_dragging
             && let Some(ref gremlin) = application.current_gremlin
             && let Some(ref animator) = gremlin.animator
         {
-            let (gremlin_x, gremlin_y) = self.current_position;
-
-            let gremlin_center = Point::new(
-                gremlin_x + ((application.canvas.window().size().0 / 2) as i32),
-                gremlin_y + ((application.canvas.window().size().1 / 2) as i32),
+            let (gremlin_x, gremlin_y) = *self.chase_position.get_or_insert((
+                self.current_position.0 as f32,
+                self.current_position.1 as f32,
+            ));
+            let (window_width, window_height) = application.window.size();
+            let gremlin_center = (
+                gremlin_x + (window_width / 2) as f32,
+                gremlin_y + (window_height / 2) as f32,
             );
 
             let (cursor_x, cursor_y) = get_cursor_position();
             let move_target = Point::new(cursor_x as i32, cursor_y as i32);
-            let (dir_x, dir_y) = get_move_direction(move_target, {
-                let mut win_rect = win_to_rect(application.canvas.window());
+            let stop_rect = {
+                let mut win_rect = Rect::new(
+                    gremlin_x.round() as i32,
+                    gremlin_y.round() as i32,
+                    window_width,
+                    window_height,
+                );
                 if self.is_window_inflated {
-                    win_rect.resize(win_rect.width() + 100, win_rect.height() + 100);
-                    win_rect.offset(-50, -50);
+                    let stop_distance = self.follow.stop_distance;
+                    win_rect.resize(
+                        win_rect.width() + stop_distance * 2,
+                        win_rect.height() + stop_distance * 2,
+                    );
+                    win_rect.offset(-(stop_distance as i32), -(stop_distance as i32));
                 }
-                self.is_window_inflated = win_rect.contains_point(move_target);
-
                 win_rect
-            });
-            let tan = ((gremlin_center.y - move_target.y) as f32)
-                / ((gremlin_center.x - move_target.x) as f32);
-            let alpha = tan.atan();
-
-            let (velo_x, x_anim) = match dir_x {
-                DirectionX::None => (0.0, ""),
-                DirectionX::Left => (-self.velocity, "LEFT"),
-                DirectionX::Right => (self.velocity, "RIGHT"),
             };
-            let (velo_y, y_anim) = match dir_y {
-                DirectionY::None => (0.0, ""),
-                DirectionY::Up => (-self.velocity, "UP"),
-                DirectionY::Down => (self.velocity, "DOWN"),
+            let (dir_x, dir_y) = get_move_direction(move_target, stop_rect);
+            self.is_window_inflated = stop_rect.contains_point(move_target);
+
+            let elapsed = self.elapsed(application);
+            let is_reached = matches!((dir_x, dir_y), (DirectionX::None, DirectionY::None));
+            let target_speed = if is_reached {
+                0.0
+            } else {
+                let (to_x, to_y) = (cursor_x - gremlin_center.0, cursor_y - gremlin_center.1);
+                let length = to_x.hypot(to_y).max(f32::EPSILON);
+                self.heading = (
+                    if matches!(dir_x, DirectionX::None) {
+                        0.0
+                    } else {
+                        to_x / length
+                    },
+                    if matches!(dir_y, DirectionY::None) {
+                        0.0
+                    } else {
+                        to_y / length
+                    },
+                );
+                self.heading_direction = (dir_x, dir_y);
+
+                // how far the cursor is from where the gremlin would stop
+                let outside_x = (stop_rect.left() as f32 - cursor_x)
+                    .max(cursor_x - stop_rect.right() as f32)
+                    .max(0.0);
+                let outside_y = (stop_rect.top() as f32 - cursor_y)
+                    .max(cursor_y - stop_rect.bottom() as f32)
+                    .max(0.0);
+                // a stride says how fast the feet go, a personality can't hurry them
+                let max_speed = gremlin
+                    .stride_speed(
+                        &animator.animation_properties.animation_name,
+                        window_width,
+                        application.speed,
+                    )
+                    .unwrap_or(self.follow.max_speed * gremlin.personality.follow_speed());
+                self.follow
+                    .target_speed(outside_x.hypot(outside_y), max_speed)
+                    .max(MIN_CHASE_SPEED.min(max_speed))
             };
+            // slows down in the direction it was already going instead of stopping dead
+            self.speed = self.follow.accelerate(self.speed, target_speed, elapsed);
 
-            let animation_name = match (dir_x, dir_y) {
-                (DirectionX::None, DirectionY::None) => "RUNIDLE".to_string(),
-                (DirectionX::None, _) => "RUN".to_string() + y_anim,
-                (_, DirectionY::None) => "RUN".to_string() + x_anim,
-                (_, _) => y_anim.to_string() + x_anim,
+            let (anim_dir_x, anim_dir_y) = if self.speed > 0.0 {
+                self.heading_direction
+            } else {
+                (DirectionX::None, DirectionY::None)
             };
+            let animation_name = directional_animation_name(anim_dir_x, anim_dir_y);
             if animator.animation_properties.animation_name != animation_name {
                 let _ = application
                     .task_channel
                     .0
                     .send(GremlinTask::PlayInterrupt(animation_name));
-                application.task_queue.clear();
+                application.cancel_animations();
             }
 
-            let (velo_x, velo_y) = (velo_x * alpha.cos().abs(), velo_y * alpha.sin().abs());
-
-            application.canvas.window_mut().set_position(
-                sdl3::video::WindowPos::Positioned(
-                    ((gremlin_x as f32) + velo_x * self.last_moved_at.elapsed().as_secs_f32())
-                        as i32,
-                ),
-                sdl3::video::WindowPos::Positioned(
-                    ((gremlin_y as f32) + velo_y * self.last_moved_at.elapsed().as_secs_f32())
-                        as i32,
-                ),
+            // sub-pixel so slow, eased steps don't get rounded away
+            let position = (
+                gremlin_x + self.heading.0 * self.speed * elapsed,
+                gremlin_y + self.heading.1 * self.speed * elapsed,
             );
-
-            self.last_moved_at = Instant::now();
+            self.chase_position = Some(position);
+            application
+                .window
+                .set_position(position.0.round() as i32, position.1.round() as i32);
         }
 
         if self.should_check_position
@@ -137,6 +321,11 @@ impl super::Behavior for GremlinMovement {
             self.current_position.1 = *y;
         }
         self.should_check_position = !self.should_check_position;
+
+        // on the move, or keeping an eye on the cursor to chase
+        if self.speed > 0.0 || !self.path.is_empty() || self.is_fleeing || application.is_chasing {
+            application.stay_awake();
+        }
     }
 }
 
@@ -144,4 +333,202 @@ impl GremlinMovement {
     pub fn new() -> Box<Self> {
         Default::default()
     }
+
+    #[allow(unused)]
+    pub fn with_flee(panic_radius: f32, scared_animation: String) -> Box<Self> {
+        Box::new(Self {
+            initial_mode: MovementMode::Flee,
+            panic_radius,
+            scared_animation,
+            ..Default::default()
+        })
+    }
+
+    #[allow(unused)]
+    pub fn with_follow(follow: FollowConfig) -> Box<Self> {
+        Box::new(Self {
+            follow,
+            ..Default::default()
+        })
+    }
+
+    /// seconds to move the gremlin for this frame, going by the runtime's frame clock. None on
+    /// the frame it sets off, that one can stand for a long idle stretch
+    fn elapsed(&mut self, application: &DesktopGremlin) -> f32 {
+        if std::mem::take(&mut self.is_setting_off) {
+            0.0
+        } else {
+            application.frame_tick().as_secs_f32()
+        }
+    }
+
+    /// heads for the next waypoint, emits `arrived` with the position once the last one is reached
+    fn follow_path(&mut self, application: &mut DesktopGremlin) {
+        let Some(&waypoint) = self.path.front() else {
+            return;
+        };
+        let elapsed = self.elapsed(application);
+
+        let (x, y) = self.path_position;
+        let (delta_x, delta_y) = (waypoint.x as f32 - x, waypoint.y as f32 - y);
+        let distance = delta_x.hypot(delta_y);
+        // ease into the last waypoint only, not every corner
+        let distance_left = distance
+            + self
+                .path
+                .iter()
+                .zip(self.path.iter().skip(1))
+                .map(|(from, to)| ((to.x - from.x) as f32).hypot((to.y - from.y) as f32))
+                .sum::<f32>();
+        let max_speed = application
+            .current_gremlin
+            .as_ref()
+            .and_then(|gremlin| {
+                gremlin.stride_speed(
+                    &gremlin
+                        .animator
+                        .as_ref()?
+                        .animation_properties
+                        .animation_name,
+                    application.window.size().0,
+                    application.speed,
+                )
+            })
+            .unwrap_or(self.follow.max_speed);
+        self.speed = self.follow.accelerate(
+            self.speed,
+            self.follow.target_speed(distance_left, max_speed),
+            elapsed,
+        );
+        let step = self.speed * elapsed;
+
+        if distance <= ARRIVAL_DISTANCE.max(step) {
+            self.path_position = (waypoint.x as f32, waypoint.y as f32);
+            application.window.set_position(waypoint.x, waypoint.y);
+            self.path.pop_front();
+            if self.path.is_empty() {
+                self.speed = 0.0;
+                application.emit(
+                    Event::Custom("arrived".to_string()),
+                    Some(EventData::Coordinate {
+                        x: waypoint.x,
+                        y: waypoint.y,
+                    }),
+                );
+                let _ = application.task_channel.0.send(GremlinTask::PlayInterrupt(
+                    application.default_animation().to_string(),
+                ));
+            }
+            return;
+        }
+
+        self.path_position = (x + delta_x / distance * step, y + delta_y / distance * step);
+        application.window.set_position(
+            self.path_position.0.round() as i32,
+            self.path_position.1.round() as i32,
+        );
+
+        let dir_x = if delta_x.abs() < distance * DIAGONAL_THRESHOLD {
+            DirectionX::None
+        } else if delta_x < 0.0 {
+            DirectionX::Left
+        } else {
+            DirectionX::Right
+        };
+        let dir_y = if delta_y.abs() < distance * DIAGONAL_THRESHOLD {
+            DirectionY::None
+        } else if delta_y < 0.0 {
+            DirectionY::Up
+        } else {
+            DirectionY::Down
+        };
+        let animation_name = directional_animation_name(dir_x, dir_y);
+        if let Some(gremlin) = &application.current_gremlin
+            && let Some(animator) = &gremlin.animator
+            && animator.animation_properties.animation_name != animation_name
+        {
+            let _ = application
+                .task_channel
+                .0
+                .send(GremlinTask::PlayInterrupt(animation_name));
+            application.cancel_animations();
+        }
+    }
+
+    fn flee(&mut self, application: &mut DesktopGremlin) {
+        let Some(ref gremlin) = application.current_gremlin else {
+            return;
+        };
+        let Some(ref animator) = gremlin.animator else {
+            return;
+        };
+
+        if !self.is_fleeing {
+            self.is_setting_off = true;
+            self.flee_position = None;
+        }
+        let (gremlin_x, gremlin_y) = *self.flee_position.get_or_insert_with(|| {
+            let (x, y) = application.window.position();
+            (x as f32, y as f32)
+        });
+        let (window_width, window_height) = application.window.size();
+        let (cursor_x, cursor_y) = get_cursor_position();
+
+        // vector pointing from the cursor to the gremlin's center
+        let away_x = gremlin_x + (window_width / 2) as f32 - cursor_x;
+        let away_y = gremlin_y + (window_height / 2) as f32 - cursor_y;
+        let distance = away_x.hypot(away_y);
+
+        if distance > self.panic_radius * gremlin.personality.flee_radius() {
+            if self.is_fleeing {
+                self.is_fleeing = false;
+                let _ = application.task_channel.0.send(GremlinTask::PlayInterrupt(
+                    application.default_animation().to_string(),
+                ));
+            }
+            return;
+        }
+        self.is_fleeing = true;
+
+        let dir_x = if away_x < 0.0 {
+            DirectionX::Left
+        } else {
+            DirectionX::Right
+        };
+        let dir_y = if away_y < 0.0 {
+            DirectionY::Up
+        } else {
+            DirectionY::Down
+        };
+
+        let animation_name = if gremlin.has_animation(&self.scared_animation) {
+            self.scared_animation.clone()
+        } else {
+            directional_animation_name(dir_x, dir_y)
+        };
+        if animator.animation_properties.animation_name != animation_name {
+            let _ = application
+                .task_channel
+                .0
+                .send(GremlinTask::PlayInterrupt(animation_name));
+            application.cancel_animations();
+        }
+
+        // cursor sitting right on the center, just pick a side and run
+        let (unit_x, unit_y) = if distance > f32::EPSILON {
+            (away_x / distance, away_y / distance)
+        } else {
+            (1.0, 0.0)
+        };
+        let elapsed = self.elapsed(application);
+        // sub-pixel like the chase, a short frame's step would get truncated away otherwise
+        let position = (
+            gremlin_x + unit_x * self.follow.max_speed * elapsed,
+            gremlin_y + unit_y * self.follow.max_speed * elapsed,
+        );
+        self.flee_position = Some(position);
+        application
+            .window
+            .set_position(position.0.round() as i32, position.1.round() as i32);
+    }
 }
@@ -0,0 +1,298 @@
+use std::{
+    collections::HashMap,
+    sync::mpsc::Receiver,
+    time::{Duration, Instant},
+};
+
+use rand::{Rng, seq::IndexedRandom};
+use tracing::warn;
+
+use crate::{
+    behavior::{Behavior, ContextData},
+    events::Event,
+    gremlin::{DesktopGremlin, GremlinTask},
+    neighbors::{Envelope, NeighborLink, NeighborMessage},
+};
+
+const STATE_INTERVAL: Duration = Duration::from_millis(500);
+// a neighbor that went this long without a word crashed or got stuck
+const FORGET_AFTER: Duration = Duration::from_secs(3);
+const DEFAULT_VISIT_EVERY: Duration = Duration::from_secs(20);
+const DEFAULT_VISIT_CHANCE: f32 = 0.3;
+const VISIT_EVENT: &str = "neighbors:visit";
+// how far apart two gremlins can stand and still high-five, edge to edge
+const MEET_DISTANCE: i32 = 32;
+// gave up on walking over, e.g. someone grabbed the gremlin on the way
+const VISIT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// another gremlin running on this machine, as it last described itself
+#[derive(Debug, Clone)]
+struct Neighbor {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    is_idle: bool,
+    last_seen: Instant,
+}
+
+impl Neighbor {
+    fn center_x(&self) -> i32 {
+        self.x + self.width as i32 / 2
+    }
+}
+
+/// Lets gremlins running side by side (see `--companion`) know about each other. Every now and then
+/// an idle gremlin walks up to an idle neighbor and they play a paired animation (WAVE, HIGHFIVE).
+pub struct GremlinNeighbors {
+    gestures: Vec<String>,
+    visit_every: Duration,
+    visit_chance: f32,
+    link: Option<(NeighborLink, Receiver<Envelope>)>,
+    neighbors: HashMap<u16, Neighbor>,
+    last_sent: Option<Instant>,
+    // walking over to this neighbor since then, greets once `arrived` comes in
+    visiting: Option<(u16, Instant)>,
+}
+
+impl Default for GremlinNeighbors {
+    fn default() -> Self {
+        Self {
+            gestures: vec!["WAVE".to_string(), "HIGHFIVE".to_string()],
+            visit_every: DEFAULT_VISIT_EVERY,
+            visit_chance: DEFAULT_VISIT_CHANCE,
+            link: None,
+            neighbors: HashMap::new(),
+            last_sent: None,
+            visiting: None,
+        }
+    }
+}
+
+impl GremlinNeighbors {
+    pub fn new() -> Box<Self> {
+        Default::default()
+    }
+
+    /// `gestures` are tried at random, only the ones this gremlin has get played
+    #[allow(unused)]
+    pub fn with_gestures(
+        gestures: Vec<String>,
+        visit_every: Duration,
+        visit_chance: f32,
+    ) -> Box<Self> {
+        Box::new(Self {
+            gestures,
+            visit_every,
+            visit_chance,
+            ..Default::default()
+        })
+    }
+
+    fn is_idle(application: &DesktopGremlin) -> bool {
+        application
+            .current_gremlin
+            .as_ref()
+            .and_then(|gremlin| gremlin.animator.as_ref())
+            .is_some_and(|animator| {
+                animator.animation_properties.animation_name == application.default_animation()
+            })
+            && application.task_queue.is_empty()
+    }
+
+    fn play(application: &DesktopGremlin, animation: String) {
+        let _ = application
+            .task_channel
+            .0
+            .send(GremlinTask::PlayInterrupt(animation));
+        let _ = application.task_channel.0.send(GremlinTask::Play(
+            application.default_animation().to_string(),
+        ));
+    }
+
+    fn describe(application: &DesktopGremlin) -> NeighborMessage {
+        let (x, y) = application.window.position();
+        let (width, height) = application.window.size();
+        NeighborMessage::State {
+            x,
+            y,
+            width,
+            height,
+            animation: application
+                .current_gremlin
+                .as_ref()
+                .and_then(|gremlin| gremlin.animator.as_ref())
+                .map(|animator| animator.animation_properties.animation_name.clone()),
+            is_idle: Self::is_idle(application),
+        }
+    }
+
+    /// the spot right next to `neighbor` on the side we're coming from, feet level with theirs
+    fn beside(application: &DesktopGremlin, neighbor: &Neighbor) -> (i32, i32) {
+        let window = application.window.rect();
+        let x = if window.center().x() < neighbor.center_x() {
+            neighbor.x - window.width() as i32
+        } else {
+            neighbor.x + neighbor.width as i32
+        };
+        (
+            x,
+            neighbor.y + neighbor.height as i32 - window.height() as i32,
+        )
+    }
+
+    fn is_next_to(application: &DesktopGremlin, neighbor: &Neighbor) -> bool {
+        let window = application.window.rect();
+        let gap_x =
+            (neighbor.x - window.right()).max(window.left() - (neighbor.x + neighbor.width as i32));
+        let gap_y = (neighbor.y - window.bottom())
+            .max(window.top() - (neighbor.y + neighbor.height as i32));
+        gap_x <= MEET_DISTANCE && gap_y <= MEET_DISTANCE
+    }
+
+    fn receive(&mut self, application: &mut DesktopGremlin) {
+        let Some((_, message_rx)) = &self.link else {
+            return;
+        };
+        for (port, message) in message_rx.try_iter() {
+            match message {
+                NeighborMessage::State {
+                    x,
+                    y,
+                    width,
+                    height,
+                    is_idle,
+                    ..
+                } => {
+                    self.neighbors.insert(
+                        port,
+                        Neighbor {
+                            x,
+                            y,
+                            width,
+                            height,
+                            is_idle,
+                            last_seen: Instant::now(),
+                        },
+                    );
+                }
+                NeighborMessage::Gesture { animation } => {
+                    let has_animation = application
+                        .current_gremlin
+                        .as_ref()
+                        .is_some_and(|gremlin| gremlin.has_animation(&animation));
+                    if has_animation && Self::is_idle(application) {
+                        Self::play(application, animation);
+                    }
+                }
+                NeighborMessage::Bye => {
+                    self.neighbors.remove(&port);
+                }
+            }
+        }
+        self.neighbors
+            .retain(|_, neighbor| neighbor.last_seen.elapsed() < FORGET_AFTER);
+    }
+
+    /// plays one of our gestures and asks `port` to join in
+    fn greet(&self, application: &mut DesktopGremlin, port: u16) {
+        let Some((link, _)) = &self.link else {
+            return;
+        };
+        let Some(gremlin) = &application.current_gremlin else {
+            return;
+        };
+        let candidates: Vec<&String> = self
+            .gestures
+            .iter()
+            .filter(|name| gremlin.has_animation(name))
+            .collect();
+        let Some(gesture) = candidates
+            .choose(&mut application.rng)
+            .map(|name| name.to_string())
+        else {
+            return;
+        };
+        link.send_to(
+            port,
+            &NeighborMessage::Gesture {
+                animation: gesture.clone(),
+            },
+        );
+        Self::play(application, gesture);
+    }
+
+    /// heads for the closest idle neighbor
+    fn visit(&mut self, application: &mut DesktopGremlin) {
+        let window = application.window.rect();
+        let closest = self
+            .neighbors
+            .iter()
+            .filter(|(_, neighbor)| neighbor.is_idle)
+            .min_by_key(|(_, neighbor)| (neighbor.center_x() - window.center().x()).abs());
+        let Some((port, neighbor)) = closest else {
+            return;
+        };
+        if Self::is_next_to(application, neighbor) {
+            self.greet(application, *port);
+            return;
+        }
+        let (x, y) = Self::beside(application, neighbor);
+        self.visiting = Some((*port, Instant::now()));
+        let _ = application.task_channel.0.send(GremlinTask::Goto(x, y));
+    }
+}
+
+impl Behavior for GremlinNeighbors {
+    fn setup(&mut self, application: &mut DesktopGremlin) {
+        self.link = NeighborLink::start()
+            .inspect_err(|err| warn!("can't look for other gremlins: {err}"))
+            .ok();
+        application.schedule_every(self.visit_every, Event::Custom(VISIT_EVENT.to_string()));
+    }
+
+    fn update(&mut self, application: &mut DesktopGremlin, context: &ContextData) {
+        let Some((link, _)) = &self.link else {
+            return;
+        };
+        if self
+            .last_sent
+            .is_none_or(|last_sent| last_sent.elapsed() >= STATE_INTERVAL)
+        {
+            link.broadcast(&Self::describe(application));
+            self.last_sent = Some(Instant::now());
+        }
+        self.receive(application);
+
+        if self
+            .visiting
+            .is_some_and(|(_, started)| started.elapsed() > VISIT_TIMEOUT)
+        {
+            self.visiting = None;
+        }
+        if let Some((port, _)) = self.visiting
+            && context
+                .events
+                .contains_key(&Event::Custom("arrived".to_string()))
+        {
+            self.visiting = None;
+            if self
+                .neighbors
+                .get(&port)
+                .is_some_and(|neighbor| Self::is_next_to(application, neighbor))
+            {
+                self.greet(application, port);
+            }
+        }
+
+        if self.visiting.is_none()
+            && context
+                .events
+                .contains_key(&Event::Custom(VISIT_EVENT.to_string()))
+            && Self::is_idle(application)
+            && application.rng.random::<f32>() < self.visit_chance
+        {
+            self.visit(application);
+        }
+    }
+}
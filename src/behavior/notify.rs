@@ -0,0 +1,129 @@
+use std::time::{Duration, Instant};
+
+use crate::{
+    behavior::{Behavior, ContextData},
+    gremlin::{DesktopGremlin, GremlinTask, Notification},
+    utils::{DirectionX, DirectionY, directional_animation_name},
+};
+
+const WALK_SPEED: f32 = 400.0;
+const NOTIFICATION_DURATION: Duration = Duration::from_secs(8);
+
+/// Delivers `DesktopGremlin::notify()` messages: walks the gremlin back on screen if it
+/// wandered off, plays ALERT and hands the message to the speech bubble.
+pub struct GremlinNotifier {
+    current: Option<Notification>,
+    last_moved_at: Instant,
+    position: (f32, f32),
+}
+
+impl Default for GremlinNotifier {
+    fn default() -> Self {
+        Self {
+            current: None,
+            last_moved_at: Instant::now(),
+            position: (0.0, 0.0),
+        }
+    }
+}
+
+impl GremlinNotifier {
+    pub fn new() -> Box<Self> {
+        Default::default()
+    }
+
+    /// closest position where the whole gremlin window is visible
+    fn on_screen_target(application: &DesktopGremlin) -> Option<(i32, i32)> {
+        let bounds = application.window.usable_bounds()?;
+        let rect = application.window.rect();
+        Some((
+            rect.x
+                .clamp(bounds.x, (bounds.x + bounds.w - rect.w).max(bounds.x)),
+            rect.y
+                .clamp(bounds.y, (bounds.y + bounds.h - rect.h).max(bounds.y)),
+        ))
+    }
+}
+
+impl Behavior for GremlinNotifier {
+    fn setup(&mut self, _: &mut DesktopGremlin) {}
+
+    fn update(&mut self, application: &mut DesktopGremlin, _: &ContextData) {
+        if self.current.is_none() {
+            self.current = application.notifications.pop_front();
+            let (x, y) = application.window.position();
+            self.position = (x as f32, y as f32);
+            self.last_moved_at = Instant::now();
+        }
+        let Some(notification) = &self.current else {
+            return;
+        };
+
+        let elapsed = self.last_moved_at.elapsed().as_secs_f32();
+        self.last_moved_at = Instant::now();
+
+        let (target_x, target_y) =
+            Self::on_screen_target(application).unwrap_or_else(|| application.window.position());
+        let (delta_x, delta_y) = (
+            target_x as f32 - self.position.0,
+            target_y as f32 - self.position.1,
+        );
+        let distance = delta_x.hypot(delta_y);
+        let step = WALK_SPEED * elapsed;
+
+        if distance > step {
+            self.position.0 += delta_x / distance * step;
+            self.position.1 += delta_y / distance * step;
+            application
+                .window
+                .set_position(self.position.0 as i32, self.position.1 as i32);
+
+            let dir_x = match delta_x {
+                x if x < -1.0 => DirectionX::Left,
+                x if x > 1.0 => DirectionX::Right,
+                _ => DirectionX::None,
+            };
+            let dir_y = match delta_y {
+                y if y < -1.0 => DirectionY::Up,
+                y if y > 1.0 => DirectionY::Down,
+                _ => DirectionY::None,
+            };
+            let animation_name = directional_animation_name(dir_x, dir_y);
+            let is_playing = application
+                .current_gremlin
+                .as_ref()
+                .and_then(|gremlin| gremlin.animator.as_ref())
+                .is_some_and(|animator| {
+                    animator.animation_properties.animation_name == animation_name
+                });
+            if !is_playing {
+                application.cancel_animations();
+                let _ = application
+                    .task_channel
+                    .0
+                    .send(GremlinTask::PlayInterrupt(animation_name));
+            }
+            return;
+        }
+
+        application.window.set_position(target_x, target_y);
+        // it goes back to resting after ALERT, not after whatever else went back to resting
+        let default_animation = application.default_animation().to_string();
+        application.cancel_tasks(&default_animation);
+        let _ = application
+            .task_channel
+            .0
+            .send(GremlinTask::PlayInterrupt("ALERT".to_string()));
+        let _ = application.task_channel.0.send(GremlinTask::Play(
+            application.default_animation().to_string(),
+        ));
+
+        let message = if notification.body.is_empty() {
+            notification.title.clone()
+        } else {
+            format!("{}\n{}", notification.title, notification.body)
+        };
+        application.say(message, NOTIFICATION_DURATION);
+        self.current = None;
+    }
+}
@@ -0,0 +1,127 @@
+use std::time::{Duration, Instant};
+
+use crate::{
+    behavior::{Behavior, ContextData},
+    events::Event,
+    gremlin::{DesktopGremlin, GremlinTask},
+    scheduler::ScheduleId,
+};
+
+const DEFAULT_WORK: Duration = Duration::from_secs(25 * 60);
+const DEFAULT_BREAK: Duration = Duration::from_secs(5 * 60);
+const BUBBLE_DURATION: Duration = Duration::from_secs(6);
+
+pub const POMODORO_START_EVENT: &str = "pomodoro:start";
+pub const POMODORO_STOP_EVENT: &str = "pomodoro:stop";
+const WORK_EVENT: &str = "pomodoro:work";
+const BREAK_EVENT: &str = "pomodoro:break";
+
+/// A pomodoro timer, the gremlin TYPEs along while you work and STRETCHes when it's break time.
+/// Started and stopped with `Event::Custom(POMODORO_START_EVENT)`/`Event::Custom(POMODORO_STOP_EVENT)`,
+/// e.g. through `desktop_gremlin send pomodoro start` or the right click menu.
+pub struct GremlinPomodoro {
+    work: Duration,
+    rest: Duration,
+    // the pending switch between work and break, `None` while the timer is off
+    next_switch: Option<ScheduleId>,
+}
+
+impl Default for GremlinPomodoro {
+    fn default() -> Self {
+        Self {
+            work: DEFAULT_WORK,
+            rest: DEFAULT_BREAK,
+            next_switch: None,
+        }
+    }
+}
+
+impl GremlinPomodoro {
+    pub fn new() -> Box<Self> {
+        Default::default()
+    }
+
+    #[allow(unused)]
+    pub fn with_durations(work: Duration, rest: Duration) -> Box<Self> {
+        Box::new(Self {
+            work,
+            rest,
+            next_switch: None,
+        })
+    }
+
+    fn stop(&mut self, application: &mut DesktopGremlin) {
+        if let Some(id) = self.next_switch.take() {
+            application.cancel_scheduled(id);
+        }
+    }
+
+    fn start_work(&mut self, application: &mut DesktopGremlin) {
+        self.stop(application);
+        self.next_switch = Some(application.schedule_at(
+            Instant::now() + self.work,
+            Event::Custom(BREAK_EVENT.to_string()),
+        ));
+        application.cancel_animations();
+        // nothing queued after it, so TYPE keeps looping until the break
+        let _ = application
+            .task_channel
+            .0
+            .send(GremlinTask::PlayInterrupt("TYPE".to_string()));
+    }
+
+    fn start_break(&mut self, application: &mut DesktopGremlin) {
+        self.next_switch = Some(application.schedule_at(
+            Instant::now() + self.rest,
+            Event::Custom(WORK_EVENT.to_string()),
+        ));
+        let default_animation = application.default_animation().to_string();
+        application.cancel_tasks(&default_animation);
+        let _ = application
+            .task_channel
+            .0
+            .send(GremlinTask::PlayInterrupt("STRETCH".to_string()));
+        let _ = application.task_channel.0.send(GremlinTask::Play(
+            application.default_animation().to_string(),
+        ));
+        application.say(
+            format!("break time! back in {} minutes", self.rest.as_secs() / 60),
+            BUBBLE_DURATION,
+        );
+    }
+}
+
+impl Behavior for GremlinPomodoro {
+    fn setup(&mut self, _: &mut DesktopGremlin) {}
+
+    fn update(&mut self, application: &mut DesktopGremlin, context: &ContextData) {
+        let has_event = |name: &str| {
+            context
+                .events
+                .contains_key(&Event::Custom(name.to_string()))
+        };
+
+        if has_event(POMODORO_STOP_EVENT) && self.next_switch.is_some() {
+            self.stop(application);
+            let _ = application.task_channel.0.send(GremlinTask::PlayInterrupt(
+                application.default_animation().to_string(),
+            ));
+            application.say("pomodoro stopped", BUBBLE_DURATION);
+        }
+        if has_event(POMODORO_START_EVENT) {
+            self.start_work(application);
+            application.say("let's get to work!", BUBBLE_DURATION);
+        }
+        // a stale switch could still be queued up from before a stop
+        if self.next_switch.is_none() {
+            return;
+        }
+        if has_event(BREAK_EVENT) {
+            self.start_break(application);
+        }
+        if has_event(WORK_EVENT) {
+            self.start_work(application);
+            application.say("back to work!", BUBBLE_DURATION);
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use std::collections::HashSet;
+
+use crate::{
+    behavior::{Behavior, ContextData},
+    events::{Event, EventData},
+    gremlin::DesktopGremlin,
+};
+
+const SAVING_FRAMERATE: u32 = 12;
+// skipped while saving power, by their `Behavior::name()`
+const SAVING_DISABLES: &[&str] = &["GremlinMovement", "RandomEvents"];
+
+/// When to start saving power.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PowerSaveWhen {
+    /// as soon as the machine is unplugged
+    OnBattery,
+    /// on battery and below this percentage
+    #[allow(unused)]
+    Below(f32),
+}
+
+/// Drops the framerate and turns off movement and random events while running on battery,
+/// switching back once the machine is plugged in again.
+pub struct PowerSaver {
+    when: PowerSaveWhen,
+    battery_percent: Option<f32>,
+    is_saving: bool,
+    // what this behavior disabled, so the user's own choices are left alone when switching back
+    disabled: HashSet<String>,
+    is_quitting: bool,
+}
+
+impl Default for PowerSaver {
+    fn default() -> Self {
+        Self {
+            when: PowerSaveWhen::OnBattery,
+            battery_percent: None,
+            is_saving: false,
+            disabled: HashSet::new(),
+            is_quitting: false,
+        }
+    }
+}
+
+impl PowerSaver {
+    pub fn new() -> Box<Self> {
+        Default::default()
+    }
+
+    #[allow(unused)]
+    pub fn with_threshold(when: PowerSaveWhen) -> Box<Self> {
+        Box::new(Self {
+            when,
+            ..Default::default()
+        })
+    }
+
+    fn should_save(&self) -> bool {
+        match (self.when, self.battery_percent) {
+            (_, None) => false,
+            (PowerSaveWhen::OnBattery, Some(_)) => true,
+            (PowerSaveWhen::Below(threshold), Some(percent)) => percent < threshold,
+        }
+    }
+}
+
+impl Behavior for PowerSaver {
+    fn setup(&mut self, _: &mut DesktopGremlin) {}
+
+    fn runs_while_paused(&self) -> bool {
+        true
+    }
+
+    fn update(&mut self, application: &mut DesktopGremlin, context: &ContextData) {
+        if let Some(Some(EventData::Value(percent))) =
+            context.events.get(&Event::Custom("battery".to_string()))
+        {
+            self.battery_percent = Some(*percent);
+        }
+        if context
+            .events
+            .contains_key(&Event::Custom("on_ac".to_string()))
+        {
+            self.battery_percent = None;
+        }
+
+        // the saved state shouldn't remember movement as turned off
+        self.is_quitting |= context.events.contains_key(&Event::Quit);
+        let should_save = self.should_save() && !self.is_quitting;
+        if should_save == self.is_saving {
+            return;
+        }
+        self.is_saving = should_save;
+
+        if should_save {
+            application.set_framerate(SAVING_FRAMERATE);
+            for name in SAVING_DISABLES {
+                if application.disabled_behaviors.insert(name.to_string()) {
+                    self.disabled.insert(name.to_string());
+                }
+            }
+        } else {
+            application.set_framerate(application.target_framerate);
+            for name in self.disabled.drain() {
+                application.disabled_behaviors.remove(&name);
+            }
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use sdl3::sys::mouse::{SDL_BUTTON_LMASK, SDL_GetGlobalMouseState};
+use tracing::warn;
+
+use crate::{
+    backend::WindowLayer,
+    behavior::{Behavior, ContextData},
+    events::{Event, kinds},
+    gremlin::{DesktopGremlin, GremlinTask},
+};
+
+const DEFAULT_HOTKEY: &str = "ctrl+alt+KeyP";
+/// turns presentation mode on or off, raised by the `present` ipc command
+pub const PRESENT_TOGGLE_EVENT: &str = "present:toggle";
+// how much bigger the gremlin gets while presenting, so it shows up on a shared screen
+const PRESENT_SCALE_FACTOR: f32 = 1.5;
+// the window's top left corner sits this far below and right of the cursor
+const CURSOR_OFFSET: (i32, i32) = (12, 12);
+const MOVEMENT_BEHAVIOR: &str = "GremlinMovement";
+
+/// A laser pointer with legs for screen sharing: on a hotkey the gremlin grows, sticks right
+/// next to the cursor and plays POINT whenever the user clicks anywhere. Same hotkey to stop.
+pub struct GremlinPresenter {
+    hotkey: String,
+    animation: String,
+    hotkey_id: Option<u32>,
+    // (scale before presenting, whether movement was on), `Some` while presenting
+    restore: Option<(f32, bool)>,
+    was_mouse_down: bool,
+    is_quitting: bool,
+}
+
+impl Default for GremlinPresenter {
+    fn default() -> Self {
+        Self {
+            hotkey: DEFAULT_HOTKEY.to_string(),
+            animation: "POINT".to_string(),
+            hotkey_id: None,
+            restore: None,
+            was_mouse_down: false,
+            is_quitting: false,
+        }
+    }
+}
+
+impl GremlinPresenter {
+    pub fn new() -> Box<Self> {
+        Default::default()
+    }
+
+    /// `hotkey` is written like "ctrl+shift+KeyP" or "alt+F11"
+    #[allow(unused)]
+    pub fn with_hotkey(hotkey: &str, animation: &str) -> Box<Self> {
+        Box::new(Self {
+            hotkey: hotkey.to_string(),
+            animation: animation.to_string(),
+            ..Default::default()
+        })
+    }
+
+    fn start(&mut self, application: &mut DesktopGremlin) {
+        // the gremlin sticks to the cursor instead of chasing it
+        let is_moving = application
+            .disabled_behaviors
+            .insert(MOVEMENT_BEHAVIOR.to_string());
+        self.restore = Some((application.scale, is_moving));
+        let _ = application.task_channel.0.send(GremlinTask::SetScale(
+            application.scale * PRESENT_SCALE_FACTOR,
+        ));
+        if let Err(err) = application.window.set_layer(WindowLayer::OnTop) {
+            warn!("couldn't bring the gremlin on top for presenting: {err:#}");
+        }
+    }
+
+    fn stop(&mut self, application: &mut DesktopGremlin) {
+        let Some((scale, was_moving)) = self.restore.take() else {
+            return;
+        };
+        if was_moving {
+            application.disabled_behaviors.remove(MOVEMENT_BEHAVIOR);
+        }
+        let _ = application
+            .task_channel
+            .0
+            .send(GremlinTask::SetScale(scale));
+    }
+}
+
+impl Behavior for GremlinPresenter {
+    fn setup(&mut self, application: &mut DesktopGremlin) {
+        match application.register_hotkey(&self.hotkey) {
+            Ok(id) => self.hotkey_id = Some(id),
+            Err(err) => warn!(
+                "couldn't register presentation hotkey {:?}: {err:#}",
+                self.hotkey
+            ),
+        }
+    }
+
+    fn update(&mut self, application: &mut DesktopGremlin, context: &ContextData) {
+        // the saved state shouldn't remember movement as turned off or the gremlin blown up
+        self.is_quitting |= context.events.contains_key(&Event::Quit);
+        if self.is_quitting {
+            self.stop(application);
+            return;
+        }
+        let mut is_toggled = context
+            .events
+            .contains_key(&Event::Custom(PRESENT_TOGGLE_EVENT.to_string()));
+        if let Some(hotkey_id) = self.hotkey_id {
+            context.on::<kinds::HotKey>(|id| is_toggled |= id == hotkey_id);
+        }
+        if is_toggled {
+            if self.restore.is_some() {
+                self.stop(application);
+            } else {
+                self.start(application);
+            }
+        }
+        if self.restore.is_none() {
+            return;
+        }
+
+        let (mut x, mut y) = (0.0, 0.0);
+        // SAFETY: behaviors only run while the SDL context is alive
+        let buttons = unsafe { SDL_GetGlobalMouseState(&mut x, &mut y) };
+        let target = (
+            x.round() as i32 + CURSOR_OFFSET.0,
+            y.round() as i32 + CURSOR_OFFSET.1,
+        );
+        if application.window.position() != target {
+            application.window.set_position(target.0, target.1);
+        }
+
+        let is_mouse_down = buttons & SDL_BUTTON_LMASK != 0;
+        if is_mouse_down
+            && !self.was_mouse_down
+            && application
+                .current_gremlin
+                .as_ref()
+                .is_some_and(|gremlin| gremlin.has_animation(&self.animation))
+        {
+            let _ = application
+                .task_channel
+                .0
+                .send(GremlinTask::PlayInterrupt(self.animation.clone()));
+            let _ = application.task_channel.0.send(GremlinTask::Play(
+                application.default_animation().to_string(),
+            ));
+        }
+        self.was_mouse_down = is_mouse_down;
+    }
+}
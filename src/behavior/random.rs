@@ -0,0 +1,130 @@
+use std::time::Duration;
+
+use rand::Rng;
+
+use crate::{
+    behavior::{Behavior, ContextData},
+    events::Event,
+    gremlin::{DesktopGremlin, GremlinTask},
+};
+
+const DEFAULT_ROLL_EVERY: Duration = Duration::from_secs(10);
+const ROLL_EVENT: &str = "random:roll";
+
+/// Something the gremlin does out of nowhere.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RandomAction {
+    /// plays the animation, then goes back to IDLE
+    Play(String),
+    /// runs over to the other side of the display it's on
+    RunAcross,
+}
+
+/// Rolls on a table of random happenings every so often, so the gremlin does things
+/// without anyone poking it. Only rolls while the gremlin is idling.
+pub struct RandomEvents {
+    roll_every: Duration,
+    // (chance between 0 and 1, action), whatever the chances don't add up to is "nothing happens"
+    table: Vec<(f32, RandomAction)>,
+}
+
+impl Default for RandomEvents {
+    fn default() -> Self {
+        Self {
+            roll_every: DEFAULT_ROLL_EVERY,
+            table: vec![
+                (0.05, RandomAction::Play("SNEEZE".to_string())),
+                (0.01, RandomAction::RunAcross),
+            ],
+        }
+    }
+}
+
+impl RandomEvents {
+    pub fn new() -> Box<Self> {
+        Default::default()
+    }
+
+    #[allow(unused)]
+    pub fn with_table(roll_every: Duration, table: Vec<(f32, RandomAction)>) -> Box<Self> {
+        Box::new(Self { roll_every, table })
+    }
+
+    /// picks at most one action, each with its own chance, less likely the more patient the
+    /// gremlin's personality is
+    fn roll(&self, application: &mut DesktopGremlin) -> Option<RandomAction> {
+        let patience = application
+            .current_gremlin
+            .as_ref()
+            .map_or(1.0, |gremlin| gremlin.personality.idle_patience());
+        let mut roll = application.rng.random::<f32>() * patience;
+        for (chance, action) in &self.table {
+            if roll < *chance {
+                return Some(action.clone());
+            }
+            roll -= chance;
+        }
+        None
+    }
+
+    /// the spot on the far side of the current display, at the same height
+    fn across(application: &DesktopGremlin) -> Option<(i32, i32)> {
+        let bounds = application.window.usable_bounds()?;
+        let window = application.window.rect();
+        let x = if window.center().x() < bounds.center().x() {
+            bounds.right() - window.width() as i32
+        } else {
+            bounds.left()
+        };
+        Some((x, window.y()))
+    }
+}
+
+impl Behavior for RandomEvents {
+    fn setup(&mut self, application: &mut DesktopGremlin) {
+        application.schedule_every(self.roll_every, Event::Custom(ROLL_EVENT.to_string()));
+    }
+
+    fn update(&mut self, application: &mut DesktopGremlin, context: &ContextData) {
+        if !context
+            .events
+            .contains_key(&Event::Custom(ROLL_EVENT.to_string()))
+        {
+            return;
+        }
+        let is_idle = application
+            .current_gremlin
+            .as_ref()
+            .and_then(|gremlin| gremlin.animator.as_ref())
+            .is_some_and(|animator| {
+                animator.animation_properties.animation_name == application.default_animation()
+            })
+            && application.task_queue.is_empty();
+        if !is_idle {
+            return;
+        }
+
+        match self.roll(application) {
+            Some(RandomAction::Play(animation_name))
+                if application
+                    .current_gremlin
+                    .as_ref()
+                    .is_some_and(|gremlin| gremlin.has_animation(&animation_name)) =>
+            {
+                let _ = application
+                    .task_channel
+                    .0
+                    .send(GremlinTask::Play(animation_name));
+                let _ = application.task_channel.0.send(GremlinTask::Play(
+                    application.default_animation().to_string(),
+                ));
+            }
+            Some(RandomAction::RunAcross) => {
+                if let Some((x, y)) = Self::across(application) {
+                    let _ = application.task_channel.0.send(GremlinTask::Goto(x, y));
+                }
+            }
+            _ => {}
+        }
+    }
+}
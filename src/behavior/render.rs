@@ -1,48 +1,679 @@
 use std::{
+    collections::VecDeque,
+    f32::consts::TAU,
+    fs,
+    path::{Path, PathBuf},
     rc::Rc,
-    sync::{Arc, Mutex},
+    sync::{Arc, Mutex, atomic::Ordering},
+    time::{Duration, Instant},
 };
 
-use sdl3::render::Texture;
+use anyhow::{Context, anyhow};
+use image::{DynamicImage, RgbaImage};
+use sdl3::{
+    pixels::Color,
+    rect::Rect,
+    render::{BlendMode, Canvas, FRect, Texture},
+    sys::mouse::SDL_GetGlobalMouseState,
+    video::Window,
+};
+use tracing::{error, info, warn};
 
 use crate::{
+    backend::{Ghost, WindowBackend},
     behavior::Behavior,
-    gremlin::{Animation, AnimationProperties, Animator, DEFAULT_COLUMN_COUNT, GremlinTask},
-    utils::{TextureCache, sdl_resize},
+    events::{
+        Event, WindowEvent,
+        kinds::{self, KeyDown},
+    },
+    gremlin::{
+        Accessory, Animation, AnimationProperties, Animator, DEFAULT_COLUMN_COUNT, DesktopGremlin,
+        GLOBAL_PIXEL_FORMAT, GremlinLoadError, GremlinTask, Layer, LayerMode, MAX_IDLE_WAIT,
+        MAX_SPEED, MIN_SPEED, Scaling,
+    },
+    io::{AsyncAnimationLoader, LoaderTask},
+    recording::Recording,
+    ui::{Render, text::Text},
+    utils::{CACHE_CAPACITY, TextureCache, data_dir, sdl_resize},
 };
 
+const DEBUG_OVERLAY_KEY: &str = "F3";
+// saves the frame on screen into `screenshots_dir()` from anywhere, see `GremlinTask::Screenshot`
+const SCREENSHOT_HOTKEY: &str = "ctrl+alt+KeyS";
+// how many of the slowest frame phases get listed
+const DEBUG_OVERLAY_SLOWEST: usize = 3;
+// how many queued tasks get listed by name
+const DEBUG_OVERLAY_TASKS: usize = 4;
+const DEBUG_OVERLAY_BACKGROUND: Color = Color::RGBA(0xff, 0xff, 0xff, 0xc0);
+const THINKING_COLOR: Color = Color::RGBA(0x40, 0x40, 0x40, 0xc0);
+// one dot lights up after the other this often
+const THINKING_STEP: Duration = Duration::from_millis(300);
+// how far the window has to move in one frame before the trail shows up
+const TRAIL_MIN_SPEED: i32 = 6;
+// of the newest ghost, older ones fade out from there
+const TRAIL_ALPHA: f32 = 0.5;
+// everything `directional_animation_name` gives, played over and over while moving around,
+// rebuilding their sheets every time shows up as a hitch. The default animation gets pinned
+// too, see `is_pinned`
+const PINNED_ANIMATIONS: &[&str] = &[
+    "RUNIDLE",
+    "RUNLEFT",
+    "RUNRIGHT",
+    "RUNUP",
+    "RUNDOWN",
+    "UPLEFT",
+    "UPRIGHT",
+    "DOWNLEFT",
+    "DOWNRIGHT",
+];
+
 #[derive(Default)]
 pub struct GremlinRender {
     pub current_animation_name: String,
-    pub texture_cache: Arc<Mutex<TextureCache>>,
+    // the sheet standing in for it, which can change with the time of day, see `Gremlin::timed_aliases`
+    current_sheet_name: String,
+    // `DesktopGremlin::texture_cache`, picked up in setup
+    texture_cache: Arc<Mutex<TextureCache>>,
     pub gremlin_texture: Option<Rc<Texture>>,
+    // an interrupt that came in while paused, played once resumed
+    pending_interrupt: Option<GremlinTask>,
+    show_debug_overlay: bool,
+    last_update: Option<Instant>,
+    // smoothed over the last few frames
+    fps: f32,
+    // the current animation's `[speeds]` entry, times the gremlin's `.fps` against the default
+    animation_speed: Option<f32>,
+    // what's on screen right now, nothing gets redrawn until this changes
+    last_drawn: Option<DrawnFrame>,
+    // the last few frames drawn while moving fast, oldest first, see `Gremlin::trail`
+    trail: VecDeque<DrawnFrame>,
+    // decodes sheets that aren't cached yet off the render thread
+    loader: AsyncAnimationLoader,
+    // (requested name, resolved sheet name) being decoded, the old animation keeps playing meanwhile
+    loading: Option<(String, String)>,
+    // when the "thinking" dots went up, shown while there's no old animation to keep playing
+    thinking_since: Option<Instant>,
+    // the current gremlin's `Gremlin::layers`, made for the window's size
+    layers: Vec<LayerSheet>,
+    // (gremlin path, window size) `layers` were made for
+    layers_made_for: Option<(Option<String>, (u32, u32))>,
+    // (index into `layers`, frame) of every layer on screen right now
+    last_layer_frames: Vec<(usize, Rect)>,
+    // the worn `DesktopGremlin::accessories`, made for the window's size
+    accessories: Vec<AccessorySheet>,
+    accessories_made_for: Option<AccessoriesFor>,
+    // (index into `accessories`, where in the window) of every accessory on screen right now
+    last_accessory_targets: Vec<(usize, Rect)>,
+    // the machine is suspended and every texture got let go of, nothing gets drawn until it resumes
+    is_asleep: bool,
+    // where to save the next frame drawn, see `GremlinTask::Screenshot`
+    pending_screenshots: Vec<PathBuf>,
+    screenshot_hotkey_id: Option<u32>,
+    // see `GremlinTask::Record`
+    recording: Option<Recording>,
+}
+
+/// (gremlin path, window size, worn accessories) accessory images were made for
+type AccessoriesFor = (Option<String>, (u32, u32), Vec<String>);
+
+struct AccessorySheet {
+    accessory: Accessory,
+    texture: Rc<Texture>,
+    size: (u32, u32),
+    // from the gremlin's own frame pixels to the window's
+    scale: f32,
+}
+
+struct LayerSheet {
+    layer: Layer,
+    texture: Rc<Texture>,
+    animator: Animator,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct DrawnFrame {
+    sheet: Option<*const Texture>,
+    frame: Rect,
+    window: Rect,
 }
 
 impl GremlinRender {
     pub fn new() -> Box<Self> {
         Default::default()
     }
+
+    /// resizes the window and remakes the current sheet at the new size,
+    /// the cached sheets were all made for the old size so they go
+    fn set_scale(&mut self, application: &mut DesktopGremlin, scale: f32) {
+        application.set_scale(scale);
+        self.gremlin_texture = None;
+        if let Ok(mut cache) = self.texture_cache.lock() {
+            cache.clear();
+        }
+
+        let Some(gremlin) = &mut application.current_gremlin else {
+            return;
+        };
+        // the sheet on screen, which might be one of several variants
+        let Some(resolved_name) = Some(self.current_sheet_name.clone())
+            .filter(|sheet_name| !sheet_name.is_empty())
+            .or_else(|| gremlin.resolve_animation(&self.current_animation_name))
+        else {
+            return;
+        };
+        let Some(animation) = gremlin.animation_map.get(&resolved_name).and_then(|props| {
+            <&AnimationProperties as TryInto<Animation>>::try_into(props)
+                .inspect_err(|err| error!("couldn't load {resolved_name}: {err}"))
+                .ok()
+        }) else {
+            return;
+        };
+        let Some(animator) = &mut gremlin.animator else {
+            return;
+        };
+
+        self.gremlin_texture = create_sheet_texture(
+            application.window.as_mut(),
+            &animation,
+            animator,
+            gremlin.scaling,
+        );
+        if let Some(texture) = &self.gremlin_texture
+            && let Ok(mut cache) = self.texture_cache.lock()
+        {
+            cache.cache(resolved_name, (animator.clone(), texture.clone()));
+        }
+    }
+
+    /// lets go of every texture while the machine sleeps, the driver may well have dropped
+    /// them by the time it wakes up anyway
+    fn release_textures(&mut self) {
+        self.gremlin_texture = None;
+        if let Ok(mut cache) = self.texture_cache.lock() {
+            cache.clear();
+        }
+        self.layers.clear();
+        self.layers_made_for = None;
+        self.accessories.clear();
+        self.accessories_made_for = None;
+        self.last_drawn = None;
+        self.trail.clear();
+    }
+
+    /// draws the frame on screen again into a texture of the window's size, transparency and all,
+    /// and reads it back. Layers and accessories come along, the trail and debug overlay don't
+    fn snapshot(&self, application: &mut DesktopGremlin, frame: Rect) -> anyhow::Result<RgbaImage> {
+        let sheet = self
+            .gremlin_texture
+            .as_ref()
+            .ok_or_else(|| anyhow!("nothing's drawn yet"))?;
+        let (width, height) = application.window.size();
+        let canvas = application
+            .window
+            .canvas()
+            .ok_or_else(|| anyhow!("there's no window to draw on"))?;
+        let mut target =
+            canvas
+                .texture_creator()
+                .create_texture_target(GLOBAL_PIXEL_FORMAT, width, height)?;
+        let mut pixels = Err(anyhow!("the frame was never drawn"));
+        let drawn = canvas.with_texture_canvas(&mut target, |canvas| {
+            canvas.set_draw_color(Color::RGBA(0, 0, 0, 0));
+            canvas.clear();
+            let _ = canvas.copy(sheet, frame, None);
+            for (index, frame) in &self.last_layer_frames {
+                let _ = canvas.copy(&self.layers[*index].texture, *frame, None);
+            }
+            for (index, target) in &self.last_accessory_targets {
+                let _ = canvas.copy(&self.accessories[*index].texture, None, *target);
+            }
+            pixels = canvas.read_pixels(None).map_err(anyhow::Error::from);
+        });
+        // SAFETY: nothing else ever saw the target
+        unsafe { target.destroy() };
+        drawn?;
+        let surface = pixels?.convert_format(GLOBAL_PIXEL_FORMAT)?;
+        // rows can come padded
+        let row = surface.width() as usize * GLOBAL_PIXEL_FORMAT.bytes_per_pixel();
+        let bytes = surface.with_lock(|pixels| {
+            pixels
+                .chunks(surface.pitch() as usize)
+                .flat_map(|line| &line[..row])
+                .copied()
+                .collect()
+        });
+        RgbaImage::from_raw(surface.width(), surface.height(), bytes)
+            .ok_or_else(|| anyhow!("sdl handed back fewer pixels than the window has"))
+    }
+
+    fn save_screenshots(&mut self, application: &mut DesktopGremlin, frame: Rect) {
+        let image = match self.snapshot(application, frame) {
+            Ok(image) => image,
+            Err(err) => {
+                warn!("couldn't take a screenshot: {err:#}");
+                self.pending_screenshots.clear();
+                return;
+            }
+        };
+        for path in self.pending_screenshots.drain(..) {
+            match save_png(&image, &path) {
+                Ok(()) => info!("saved a screenshot to {}", path.display()),
+                Err(err) => warn!("couldn't save a screenshot to {}: {err:#}", path.display()),
+            }
+        }
+    }
+
+    fn record(&mut self, application: &mut DesktopGremlin, now: Instant, is_drawn: bool) {
+        let Some(recording) = &self.recording else {
+            return;
+        };
+        if recording.is_done(now) {
+            if let Some(recording) = self.recording.take() {
+                recording.finish();
+            }
+            return;
+        }
+        if let Some(drawn) = self.last_drawn
+            && recording.wants_frame(now, is_drawn)
+        {
+            match self.snapshot(application, drawn.frame) {
+                Ok(image) => {
+                    if let Some(recording) = &mut self.recording {
+                        recording.capture(now, image);
+                    }
+                }
+                Err(err) => {
+                    warn!("couldn't record a frame, stopping: {err:#}");
+                    self.recording = None;
+                }
+            }
+        }
+    }
+
+    /// remakes the layer sheets when the gremlin or the window's size changed,
+    /// they're small enough to decode right here
+    fn update_layers(&mut self, application: &mut DesktopGremlin) {
+        let made_for = (application.gremlin_path.clone(), application.window.size());
+        if self.layers_made_for.as_ref() == Some(&made_for) {
+            return;
+        }
+        self.layers_made_for = Some(made_for);
+        self.last_layer_frames.clear();
+        let Some(gremlin) = &application.current_gremlin else {
+            self.layers.clear();
+            return;
+        };
+        self.layers = gremlin
+            .layers
+            .iter()
+            .filter_map(|layer| {
+                let name = &layer.properties.animation_name;
+                let animation =
+                    <&AnimationProperties as TryInto<Animation>>::try_into(&layer.properties)
+                        .inspect_err(|err| error!("couldn't load the {name} layer: {err}"))
+                        .ok()?;
+                let mut animator: Animator = (&animation).into();
+                let texture = create_sheet_texture(
+                    application.window.as_mut(),
+                    &animation,
+                    &mut animator,
+                    gremlin.scaling,
+                )?;
+                Some(LayerSheet {
+                    layer: layer.clone(),
+                    texture,
+                    animator,
+                })
+            })
+            .collect();
+    }
+
+    /// remakes the accessory images when the gremlin, the window's size or what it wears changed
+    fn update_accessories(&mut self, application: &mut DesktopGremlin) {
+        let made_for = (
+            application.gremlin_path.clone(),
+            application.window.size(),
+            application.accessories.clone(),
+        );
+        if self.accessories_made_for.as_ref() == Some(&made_for) {
+            return;
+        }
+        self.accessories_made_for = Some(made_for);
+        self.last_accessory_targets.clear();
+        let Some(gremlin) = &application.current_gremlin else {
+            self.accessories.clear();
+            return;
+        };
+        let (width, _) = application.window.size();
+        let scale = width as f32 / gremlin.frame_width.unwrap_or(width).max(1) as f32;
+        self.accessories = gremlin
+            .accessories
+            .iter()
+            .filter(|accessory| application.accessories.contains(&accessory.name))
+            .filter_map(|accessory| {
+                let image = image::open(&accessory.sprite_path)
+                    .inspect_err(|err| {
+                        error!("couldn't load the {} accessory: {err}", accessory.name)
+                    })
+                    .ok()?;
+                // img_get_bytes_global hands the raw buffer straight to sdl
+                let image = DynamicImage::from(image.into_rgba8());
+                let size = (
+                    ((image.width() as f32 * scale).round() as u32).max(1),
+                    ((image.height() as f32 * scale).round() as u32).max(1),
+                );
+                let canvas = application.window.canvas()?;
+                let texture = sdl_resize(&image, size, gremlin.scaling, canvas)
+                    .inspect_err(|err| {
+                        error!("couldn't load the {} accessory: {err}", accessory.name)
+                    })
+                    .ok()?;
+                Some(AccessorySheet {
+                    accessory: accessory.clone(),
+                    texture: Rc::new(texture),
+                    size,
+                    scale,
+                })
+            })
+            .collect();
+    }
+
+    /// where every worn accessory anchored in the current animation goes in the window
+    fn accessory_targets(&self) -> Vec<(usize, Rect)> {
+        self.accessories
+            .iter()
+            .enumerate()
+            .filter_map(|(index, sheet)| {
+                let (x, y) = sheet.accessory.anchor(&self.current_animation_name)?;
+                let (width, height) = sheet.size;
+                Some((
+                    index,
+                    Rect::new(
+                        (x as f32 * sheet.scale).round() as i32 - width as i32 / 2,
+                        (y as f32 * sheet.scale).round() as i32 - height as i32 / 2,
+                        width,
+                        height,
+                    ),
+                ))
+            })
+            .collect()
+    }
+
+    /// picks the frame of every layer that shows over the current animation
+    fn layer_frames(&mut self, application: &DesktopGremlin) -> Vec<(usize, Rect)> {
+        if self.layers.is_empty() {
+            return Vec::new();
+        }
+        let cursor = application.sdl.is_some().then(|| {
+            let (mut x, mut y) = (0.0, 0.0);
+            // SAFETY: SDL is up as long as there's an `Sdl` around
+            unsafe { SDL_GetGlobalMouseState(&mut x, &mut y) };
+            (x, y)
+        });
+        let window = application.window.rect();
+        self.layers
+            .iter_mut()
+            .enumerate()
+            .filter(|(_, sheet)| sheet.layer.shows_over(&self.current_animation_name))
+            .map(|(index, sheet)| {
+                let frame = match sheet.layer.mode {
+                    LayerMode::Look => {
+                        look_frame(sheet.layer.properties.sprite_count, window, cursor)
+                    }
+                };
+                sheet.animator.show_frame(frame);
+                (index, sheet.animator.get_frame_rect())
+            })
+            .collect()
+    }
+
+    /// puts a sheet the loader finished decoding on screen, if it's still the one wanted
+    fn finish_loading(
+        &mut self,
+        application: &mut DesktopGremlin,
+        name: String,
+        loaded: Result<Animation, GremlinLoadError>,
+    ) {
+        let Some((animation_name, resolved_name)) = self
+            .loading
+            .take_if(|(_, resolved_name)| *resolved_name == name)
+        else {
+            return;
+        };
+        let animation = match loaded {
+            Ok(animation) => animation,
+            Err(err) => {
+                error!("couldn't load {resolved_name}: {err}");
+                application.should_check_for_action = true;
+                return;
+            }
+        };
+
+        let mut animator: Animator = (&animation).into();
+        let scaling = application
+            .current_gremlin
+            .as_ref()
+            .map_or_else(Default::default, |gremlin| gremlin.scaling);
+        let texture = create_sheet_texture(
+            application.window.as_mut(),
+            &animation,
+            &mut animator,
+            scaling,
+        );
+        drop(animation);
+        if let Some(texture) = &texture
+            && let Ok(mut cache) = self.texture_cache.lock()
+        {
+            cache.cache(resolved_name.clone(), (animator.clone(), texture.clone()));
+            // aliases and variants are cached under the sheet it ended up as
+            if is_pinned(application, &animation_name) {
+                cache.pin(animation_name.clone(), resolved_name.clone());
+            }
+        }
+        self.gremlin_texture = texture;
+        if let Some(gremlin) = &mut application.current_gremlin {
+            gremlin.animator = Some(animator);
+        }
+        self.start_animation(application, animation_name, &resolved_name);
+    }
+
+    /// everything that comes with switching animations once the new sheet is in place
+    fn start_animation(
+        &mut self,
+        application: &mut DesktopGremlin,
+        animation_name: String,
+        resolved_name: &str,
+    ) {
+        let Some(gremlin) = &mut application.current_gremlin else {
+            return;
+        };
+        // behaviors keep seeing the name they asked for, whatever sheet stands in for it
+        if let Some(animator) = &mut gremlin.animator {
+            animator.animation_properties.animation_name = animation_name.clone();
+        }
+
+        if let Some(sound) = gremlin
+            .sounds
+            .get(&animation_name)
+            .or_else(|| gremlin.sounds.get(resolved_name))
+            && let Some(audio) = &mut application.audio
+            && let Err(err) = audio.play(sound)
+        {
+            warn!("couldn't play {}: {err}", sound.display());
+        }
+
+        self.animation_speed = Some(gremlin.playback_speed(&animation_name, resolved_name));
+        application.should_check_for_action = false;
+        self.current_animation_name = animation_name;
+        self.current_sheet_name = resolved_name.to_string();
+    }
+}
+
+/// whether `animation_name`'s sheet should stay cached, see `PINNED_ANIMATIONS`
+fn is_pinned(application: &DesktopGremlin, animation_name: &str) -> bool {
+    animation_name == application.default_animation() || PINNED_ANIMATIONS.contains(&animation_name)
 }
 
 impl Behavior for GremlinRender {
-    fn setup(&mut self, _: &mut crate::gremlin::DesktopGremlin) {}
+    fn setup(&mut self, application: &mut crate::gremlin::DesktopGremlin) {
+        self.texture_cache = application.texture_cache.clone();
+
+        match application.register_hotkey(SCREENSHOT_HOTKEY) {
+            Ok(id) => self.screenshot_hotkey_id = Some(id),
+            Err(err) => warn!("couldn't register screenshot hotkey {SCREENSHOT_HOTKEY:?}: {err:#}"),
+        }
+    }
+
+    fn runs_while_paused(&self) -> bool {
+        true
+    }
+
+    fn update(
+        &mut self,
+        application: &mut crate::gremlin::DesktopGremlin,
+        context: &super::ContextData,
+    ) {
+        let now = Instant::now();
+        let elapsed = self
+            .last_update
+            .replace(now)
+            .map_or(Duration::ZERO, |last_update| now - last_update);
+        if !elapsed.is_zero() {
+            self.fps = self.fps * 0.9 + 0.1 / elapsed.as_secs_f32();
+        }
+        context.on::<KeyDown>(|key| {
+            if key == DEBUG_OVERLAY_KEY {
+                self.show_debug_overlay = !self.show_debug_overlay;
+            }
+        });
+        if let Some(hotkey_id) = self.screenshot_hotkey_id {
+            let mut is_pressed = false;
+            context.on::<kinds::HotKey>(|id| is_pressed |= id == hotkey_id);
+            if is_pressed {
+                self.pending_screenshots
+                    .push(capture_path(application, "png"));
+            }
+        }
+
+        if context.events.contains_key(&Event::Window {
+            win_event: WindowEvent::Exposed,
+        }) {
+            self.last_drawn = None;
+        }
+        if context
+            .events
+            .contains_key(&Event::Custom("system_sleep".to_string()))
+        {
+            self.is_asleep = true;
+            self.release_textures();
+        }
+        if context
+            .events
+            .contains_key(&Event::Custom("system_resume".to_string()))
+            && std::mem::take(&mut self.is_asleep)
+        {
+            // remakes the current sheet, layers and accessories follow by themselves
+            self.set_scale(application, application.scale);
+        }
 
-    fn update(&mut self, application: &mut crate::gremlin::DesktopGremlin, _: &super::ContextData) {
         let mut task_board = None;
+        // interrupts that weren't allowed to cut in, in the order they came
+        let mut blocked_interrupts = Vec::new();
+        let frame_tick = application.frame_tick();
 
         // check for tasks and append to task queue
         while let Ok(task) = application.task_channel.1.try_recv() {
-            if let GremlinTask::PlayInterrupt(_) = &task {
-                task_board = Some(task);
-                break;
+            match task {
+                GremlinTask::PlayInterrupt(_)
+                    if application.should_check_for_action
+                        || application
+                            .interrupt_policy
+                            .may_interrupt(task.name(), &self.current_animation_name) =>
+                {
+                    task_board = Some(task);
+                    break;
+                }
+                // not allowed to cut the current one short, it goes next instead
+                GremlinTask::PlayInterrupt(_) => {
+                    blocked_interrupts.push(task);
+                }
+                // control tasks don't wait for the current animation to finish
+                GremlinTask::SetMovementMode(mode) => {
+                    application.movement_mode = mode;
+                }
+                GremlinTask::Chase(is_chasing) => {
+                    application.is_chasing = is_chasing;
+                }
+                GremlinTask::Pause => {
+                    application.is_paused.store(true, Ordering::Relaxed);
+                }
+                GremlinTask::Resume => {
+                    application.is_paused.store(false, Ordering::Relaxed);
+                }
+                GremlinTask::ToggleDebugOverlay => {
+                    self.show_debug_overlay = !self.show_debug_overlay;
+                }
+                GremlinTask::SetScale(scale) => {
+                    self.set_scale(application, scale);
+                }
+                GremlinTask::SetSpeed(speed) => {
+                    if !speed.is_nan() {
+                        application.speed = speed.clamp(MIN_SPEED, MAX_SPEED);
+                    }
+                }
+                GremlinTask::Goto(x, y) => {
+                    application.goto_target = Some((x, y));
+                }
+                GremlinTask::Spawn { gremlin, offset } => {
+                    application.spawn_requests.push_back((gremlin, offset));
+                }
+                GremlinTask::Screenshot(path) => {
+                    self.pending_screenshots.push(path);
+                }
+                GremlinTask::Record { path, duration } => {
+                    if self.recording.is_some() {
+                        warn!("already recording, not starting {}", path.display());
+                    } else {
+                        self.recording = Some(Recording::start(path, duration));
+                    }
+                }
+                GremlinTask::Custom(task) if task.0.is_immediate() => {
+                    task.0.run(application);
+                }
+                _ => {
+                    let _ = &application.task_queue.push_back(task);
+                }
             }
-            let _ = &application.task_queue.push_back(task);
+        }
+        for task in blocked_interrupts.into_iter().rev() {
+            application.task_queue.push_front(task);
+        }
+
+        // behaviors each sending the gremlin back to rest shouldn't make it rest over and over
+        application.dedup_tasks();
+
+        let is_paused = application.is_paused();
+        if is_paused {
+            if task_board.is_some() {
+                self.pending_interrupt = task_board.take();
+            }
+        } else if task_board.is_none() {
+            task_board = self.pending_interrupt.take();
+        }
+
+        while let Ok((name, loaded)) = self.loader.result_rx.try_recv() {
+            self.finish_loading(application, name, loaded);
         }
 
         if let None = task_board
             && application.should_check_for_action
+            && !is_paused
+            && self.loading.is_none()
         {
-            task_board = application.task_queue.pop_front();
+            task_board = application
+                .interrupt_policy
+                .next_in(&application.task_queue)
+                .and_then(|index| application.task_queue.remove(index));
         }
 
         let mut cache_hit_index: Option<usize> = None;
@@ -52,102 +683,395 @@ impl Behavior for GremlinRender {
             // update the texture according to the task
             match task_board {
                 GremlinTask::Play(animation_name) | GremlinTask::PlayInterrupt(animation_name) => {
-                    if let Some(animator) = &mut gremlin.animator
-                        && animation_name == self.current_animation_name
+                    // whatever was still loading isn't wanted anymore
+                    self.loading = None;
+                    let resolved_name =
+                        gremlin.resolve_variant(&animation_name, &mut application.rng);
+                    if animation_name == self.current_animation_name
+                        && resolved_name.as_deref() == Some(self.current_sheet_name.as_str())
+                        && let Some(animator) = &mut gremlin.animator
                     {
-                        animator.current_frame = 0;
-                    } else if let Some(animation_props) =
-                        gremlin.animation_map.get(animation_name.as_str())
+                        animator.restart();
+                    } else if let Some(resolved_name) = resolved_name
+                        && let Some(animation_props) = gremlin.animation_map.get(&resolved_name)
                     {
                         let cache_lookup = {
                             self.texture_cache
                                 .lock()
                                 .unwrap()
-                                .lookup(animation_name.clone())
+                                .lookup(resolved_name.clone())
                                 .map(|a| a.0)
                         };
                         if let Some(index) = cache_lookup {
                             self.texture_cache.lock().unwrap().rearrange(index);
-                            // unwrap safety: the mutex is guaranteed to not be poisoned and released after the rearrange cache function goes out of scope
-                            let lock: &std::sync::MutexGuard<'_, TextureCache> =
-                                &self.texture_cache.lock().unwrap();
-                            // unwrap safety: the back element is guaranteed to exist because the index before rearranging exists.
-                            let (animator, texture) = &lock.data.back().unwrap().1;
-                            let _ = gremlin.animator.insert(animator.clone());
-                            let _ = self.gremlin_texture.insert(texture.clone());
-                            let _ = cache_hit_index.insert(index);
-                        } else if let Ok(animation) =
-                            <&AnimationProperties as TryInto<Animation>>::try_into(animation_props)
-                        {
-                            let mut animator: Animator = (&animation).into();
-
-                            let texture_rc = Rc::new({
-                                let scale_factor = (1, 1);
-                                let (sprite_width, sprite_height) =
-                                    application.canvas.window().size();
-                                let (target_width, target_height) = (
-                                    (DEFAULT_COLUMN_COUNT * sprite_width * scale_factor.0)
-                                        / scale_factor.1,
-                                    (animation
-                                        .properties
-                                        .sprite_count
-                                        .div_ceil(DEFAULT_COLUMN_COUNT)
-                                        * sprite_height
-                                        * scale_factor.0)
-                                        / scale_factor.1,
-                                );
-                                animator.sprite_size = (sprite_width, sprite_height);
-                                animator.texture_size = (target_width, target_height);
-
-                                sdl_resize(
-                                    &animation.sprite_sheet.image,
-                                    animator.texture_size,
-                                    &mut application.canvas,
-                                )
-                                .unwrap()
-                            });
-
-                            let _ = self.gremlin_texture.insert(texture_rc.clone());
-                            drop(animation);
-
-                            gremlin.animator = Some(animator);
-
-                            if let Some(ref animator) = gremlin.animator {
-                                self.texture_cache.lock().unwrap().cache(
-                                    animator.animation_properties.animation_name.clone(),
-                                    (animator.clone(), texture_rc),
-                                );
+                            {
+                                // unwrap safety: the mutex is guaranteed to not be poisoned and released after the rearrange cache function goes out of scope
+                                let lock: &std::sync::MutexGuard<'_, TextureCache> =
+                                    &self.texture_cache.lock().unwrap();
+                                // unwrap safety: the back element is guaranteed to exist because the index before rearranging exists.
+                                let (animator, texture) = &lock.data.back().unwrap().1;
+                                let mut animator = animator.clone();
+                                animator.restart();
+                                let _ = gremlin.animator.insert(animator);
+                                let _ = self.gremlin_texture.insert(texture.clone());
                             }
+                            let _ = cache_hit_index.insert(index);
+                            self.start_animation(application, animation_name, &resolved_name);
+                        } else {
+                            // decoding a big sheet takes long enough to drop frames,
+                            // so it happens on the loader and the switch waits for it
+                            let _ = self
+                                .loader
+                                .task_tx
+                                .send(LoaderTask::Load(animation_props.clone()));
+                            self.loading = Some((animation_name, resolved_name));
                         }
-
-                        application.should_check_for_action = false;
-                        self.current_animation_name = animation_name;
+                    } else {
+                        warn!(
+                            "{} has no {animation_name} and nothing to fall back to",
+                            gremlin.name
+                        );
                     }
                 }
+                GremlinTask::SetMovementMode(mode) => {
+                    application.movement_mode = mode;
+                }
+                GremlinTask::Chase(is_chasing) => {
+                    application.is_chasing = is_chasing;
+                }
+                // handled as soon as they're received
+                GremlinTask::Pause
+                | GremlinTask::Resume
+                | GremlinTask::ToggleDebugOverlay
+                | GremlinTask::SetScale(_)
+                | GremlinTask::SetSpeed(_)
+                | GremlinTask::Goto(_, _)
+                | GremlinTask::Spawn { .. }
+                | GremlinTask::Screenshot(_)
+                | GremlinTask::Record { .. } => {}
+                GremlinTask::Custom(task) => {
+                    task.0.run(application);
+                }
             }
         }
 
+        let cache_stats = self
+            .texture_cache
+            .lock()
+            .map(|cache| cache.stats())
+            .unwrap_or_default();
+
+        if self.is_asleep {
+            return;
+        }
+        self.update_layers(application);
+        let layer_frames = self.layer_frames(application);
+        self.update_accessories(application);
+        let accessory_targets = self.accessory_targets();
+
+        // how long the loop may sleep once this frame is done, see `DesktopGremlin::idle_for`
+        let mut idle_for = None;
+        let mut is_drawn = false;
+        // the very first sheet is still decoding, there's nothing else to show meanwhile
+        if self.loading.is_some()
+            && application
+                .current_gremlin
+                .as_ref()
+                .is_some_and(|gremlin| gremlin.animator.is_none())
+        {
+            let since = *self.thinking_since.get_or_insert_with(Instant::now);
+            if let Some(canvas) = application.window.canvas() {
+                draw_thinking(canvas, since.elapsed());
+                application.window.present();
+                is_drawn = true;
+            }
+            idle_for = Some(THINKING_STEP);
+        } else {
+            self.thinking_since = None;
+        }
         // draws the next frame and update frame counter
         if let Some(gremlin) = &mut application.current_gremlin
-            && let Some(gremlin_texture) = &self.gremlin_texture
             && let Some(animator) = &mut gremlin.animator
         {
-            application.canvas.clear();
-            application
-                .canvas
-                .copy(&gremlin_texture, animator.get_frame_rect(), None)
-                .unwrap();
-            application.canvas.present();
-            if animator.current_frame + 1 == animator.animation_properties.sprite_count {
+            let drawn = DrawnFrame {
+                sheet: self.gremlin_texture.as_ref().map(Rc::as_ptr),
+                frame: animator.get_frame_rect(),
+                window: application.window.rect(),
+            };
+            let trail = update_trail(&mut self.trail, drawn, gremlin.trail);
+            // the overlay's numbers change every frame
+            let is_dirty = self.show_debug_overlay
+                || self.last_drawn != Some(drawn)
+                || self.last_layer_frames != layer_frames
+                || self.last_accessory_targets != accessory_targets;
+            is_drawn = is_dirty;
+            if is_dirty {
+                self.last_drawn = Some(drawn);
+                application
+                    .window
+                    .draw_frame(self.gremlin_texture.as_deref(), drawn.frame, &trail);
+                // nothing to put them on without the body
+                if self.gremlin_texture.is_some() {
+                    for (index, frame) in &layer_frames {
+                        application.window.draw_layer(
+                            &self.layers[*index].texture,
+                            Some(*frame),
+                            None,
+                        );
+                    }
+                    for (index, target) in &accessory_targets {
+                        application.window.draw_layer(
+                            &self.accessories[*index].texture,
+                            None,
+                            Some(*target),
+                        );
+                    }
+                }
+                self.last_layer_frames = layer_frames;
+                self.last_accessory_targets = accessory_targets;
+            }
+            if self.show_debug_overlay {
+                let (x, y) = application.window.position();
+                // not `queued_task_names()`, the animator still has the gremlin borrowed
+                let mut queued_tasks = application
+                    .task_queue
+                    .iter()
+                    .map(GremlinTask::name)
+                    .take(DEBUG_OVERLAY_TASKS)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                if application.task_queue.len() > DEBUG_OVERLAY_TASKS {
+                    queued_tasks +=
+                        &format!(" +{}", application.task_queue.len() - DEBUG_OVERLAY_TASKS);
+                }
+                let mut overlay = format!(
+                    "{:.0} fps, {:.1} ms/frame{}\np95 {:.1} ms, jitter {:.1} ms\n{} {}/{}\ntasks: [{}]\ntextures: {}/{CACHE_CAPACITY}, {:.1} MB\ncache: {} hits, {} misses, {} evicted\nat {x}, {y}",
+                    self.fps,
+                    application.profile.frame_ms(),
+                    if is_paused { " (paused)" } else { "" },
+                    application.profile.frame_p95_ms(),
+                    application.profile.jitter_ms(),
+                    self.current_animation_name,
+                    animator.current_frame + 1,
+                    animator.animation_properties.sprite_count,
+                    queued_tasks,
+                    cache_stats.entries,
+                    cache_stats.bytes as f32 / (1024.0 * 1024.0),
+                    cache_stats.hits,
+                    cache_stats.misses,
+                    cache_stats.evictions,
+                );
+                for timing in application.profile.slowest(DEBUG_OVERLAY_SLOWEST) {
+                    overlay += &format!("\n{} {:.2} ms", timing.name, timing.average_ms);
+                }
+                if let Some(canvas) = application.window.canvas() {
+                    draw_debug_overlay(canvas, overlay);
+                }
+            }
+            if is_dirty {
+                application.window.present();
+            }
+            let speed = application.speed * self.animation_speed.unwrap_or(1.0);
+            // nothing new went on screen and nothing's waiting, so nothing changes until the
+            // next frame of the animation comes up
+            if !is_dirty
+                && application.task_queue.is_empty()
+                && self.loading.is_none()
+                && application.goto_target.is_none()
+            {
+                let until_next_frame = animator
+                    .until_next_frame()
+                    .filter(|_| !is_paused && speed > 0.0)
+                    .map(|until| until.div_f32(speed));
+                idle_for = Some(until_next_frame.unwrap_or(MAX_IDLE_WAIT));
+            }
+            // keep showing the same frame while paused. Whole nominal ticks rather than wall
+            // time so a seeded run plays back the same
+            if !is_paused && animator.advance(frame_tick.mul_f32(speed)) {
                 application.should_check_for_action = true;
                 if "OUTRO" == &self.current_animation_name {
-                    println!("goodbye!");
+                    info!("goodbye!");
                     *application.should_exit.lock().unwrap() = true;
                 }
             }
+        }
+        // `last_drawn` is what's on screen right now, drawn or not this frame
+        if !self.pending_screenshots.is_empty()
+            && let Some(drawn) = self.last_drawn
+        {
+            self.save_screenshots(application, drawn.frame);
+        }
+        self.record(application, now, is_drawn);
+        if let Some(duration) = idle_for {
+            application.idle_for(duration);
+        }
+    }
+}
+
+/// where screenshots and recordings without a path of their own go
+pub fn screenshots_dir() -> PathBuf {
+    data_dir().join("screenshots")
+}
+
+/// a new `extension` file in `screenshots_dir()` named after the gremlin and the time
+pub fn capture_path(application: &DesktopGremlin, extension: &str) -> PathBuf {
+    let name = application
+        .current_gremlin
+        .as_ref()
+        .map_or("gremlin", |gremlin| gremlin.name.as_str());
+    screenshots_dir().join(format!(
+        "{name}-{}.{extension}",
+        chrono::Local::now().format("%Y%m%d-%H%M%S%.3f")
+    ))
+}
+
+fn save_png(image: &RgbaImage, path: &Path) -> anyhow::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).context("couldn't make its folder")?;
+    }
+    image.save_with_format(path, image::ImageFormat::Png)?;
+    Ok(())
+}
+
+/// Remembers `drawn` and hands back the ghosts to draw behind it, none unless the window
+/// moved at least `TRAIL_MIN_SPEED` since the last frame.
+fn update_trail(trail: &mut VecDeque<DrawnFrame>, drawn: DrawnFrame, length: usize) -> Vec<Ghost> {
+    let is_fast = trail.back().is_some_and(|last| {
+        last.sheet == drawn.sheet
+            && (last.window.x() - drawn.window.x())
+                .abs()
+                .max((last.window.y() - drawn.window.y()).abs())
+                >= TRAIL_MIN_SPEED
+    });
+    let ghosts = if is_fast {
+        trail
+            .iter()
+            .enumerate()
+            .map(|(index, ghost)| Ghost {
+                frame: ghost.frame,
+                offset: (
+                    ghost.window.x() - drawn.window.x(),
+                    ghost.window.y() - drawn.window.y(),
+                ),
+                alpha: (u8::MAX as f32 * TRAIL_ALPHA * (index + 1) as f32
+                    / (trail.len() + 1) as f32) as u8,
+            })
+            .collect()
+    } else {
+        // a new sheet or standing still, the old frames would only smear
+        trail.clear();
+        Vec::new()
+    };
+    if length > 0 {
+        trail.push_back(drawn);
+        while trail.len() > length {
+            trail.pop_front();
+        }
+    }
+    ghosts
+}
 
-            animator.current_frame =
-                (animator.current_frame + 1) % animator.animation_properties.sprite_count;
+/// The `LayerMode::Look` frame facing `cursor` from the middle of `window`, 0 for straight ahead.
+fn look_frame(frame_count: u32, window: Rect, cursor: Option<(f32, f32)>) -> u32 {
+    let directions = frame_count.saturating_sub(1);
+    let Some((x, y)) = cursor else {
+        return 0;
+    };
+    if directions == 0 || window.contains_point((x as i32, y as i32)) {
+        return 0;
+    }
+    let center = window.center();
+    // screen y points down, so the angle goes clockwise
+    let angle = (y - center.y() as f32).atan2(x - center.x() as f32);
+    let sector = TAU / directions as f32;
+    1 + (angle.rem_euclid(TAU) / sector).round() as u32 % directions
+}
+
+fn draw_debug_overlay(canvas: &mut Canvas<Window>, overlay: String) {
+    let (width, _) = canvas.window().size();
+    let text = Text::new(overlay).size(11.0).max_width(width as f32);
+    let Some((text_width, text_height, _)) = text.rasterize() else {
+        return;
+    };
+
+    // the window gets cleared with the draw color, put it back after
+    let clear_color = canvas.draw_color();
+    canvas.set_blend_mode(BlendMode::Blend);
+    canvas.set_draw_color(DEBUG_OVERLAY_BACKGROUND);
+    let _ = canvas.fill_rect(FRect::new(
+        0.0,
+        0.0,
+        (text_width + 4) as f32,
+        (text_height + 4) as f32,
+    ));
+    canvas.set_draw_color(clear_color);
+    let _ = text
+        .render_canvas(
+            canvas,
+            Some(FRect::new(2.0, 2.0, text_width as f32, text_height as f32)),
+        )
+        .inspect_err(|err| warn!("couldn't draw the debug overlay: {err}"));
+}
+
+/// three dots lighting up one after the other in the middle of the window
+fn draw_thinking(canvas: &mut Canvas<Window>, elapsed: Duration) {
+    let (width, height) = canvas.window().size();
+    let lit = (elapsed.as_millis() / THINKING_STEP.as_millis()) % 3;
+    let size = (width.min(height) / 12).max(4) as f32;
+    let center = (width as f32 / 2.0, height as f32 / 2.0);
+
+    let clear_color = canvas.draw_color();
+    canvas.set_draw_color(Color::RGBA(0, 0, 0, 0));
+    canvas.clear();
+    canvas.set_blend_mode(BlendMode::Blend);
+    for dot in 0..3 {
+        let mut color = THINKING_COLOR;
+        if dot != lit {
+            color.a /= 3;
         }
+        canvas.set_draw_color(color);
+        let _ = canvas.fill_rect(FRect::new(
+            center.0 + (dot as f32 - 1.0) * size * 2.0 - size / 2.0,
+            center.1 - size / 2.0,
+            size,
+            size,
+        ));
     }
+    canvas.set_draw_color(clear_color);
+}
+
+/// scales the sprite sheet so one sprite fills the window, headless there's no canvas
+/// to make textures with so it's `None` but the animation still plays
+pub fn create_sheet_texture(
+    window: &mut dyn WindowBackend,
+    animation: &Animation,
+    animator: &mut Animator,
+    scaling: Scaling,
+) -> Option<Rc<Texture>> {
+    let scale_factor = (1, 1);
+    let (sprite_width, sprite_height) = window.size();
+    let (target_width, target_height) = (
+        (DEFAULT_COLUMN_COUNT * sprite_width * scale_factor.0) / scale_factor.1,
+        (animation
+            .properties
+            .sprite_count
+            .div_ceil(DEFAULT_COLUMN_COUNT)
+            * sprite_height
+            * scale_factor.0)
+            / scale_factor.1,
+    );
+    animator.sprite_size = (sprite_width, sprite_height);
+    animator.texture_size = (target_width, target_height);
+
+    window.canvas().map(|canvas| {
+        Rc::new(
+            sdl_resize(
+                &animation.sprite_sheet.image,
+                animator.texture_size,
+                scaling,
+                canvas,
+            )
+            .unwrap(),
+        )
+    })
 }
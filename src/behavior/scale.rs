@@ -0,0 +1,43 @@
+use crate::{
+    behavior::{Behavior, ContextData},
+    events::kinds::Wheel,
+    gremlin::{DesktopGremlin, GremlinTask},
+};
+
+/// Scrolling over the gremlin makes it bigger or smaller, so does the size slider in the right
+/// click menu, see `GremlinMenu`.
+pub struct GremlinScale {
+    // scale change per wheel notch
+    step: f32,
+}
+
+impl Default for GremlinScale {
+    fn default() -> Self {
+        Self { step: 0.1 }
+    }
+}
+
+impl GremlinScale {
+    pub fn new() -> Box<Self> {
+        Default::default()
+    }
+
+    #[allow(unused)]
+    pub fn with_step(step: f32) -> Box<Self> {
+        Box::new(Self { step })
+    }
+}
+
+impl Behavior for GremlinScale {
+    fn setup(&mut self, _: &mut DesktopGremlin) {}
+
+    fn update(&mut self, application: &mut DesktopGremlin, context: &ContextData) {
+        let mut notches = 0.0;
+        context.on::<Wheel>(|scrolled| notches += scrolled);
+        if notches != 0.0 {
+            let _ = application.task_channel.0.send(GremlinTask::SetScale(
+                application.scale + notches * self.step,
+            ));
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use std::time::{Duration, Instant};
+
+use tracing::warn;
+
+use crate::{
+    behavior::{Behavior, ContextData},
+    events::{Event, MouseButton},
+    gremlin::{DesktopGremlin, GremlinTask},
+};
+
+const SAVE_INTERVAL: Duration = Duration::from_secs(30);
+const MOOD_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+const SAVE_EVENT: &str = "stats:save";
+const MOOD_CHECK_EVENT: &str = "stats:mood_check";
+
+// per second rates
+const HUNGER_RATE: f32 = 0.05;
+const BOREDOM_RATE: f32 = 0.02;
+const TIREDNESS_RATE: f32 = 0.02;
+const REST_RATE: f32 = 0.1;
+
+/// Drives `DesktopGremlin::stats` from interactions and idle time, plays mood animations
+/// and keeps the stats saved to disk.
+pub struct GremlinStats {
+    last_tick: Instant,
+}
+
+impl Default for GremlinStats {
+    fn default() -> Self {
+        Self {
+            last_tick: Instant::now(),
+        }
+    }
+}
+
+impl GremlinStats {
+    pub fn new() -> Box<Self> {
+        Default::default()
+    }
+
+    /// picks an animation reflecting the current mood, if the gremlin feels strongly about anything
+    fn mood_animation(application: &DesktopGremlin) -> Option<&'static str> {
+        let stats = &application.stats;
+        if stats.energy < 20.0 {
+            Some("SLEEP")
+        } else if stats.hunger > 80.0 {
+            Some("HUNGRY")
+        } else if stats.happiness < 20.0 {
+            Some("SAD")
+        } else if stats.happiness > 80.0 {
+            Some("HAPPY")
+        } else {
+            None
+        }
+    }
+}
+
+impl Behavior for GremlinStats {
+    fn setup(&mut self, application: &mut DesktopGremlin) {
+        application.stats = crate::stats::PetStats::load();
+        application.schedule_every(SAVE_INTERVAL, Event::Custom(SAVE_EVENT.to_string()));
+        application.schedule_every(
+            MOOD_CHECK_INTERVAL,
+            Event::Custom(MOOD_CHECK_EVENT.to_string()),
+        );
+    }
+
+    fn update(&mut self, application: &mut DesktopGremlin, context: &ContextData) {
+        let elapsed = self.last_tick.elapsed().as_secs_f32();
+        self.last_tick = Instant::now();
+
+        let is_idle = application
+            .current_gremlin
+            .as_ref()
+            .and_then(|gremlin| gremlin.animator.as_ref())
+            .is_some_and(|animator| {
+                animator.animation_properties.animation_name == application.default_animation()
+            });
+
+        let stats = &mut application.stats;
+        stats.add_hunger(HUNGER_RATE * elapsed);
+        stats.add_happiness(-BOREDOM_RATE * elapsed);
+        if is_idle {
+            stats.add_energy(REST_RATE * elapsed);
+        } else {
+            stats.add_energy(-TIREDNESS_RATE * elapsed);
+        }
+
+        if context.events.contains_key(&Event::Click {
+            mouse_btn: MouseButton::Left,
+        }) {
+            stats.add_happiness(5.0);
+            stats.add_energy(-1.0);
+        }
+        if context.events.contains_key(&Event::Click {
+            mouse_btn: MouseButton::Middle,
+        }) {
+            // snack time
+            stats.add_hunger(-20.0);
+            stats.add_happiness(2.0);
+        }
+        if context.events.contains_key(&Event::DragEnd {
+            mouse_btn: MouseButton::Left,
+        }) {
+            stats.add_happiness(3.0);
+            stats.add_energy(-2.0);
+        }
+
+        if is_idle
+            && application.task_queue.is_empty()
+            && context
+                .events
+                .contains_key(&Event::Custom(MOOD_CHECK_EVENT.to_string()))
+            && let Some(animation_name) = Self::mood_animation(application)
+            && let Some(gremlin) = &application.current_gremlin
+            && gremlin.has_animation(animation_name)
+        {
+            let _ = application
+                .task_channel
+                .0
+                .send(GremlinTask::Play(animation_name.to_string()));
+            let _ = application.task_channel.0.send(GremlinTask::Play(
+                application.default_animation().to_string(),
+            ));
+        }
+
+        let should_save = context.events.contains_key(&Event::Quit)
+            || context
+                .events
+                .contains_key(&Event::Custom(SAVE_EVENT.to_string()));
+        if should_save && let Err(err) = application.stats.save() {
+            warn!("couldn't save stats: {err}");
+        }
+    }
+}
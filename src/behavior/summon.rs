@@ -0,0 +1,116 @@
+use tracing::warn;
+
+use crate::{
+    behavior::{Behavior, ContextData},
+    events::{Event, kinds},
+    gremlin::{DesktopGremlin, GremlinTask},
+    utils::get_cursor_position,
+};
+
+const DEFAULT_HOTKEY: &str = "ctrl+alt+KeyG";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SummonMode {
+    /// shows up under the cursor right away
+    Teleport,
+    /// walks over with `GremlinTask::Goto`
+    #[allow(unused)]
+    Walk,
+}
+
+/// Brings the gremlin to the cursor on a global hotkey, for when it got lost on another monitor.
+pub struct GremlinSummon {
+    hotkey: String,
+    mode: SummonMode,
+    greeting: String,
+    hotkey_id: Option<u32>,
+    // walking over, greets once `arrived` comes in
+    is_walking: bool,
+}
+
+impl Default for GremlinSummon {
+    fn default() -> Self {
+        Self {
+            hotkey: DEFAULT_HOTKEY.to_string(),
+            mode: SummonMode::Teleport,
+            greeting: "GREET".to_string(),
+            hotkey_id: None,
+            is_walking: false,
+        }
+    }
+}
+
+impl GremlinSummon {
+    pub fn new() -> Box<Self> {
+        Default::default()
+    }
+
+    /// `hotkey` is written like "ctrl+shift+KeyG" or "alt+F12"
+    #[allow(unused)]
+    pub fn with_hotkey(hotkey: &str, mode: SummonMode, greeting: &str) -> Box<Self> {
+        Box::new(Self {
+            hotkey: hotkey.to_string(),
+            mode,
+            greeting: greeting.to_string(),
+            ..Default::default()
+        })
+    }
+
+    fn greet(&self, application: &mut DesktopGremlin) {
+        let _ = application
+            .task_channel
+            .0
+            .send(GremlinTask::PlayInterrupt(self.greeting.clone()));
+        let _ = application.task_channel.0.send(GremlinTask::Play(
+            application.default_animation().to_string(),
+        ));
+    }
+}
+
+impl Behavior for GremlinSummon {
+    fn setup(&mut self, application: &mut DesktopGremlin) {
+        match application.register_hotkey(&self.hotkey) {
+            Ok(id) => self.hotkey_id = Some(id),
+            Err(err) => warn!("couldn't register summon hotkey {:?}: {err:#}", self.hotkey),
+        }
+    }
+
+    fn update(&mut self, application: &mut DesktopGremlin, context: &ContextData) {
+        if self.is_walking
+            && context
+                .events
+                .contains_key(&Event::Custom("arrived".to_string()))
+        {
+            self.is_walking = false;
+            self.greet(application);
+        }
+
+        let Some(hotkey_id) = self.hotkey_id else {
+            return;
+        };
+        let mut is_summoned = false;
+        context.on::<kinds::HotKey>(|id| is_summoned |= id == hotkey_id);
+        if !is_summoned {
+            return;
+        }
+
+        // center the gremlin on the cursor
+        let (cursor_x, cursor_y) = get_cursor_position();
+        let (width, height) = application.window.size();
+        let x = cursor_x.round() as i32 - width as i32 / 2;
+        let y = cursor_y.round() as i32 - height as i32 / 2;
+
+        let default_animation = application.default_animation().to_string();
+        application.cancel_tasks(&default_animation);
+        match self.mode {
+            SummonMode::Teleport => {
+                application.window.set_position(x, y);
+                self.greet(application);
+            }
+            SummonMode::Walk => {
+                self.is_walking = true;
+                let _ = application.task_channel.0.send(GremlinTask::Goto(x, y));
+            }
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use std::sync::atomic::Ordering;
+
+use sdl3::rect::{Point, Rect};
+
+use crate::{
+    behavior::{Behavior, ContextData},
+    events::Event,
+    gremlin::{DesktopGremlin, GremlinTask},
+};
+
+// slow enough to cost nothing, but the loop still hears about resuming
+const SLEEPING_FRAMERATE: u32 = 1;
+
+/// Winds the gremlin down while the machine is suspended (see `SystemMonitor`'s `system_sleep`
+/// and `system_resume`): pauses it and slows the main loop down, while `GremlinRender` lets go
+/// of its textures. Once the machine is back it puts the gremlin back on a monitor that's still
+/// there, since docking and undocking tend to move them around, and plays WAKE.
+#[derive(Default)]
+pub struct SuspendAwareness {
+    // what this behavior changed going to sleep, to put back after
+    framerate_before: Option<u32>,
+    has_paused: bool,
+}
+
+impl SuspendAwareness {
+    pub fn new() -> Box<Self> {
+        Default::default()
+    }
+
+    fn go_to_sleep(&mut self, application: &mut DesktopGremlin) {
+        if self.framerate_before.is_none() {
+            self.framerate_before = Some(application.framerate.load(Ordering::Relaxed));
+            application.set_framerate(SLEEPING_FRAMERATE);
+        }
+        if !application.is_paused() {
+            self.has_paused = true;
+            let _ = application.task_channel.0.send(GremlinTask::Pause);
+        }
+    }
+
+    fn wake_up(&mut self, application: &mut DesktopGremlin) {
+        if let Some(framerate) = self.framerate_before.take() {
+            application.set_framerate(framerate);
+        }
+        if std::mem::take(&mut self.has_paused) {
+            let _ = application.task_channel.0.send(GremlinTask::Resume);
+        }
+        Self::reseat(application);
+        if application
+            .current_gremlin
+            .as_ref()
+            .is_some_and(|gremlin| gremlin.has_animation("WAKE"))
+        {
+            let _ = application
+                .task_channel
+                .0
+                .send(GremlinTask::PlayInterrupt("WAKE".to_string()));
+            let _ = application.task_channel.0.send(GremlinTask::Play(
+                application.default_animation().to_string(),
+            ));
+        }
+    }
+
+    /// keeps the gremlin on the monitor its middle is on, or moves it to the first one when
+    /// that monitor is gone
+    fn reseat(application: &mut DesktopGremlin) {
+        let rect = application.window.rect();
+        let all_bounds = application.window.all_usable_bounds();
+        let Some(bounds) = all_bounds
+            .iter()
+            .find(|bounds| bounds.contains_point(rect.center()))
+            .or(all_bounds.first())
+        else {
+            return;
+        };
+        let (x, y) = clamp_into(rect, *bounds);
+        if (x, y) != (rect.x(), rect.y()) {
+            application.window.set_position(x, y);
+        }
+    }
+}
+
+/// the top left corner that puts `rect` as close to where it is as fits inside `bounds`
+fn clamp_into(rect: Rect, bounds: Rect) -> (i32, i32) {
+    let max = Point::new(
+        (bounds.right() - rect.width() as i32).max(bounds.x()),
+        (bounds.bottom() - rect.height() as i32).max(bounds.y()),
+    );
+    (
+        rect.x().clamp(bounds.x(), max.x()),
+        rect.y().clamp(bounds.y(), max.y()),
+    )
+}
+
+impl Behavior for SuspendAwareness {
+    fn setup(&mut self, _: &mut DesktopGremlin) {}
+
+    // it does the pausing, so it has to be around for the resuming
+    fn runs_while_paused(&self) -> bool {
+        true
+    }
+
+    fn update(&mut self, application: &mut DesktopGremlin, context: &ContextData) {
+        if context
+            .events
+            .contains_key(&Event::Custom("system_sleep".to_string()))
+        {
+            self.go_to_sleep(application);
+        }
+        if context
+            .events
+            .contains_key(&Event::Custom("system_resume".to_string()))
+        {
+            self.wake_up(application);
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use crate::{
+    behavior::{Behavior, ContextData},
+    events::Event,
+    gremlin::{DesktopGremlin, GremlinTask},
+};
+
+/// Plays an animation when the system monitor reports something worth reacting to,
+/// e.g. looking tired on low battery or fanning itself when the cpu is pegged.
+pub struct SystemReactions {
+    // (custom event name, animation name)
+    reactions: Vec<(String, String)>,
+}
+
+impl Default for SystemReactions {
+    fn default() -> Self {
+        Self {
+            reactions: vec![
+                ("battery_low".to_string(), "SLEEP".to_string()),
+                ("cpu_high".to_string(), "FAN".to_string()),
+            ],
+        }
+    }
+}
+
+impl SystemReactions {
+    pub fn new() -> Box<Self> {
+        Default::default()
+    }
+
+    #[allow(unused)]
+    pub fn with_reactions(reactions: Vec<(String, String)>) -> Box<Self> {
+        Box::new(Self { reactions })
+    }
+}
+
+impl Behavior for SystemReactions {
+    fn setup(&mut self, _: &mut DesktopGremlin) {}
+
+    fn update(&mut self, application: &mut DesktopGremlin, context: &ContextData) {
+        let Some(gremlin) = &application.current_gremlin else {
+            return;
+        };
+        for (event_name, animation_name) in &self.reactions {
+            if context
+                .events
+                .contains_key(&Event::Custom(event_name.clone()))
+                && gremlin.has_animation(animation_name)
+            {
+                let _ = application
+                    .task_channel
+                    .0
+                    .send(GremlinTask::Play(animation_name.clone()));
+                let _ = application.task_channel.0.send(GremlinTask::Play(
+                    application.default_animation().to_string(),
+                ));
+            }
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use std::time::{Duration, Instant};
+
+use chrono::{Local, Timelike};
+use rand::{Rng, seq::IndexedRandom};
+
+use crate::{
+    behavior::{Behavior, ContextData},
+    events::{Event, MouseButton},
+    gremlin::{DesktopGremlin, GremlinTask},
+    tree::{Action, Blackboard, Condition},
+    utils::{get_cursor_position, is_hour_between},
+};
+
+const TICK_EVERY: Duration = Duration::from_millis(500);
+const TICK_EVENT: &str = "tree:tick";
+
+/// Runs the current gremlin's `[tree]` every `TICK_EVERY` and turns what it decides into
+/// `GremlinTask`s, see `BehaviorTree`. Gremlins without one chase the cursor when clicked
+/// and are otherwise left to the other behaviors.
+#[derive(Default)]
+pub struct GremlinTree {
+    is_toggled: bool,
+    idle_since: Option<Instant>,
+}
+
+impl GremlinTree {
+    pub fn new() -> Box<Self> {
+        Default::default()
+    }
+}
+
+/// What a tick gets to look at and change, also carries out `GremlinUtility`'s picks.
+pub(super) struct TreeBlackboard<'a> {
+    pub application: &'a mut DesktopGremlin,
+    pub is_toggled: bool,
+    pub idle_since: Option<Instant>,
+    // whether anything the tree decided on this tick was to follow
+    pub wants_chase: bool,
+}
+
+impl TreeBlackboard<'_> {
+    /// stops chasing unless the tick decided to follow
+    pub fn finish(self) -> Option<Instant> {
+        if !self.wants_chase && self.application.is_chasing {
+            let _ = self
+                .application
+                .task_channel
+                .0
+                .send(GremlinTask::Chase(false));
+        }
+        self.idle_since
+    }
+
+    fn play(&mut self, animation_name: &str) -> bool {
+        let Some(gremlin) = &self.application.current_gremlin else {
+            return false;
+        };
+        if !gremlin.has_animation(animation_name) {
+            return false;
+        }
+        // already busy with it or something else it decided on
+        if self.idle_since.is_none() {
+            return true;
+        }
+        let default_animation = gremlin.metadata.default_animation().to_string();
+        let task_tx = &self.application.task_channel.0;
+        let _ = task_tx.send(GremlinTask::Play(animation_name.to_string()));
+        let _ = task_tx.send(GremlinTask::Play(default_animation));
+        self.idle_since = None;
+        true
+    }
+}
+
+impl Blackboard for TreeBlackboard<'_> {
+    fn check(&mut self, condition: &Condition) -> bool {
+        match condition {
+            Condition::Toggled => self.is_toggled,
+            Condition::Idle => self.idle_since.is_some(),
+            Condition::IdleFor(duration) => self
+                .idle_since
+                .is_some_and(|idle_since| idle_since.elapsed() >= *duration),
+            Condition::CursorNear(pixels) => {
+                let center = self.application.window.rect().center();
+                let (cursor_x, cursor_y) = get_cursor_position();
+                (cursor_x - center.x() as f32).hypot(cursor_y - center.y() as f32) <= *pixels
+            }
+            Condition::Chance(chance) => self.application.rng.random::<f32>() < *chance,
+            Condition::Hours(from, to) => is_hour_between(Local::now().hour(), *from, *to),
+        }
+    }
+
+    fn act(&mut self, action: &Action) -> bool {
+        match action {
+            Action::Follow => {
+                self.wants_chase = true;
+                if !self.application.is_chasing {
+                    let _ = self
+                        .application
+                        .task_channel
+                        .0
+                        .send(GremlinTask::Chase(true));
+                }
+                true
+            }
+            Action::Wander => {
+                if self.idle_since.is_none() {
+                    return true;
+                }
+                let Some(bounds) = self.application.window.usable_bounds() else {
+                    return false;
+                };
+                let (width, height) = self.application.window.size();
+                let (max_x, max_y) = (
+                    bounds.right() - width as i32,
+                    bounds.bottom() - height as i32,
+                );
+                if max_x <= bounds.left() || max_y <= bounds.top() {
+                    return false;
+                }
+                let rng = &mut self.application.rng;
+                let target = (
+                    rng.random_range(bounds.left()..max_x),
+                    rng.random_range(bounds.top()..max_y),
+                );
+                let _ = self
+                    .application
+                    .task_channel
+                    .0
+                    .send(GremlinTask::Goto(target.0, target.1));
+                self.idle_since = None;
+                true
+            }
+            Action::Sleep => self.play("SLEEP"),
+            Action::Play(animation_name) => self.play(animation_name),
+            Action::PlayTagged(tag) => {
+                let Some(gremlin) = &self.application.current_gremlin else {
+                    return false;
+                };
+                let Some(animation_name) = gremlin
+                    .animations_with_tag(tag)
+                    .choose(&mut self.application.rng)
+                    .map(|name| name.to_string())
+                else {
+                    return false;
+                };
+                self.play(&animation_name)
+            }
+        }
+    }
+}
+
+/// keeps `idle_since` at when the gremlin last went back to its default animation with nothing
+/// else to do, `None` while it's busy
+pub(super) fn track_idle(application: &DesktopGremlin, idle_since: &mut Option<Instant>) {
+    let is_idle = application.current_gremlin.as_ref().is_some_and(|gremlin| {
+        gremlin.animator.as_ref().is_some_and(|animator| {
+            animator.animation_properties.animation_name == gremlin.metadata.default_animation()
+        })
+    }) && application.task_queue.is_empty()
+        && application.goto_target.is_none();
+    if !is_idle {
+        *idle_since = None;
+    } else if idle_since.is_none() {
+        *idle_since = Some(Instant::now());
+    }
+}
+
+impl Behavior for GremlinTree {
+    fn setup(&mut self, application: &mut DesktopGremlin) {
+        application.schedule_every(TICK_EVERY, Event::Custom(TICK_EVENT.to_string()));
+    }
+
+    fn update(&mut self, application: &mut DesktopGremlin, context: &ContextData) {
+        let Some(gremlin) = &application.current_gremlin else {
+            return;
+        };
+        let Some(tree) = gremlin.tree.clone() else {
+            self.is_toggled = false;
+            self.idle_since = None;
+            return;
+        };
+        if context.events.contains_key(&Event::Click {
+            mouse_btn: MouseButton::Left,
+        }) {
+            self.is_toggled = !self.is_toggled;
+        }
+        track_idle(application, &mut self.idle_since);
+
+        if !context
+            .events
+            .contains_key(&Event::Custom(TICK_EVENT.to_string()))
+        {
+            return;
+        }
+        let mut blackboard = TreeBlackboard {
+            application,
+            is_toggled: self.is_toggled,
+            idle_since: self.idle_since,
+            wants_chase: false,
+        };
+        tree.tick(&mut blackboard);
+        self.idle_since = blackboard.finish();
+    }
+}
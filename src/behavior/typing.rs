@@ -0,0 +1,183 @@
+use std::{
+    collections::VecDeque,
+    env,
+    sync::mpsc::Receiver,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    behavior::{Behavior, ContextData},
+    gremlin::{DesktopGremlin, GremlinTask},
+    keyboard::KeystrokeMonitor,
+};
+
+/// "1" turns the behavior on, it stays off without it
+const TYPING_ENV: &str = "GREMLIN_TYPING";
+const POLL_INTERVAL: Duration = Duration::from_millis(25);
+// presses are counted over this long and scaled to a per minute rate
+const RATE_WINDOW: Duration = Duration::from_secs(10);
+// keys per minute, starts typing along above the first and stops below the second
+const FAST_RATE: u32 = 300;
+const SLOW_RATE: u32 = 180;
+const DEFAULT_DOZE_AFTER: Duration = Duration::from_secs(5 * 60);
+// on top of `DesktopGremlin::speed` while typing along
+const FRANTIC_SPEED: f32 = 1.5;
+
+/// Types along when the user is typing fast and dozes off once the keyboard has been quiet for
+/// `doze_after`, from how many keys get pressed anywhere on the desktop, see `KeystrokeMonitor`.
+/// Optional, does nothing unless turned on with `enabled` or `GREMLIN_TYPING=1`.
+pub struct GremlinTyping {
+    is_enabled: bool,
+    doze_after: Duration,
+    monitor: Option<(KeystrokeMonitor, Receiver<u32>)>,
+    // when keys were pressed within `RATE_WINDOW`, and how many
+    presses: VecDeque<(Instant, u32)>,
+    last_press: Instant,
+    // the speed typing along replaced, to put back after
+    speed_before: Option<f32>,
+    is_dozing: bool,
+}
+
+impl Default for GremlinTyping {
+    fn default() -> Self {
+        Self {
+            is_enabled: env::var(TYPING_ENV).is_ok_and(|value| value == "1"),
+            doze_after: DEFAULT_DOZE_AFTER,
+            monitor: None,
+            presses: VecDeque::new(),
+            last_press: Instant::now(),
+            speed_before: None,
+            is_dozing: false,
+        }
+    }
+}
+
+impl GremlinTyping {
+    pub fn new() -> Box<Self> {
+        Default::default()
+    }
+
+    #[allow(unused)]
+    pub fn enabled(doze_after: Duration) -> Box<Self> {
+        Box::new(Self {
+            is_enabled: true,
+            doze_after,
+            ..Default::default()
+        })
+    }
+
+    /// keys per minute over the last `RATE_WINDOW`
+    fn rate(&self) -> u32 {
+        let presses: u32 = self.presses.iter().map(|(_, count)| count).sum();
+        (presses as f32 * 60.0 / RATE_WINDOW.as_secs_f32()) as u32
+    }
+
+    fn current_animation(application: &DesktopGremlin) -> Option<&str> {
+        application
+            .current_gremlin
+            .as_ref()
+            .and_then(|gremlin| gremlin.animator.as_ref())
+            .map(|animator| animator.animation_properties.animation_name.as_str())
+    }
+
+    fn has_animation(application: &DesktopGremlin, animation: &str) -> bool {
+        application
+            .current_gremlin
+            .as_ref()
+            .is_some_and(|gremlin| gremlin.has_animation(animation))
+    }
+
+    fn start_typing(&mut self, application: &mut DesktopGremlin) {
+        if Self::current_animation(application) != Some(application.default_animation())
+            || !Self::has_animation(application, "TYPE")
+        {
+            return;
+        }
+        self.speed_before = Some(application.speed);
+        let _ = application
+            .task_channel
+            .0
+            .send(GremlinTask::SetSpeed(application.speed * FRANTIC_SPEED));
+        // nothing queued after it, so TYPE keeps looping until the typing slows down
+        let _ = application
+            .task_channel
+            .0
+            .send(GremlinTask::PlayInterrupt("TYPE".to_string()));
+    }
+
+    fn stop_typing(&mut self, application: &mut DesktopGremlin) {
+        let Some(speed) = self.speed_before.take() else {
+            return;
+        };
+        let _ = application
+            .task_channel
+            .0
+            .send(GremlinTask::SetSpeed(speed));
+        // something else might have taken over since
+        if Self::current_animation(application) == Some("TYPE") {
+            let _ = application.task_channel.0.send(GremlinTask::PlayInterrupt(
+                application.default_animation().to_string(),
+            ));
+        }
+    }
+}
+
+impl Behavior for GremlinTyping {
+    fn setup(&mut self, _: &mut DesktopGremlin) {
+        if self.is_enabled {
+            self.monitor = Some(KeystrokeMonitor::spawn(POLL_INTERVAL));
+        }
+    }
+
+    fn update(&mut self, application: &mut DesktopGremlin, _: &ContextData) {
+        let Some((_, presses)) = &self.monitor else {
+            return;
+        };
+        let now = Instant::now();
+        let mut is_typing = false;
+        for count in presses.try_iter() {
+            self.presses.push_back((now, count));
+            is_typing = true;
+        }
+        while self
+            .presses
+            .front()
+            .is_some_and(|(pressed_at, _)| now - *pressed_at > RATE_WINDOW)
+        {
+            self.presses.pop_front();
+        }
+
+        if is_typing {
+            self.last_press = now;
+            if self.is_dozing {
+                self.is_dozing = false;
+                if Self::current_animation(application) == Some("SLEEP") {
+                    let _ = application.task_channel.0.send(GremlinTask::PlayInterrupt(
+                        application.default_animation().to_string(),
+                    ));
+                }
+            }
+        }
+
+        let rate = self.rate();
+        if self.speed_before.is_none() && rate >= FAST_RATE {
+            self.start_typing(application);
+        } else if self.speed_before.is_some() && rate < SLOW_RATE {
+            self.stop_typing(application);
+        }
+
+        if !self.is_dozing
+            && self.last_press.elapsed() >= self.doze_after
+            && Self::current_animation(application) == Some(application.default_animation())
+            && application.task_queue.is_empty()
+            && Self::has_animation(application, "SLEEP")
+        {
+            self.is_dozing = true;
+            // nothing queued after it, so it sleeps until the next keystroke
+            let _ = application
+                .task_channel
+                .0
+                .send(GremlinTask::PlayInterrupt("SLEEP".to_string()));
+        }
+    }
+}
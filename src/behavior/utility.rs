@@ -0,0 +1,107 @@
+use std::time::{Duration, Instant};
+
+use chrono::{Local, Timelike};
+
+use crate::{
+    behavior::{
+        Behavior, ContextData,
+        tree::{TreeBlackboard, track_idle},
+    },
+    events::{Event, MouseButton},
+    gremlin::DesktopGremlin,
+    stats::STAT_MAX,
+    tree::Blackboard,
+    utility::Input,
+};
+
+const TICK_EVERY: Duration = Duration::from_secs(1);
+const TICK_EVENT: &str = "utility:tick";
+// how long `idle` takes to fill up and `clicked` to wear off
+const RECENT: Duration = Duration::from_secs(60);
+// below this nothing seems worth doing and the gremlin's left to the other behaviors
+const MIN_SCORE: f32 = 0.05;
+
+/// Scores the current gremlin's `[utility]` candidates every `TICK_EVERY` from its stats, the
+/// time of day and what happened lately, and carries out the best one, see `UtilityCandidate`.
+/// Leaves gremlins with a `[tree]` to `GremlinTree`.
+#[derive(Default)]
+pub struct GremlinUtility {
+    idle_since: Option<Instant>,
+    clicked_at: Option<Instant>,
+}
+
+impl GremlinUtility {
+    pub fn new() -> Box<Self> {
+        Default::default()
+    }
+
+    /// how long ago `since` was, as a part of `RECENT`
+    fn recency(since: Option<Instant>) -> f32 {
+        since.map_or(0.0, |since| {
+            (since.elapsed().as_secs_f32() / RECENT.as_secs_f32()).min(1.0)
+        })
+    }
+}
+
+impl Behavior for GremlinUtility {
+    fn setup(&mut self, application: &mut DesktopGremlin) {
+        application.schedule_every(TICK_EVERY, Event::Custom(TICK_EVENT.to_string()));
+    }
+
+    fn update(&mut self, application: &mut DesktopGremlin, context: &ContextData) {
+        let Some(gremlin) = &application.current_gremlin else {
+            return;
+        };
+        if gremlin.utility.is_empty() || gremlin.tree.is_some() {
+            self.idle_since = None;
+            return;
+        }
+        if context.events.contains_key(&Event::Click {
+            mouse_btn: MouseButton::Left,
+        }) {
+            self.clicked_at = Some(Instant::now());
+        }
+        track_idle(application, &mut self.idle_since);
+        if !context
+            .events
+            .contains_key(&Event::Custom(TICK_EVENT.to_string()))
+        {
+            return;
+        }
+
+        let stats = application.stats;
+        let now = Local::now();
+        let hour = now.num_seconds_from_midnight() as f32 / (24.0 * 60.0 * 60.0);
+        let (idle, clicked) = (
+            Self::recency(self.idle_since),
+            self.clicked_at
+                .map_or(0.0, |_| 1.0 - Self::recency(self.clicked_at)),
+        );
+        let value = |input| match input {
+            Input::Hunger => stats.hunger / STAT_MAX,
+            Input::Happiness => stats.happiness / STAT_MAX,
+            Input::Energy => stats.energy / STAT_MAX,
+            Input::Hour => hour,
+            Input::Idle => idle,
+            Input::Clicked => clicked,
+        };
+        let best = gremlin
+            .utility
+            .iter()
+            .map(|candidate| (candidate.score(value), &candidate.action))
+            .filter(|(score, _)| *score >= MIN_SCORE)
+            .max_by(|(a, _), (b, _)| a.total_cmp(b))
+            .map(|(_, action)| action.clone());
+
+        let mut blackboard = TreeBlackboard {
+            application,
+            is_toggled: false,
+            idle_since: self.idle_since,
+            wants_chase: false,
+        };
+        if let Some(action) = best {
+            blackboard.act(&action);
+        }
+        self.idle_since = blackboard.finish();
+    }
+}
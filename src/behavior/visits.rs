@@ -0,0 +1,161 @@
+use std::{
+    env,
+    process::{Child, Command},
+    sync::mpsc::Receiver,
+    time::Duration,
+};
+
+use tracing::{info, warn};
+
+use crate::{
+    behavior::{Behavior, ContextData},
+    events::Event,
+    gremlin::DesktopGremlin,
+    visit::{DEFAULT_VISIT_PORT, VisitListener, Visitor},
+};
+
+/// "1" for the default port or the port to take visitors on, the behavior stays off without it
+const VISIT_PORT_ENV: &str = "GREMLIN_VISIT_PORT";
+/// sends every visiting gremlin home, raised by the `home` ipc command
+pub const VISIT_HOME_EVENT: &str = "visit:home";
+const MAX_VISITORS: usize = 3;
+const BUBBLE_DURATION: Duration = Duration::from_secs(4);
+
+/// Lets a friend's gremlin come over from the LAN (see `desktop_gremlin visit`). Each visitor runs
+/// as a `--companion` of this gremlin until it's sent home or its window gets closed.
+/// Optional, does nothing unless a port is given with `with_port` or `GREMLIN_VISIT_PORT`.
+pub struct GremlinVisits {
+    port: Option<u16>,
+    listener: Option<(VisitListener, Receiver<Visitor>)>,
+    visitors: Vec<(Visitor, Child)>,
+}
+
+impl Default for GremlinVisits {
+    fn default() -> Self {
+        Self {
+            port: env::var(VISIT_PORT_ENV)
+                .ok()
+                .and_then(|port| match port.as_str() {
+                    "1" => Some(DEFAULT_VISIT_PORT),
+                    _ => port
+                        .parse()
+                        .inspect_err(|_| {
+                            warn!("{VISIT_PORT_ENV} should be 1 or a port, got {port:?}")
+                        })
+                        .ok(),
+                }),
+            listener: None,
+            visitors: Vec::new(),
+        }
+    }
+}
+
+impl GremlinVisits {
+    pub fn new() -> Box<Self> {
+        Default::default()
+    }
+
+    #[allow(unused)]
+    pub fn with_port(port: u16) -> Box<Self> {
+        // no struct update syntax, `Drop` is implemented
+        let mut visits = Self::default();
+        visits.port = Some(port);
+        Box::new(visits)
+    }
+
+    fn let_in(&mut self, application: &mut DesktopGremlin, visitor: Visitor) {
+        if self.visitors.len() >= MAX_VISITORS {
+            application.say(
+                format!("no room for {} right now", visitor.name),
+                BUBBLE_DURATION,
+            );
+            visitor.clean_up();
+            return;
+        }
+        let spawned = env::current_exe().and_then(|exe| {
+            Command::new(exe)
+                .arg("--companion")
+                .arg("--gremlin")
+                .arg(&visitor.pack_path)
+                .arg("--scale")
+                .arg(visitor.scale.to_string())
+                .spawn()
+        });
+        match spawned {
+            Ok(child) => {
+                info!("{} came to visit", visitor.name);
+                application.say(format!("{} came to visit!", visitor.name), BUBBLE_DURATION);
+                self.visitors.push((visitor, child));
+            }
+            Err(err) => {
+                warn!("couldn't let {} in: {err}", visitor.name);
+                visitor.clean_up();
+            }
+        }
+    }
+
+    /// forgets the visitors whose window was closed on their own
+    fn reap(&mut self) {
+        self.visitors.retain_mut(|(visitor, child)| {
+            let has_left = !matches!(child.try_wait(), Ok(None));
+            if has_left {
+                visitor.clean_up();
+            }
+            !has_left
+        });
+    }
+
+    fn send_home(&mut self) -> Vec<String> {
+        self.visitors
+            .drain(..)
+            .map(|(visitor, mut child)| {
+                let _ = child.kill();
+                let _ = child.wait();
+                visitor.clean_up();
+                visitor.name
+            })
+            .collect()
+    }
+}
+
+impl Behavior for GremlinVisits {
+    fn setup(&mut self, _: &mut DesktopGremlin) {
+        let Some(port) = self.port else {
+            return;
+        };
+        self.listener = VisitListener::start(port)
+            .inspect_err(|err| warn!("can't take visitors on port {port}: {err}"))
+            .ok();
+    }
+
+    fn update(&mut self, application: &mut DesktopGremlin, context: &ContextData) {
+        let arrived: Vec<Visitor> = match &self.listener {
+            Some((_, visitor_rx)) => visitor_rx.try_iter().collect(),
+            None => return,
+        };
+        for visitor in arrived {
+            self.let_in(application, visitor);
+        }
+        self.reap();
+        if let Some((listener, _)) = &self.listener {
+            listener.set_has_room(self.visitors.len() < MAX_VISITORS);
+        }
+
+        if context
+            .events
+            .contains_key(&Event::Custom(VISIT_HOME_EVENT.to_string()))
+        {
+            let names = self.send_home();
+            if !names.is_empty() {
+                application.say(format!("bye {}!", names.join(", ")), BUBBLE_DURATION);
+            }
+        }
+    }
+}
+
+impl Drop for GremlinVisits {
+    fn drop(&mut self) {
+        // visitors don't stay over once their host is gone
+        self.send_home();
+    }
+}
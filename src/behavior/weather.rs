@@ -0,0 +1,141 @@
+use std::{env, sync::mpsc::Receiver, time::Duration};
+
+use tracing::warn;
+
+use crate::{
+    behavior::{Behavior, ContextData},
+    events::Event,
+    gremlin::{DesktopGremlin, GremlinTask},
+    weather::{WeatherCondition, WeatherPoller, WeatherReport},
+};
+
+// "latitude,longitude", the behavior stays off without it
+const LOCATION_ENV: &str = "GREMLIN_WEATHER_LOCATION";
+const POLL_INTERVAL: Duration = Duration::from_secs(30 * 60);
+const FLAVOR_INTERVAL: Duration = Duration::from_secs(90);
+const FLAVOR_EVENT: &str = "weather:flavor";
+const BUBBLE_DURATION: Duration = Duration::from_secs(5);
+
+const HOT_ABOVE: f32 = 28.0;
+const COLD_BELOW: f32 = 3.0;
+
+/// Looks up the local weather now and then and plays weather-flavored animations
+/// in between IDLE loops: UMBRELLA in the rain, SUNGLASSES when it's sunny and hot, SHIVER in the cold.
+/// Optional, does nothing unless a location is given with `with_location` or `GREMLIN_WEATHER_LOCATION`.
+pub struct GremlinWeather {
+    location: Option<(f64, f64)>,
+    poller: Option<(WeatherPoller, Receiver<WeatherReport>)>,
+    report: Option<WeatherReport>,
+}
+
+impl Default for GremlinWeather {
+    fn default() -> Self {
+        Self {
+            location: env::var(LOCATION_ENV).ok().and_then(|location| {
+                let parsed = parse_location(&location);
+                if parsed.is_none() {
+                    warn!("{LOCATION_ENV} should look like \"52.52,13.40\", got {location:?}");
+                }
+                parsed
+            }),
+            poller: None,
+            report: None,
+        }
+    }
+}
+
+fn parse_location(location: &str) -> Option<(f64, f64)> {
+    let (latitude, longitude) = location.split_once(',')?;
+    Some((
+        latitude.trim().parse().ok()?,
+        longitude.trim().parse().ok()?,
+    ))
+}
+
+impl GremlinWeather {
+    pub fn new() -> Box<Self> {
+        Default::default()
+    }
+
+    #[allow(unused)]
+    pub fn with_location(latitude: f64, longitude: f64) -> Box<Self> {
+        Box::new(Self {
+            location: Some((latitude, longitude)),
+            ..Default::default()
+        })
+    }
+
+    fn flavor(report: &WeatherReport) -> Option<&'static str> {
+        match report.condition {
+            WeatherCondition::Rain | WeatherCondition::Storm => Some("UMBRELLA"),
+            WeatherCondition::Snow => Some("SHIVER"),
+            _ if report.temperature < COLD_BELOW => Some("SHIVER"),
+            WeatherCondition::Clear if report.temperature > HOT_ABOVE => Some("SUNGLASSES"),
+            _ => None,
+        }
+    }
+
+    fn remark(report: &WeatherReport) -> Option<&'static str> {
+        match report.condition {
+            WeatherCondition::Rain => Some("looks like rain out there"),
+            WeatherCondition::Storm => Some("a storm?! i'm staying in"),
+            WeatherCondition::Snow => Some("it's snowing!"),
+            WeatherCondition::Fog => Some("can't see a thing outside"),
+            _ if report.temperature < COLD_BELOW => Some("brr, it's freezing"),
+            WeatherCondition::Clear if report.temperature > HOT_ABOVE => Some("so hot today..."),
+            _ => None,
+        }
+    }
+}
+
+impl Behavior for GremlinWeather {
+    fn setup(&mut self, application: &mut DesktopGremlin) {
+        let Some(location) = self.location else {
+            return;
+        };
+        self.poller = Some(WeatherPoller::spawn(location, POLL_INTERVAL));
+        application.schedule_every(FLAVOR_INTERVAL, Event::Custom(FLAVOR_EVENT.to_string()));
+    }
+
+    fn update(&mut self, application: &mut DesktopGremlin, context: &ContextData) {
+        let Some((_, report_rx)) = &self.poller else {
+            return;
+        };
+        while let Ok(report) = report_rx.try_recv() {
+            // only speak up when the weather changed
+            if self
+                .report
+                .is_none_or(|last| last.condition != report.condition)
+                && let Some(remark) = Self::remark(&report)
+            {
+                application.say(remark, BUBBLE_DURATION);
+            }
+            self.report = Some(report);
+        }
+
+        if !context
+            .events
+            .contains_key(&Event::Custom(FLAVOR_EVENT.to_string()))
+        {
+            return;
+        }
+        let Some(gremlin) = &application.current_gremlin else {
+            return;
+        };
+        let is_idle = gremlin.animator.as_ref().is_some_and(|animator| {
+            animator.animation_properties.animation_name == gremlin.metadata.default_animation()
+        }) && application.task_queue.is_empty();
+        if is_idle
+            && let Some(animation_name) = self.report.as_ref().and_then(Self::flavor)
+            && gremlin.has_animation(animation_name)
+        {
+            let _ = application
+                .task_channel
+                .0
+                .send(GremlinTask::Play(animation_name.to_string()));
+            let _ = application.task_channel.0.send(GremlinTask::Play(
+                application.default_animation().to_string(),
+            ));
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use std::{
+    process::ExitCode,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, anyhow};
+use sdl3::video::WindowFlags;
+
+use crate::{
+    behavior::create_sheet_texture,
+    gremlin::{Animation, Animator, DesktopGremlin, LaunchArguments},
+    state::AppState,
+    utils::{CACHE_CAPACITY, TextureCache},
+};
+
+/// How long each stage of the sprite pipeline took, every time it ran.
+#[derive(Default)]
+struct StageTimings {
+    millis: Vec<f64>,
+}
+
+impl StageTimings {
+    fn time<T>(&mut self, stage: impl FnOnce() -> T) -> T {
+        let started = Instant::now();
+        let result = stage();
+        self.millis.push(started.elapsed().as_secs_f64() * 1000.0);
+        result
+    }
+
+    fn report(&mut self, name: &str) {
+        if self.millis.is_empty() {
+            println!("{name:<8} {:>7}", 0);
+            return;
+        }
+        self.millis.sort_by(f64::total_cmp);
+        let count = self.millis.len();
+        let mean = self.millis.iter().sum::<f64>() / count as f64;
+        let p95 = self.millis[(count * 95 / 100).min(count - 1)];
+        let max = self.millis[count - 1];
+        println!("{name:<8} {count:>7} {mean:>10.3} {p95:>10.3} {max:>10.3}");
+    }
+}
+
+/// `desktop_gremlin --bench <seconds>`: plays every animation of the gremlin (`--gremlin`, or the
+/// saved one) in a hidden window for `seconds`, the same way `GremlinRender` does, and prints
+/// how long decoding, resizing and drawing took and how the texture cache held up.
+pub fn run_cli(seconds: &str, gremlin_path: Option<String>) -> ExitCode {
+    let Some(duration) = seconds
+        .parse::<f64>()
+        .ok()
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        .filter(|duration| !duration.is_zero())
+    else {
+        eprintln!("usage: desktop_gremlin --bench <seconds> [--gremlin <manifest or pack>]");
+        return ExitCode::FAILURE;
+    };
+    match bench(duration, gremlin_path) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("couldn't run the benchmark: {err:#}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn bench(duration: Duration, gremlin_path: Option<String>) -> anyhow::Result<()> {
+    let gremlin_path = gremlin_path
+        .or_else(|| AppState::load().gremlin_path)
+        .ok_or_else(|| anyhow!("there's no saved gremlin, pick one with --gremlin"))?;
+    let mut application = DesktopGremlin::new(Some(LaunchArguments {
+        window_flags: vec![WindowFlags::HIDDEN, WindowFlags::BORDERLESS],
+        // the same run every time
+        seed: Some(0),
+        ..Default::default()
+    }))?;
+    let gremlin = application
+        .load_gremlin(gremlin_path.clone())
+        .with_context(|| format!("couldn't load {gremlin_path}"))?;
+    application.current_gremlin = Some(gremlin);
+    application.fit_window_to_gremlin();
+    let Some(gremlin) = application.current_gremlin.take() else {
+        unreachable!("the gremlin was just put there");
+    };
+
+    let mut names: Vec<&String> = gremlin
+        .animation_map
+        .keys()
+        .filter(|name| gremlin.has_animation(name))
+        .collect();
+    names.sort();
+    if names.is_empty() {
+        return Err(anyhow!("{} has no animations to play", gremlin.name));
+    }
+
+    let mut decode = StageTimings::default();
+    let mut resize = StageTimings::default();
+    let mut render = StageTimings::default();
+    let mut cache = TextureCache::default();
+    let mut frames = 0;
+    let started = Instant::now();
+    'bench: for name in names.iter().cycle() {
+        let (mut animator, texture) = match cache.lookup(name.to_string()) {
+            Some((index, (animator, texture))) => {
+                cache.rearrange(index);
+                (animator, texture)
+            }
+            None => {
+                let properties = &gremlin.animation_map[*name];
+                let animation: Animation = decode
+                    .time(|| properties.try_into())
+                    .with_context(|| format!("couldn't decode {name}"))?;
+                let mut animator: Animator = (&animation).into();
+                let texture = resize
+                    .time(|| {
+                        create_sheet_texture(
+                            application.window.as_mut(),
+                            &animation,
+                            &mut animator,
+                            gremlin.scaling,
+                        )
+                    })
+                    .context("the hidden window has no canvas to draw on")?;
+                cache.cache(name.to_string(), (animator.clone(), Rc::clone(&texture)));
+                (animator, texture)
+            }
+        };
+
+        animator.restart();
+        for _ in 0..animator.animation_properties.sprite_count.max(1) {
+            if started.elapsed() >= duration {
+                break 'bench;
+            }
+            render.time(|| {
+                application
+                    .window
+                    .draw_frame(Some(&texture), animator.get_frame_rect(), &[]);
+                application.window.present();
+            });
+            animator.show_frame(animator.current_frame + 1);
+            frames += 1;
+        }
+    }
+
+    let elapsed = started.elapsed().as_secs_f64();
+    println!(
+        "{} ({} animations) for {elapsed:.1}s, {frames} frames, {:.0} fps",
+        gremlin.name,
+        names.len(),
+        frames as f64 / elapsed
+    );
+    println!(
+        "{:<8} {:>7} {:>10} {:>10} {:>10}",
+        "stage", "runs", "mean ms", "p95 ms", "max ms"
+    );
+    decode.report("decode");
+    resize.report("resize");
+    render.report("render");
+    let stats = cache.stats();
+    println!(
+        "cache    {} hits, {} misses, {} evicted, {}/{CACHE_CAPACITY} sheets, {:.1} MB",
+        stats.hits,
+        stats.misses,
+        stats.evictions,
+        stats.entries,
+        stats.bytes as f64 / (1024.0 * 1024.0)
+    );
+    Ok(())
+}
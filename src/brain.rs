@@ -0,0 +1,69 @@
+use std::env;
+
+use serde_json::json;
+use tracing::warn;
+
+// anything speaking the OpenAI chat completions api works, this is ollama's local one
+const DEFAULT_CHAT_URL: &str = "http://localhost:11434/v1/chat/completions";
+const DEFAULT_CHAT_MODEL: &str = "llama3.2";
+const PERSONA: &str = "You are a small mischievous gremlin living on the user's desktop. \
+    Answer in one or two short sentences, they have to fit in a speech bubble.";
+// said when the brain can't be reached
+const BLANK_STARE: &str = "...huh? my head's empty right now";
+
+/// Comes up with what the gremlin says back when it's talked to.
+/// `respond` blocks, so it gets called off the main thread.
+pub trait Brain: Send + Sync {
+    fn respond(&self, prompt: &str) -> String;
+}
+
+/// Asks an OpenAI-compatible chat completions endpoint.
+pub struct HttpBrain {
+    pub url: String,
+    pub api_key: Option<String>,
+    pub model: String,
+}
+
+impl HttpBrain {
+    /// configured by `GREMLIN_CHAT_URL`, `GREMLIN_CHAT_KEY` and `GREMLIN_CHAT_MODEL`,
+    /// a local ollama without a key otherwise
+    pub fn from_env() -> Self {
+        Self {
+            url: env::var("GREMLIN_CHAT_URL").unwrap_or_else(|_| DEFAULT_CHAT_URL.to_string()),
+            api_key: env::var("GREMLIN_CHAT_KEY").ok(),
+            model: env::var("GREMLIN_CHAT_MODEL")
+                .unwrap_or_else(|_| DEFAULT_CHAT_MODEL.to_string()),
+        }
+    }
+
+    fn complete(&self, prompt: &str) -> anyhow::Result<String> {
+        let body = json!({
+            "model": self.model,
+            "messages": [
+                { "role": "system", "content": PERSONA },
+                { "role": "user", "content": prompt },
+            ],
+        });
+        let mut request = ureq::post(&self.url).header("Content-Type", "application/json");
+        if let Some(api_key) = &self.api_key {
+            request = request.header("Authorization", format!("Bearer {api_key}"));
+        }
+        let response = request
+            .send(serde_json::to_string(&body)?)?
+            .body_mut()
+            .read_to_string()?;
+        let response: serde_json::Value = serde_json::from_str(&response)?;
+        response["choices"][0]["message"]["content"]
+            .as_str()
+            .map(|reply| reply.trim().to_string())
+            .ok_or_else(|| anyhow::anyhow!("no reply in {response}"))
+    }
+}
+
+impl Brain for HttpBrain {
+    fn respond(&self, prompt: &str) -> String {
+        self.complete(prompt)
+            .inspect_err(|err| warn!("couldn't reach {}: {err}", self.url))
+            .unwrap_or_else(|_| BLANK_STARE.to_string())
+    }
+}
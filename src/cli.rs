@@ -0,0 +1,14 @@
+//! What the `desktop_gremlin` binary runs besides the gremlin itself, each one takes the
+//! arguments after its subcommand or flag and returns the exit code.
+
+pub use crate::{
+    autostart::run_cli as autostart,
+    bench::run_cli as bench,
+    fetch::run_cli as fetch,
+    instance::InstanceLock,
+    io::run_assemble_cli as assemble,
+    ipc::{forward_launch, run_client as send},
+    logging::init as init_logging,
+    visit::run_cli as visit,
+    wizard::run_cli as new_gremlin,
+};
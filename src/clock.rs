@@ -0,0 +1,109 @@
+use std::time::{Duration, Instant};
+
+// a frame never catches up more than this many ticks, like the first one after the machine slept
+const MAX_OWED_TICKS: f64 = 48.0;
+// on top of what was planned, a late frame catches up this many ticks and leaves the rest to the
+// frames after it
+const CATCH_UP_TICKS: f64 = 1.0;
+
+/// Hands out every frame's animation time as whole nominal ticks (see
+/// `DesktopGremlin::frame_ticks`) from the wall time between frames. Time that doesn't make a
+/// whole tick carries over instead of being rounded away, and a frame coming late only catches up
+/// a tick at a time, so a loop stalled by something like a flood of window move events plays on
+/// smoothly instead of jumping ahead.
+#[derive(Debug)]
+pub struct FrameClock {
+    last_frame_at: Instant,
+    // when the loop meant to run the next frame, see `plan`
+    planned_at: Option<Instant>,
+    // ticks that went by but haven't been handed out yet
+    owed: f64,
+    // how much later than planned the last frame came, zero when it came early
+    lateness: Duration,
+}
+
+impl Default for FrameClock {
+    fn default() -> Self {
+        Self {
+            last_frame_at: Instant::now(),
+            planned_at: None,
+            owed: 0.0,
+            lateness: Duration::ZERO,
+        }
+    }
+}
+
+impl FrameClock {
+    /// the ticks the frame starting `now` stands for, none when it came early
+    pub fn tick(&mut self, now: Instant, interval: Duration) -> u32 {
+        let interval = interval.as_secs_f64().max(f64::EPSILON);
+        let planned_at = self.planned_at.take().unwrap_or(now);
+        let planned = planned_at.saturating_duration_since(self.last_frame_at);
+        self.lateness = now.saturating_duration_since(planned_at);
+        let elapsed = now.saturating_duration_since(self.last_frame_at);
+        self.last_frame_at = now;
+
+        self.owed = (self.owed + elapsed.as_secs_f64() / interval).min(MAX_OWED_TICKS);
+        let planned_ticks = (planned.as_secs_f64() / interval).ceil().max(1.0);
+        let ticks = self.owed.floor().min(planned_ticks + CATCH_UP_TICKS);
+        self.owed -= ticks;
+        ticks as u32
+    }
+
+    /// the loop means to run the next frame at `at`
+    pub fn plan(&mut self, at: Instant) {
+        self.planned_at = Some(at);
+    }
+
+    pub fn lateness(&self) -> Duration {
+        self.lateness
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a whole second, so halves and quarters of it add up exactly
+    const INTERVAL: Duration = Duration::from_secs(1);
+
+    #[test]
+    fn hands_out_whole_ticks_and_carries_the_rest() {
+        let mut clock = FrameClock::default();
+        let start = clock.last_frame_at;
+        assert_eq!(clock.tick(start + Duration::from_millis(500), INTERVAL), 0);
+        assert_eq!(clock.tick(start + Duration::from_millis(1500), INTERVAL), 1);
+        assert_eq!(clock.tick(start + Duration::from_millis(2000), INTERVAL), 1);
+        assert_eq!(clock.tick(start + Duration::from_millis(2750), INTERVAL), 0);
+        assert_eq!(clock.tick(start + Duration::from_millis(3250), INTERVAL), 1);
+    }
+
+    #[test]
+    fn late_frames_catch_up_a_tick_at_a_time() {
+        let mut clock = FrameClock::default();
+        let start = clock.last_frame_at;
+        clock.plan(start + INTERVAL);
+        // five ticks late, one planned and one to catch up
+        let late = start + INTERVAL * 6;
+        assert_eq!(clock.tick(late, INTERVAL), 2);
+        assert_eq!(clock.lateness(), INTERVAL * 5);
+
+        // the rest comes with the frames after it
+        clock.plan(late + INTERVAL);
+        assert_eq!(clock.tick(late + INTERVAL, INTERVAL), 2);
+        assert_eq!(clock.lateness(), Duration::ZERO);
+    }
+
+    #[test]
+    fn owes_at_most_a_few_seconds() {
+        let mut clock = FrameClock::default();
+        let start = clock.last_frame_at;
+        // waking up after a day asleep
+        let woke = start + Duration::from_secs(24 * 60 * 60);
+        let mut handed_out = clock.tick(woke, INTERVAL);
+        for frame in 1..1000 {
+            handed_out += clock.tick(woke + INTERVAL * frame, INTERVAL);
+        }
+        assert!(handed_out <= 1000 + MAX_OWED_TICKS as u32);
+    }
+}
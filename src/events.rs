@@ -1,25 +1,266 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, path::PathBuf, str::FromStr, sync::mpsc::Receiver};
 
 use sdl3::{EventPump, event::Event as SdlEvent};
+use serde::Serialize;
 
 use crate::utils::MouseKeysState;
 
 // this is to implement eq and hash for event enum
-#[derive(PartialEq, Eq, Hash, Debug)]
+#[derive(PartialEq, Eq, Hash, Debug, Clone)]
 pub enum Event {
     Quit,
     Click { mouse_btn: MouseButton },
     MouseButtonDown { mouse_btn: MouseButton },
     MouseMove,
     MouseButtonUp { mouse_btn: MouseButton },
+    // comes with how far it scrolled as `EventData::Value`, positive is away from the user
+    MouseWheel,
     Window { win_event: WindowEvent },
     DragStart { mouse_btn: MouseButton },
     Drag { mouse_btn: MouseButton },
     DragEnd { mouse_btn: MouseButton },
+    // named like SDL names keys, e.g. "F3", "Space"
+    KeyDown { key: String },
+    // a global hotkey was pressed, by `HotKey::id()`, wherever the focus is
+    HotKey(u32),
+    // a file from the file manager was dropped on the gremlin
+    FileDropped { path: PathBuf },
+    // a key went down in another of the app's windows (an editor, not a clone), see `set_window`
+    OtherWindowKeyDown { window_id: u32, key: String },
+    // another of the app's windows asked to be closed
+    OtherWindowClosed { window_id: u32 },
+    // a button went down on any connected gamepad
+    GamepadButton { button: GamepadButton },
+    // the left stick moved, comes with where it is now as `EventData::FCoordinate`, -1 to 1 on
+    // each axis with the dead zone taken out
+    GamepadStick,
+    // a gamepad was plugged in or out, by its joystick id, see `Gamepads`
+    GamepadAdded(u32),
+    GamepadRemoved(u32),
+    // events coming from outside of sdl, e.g. the system monitor
+    Custom(String),
     Unhandled,
 }
 
-#[derive(PartialEq, Debug)]
+/// Typed views of `Event`s for `ContextData::on`, e.g. `context.on::<kinds::Click>(|click| ...)`,
+/// so behaviors don't have to match on the event and its data by hand.
+pub mod kinds {
+    use std::path::PathBuf;
+
+    use super::{Event, EventData, GamepadButton, MouseButton};
+
+    pub trait EventKind {
+        type Data;
+
+        /// `None` when `event` isn't one of these
+        fn extract(event: &Event, data: Option<&EventData>) -> Option<Self::Data>;
+    }
+
+    /// where the pointer was when a button did something, in window coordinates
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Pointer {
+        pub mouse_btn: MouseButton,
+        pub x: f32,
+        pub y: f32,
+    }
+
+    fn pointer(mouse_btn: MouseButton, data: Option<&EventData>) -> Pointer {
+        let (x, y) = match data {
+            Some(EventData::FCoordinate { x, y }) => (*x, *y),
+            _ => (0.0, 0.0),
+        };
+        Pointer { mouse_btn, x, y }
+    }
+
+    pub struct Quit;
+
+    impl EventKind for Quit {
+        type Data = ();
+
+        fn extract(event: &Event, _: Option<&EventData>) -> Option<()> {
+            matches!(event, Event::Quit).then_some(())
+        }
+    }
+
+    pub struct Click;
+
+    impl EventKind for Click {
+        type Data = Pointer;
+
+        fn extract(event: &Event, data: Option<&EventData>) -> Option<Pointer> {
+            match event {
+                Event::Click { mouse_btn } => Some(pointer(*mouse_btn, data)),
+                _ => None,
+            }
+        }
+    }
+
+    pub struct DragStart;
+
+    impl EventKind for DragStart {
+        type Data = Pointer;
+
+        fn extract(event: &Event, data: Option<&EventData>) -> Option<Pointer> {
+            match event {
+                Event::DragStart { mouse_btn } => Some(pointer(*mouse_btn, data)),
+                _ => None,
+            }
+        }
+    }
+
+    /// how far one move of a drag went, in pixels
+    pub struct Step {
+        pub mouse_btn: MouseButton,
+        pub x_rel: f32,
+        pub y_rel: f32,
+    }
+
+    pub struct Drag;
+
+    impl EventKind for Drag {
+        type Data = Step;
+
+        fn extract(event: &Event, data: Option<&EventData>) -> Option<Step> {
+            match (event, data) {
+                (Event::Drag { mouse_btn }, Some(EventData::Difference { x_rel, y_rel, .. })) => {
+                    Some(Step {
+                        mouse_btn: *mouse_btn,
+                        x_rel: *x_rel,
+                        y_rel: *y_rel,
+                    })
+                }
+                _ => None,
+            }
+        }
+    }
+
+    pub struct DragEnd;
+
+    impl EventKind for DragEnd {
+        type Data = Pointer;
+
+        fn extract(event: &Event, data: Option<&EventData>) -> Option<Pointer> {
+            match event {
+                Event::DragEnd { mouse_btn } => Some(pointer(*mouse_btn, data)),
+                _ => None,
+            }
+        }
+    }
+
+    /// how many notches it scrolled, positive is away from the user
+    pub struct Wheel;
+
+    impl EventKind for Wheel {
+        type Data = f32;
+
+        fn extract(event: &Event, data: Option<&EventData>) -> Option<f32> {
+            match (event, data) {
+                (Event::MouseWheel, Some(EventData::Value(notches))) => Some(*notches),
+                _ => None,
+            }
+        }
+    }
+
+    /// the pressed hotkey's id, see `HotKey::id()`
+    pub struct HotKey;
+
+    impl EventKind for HotKey {
+        type Data = u32;
+
+        fn extract(event: &Event, _: Option<&EventData>) -> Option<u32> {
+            match event {
+                Event::HotKey(id) => Some(*id),
+                _ => None,
+            }
+        }
+    }
+
+    /// where the dropped file is
+    pub struct FileDropped;
+
+    impl EventKind for FileDropped {
+        type Data = PathBuf;
+
+        fn extract(event: &Event, _: Option<&EventData>) -> Option<PathBuf> {
+            match event {
+                Event::FileDropped { path } => Some(path.clone()),
+                _ => None,
+            }
+        }
+    }
+
+    /// the key's SDL name
+    pub struct KeyDown;
+
+    impl EventKind for KeyDown {
+        type Data = String;
+
+        fn extract(event: &Event, _: Option<&EventData>) -> Option<String> {
+            match event {
+                Event::KeyDown { key } => Some(key.clone()),
+                _ => None,
+            }
+        }
+    }
+
+    /// (which window, the key's SDL name)
+    pub struct OtherWindowKeyDown;
+
+    impl EventKind for OtherWindowKeyDown {
+        type Data = (u32, String);
+
+        fn extract(event: &Event, _: Option<&EventData>) -> Option<(u32, String)> {
+            match event {
+                Event::OtherWindowKeyDown { window_id, key } => Some((*window_id, key.clone())),
+                _ => None,
+            }
+        }
+    }
+
+    /// which button, on whichever gamepad
+    pub struct GamepadPress;
+
+    impl EventKind for GamepadPress {
+        type Data = GamepadButton;
+
+        fn extract(event: &Event, _: Option<&EventData>) -> Option<GamepadButton> {
+            match event {
+                Event::GamepadButton { button } => Some(*button),
+                _ => None,
+            }
+        }
+    }
+
+    /// where the left stick is now, -1 to 1 on each axis, down is positive
+    pub struct GamepadStick;
+
+    impl EventKind for GamepadStick {
+        type Data = (f32, f32);
+
+        fn extract(event: &Event, data: Option<&EventData>) -> Option<(f32, f32)> {
+            match (event, data) {
+                (Event::GamepadStick, Some(EventData::FCoordinate { x, y })) => Some((*x, *y)),
+                _ => None,
+            }
+        }
+    }
+
+    /// which window
+    pub struct OtherWindowClosed;
+
+    impl EventKind for OtherWindowClosed {
+        type Data = u32;
+
+        fn extract(event: &Event, _: Option<&EventData>) -> Option<u32> {
+            match event {
+                Event::OtherWindowClosed { window_id } => Some(*window_id),
+                _ => None,
+            }
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, Clone)]
 pub enum EventData {
     Coordinate {
         x: i32,
@@ -35,9 +276,11 @@ pub enum EventData {
         x: f32,
         y: f32,
     },
+    Value(f32),
 }
 
-#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum MouseButton {
     Left,
     Right,
@@ -46,9 +289,90 @@ pub enum MouseButton {
     X1,
     X2,
 }
-#[derive(PartialEq, Eq, Hash, Debug)]
+
+impl FromStr for MouseButton {
+    type Err = ();
+
+    fn from_str(button: &str) -> Result<Self, Self::Err> {
+        Ok(match button.trim().to_lowercase().as_str() {
+            "left" => Self::Left,
+            "right" => Self::Right,
+            "middle" => Self::Middle,
+            "x1" => Self::X1,
+            "x2" => Self::X2,
+            _ => return Err(()),
+        })
+    }
+}
+
+/// Gamepad buttons by where they are rather than what they're labelled, `South` is A on an Xbox
+/// pad and cross on a PlayStation one.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum GamepadButton {
+    South,
+    East,
+    West,
+    North,
+    Back,
+    Start,
+    LeftShoulder,
+    RightShoulder,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+    Other,
+}
+
+/// as written in the manifest's `[gamepad]`, e.g. `south` or `left_shoulder`
+impl FromStr for GamepadButton {
+    type Err = ();
+
+    fn from_str(button: &str) -> Result<Self, Self::Err> {
+        Ok(match button.trim().to_lowercase().as_str() {
+            "south" => Self::South,
+            "east" => Self::East,
+            "west" => Self::West,
+            "north" => Self::North,
+            "back" => Self::Back,
+            "start" => Self::Start,
+            "left_shoulder" => Self::LeftShoulder,
+            "right_shoulder" => Self::RightShoulder,
+            "dpad_up" => Self::DPadUp,
+            "dpad_down" => Self::DPadDown,
+            "dpad_left" => Self::DPadLeft,
+            "dpad_right" => Self::DPadRight,
+            _ => return Err(()),
+        })
+    }
+}
+
+impl From<sdl3::gamepad::Button> for GamepadButton {
+    fn from(value: sdl3::gamepad::Button) -> Self {
+        use sdl3::gamepad::Button;
+        match value {
+            Button::South => Self::South,
+            Button::East => Self::East,
+            Button::West => Self::West,
+            Button::North => Self::North,
+            Button::Back => Self::Back,
+            Button::Start => Self::Start,
+            Button::LeftShoulder => Self::LeftShoulder,
+            Button::RightShoulder => Self::RightShoulder,
+            Button::DPadUp => Self::DPadUp,
+            Button::DPadDown => Self::DPadDown,
+            Button::DPadLeft => Self::DPadLeft,
+            Button::DPadRight => Self::DPadRight,
+            _ => Self::Other,
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
 pub enum WindowEvent {
     Moved,
+    // the window's contents were lost and need drawing again, e.g. after being shown
+    Exposed,
     Unhandled,
 }
 
@@ -64,9 +388,25 @@ impl From<sdl3::event::Event> for Event {
                 mouse_btn: MouseButton::from(mouse_btn),
             },
             SdlEvent::MouseMotion { .. } => Event::MouseMove,
+            SdlEvent::MouseWheel { .. } => Event::MouseWheel,
+            SdlEvent::KeyDown {
+                keycode: Some(keycode),
+                repeat: false,
+                ..
+            } => Event::KeyDown {
+                key: keycode.name(),
+            },
             SdlEvent::Window { win_event, .. } => Event::Window {
                 win_event: WindowEvent::from(win_event),
             },
+            SdlEvent::DropFile { filename, .. } => Event::FileDropped {
+                path: PathBuf::from(filename),
+            },
+            SdlEvent::ControllerButtonDown { button, .. } => Event::GamepadButton {
+                button: GamepadButton::from(button),
+            },
+            SdlEvent::ControllerDeviceAdded { which, .. } => Event::GamepadAdded(which),
+            SdlEvent::ControllerDeviceRemoved { which, .. } => Event::GamepadRemoved(which),
             _ => Event::Unhandled,
         }
     }
@@ -76,6 +416,9 @@ impl From<sdl3::event::WindowEvent> for WindowEvent {
     fn from(value: sdl3::event::WindowEvent) -> Self {
         match value {
             sdl3::event::WindowEvent::Moved(_, _) => WindowEvent::Moved,
+            sdl3::event::WindowEvent::Exposed | sdl3::event::WindowEvent::Shown => {
+                WindowEvent::Exposed
+            }
             _ => WindowEvent::Unhandled,
         }
     }
@@ -93,9 +436,19 @@ impl From<sdl3::mouse::MouseButton> for MouseButton {
         }
     }
 }
+pub type EventSource = Receiver<(Event, Option<EventData>)>;
+
+// how far off center the stick has to be before it counts, sticks rarely rest at exactly 0
+const STICK_DEAD_ZONE: f32 = 0.2;
+
 #[derive(Debug, Default)]
 pub struct EventMediator {
     mouse: MouseState,
+    sources: Vec<EventSource>,
+    injected: Vec<(Event, Option<EventData>)>,
+    window_id: Option<u32>,
+    // where each gamepad's left stick is by joystick id, -1 to 1 on each axis before the dead zone
+    sticks: HashMap<u32, (f32, f32)>,
 }
 #[derive(Debug, Default)]
 
@@ -133,12 +486,95 @@ impl MouseState {
 }
 
 impl EventMediator {
+    /// events sent through an attached source get merged into the next pumped batch
+    pub fn attach_source(&mut self, source: EventSource) {
+        self.sources.push(source);
+    }
+
+    /// Only SDL events of the window with `window_id` get through, so the other windows
+    /// behaviors open (clones, overlays) aren't mistaken for the gremlin being clicked.
+    /// Their key presses and close requests still come through as `OtherWindowKeyDown` and
+    /// `OtherWindowClosed`.
+    pub fn set_window(&mut self, window_id: u32) {
+        self.window_id = Some(window_id);
+    }
+
+    /// Queues a made up event for the next batch, as if SDL or a source had produced it.
+    /// Injected events skip the mouse tracking, so inject `Click`/`DragStart`/`Drag`/`DragEnd` directly.
+    pub fn inject(&mut self, event: Event, data: Option<EventData>) {
+        self.injected.push((event, data));
+    }
+
+    /// Only the attached sources and injected events, for driving the runtime without an SDL event pump.
+    #[allow(unused)]
+    pub fn pump_synthetic_events(&mut self) -> Vec<(Event, Option<EventData>)> {
+        let mut events = Vec::new();
+        self.merge_synthetic_events(&mut events);
+        events
+    }
+
+    /// every gamepad's left stick past the dead zone added up, so a pad lying around doesn't
+    /// hold back the one being played with
+    fn stick(&self) -> EventData {
+        let dead_zone = |value: f32| {
+            if value.abs() < STICK_DEAD_ZONE {
+                0.0
+            } else {
+                (value - STICK_DEAD_ZONE * value.signum()) / (1.0 - STICK_DEAD_ZONE)
+            }
+        };
+        let (x, y) = self.sticks.values().fold((0.0, 0.0), |(x, y), stick| {
+            (x + dead_zone(stick.0), y + dead_zone(stick.1))
+        });
+        EventData::FCoordinate {
+            x: x.clamp(-1.0, 1.0),
+            y: y.clamp(-1.0, 1.0),
+        }
+    }
+
+    fn merge_synthetic_events(&mut self, events: &mut Vec<(Event, Option<EventData>)>) {
+        for source in &self.sources {
+            events.extend(source.try_iter());
+        }
+        events.append(&mut self.injected);
+    }
+
+    /// every event since the last pump in the order they happened, SDL's first
     pub fn pump_events(
         &mut self,
         sdl_event_pump: &mut EventPump,
-    ) -> HashMap<Event, Option<EventData>> {
-        let mut event_set: HashMap<Event, Option<EventData>> = Default::default();
+    ) -> Vec<(Event, Option<EventData>)> {
+        let mut events: Vec<(Event, Option<EventData>)> = Vec::new();
         for event in sdl_event_pump.poll_iter() {
+            if let Some(window_id) = self.window_id
+                && let Some(event_window_id) = event.get_window_id()
+                && event_window_id != window_id
+            {
+                match event {
+                    SdlEvent::KeyDown {
+                        keycode: Some(keycode),
+                        repeat: false,
+                        ..
+                    } => events.push((
+                        Event::OtherWindowKeyDown {
+                            window_id: event_window_id,
+                            key: keycode.name(),
+                        },
+                        None,
+                    )),
+                    SdlEvent::Window {
+                        win_event: sdl3::event::WindowEvent::CloseRequested,
+                        ..
+                    } => events.push((
+                        Event::OtherWindowClosed {
+                            window_id: event_window_id,
+                        },
+                        None,
+                    )),
+                    _ => {}
+                }
+                continue;
+            }
             let mut parsed_ev: Option<Event> = None;
             let mut ev_data: Option<EventData> = None;
             match event {
@@ -184,14 +620,14 @@ impl EventMediator {
                         ),
                     ] {
                         if is_down && !is_dragging {
-                            event_set.insert(
+                            events.push((
                                 Event::DragStart { mouse_btn: btn },
                                 Some(EventData::FCoordinate { x, y }),
-                            );
+                            ));
                             self.mouse.dragging.set_button(&btn, true);
                         }
                         if is_down && is_dragging {
-                            event_set.insert(
+                            events.push((
                                 Event::Drag { mouse_btn: btn },
                                 Some(EventData::Difference {
                                     x_rel: xrel,
@@ -199,27 +635,57 @@ impl EventMediator {
                                     x,
                                     y,
                                 }),
-                            );
+                            ));
                         }
                     }
                 }
 
+                SdlEvent::MouseWheel { y, direction, .. } => {
+                    // natural scrolling reports flipped values
+                    let y = match direction {
+                        sdl3::mouse::MouseWheelDirection::Flipped => -y,
+                        _ => y,
+                    };
+                    ev_data = Some(EventData::Value(y));
+                }
+
                 SdlEvent::Window {
                     win_event: sdl3::event::WindowEvent::Moved(x, y),
                     ..
                 } => {
                     let _ = ev_data.insert(EventData::Coordinate { x, y });
                 }
+                SdlEvent::ControllerAxisMotion {
+                    which, axis, value, ..
+                } => {
+                    let value = (value as f32 / i16::MAX as f32).clamp(-1.0, 1.0);
+                    let stick = self.sticks.entry(which).or_default();
+                    match axis {
+                        sdl3::gamepad::Axis::LeftX => stick.0 = value,
+                        sdl3::gamepad::Axis::LeftY => stick.1 = value,
+                        _ => continue,
+                    }
+                    parsed_ev = Some(Event::GamepadStick);
+                    ev_data = Some(self.stick());
+                }
+                // a pad pulled out mid-push would otherwise keep pushing
+                SdlEvent::ControllerDeviceRemoved { which, .. }
+                    if self.sticks.contains_key(&which) =>
+                {
+                    self.sticks.remove(&which);
+                    events.push((Event::GamepadStick, Some(self.stick())));
+                }
                 _ => {}
             }
 
             if let Some(parsed_ev) = parsed_ev {
-                event_set.insert(parsed_ev, ev_data);
+                events.push((parsed_ev, ev_data));
             } else {
-                event_set.insert(event.into(), ev_data);
+                events.push((event.into(), ev_data));
             }
         }
 
-        event_set
+        self.merge_synthetic_events(&mut events);
+        events
     }
 }
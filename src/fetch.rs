@@ -0,0 +1,122 @@
+use std::{fs, path::PathBuf, process::ExitCode};
+
+use anyhow::{anyhow, bail};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::{pack::PACK_EXTENSION, utils::data_dir};
+
+// sprite sheets add up, but nothing legit gets anywhere near this
+pub const MAX_PACK_SIZE: u64 = 256 * 1024 * 1024;
+
+/// One gremlin in a community index, the index itself is a JSON array of these.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct CatalogEntry {
+    pub name: String,
+    pub url: String,
+    pub sha256: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+impl CatalogEntry {
+    pub fn install(&self) -> anyhow::Result<PathBuf> {
+        fetch_pack(&self.url, Some(&self.name), Some(&self.sha256))
+    }
+}
+
+/// where downloaded packs live, anything in here can be picked as the active gremlin
+pub fn gremlins_dir() -> PathBuf {
+    data_dir().join("gremlins")
+}
+
+pub fn fetch_catalog(index_url: &str) -> anyhow::Result<Vec<CatalogEntry>> {
+    let json = ureq::get(index_url).call()?.body_mut().read_to_string()?;
+    Ok(serde_json::from_str(&json)?)
+}
+
+/// Downloads a `.gremlin` pack into `gremlins_dir()`, refusing it when the sha256 doesn't match.
+/// The file is named after `name`, or the last segment of the url when there's none.
+pub fn fetch_pack(
+    url: &str,
+    name: Option<&str>,
+    expected_sha256: Option<&str>,
+) -> anyhow::Result<PathBuf> {
+    let name = name
+        .or_else(|| url.rsplit('/').next())
+        .map(|name| name.split(['?', '#']).next().unwrap_or(name))
+        .map(|name| name.strip_suffix(".gremlin").unwrap_or(name))
+        .filter(|name| is_safe_file_name(name))
+        .ok_or_else(|| anyhow!("can't name a gremlin after {url}"))?;
+
+    let bytes = ureq::get(url)
+        .call()?
+        .body_mut()
+        .with_config()
+        .limit(MAX_PACK_SIZE)
+        .read_to_vec()?;
+
+    if let Some(expected) = expected_sha256 {
+        let actual = Sha256::digest(&bytes)
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<String>();
+        if !actual.eq_ignore_ascii_case(expected.trim()) {
+            bail!("checksum mismatch for {name}: expected {expected}, got {actual}");
+        }
+    }
+
+    let dir = gremlins_dir();
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("{name}.{PACK_EXTENSION}"));
+    // write next to it first so a failed download never leaves half a pack behind
+    let partial_path = dir.join(format!("{name}.{PACK_EXTENSION}.part"));
+    fs::write(&partial_path, &bytes)?;
+    fs::rename(&partial_path, &path)?;
+    Ok(path)
+}
+
+pub fn is_safe_file_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|char| char.is_alphanumeric() || matches!(char, '-' | '_' | ' '))
+}
+
+/// entry point for `desktop_gremlin fetch ...`
+pub fn run_cli(args: &[String]) -> ExitCode {
+    let result = match args {
+        [flag, index_url] if flag == "--index" => fetch_catalog(index_url).map(|catalog| {
+            for entry in catalog {
+                println!("{:<20} {}", entry.name, entry.description);
+            }
+        }),
+        [flag, index_url, name] if flag == "--index" => fetch_catalog(index_url)
+            .and_then(|catalog| {
+                catalog
+                    .into_iter()
+                    .find(|entry| entry.name.eq_ignore_ascii_case(name))
+                    .ok_or_else(|| anyhow!("{name} isn't in the index"))
+            })
+            .and_then(|entry| entry.install())
+            .map(|path| println!("installed {}", path.display())),
+        [url] => fetch_pack(url, None, None).map(|path| println!("installed {}", path.display())),
+        [url, sha256] => {
+            fetch_pack(url, None, Some(sha256)).map(|path| println!("installed {}", path.display()))
+        }
+        _ => {
+            eprintln!(
+                "usage: desktop_gremlin fetch <url> [sha256]\n       desktop_gremlin fetch --index <index url> [name]"
+            );
+            return ExitCode::FAILURE;
+        }
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("couldn't fetch the gremlin: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
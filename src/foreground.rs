@@ -0,0 +1,232 @@
+use std::{
+    sync::mpsc::{self, Receiver, RecvTimeoutError, Sender},
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+/// The app owning the focused window.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForegroundApp {
+    // executable name without the extension, lowercase, e.g. "code" or "firefox"
+    pub name: String,
+    // covers its whole display, like games and videos do
+    pub is_fullscreen: bool,
+}
+
+/// Checks which app is in the foreground on its own thread and sends it over whenever that changes,
+/// `None` when there's no focused window or it can't be told (wayland, macos).
+pub struct ForegroundWatcher {
+    thread_handle: Option<JoinHandle<()>>,
+    stop_tx: Sender<()>,
+}
+
+impl ForegroundWatcher {
+    pub fn spawn(interval: Duration) -> (Self, Receiver<Option<ForegroundApp>>) {
+        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+        let (app_tx, app_rx) = mpsc::channel();
+
+        let thread_handle = thread::spawn(move || {
+            let Some(probe) = ForegroundProbe::connect() else {
+                return;
+            };
+            let mut last_app = None;
+            while let Err(RecvTimeoutError::Timeout) = stop_rx.recv_timeout(interval) {
+                let app = probe.foreground_app();
+                if app != last_app {
+                    last_app = app.clone();
+                    if app_tx.send(app).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+
+        (
+            Self {
+                thread_handle: Some(thread_handle),
+                stop_tx,
+            },
+            app_rx,
+        )
+    }
+}
+
+impl Drop for ForegroundWatcher {
+    fn drop(&mut self) {
+        let _ = self.stop_tx.send(());
+        if let Some(handle) = self.thread_handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+struct ForegroundProbe;
+
+#[cfg(target_os = "windows")]
+impl ForegroundProbe {
+    fn connect() -> Option<Self> {
+        Some(Self)
+    }
+
+    fn foreground_app(&self) -> Option<ForegroundApp> {
+        use windows::{
+            Win32::{
+                Foundation::{CloseHandle, RECT},
+                Graphics::Gdi::{
+                    GetMonitorInfoW, MONITOR_DEFAULTTONEAREST, MONITORINFO, MonitorFromWindow,
+                },
+                System::Threading::{
+                    OpenProcess, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION,
+                    QueryFullProcessImageNameW,
+                },
+                UI::WindowsAndMessaging::{
+                    GetForegroundWindow, GetShellWindow, GetWindowRect, GetWindowThreadProcessId,
+                },
+            },
+            core::PWSTR,
+        };
+
+        unsafe {
+            let hwnd = GetForegroundWindow();
+            // nothing focused, or just the desktop
+            if hwnd.is_invalid() || hwnd == GetShellWindow() {
+                return None;
+            }
+
+            let mut process_id = 0;
+            GetWindowThreadProcessId(hwnd, Some(&mut process_id));
+            let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, process_id).ok()?;
+            let mut buffer = [0u16; 1024];
+            let mut length = buffer.len() as u32;
+            let queried = QueryFullProcessImageNameW(
+                process,
+                PROCESS_NAME_WIN32,
+                PWSTR(buffer.as_mut_ptr()),
+                &mut length,
+            );
+            let _ = CloseHandle(process);
+            queried.ok()?;
+            let path = String::from_utf16_lossy(&buffer[..length as usize]);
+            let name = std::path::Path::new(&path)
+                .file_stem()?
+                .to_string_lossy()
+                .to_lowercase();
+
+            let mut window_rect = RECT::default();
+            let mut monitor_info = MONITORINFO {
+                cbSize: size_of::<MONITORINFO>() as u32,
+                ..Default::default()
+            };
+            let is_fullscreen = GetWindowRect(hwnd, &mut window_rect).is_ok()
+                && GetMonitorInfoW(
+                    MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST),
+                    &mut monitor_info,
+                )
+                .as_bool()
+                && window_rect == monitor_info.rcMonitor;
+
+            Some(ForegroundApp {
+                name,
+                is_fullscreen,
+            })
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+struct ForegroundProbe {
+    connection: x11rb::rust_connection::RustConnection,
+    root: u32,
+    net_active_window: u32,
+    net_wm_pid: u32,
+    net_wm_state: u32,
+    net_wm_state_fullscreen: u32,
+}
+
+#[cfg(target_os = "linux")]
+impl ForegroundProbe {
+    /// only works on X11 (and XWayland windows), wayland doesn't tell clients about other windows
+    fn connect() -> Option<Self> {
+        use x11rb::{connection::Connection, protocol::xproto::ConnectionExt};
+
+        let (connection, screen_number) = x11rb::connect(None)
+            .inspect_err(|err| tracing::debug!("no X11 display to watch the foreground on: {err}"))
+            .ok()?;
+        let root = connection.setup().roots.get(screen_number)?.root;
+        let atom = |name: &str| {
+            connection
+                .intern_atom(false, name.as_bytes())
+                .ok()?
+                .reply()
+                .ok()
+                .map(|reply| reply.atom)
+        };
+        let net_active_window = atom("_NET_ACTIVE_WINDOW")?;
+        let net_wm_pid = atom("_NET_WM_PID")?;
+        let net_wm_state = atom("_NET_WM_STATE")?;
+        let net_wm_state_fullscreen = atom("_NET_WM_STATE_FULLSCREEN")?;
+        Some(Self {
+            connection,
+            root,
+            net_active_window,
+            net_wm_pid,
+            net_wm_state,
+            net_wm_state_fullscreen,
+        })
+    }
+
+    fn property(
+        &self,
+        window: u32,
+        property: u32,
+        kind: x11rb::protocol::xproto::AtomEnum,
+    ) -> Option<Vec<u32>> {
+        use x11rb::protocol::xproto::ConnectionExt;
+
+        let reply = self
+            .connection
+            .get_property(false, window, property, kind, 0, 32)
+            .ok()?
+            .reply()
+            .ok()?;
+        Some(reply.value32()?.collect())
+    }
+
+    fn foreground_app(&self) -> Option<ForegroundApp> {
+        use x11rb::protocol::xproto::AtomEnum;
+
+        let window = *self
+            .property(self.root, self.net_active_window, AtomEnum::WINDOW)?
+            .first()
+            .filter(|window| **window != 0)?;
+        let pid = *self
+            .property(window, self.net_wm_pid, AtomEnum::CARDINAL)?
+            .first()?;
+        let name = std::fs::read_to_string(format!("/proc/{pid}/comm"))
+            .ok()?
+            .trim()
+            .to_lowercase();
+        let is_fullscreen = self
+            .property(window, self.net_wm_state, AtomEnum::ATOM)
+            .is_some_and(|states| states.contains(&self.net_wm_state_fullscreen));
+        Some(ForegroundApp {
+            name,
+            is_fullscreen,
+        })
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+struct ForegroundProbe;
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+impl ForegroundProbe {
+    fn connect() -> Option<Self> {
+        None
+    }
+
+    fn foreground_app(&self) -> Option<ForegroundApp> {
+        None
+    }
+}
@@ -0,0 +1,43 @@
+use sdl3::{GamepadSubsystem, Sdl, gamepad::Gamepad, hint, sys::joystick::SDL_JoystickID};
+use tracing::{info, warn};
+
+use crate::events::{Event, EventData};
+
+/// Keeps every connected gamepad open so their input comes through the event pump as
+/// `Event::GamepadButton` and `Event::GamepadStick`, whichever window has the focus. SDL
+/// announces the ones already plugged in as `Event::GamepadAdded` right after starting, see
+/// `handle`.
+pub struct Gamepads {
+    subsystem: GamepadSubsystem,
+    open: Vec<(u32, Gamepad)>,
+}
+
+impl Gamepads {
+    pub fn start(sdl: &Sdl) -> anyhow::Result<Self> {
+        // the gremlin's window never takes the focus, without this SDL would drop every press
+        hint::set("SDL_JOYSTICK_ALLOW_BACKGROUND_EVENTS", "1");
+        Ok(Self {
+            subsystem: sdl.gamepad()?,
+            open: Vec::new(),
+        })
+    }
+
+    /// opens the gamepads plugged in and lets go of the ones pulled out among `events`
+    pub fn handle(&mut self, events: &[(Event, Option<EventData>)]) {
+        for (event, _) in events {
+            match event {
+                Event::GamepadAdded(id) if !self.open.iter().any(|(open, _)| open == id) => {
+                    match self.subsystem.open(SDL_JoystickID(*id)) {
+                        Ok(gamepad) => {
+                            info!("gamepad connected: {}", gamepad.name().unwrap_or_default());
+                            self.open.push((*id, gamepad));
+                        }
+                        Err(err) => warn!("couldn't open gamepad {id}: {err}"),
+                    }
+                }
+                Event::GamepadRemoved(id) => self.open.retain(|(open, _)| open != id),
+                _ => {}
+            }
+        }
+    }
+}
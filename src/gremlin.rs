@@ -1,35 +1,49 @@
 use std::{
-    collections::{HashMap, LinkedList, VecDeque},
-    env,
+    any::Any,
+    collections::{HashMap, HashSet, LinkedList, VecDeque},
+    env, fmt,
     fs::{self},
     io,
     path::{Path, PathBuf},
     str::FromStr,
     sync::{
         Arc, Mutex,
+        atomic::{AtomicBool, AtomicU32, Ordering},
         mpsc::{self, Receiver, Sender},
     },
+    time::{Duration, Instant},
 };
 
 use anyhow::Result;
-use image::{DynamicImage, EncodableLayout};
+use chrono::{Local, Timelike};
+use image::{ColorType, DynamicImage, EncodableLayout, ImageError};
+use rand::{
+    Rng, SeedableRng,
+    distr::{Distribution, weighted::WeightedIndex},
+    rngs::StdRng,
+};
 // absolutely goated.
 use sdl3::{
     // might move to winit & wgpu but,... ehhhhhhhhh too lazy.... i love sdl
     Sdl,
     pixels::PixelFormat,
     rect::Rect,
-    render::{Canvas, Texture, TextureCreator},
+    render::{Texture, TextureCreator},
     sys::{
         properties::SDL_GetPointerProperty,
         video::{SDL_GetWindowProperties, SDL_PROP_WINDOW_WIN32_HWND_POINTER},
     },
     video::{Window, WindowBuilder, WindowContext, WindowFlags},
 };
+use tracing::warn;
 
 #[cfg(target_os = "windows")]
 use windows::Win32::{
     Foundation::{COLORREF, HWND},
+    Graphics::{
+        Dwm::{DWM_BB_BLURREGION, DWM_BB_ENABLE, DWM_BLURBEHIND, DwmEnableBlurBehindWindow},
+        Gdi::{CreateRectRgn, DeleteObject},
+    },
     UI::WindowsAndMessaging::{
         GWL_EXSTYLE, GetWindowLongW, LWA_COLORKEY, SetLayeredWindowAttributes, SetWindowLongW,
         WS_EX_LAYERED,
@@ -38,7 +52,22 @@ use windows::Win32::{
 
 pub const GLOBAL_PIXEL_FORMAT: PixelFormat = PixelFormat::RGBA32;
 
-use crate::utils::get_png_list;
+use crate::{
+    achievements::{self, Achievements},
+    audio::GremlinAudio,
+    backend::{BackendCall, HeadlessBackend, SdlBackend, WindowBackend, has_compositor},
+    behavior::{ClickAction, MovementMode},
+    events::{Event, EventData, GamepadButton, MouseButton},
+    foreground::ForegroundApp,
+    hotkeys::Hotkeys,
+    pack,
+    profile::Profile,
+    scheduler::{ScheduleId, ScheduleRule, ScheduledAction, Scheduler},
+    stats::PetStats,
+    tree::BehaviorTree,
+    utility::UtilityCandidate,
+    utils::{TextureCache, get_png_list, is_hour_between},
+};
 
 #[derive(Debug, Clone)]
 pub struct SpriteSheet {
@@ -121,7 +150,16 @@ pub enum SpriteError {
 pub struct AnimationProperties {
     pub animation_name: String,
     pub sprite_path: Option<PathBuf>,
+    // a sheet built into the binary, used over `sprite_path`, see `Gremlin::fallback`
+    pub sprite_bytes: Option<&'static [u8]>,
     pub sprite_count: u32,
+    // how long each frame stays up, frames past the end of the table use the last entry.
+    // empty means every frame lasts a single `FRAME_TICK`
+    pub frame_durations: Vec<Duration>,
+    // the gremlin's `.colorkey`, pixels this color are cut out of the sheet
+    pub colorkey: Option<[u8; 3]>,
+    // what kind of animation it is from `[tags]`, e.g. "happy" or "reaction", lowercase
+    pub tags: Vec<String>,
 }
 
 impl AnimationProperties {
@@ -130,8 +168,47 @@ impl AnimationProperties {
             animation_name: name,
             sprite_count,
             sprite_path: None,
+            sprite_bytes: None,
+            frame_durations: Vec::new(),
+            colorkey: None,
+            tags: Vec::new(),
+        }
+    }
+
+    /// plays the sheet built into the binary, for when there's nothing on disk to play
+    pub fn fallback(name: String) -> AnimationProperties {
+        Self {
+            sprite_bytes: Some(FALLBACK_SHEET),
+            ..Self::new(name, FALLBACK_FRAME_COUNT)
         }
     }
+
+    pub fn frame_duration(&self, frame: u32) -> Option<Duration> {
+        self.frame_durations
+            .get(frame as usize)
+            .or(self.frame_durations.last())
+            .copied()
+    }
+}
+
+/// frame durations from the Aseprite json export of a sheet, works with both the array and hash layouts
+fn aseprite_frame_durations(json_path: &Path) -> Vec<Duration> {
+    let Some(export) = fs::read_to_string(json_path)
+        .ok()
+        .and_then(|json| serde_json::from_str::<serde_json::Value>(&json).ok())
+    else {
+        return Vec::new();
+    };
+    let frames: Vec<&serde_json::Value> = match &export["frames"] {
+        serde_json::Value::Array(frames) => frames.iter().collect(),
+        serde_json::Value::Object(frames) => frames.values().collect(),
+        _ => return Vec::new(),
+    };
+    frames
+        .iter()
+        .filter_map(|frame| frame["duration"].as_u64())
+        .map(Duration::from_millis)
+        .collect()
 }
 
 impl Animation {
@@ -150,22 +227,60 @@ impl TryInto<Animation> for &AnimationProperties {
     type Error = GremlinLoadError;
 
     fn try_into(self) -> std::result::Result<Animation, Self::Error> {
-        if let Some(path) = &self.sprite_path
-            && let Ok(image) = image::open(path)
-        {
-            let sprite_sheet = SpriteSheet {
-                column_count: 10,
-                frame_count: self.sprite_count as u16,
-                image,
-                filter: Default::default(),
-            };
-            return std::result::Result::Ok(Animation {
-                sprite_sheet,
-                current_frame: 0,
-                properties: self.clone(),
+        let animation = self.animation_name.clone();
+        let image = match (self.sprite_bytes, &self.sprite_path) {
+            (Some(bytes), _) => image::load_from_memory(bytes),
+            (None, Some(path)) => image::open(path),
+            (None, None) => return Err(GremlinLoadError::MissingSheet { animation }),
+        }
+        .map_err(|source| match source {
+            ImageError::IoError(err) => GremlinLoadError::Io(err),
+            source => GremlinLoadError::Image {
+                animation: animation.clone(),
+                source,
+            },
+        })?;
+        let image = match self.colorkey {
+            Some(colorkey) if matches!(image.color(), ColorType::Rgb8 | ColorType::Rgba8) => {
+                let mut image = image.into_rgba8();
+                for pixel in image.pixels_mut() {
+                    if pixel.0[..3] == colorkey {
+                        pixel.0 = [0; 4];
+                    }
+                }
+                DynamicImage::ImageRgba8(image)
+            }
+            _ => image,
+        };
+
+        // img_get_bytes_global hands the raw buffer straight to sdl
+        if image.color() != ColorType::Rgba8 {
+            return Err(GremlinLoadError::UnsupportedPixelFormat {
+                animation,
+                color_type: image.color(),
             });
         }
-        Err(GremlinLoadError::FsError(None))
+        let rows = self.sprite_count.div_ceil(DEFAULT_COLUMN_COUNT).max(1);
+        if image.width() % DEFAULT_COLUMN_COUNT != 0 || image.height() % rows != 0 {
+            return Err(GremlinLoadError::FrameCountMismatch {
+                animation,
+                frame_count: self.sprite_count,
+                width: image.width(),
+                height: image.height(),
+            });
+        }
+
+        let sprite_sheet = SpriteSheet {
+            column_count: 10,
+            frame_count: self.sprite_count as u16,
+            image,
+            filter: Default::default(),
+        };
+        Ok(Animation {
+            sprite_sheet,
+            current_frame: 0,
+            properties: self.clone(),
+        })
     }
 }
 
@@ -174,19 +289,564 @@ pub struct Gremlin {
     pub name: String,
     // map between animation name and directory
     pub animation_map: HashMap<String, AnimationProperties>,
-    pub metadata: HashMap<String, String>,
+    pub metadata: GremlinMetadata,
+    // map between animation name and the sound played when it starts
+    pub sounds: HashMap<String, PathBuf>,
+    // logical animation name used by behaviors -> name of the sheet in this pack
+    pub aliases: HashMap<String, String>,
+    // logical animation name -> (from hour, to hour, sheet) from `[aliases]`'s `NAME@period`
+    // lines, played over `aliases` during those hours, the first that fits wins
+    pub timed_aliases: HashMap<String, Vec<(u32, u32, String)>>,
+    // logical animation name -> (animation, weight) from `[variants]`, one picked at random each
+    // time it's played, see `resolve_variant`
+    pub variants: HashMap<String, Vec<(String, u32)>>,
+    // animations to try, in order, when the gremlin doesn't have the one asked for
+    pub fallbacks: HashMap<String, Vec<String>>,
+    // per-animation playback speed from `[speeds]`, on top of `DesktopGremlin::speed`
+    pub speeds: HashMap<String, f32>,
+    // how far one loop of a walk carries the gremlin from `[strides]`, in the frame's own pixels,
+    // see `stride_speed`
+    pub strides: HashMap<String, f32>,
+    // gamepad button -> animation it plays from `[gamepad]`, see `GremlinGamepad`
+    pub gamepad: HashMap<GamepadButton, String>,
+    // mouse button -> what clicking with it does from `[clicks]`, over `GremlinClick`'s own
+    pub clicks: HashMap<MouseButton, ClickAction>,
+    // native frame size from `.width`/`.height`, otherwise measured off the sheets
+    pub frame_width: Option<u32>,
+    pub frame_height: Option<u32>,
+    // `.scaling`, how its sheets get blown up to the window's size
+    pub scaling: Scaling,
+    // `.trail`, how many earlier frames fade out behind the gremlin while it moves fast, off when 0,
+    // at most `MAX_TRAIL`
+    pub trail: usize,
+    // how it goes about things from `[personality]`, see `Personality`
+    pub personality: Personality,
+    // what it decides to do on its own from `[tree]`, see `BehaviorTree`
+    pub tree: Option<BehaviorTree>,
+    // what it might do on its own from `[utility]`, see `UtilityCandidate`
+    pub utility: Vec<UtilityCandidate>,
+    // the gremlin's daily routine from `[schedule]`, animations to play and when
+    pub schedule: Vec<(ScheduleRule, String)>,
+    // sheets from `[layers]` drawn over the body, in order
+    pub layers: Vec<Layer>,
+    // what it can wear from `[accessories]`, drawn in this order when worn
+    pub accessories: Vec<Accessory>,
+    // animation or accessory -> the id of the `Achievement` that unlocks it, from `[unlocks]`
+    pub unlocks: HashMap<String, String>,
+    // what of `unlocks` isn't earned yet, all of it until `GremlinAchievements` says otherwise
+    pub locked: HashSet<String>,
     pub animator: Option<Animator>,
 }
 
+/// The manifest's `.key=value` lines that say something about the gremlin as a whole rather than
+/// how it's laid out, checked while loading it. Keys it doesn't know end up in `extra` for
+/// behaviors to pick up, see `get`.
+#[derive(Debug, Clone, Default)]
+pub struct GremlinMetadata {
+    // `.scale`, how much bigger than its frames this gremlin looks right at
+    pub scale: Option<f32>,
+    // `.fps`, the framerate the sheets were drawn for, `DEFAULT_FRAMERATE` without one
+    pub fps: Option<u32>,
+    // `.colorkey`, `r,g,b` or `#rrggbb`, the background color of sheets without transparency
+    pub colorkey: Option<[u8; 3]>,
+    pub author: Option<String>,
+    pub version: Option<String>,
+    // `.default`, what it plays when there's nothing else to do, IDLE without one
+    pub default_animation: Option<String>,
+    // every other `.key`, without the dot
+    pub extra: HashMap<String, String>,
+}
+
+impl GremlinMetadata {
+    /// the value of a `.key` this doesn't know about, `key` without the dot
+    #[allow(unused)]
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.extra.get(key).map(String::as_str)
+    }
+
+    /// how much faster than the default framerate its animations play
+    pub fn speed(&self) -> f32 {
+        self.fps
+            .map_or(1.0, |fps| fps as f32 / DEFAULT_FRAMERATE as f32)
+    }
+
+    pub fn default_animation(&self) -> &str {
+        self.default_animation.as_deref().unwrap_or(IDLE_ANIMATION)
+    }
+
+    /// `1.2 by someone`, what's known of it
+    pub fn byline(&self) -> Option<String> {
+        match (&self.version, &self.author) {
+            (Some(version), Some(author)) => Some(format!("{version} by {author}")),
+            (Some(version), None) => Some(version.clone()),
+            (None, Some(author)) => Some(format!("by {author}")),
+            (None, None) => None,
+        }
+    }
+}
+
+/// Leanings from the manifest's `[personality]`, each between 0 and 1 and 0.5 when left out,
+/// which leaves the behaviors as they are. Behaviors scale what they do by these instead of
+/// every gremlin needing its own code, e.g. `curiosity=0.9` and `laziness=0.1` for an eager one.
+#[derive(Debug, Clone, Copy)]
+pub struct Personality {
+    // comes closer and wanders off into flavor animations and random events more
+    pub curiosity: f32,
+    // chases slower and does less while idling
+    pub laziness: f32,
+    // chases faster and lets the cursor closer before fleeing
+    pub clinginess: f32,
+}
+
+impl Default for Personality {
+    fn default() -> Self {
+        Self {
+            curiosity: 0.5,
+            laziness: 0.5,
+            clinginess: 0.5,
+        }
+    }
+}
+
+impl Personality {
+    // none of them ever stops a behavior altogether
+    const MIN_FACTOR: f32 = 0.25;
+
+    /// how much faster than usual it chases the cursor
+    pub fn follow_speed(&self) -> f32 {
+        (1.0 + self.clinginess - self.laziness).max(Self::MIN_FACTOR)
+    }
+
+    /// how much longer than usual it waits before doing something on its own while idling
+    pub fn idle_patience(&self) -> f32 {
+        (1.0 + self.laziness - self.curiosity).max(Self::MIN_FACTOR)
+    }
+
+    /// how much further than usual the cursor has to stay to not scare it off
+    pub fn flee_radius(&self) -> f32 {
+        (2.0 - self.clinginess - self.curiosity).max(Self::MIN_FACTOR)
+    }
+}
+
+/// what gremlins play while they've got nothing else to do, unless their manifest says otherwise
+pub const IDLE_ANIMATION: &str = "IDLE";
+
+// the times of day `[aliases]` understands, (name, from hour, to hour)
+const PERIODS: &[(&str, u32, u32)] = &[
+    ("night", 22, 6),
+    ("morning", 6, 12),
+    ("day", 6, 18),
+    ("afternoon", 12, 18),
+    ("evening", 18, 22),
+];
+
+/// a named time of day from `PERIODS` or hours like `22-6`
+fn parse_period(period: &str) -> Option<(u32, u32)> {
+    let period = period.trim().to_lowercase();
+    if let Some((_, from, to)) = PERIODS.iter().find(|(name, _, _)| *name == period) {
+        return Some((*from, *to));
+    }
+    let (from, to) = period.split_once('-')?;
+    let (from, to) = (from.trim().parse().ok()?, to.trim().parse().ok()?);
+    (from < 24 && to <= 24).then_some((from, to))
+}
+
+fn parse_colorkey(value: &str) -> Option<[u8; 3]> {
+    if let Some(hex) = value.strip_prefix('#') {
+        let rgb = u32::from_str_radix(hex, 16)
+            .ok()
+            .filter(|_| hex.len() == 6)?;
+        return Some([(rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8]);
+    }
+    let channels = value
+        .split(',')
+        .map(|channel| channel.trim().parse::<u8>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    channels.try_into().ok()
+}
+
+/// `.width`/`.height`, a window can't be less than a pixel across
+fn parse_frame_size(value: &str) -> Option<u32> {
+    value.trim().parse().ok().filter(|size| *size >= 1)
+}
+
+/// A smaller sheet drawn over the body's frame, e.g. eyes. Its frames are the same size as the
+/// body's, transparent wherever the layer has nothing to add.
+/// Written in `[layers]` as `NAME=frame count,mode` and optionally the animations it shows over
+/// after that, e.g. `EYES=9,look,IDLE,SIT`. Without any it shows over every animation.
+#[derive(Debug, Clone)]
+pub struct Layer {
+    pub properties: AnimationProperties,
+    pub mode: LayerMode,
+    pub animations: Vec<String>,
+}
+
+impl Layer {
+    pub fn shows_over(&self, animation: &str) -> bool {
+        self.animations.is_empty() || self.animations.iter().any(|name| name == animation)
+    }
+}
+
+/// A hat, scarf and the like, drawn over the body while it's worn, see `DesktopGremlin::accessories`.
+/// `[accessories]` has `NAME=path` lines with its image, relative to the manifest. `[anchors]` has
+/// `NAME.ANIMATION=x,y` lines saying where the image's middle goes in that animation's frames,
+/// in the frame's own pixels, and `NAME=x,y` for the animations without one.
+/// It's left off the animations with no anchor at all.
+#[derive(Debug, Clone, Default)]
+pub struct Accessory {
+    pub name: String,
+    pub sprite_path: PathBuf,
+    // animation -> anchor, "" for every other animation
+    pub anchors: HashMap<String, (i32, i32)>,
+}
+
+impl Accessory {
+    pub fn anchor(&self, animation: &str) -> Option<(i32, i32)> {
+        self.anchors
+            .get(animation)
+            .or_else(|| self.anchors.get(""))
+            .copied()
+    }
+}
+
+/// How a layer picks which of its frames to show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayerMode {
+    /// follows the cursor: frame 0 while it's over the gremlin, the rest are directions going
+    /// clockwise from right, e.g. right, down, left, up for 5 frames
+    Look,
+}
+
+impl FromStr for LayerMode {
+    type Err = ();
+
+    fn from_str(mode: &str) -> std::result::Result<Self, Self::Err> {
+        match mode.to_lowercase().as_str() {
+            "look" => Ok(LayerMode::Look),
+            _ => Err(()),
+        }
+    }
+}
+
+/// a little blob that bobs and blinks, shown when a gremlin can't be loaded or is missing a sheet
+/// so there's always something on screen to tell the user about it
+const FALLBACK_SHEET: &[u8] = include_bytes!("../assets/fallback.png");
+const FALLBACK_FRAME_COUNT: u32 = 10;
+const FALLBACK_FRAME_SIZE: u32 = 48;
+// `.trail` past this is malformed, each ghost is another copy of the frame drawn every frame
+const MAX_TRAIL: usize = 16;
+
+/// How a gremlin's sheets are scaled up to the window's size.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Scaling {
+    /// filtered, for drawn or rendered sprites
+    #[default]
+    Smooth,
+    /// nearest neighbor at whole multiples of the frame size, so pixel art stays crisp
+    Pixel,
+}
+
+impl FromStr for Scaling {
+    type Err = ();
+
+    fn from_str(scaling: &str) -> std::result::Result<Self, Self::Err> {
+        match scaling.to_lowercase().as_str() {
+            "smooth" | "linear" => Ok(Scaling::Smooth),
+            "pixel" | "nearest" => Ok(Scaling::Pixel),
+            _ => Err(()),
+        }
+    }
+}
+
+/// used when the manifest doesn't say otherwise
+const DEFAULT_FALLBACKS: &[(&str, &[&str])] = &[
+    ("UPLEFT", &["RUNLEFT", "RUNUP"]),
+    ("UPRIGHT", &["RUNRIGHT", "RUNUP"]),
+    ("DOWNLEFT", &["RUNLEFT", "RUNDOWN"]),
+    ("DOWNRIGHT", &["RUNRIGHT", "RUNDOWN"]),
+    ("RUNLEFT", &["WALKLEFT", "RUN"]),
+    ("RUNRIGHT", &["WALKRIGHT", "RUN"]),
+    ("RUNUP", &["WALKUP", "RUN"]),
+    ("RUNDOWN", &["WALKDOWN", "RUN"]),
+    ("RUN", &["WALK", "IDLE"]),
+    ("RUNIDLE", &["IDLE"]),
+    ("PAT", &["CLICK", "IDLE"]),
+    ("CLICK", &["IDLE"]),
+    ("GRAB", &["HOVER", "IDLE"]),
+    ("HOVER", &["IDLE"]),
+];
+const MAX_FALLBACK_DEPTH: usize = 8;
+
+impl Gremlin {
+    /// The built-in gremlin, used when no gremlin on disk loads. Its one sheet stands in for
+    /// everything `CommonBehavior` plays, OUTRO included so quitting still works.
+    pub fn fallback() -> Gremlin {
+        Gremlin {
+            name: "fallback".to_string(),
+            animation_map: ["IDLE", "INTRO", "OUTRO"]
+                .into_iter()
+                .map(|name| {
+                    (
+                        name.to_string(),
+                        AnimationProperties::fallback(name.to_string()),
+                    )
+                })
+                .collect(),
+            frame_width: Some(FALLBACK_FRAME_SIZE),
+            frame_height: Some(FALLBACK_FRAME_SIZE),
+            metadata: GremlinMetadata {
+                scale: Some(2.0),
+                ..Default::default()
+            },
+            scaling: Scaling::Pixel,
+            ..Default::default()
+        }
+    }
+
+    /// Size of one frame going by IDLE's sheet (or any sheet, without an IDLE),
+    /// only reads the image header.
+    fn sheet_frame_size(&self) -> Option<(u32, u32)> {
+        let properties = self
+            .animation_map
+            .get("IDLE")
+            .filter(|properties| properties.sprite_path.is_some())
+            .or_else(|| {
+                self.animation_map
+                    .values()
+                    .find(|properties| properties.sprite_path.is_some())
+            })?;
+        let (width, height) = image::image_dimensions(properties.sprite_path.as_ref()?)
+            .inspect_err(|err| warn!("couldn't read {}: {err}", properties.animation_name))
+            .ok()?;
+        let rows = properties
+            .sprite_count
+            .div_ceil(DEFAULT_COLUMN_COUNT)
+            .max(1);
+        Some((width / DEFAULT_COLUMN_COUNT, height / rows))
+            .filter(|(width, height)| *width > 0 && *height > 0)
+    }
+
+    /// The sheet that actually gets played for `name`: itself (or its alias) when the gremlin has it, otherwise
+    /// the first fallback that resolves, manifest `[fallbacks]` first and then `DEFAULT_FALLBACKS`.
+    pub fn resolve_animation(&self, name: &str) -> Option<String> {
+        self.resolve_animation_at(name, 0)
+    }
+
+    /// the sheet name behind a logical name, per the manifest's `[aliases]`
+    pub fn alias<'a>(&'a self, name: &'a str) -> &'a str {
+        self.aliases.get(name).map(String::as_str).unwrap_or(name)
+    }
+
+    /// whether the gremlin has a sheet for `name` itself, aliases and variants included but no
+    /// fallbacks
+    pub fn has_animation(&self, name: &str) -> bool {
+        self.has_sheet(self.alias(name))
+            || self.variants.get(name).is_some_and(|variants| {
+                variants
+                    .iter()
+                    .any(|(variant, _)| self.has_sheet(self.alias(variant)))
+            })
+    }
+
+    /// how much faster than a frame per `FRAME_TICK` the `name` animation plays, with `sheet`
+    /// standing in for it, `[speeds]` and `.fps` but not `DesktopGremlin::speed`
+    pub fn playback_speed(&self, name: &str, sheet: &str) -> f32 {
+        self.speeds
+            .get(name)
+            .or_else(|| self.speeds.get(sheet))
+            .copied()
+            .unwrap_or(1.0)
+            * self.metadata.speed()
+    }
+
+    /// How fast the window has to move while `animation` plays, in screen pixels a second, for
+    /// its feet to keep up with the ground per `[strides]`, `None` when it has no stride.
+    /// `window_width` is what the frame is blown up to and `speed` is `DesktopGremlin::speed`.
+    pub fn stride_speed(&self, animation: &str, window_width: u32, speed: f32) -> Option<f32> {
+        let sheet = self.resolve_animation(animation)?;
+        let stride = self
+            .strides
+            .get(animation)
+            .or_else(|| self.strides.get(&sheet))?;
+        let properties = self.animation_map.get(&sheet)?;
+        let cycle = (0..properties.sprite_count.max(1))
+            .map(|frame| properties.frame_duration(frame).unwrap_or(FRAME_TICK))
+            .sum::<Duration>()
+            .as_secs_f32()
+            / (speed * self.playback_speed(animation, &sheet));
+        let frame_width = self.frame_width.filter(|width| *width > 0)?;
+        (cycle.is_finite() && cycle > 0.0)
+            .then(|| stride * window_width as f32 / frame_width as f32 / cycle)
+    }
+
+    /// The animations tagged `tag` in `[tags]` it can play, sorted so a seeded pick between them
+    /// comes out the same, e.g. `animations_with_tag("reaction")` for something to react with.
+    pub fn animations_with_tag(&self, tag: &str) -> Vec<&str> {
+        let mut names: Vec<&str> = self
+            .animation_map
+            .iter()
+            .filter(|(name, properties)| {
+                properties
+                    .tags
+                    .iter()
+                    .any(|own| own.eq_ignore_ascii_case(tag))
+                    && self.has_sheet(name)
+            })
+            .map(|(name, _)| name.as_str())
+            .collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Like `resolve_animation`, but when `name` has `[variants]` one of those the gremlin has
+    /// is picked by weight instead, so playing it twice doesn't have to look the same.
+    pub fn resolve_variant(&self, name: &str, rng: &mut impl Rng) -> Option<String> {
+        let candidates: Vec<&(String, u32)> = self
+            .variants
+            .get(name)
+            .into_iter()
+            .flatten()
+            .filter(|(variant, weight)| *weight > 0 && self.has_sheet(self.alias(variant)))
+            .collect();
+        match WeightedIndex::new(candidates.iter().map(|(_, weight)| *weight)) {
+            Ok(distribution) => self.resolve_animation(&candidates[distribution.sample(rng)].0),
+            Err(_) => self.resolve_animation(name),
+        }
+    }
+
+    /// locked sheets are as good as missing until they're unlocked
+    fn has_sheet(&self, sheet_name: &str) -> bool {
+        self.animation_map.contains_key(sheet_name) && !self.locked.contains(sheet_name)
+    }
+
+    /// the sheet `[aliases]` has for `name` at this time of day, if it has one
+    fn timed_alias(&self, name: &str) -> Option<&str> {
+        let hour = Local::now().hour();
+        self.timed_aliases
+            .get(name)?
+            .iter()
+            .find(|(from, to, sheet)| is_hour_between(hour, *from, *to) && self.has_sheet(sheet))
+            .map(|(_, _, sheet)| sheet.as_str())
+    }
+
+    fn resolve_animation_at(&self, name: &str, depth: usize) -> Option<String> {
+        let sheet_name = self.timed_alias(name).unwrap_or_else(|| self.alias(name));
+        if self.has_sheet(sheet_name) {
+            return Some(sheet_name.to_string());
+        }
+        // also keeps a cycle like A=B, B=A from recursing forever
+        if depth >= MAX_FALLBACK_DEPTH {
+            return None;
+        }
+        // the first variant stands in when there's no picking, e.g. for the size of the window
+        if let Some((variant, _)) = self.variants.get(name).and_then(|variants| {
+            variants
+                .iter()
+                .find(|(variant, _)| self.has_sheet(self.alias(variant)))
+        }) {
+            return self.resolve_animation_at(variant, depth + 1);
+        }
+        let defaults = DEFAULT_FALLBACKS
+            .iter()
+            .find(|(animation, _)| *animation == name)
+            .map(|(_, fallbacks)| {
+                fallbacks
+                    .iter()
+                    .map(|fallback| fallback.to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let fallbacks: &Vec<String> = self.fallbacks.get(name).unwrap_or(&defaults);
+        fallbacks
+            .iter()
+            .find_map(|fallback| self.resolve_animation_at(fallback, depth + 1))
+    }
+}
+
 pub struct DesktopGremlin {
-    pub sdl: Sdl,
+    // `None` when running headless
+    pub sdl: Option<Sdl>,
     pub current_gremlin: Option<Gremlin>,
-    pub canvas: Canvas<Window>,
+    pub window: Box<dyn WindowBackend>,
     pub should_exit: Arc<Mutex<bool>>,
-    // pub texture_cache: Arc<Mutex<TextureCache<'a>>>,
+    // sheets already uploaded to the gpu, shared by every behavior that draws so nothing gets uploaded twice
+    pub texture_cache: Arc<Mutex<TextureCache>>,
     pub task_queue: VecDeque<GremlinTask>,
+    // what may interrupt what and which queued task goes first, applied by `GremlinRender`
+    pub interrupt_policy: InterruptPolicy,
     pub task_channel: (Sender<GremlinTask>, Receiver<GremlinTask>),
     pub should_check_for_action: bool,
+    pub movement_mode: MovementMode,
+    // whether `GremlinMovement` chases the cursor, flipped by clicking the gremlin or by a
+    // `[tree]` deciding to follow, see `GremlinTask::Chase`
+    pub is_chasing: bool,
+    pub stats: PetStats,
+    pub audio: Option<GremlinAudio>,
+    pub speech_queue: VecDeque<Speech>,
+    pub notifications: VecDeque<Notification>,
+    // things the user said to the gremlin, answered by `GremlinChat` when built with the `chat` feature
+    pub chat_prompts: VecDeque<String>,
+    // window size at scale 1.0
+    pub base_size: (u32, u32),
+    // window size it launched with, what `fit_window_to_gremlin` fills in a manifest's gaps from
+    launch_size: (u32, u32),
+    pub scale: f32,
+    // manifest of the gremlin to load at startup, restored from the saved state
+    pub gremlin_path: Option<String>,
+    // names of behaviors the runtime skips updating
+    pub disabled_behaviors: HashSet<String>,
+    // shared with the runtime, see `DGRuntime::pause()`
+    pub is_paused: Arc<AtomicBool>,
+    // frames per second while anything's going on, see `idle_for`
+    pub framerate: Arc<AtomicU32>,
+    // the framerate the user picked, `framerate` only drops below it to save power
+    pub target_framerate: u32,
+    // how many frame intervals the current frame stands for, more than one after the loop idled,
+    // handed out by the runtime's `FrameClock`
+    pub frame_ticks: u32,
+    // the soonest a behavior idling wants the next frame, see `idle_for`
+    pub wake_at: Option<Instant>,
+    // playback speed of every animation, see `GremlinTask::SetSpeed`
+    pub speed: f32,
+    // set by `GremlinTask::Goto`, picked up by `GremlinMovement`
+    pub goto_target: Option<(i32, i32)>,
+    // dropped this close to a screen edge the gremlin slides onto it, see `GremlinDrag`. `None`
+    // never snaps
+    pub snap_distance: Option<u32>,
+    // (gremlin, offset) from `GremlinTask::Spawn`, picked up by `GremlinClones`
+    pub spawn_requests: VecDeque<(Option<String>, (i32, i32))>,
+    // strokes in screen coordinates waiting to go on the overlay, picked up by `GremlinInk`
+    pub ink: VecDeque<Vec<(i32, i32)>>,
+    // names of the `Gremlin::accessories` being worn, kept across gremlins that have the same ones
+    pub accessories: Vec<String>,
+    pub achievements: Achievements,
+    // events behaviors raised this frame, everyone sees them next frame
+    pub emitted_events: Vec<(Event, Option<EventData>)>,
+    // behaviors take their randomness from here so a run can be replayed with `--seed`
+    pub rng: StdRng,
+    pub seed: u64,
+    // filled in by the runtime
+    pub profile: Profile,
+    // fired by the runtime every frame, see `schedule_every`/`schedule_at`
+    pub scheduler: Scheduler,
+    // the app the user is in, kept up to date by the runtime which also emits `foreground_changed`
+    pub foreground_app: Option<ForegroundApp>,
+    // hotkeys behaviors asked for, see `register_hotkey`
+    pub hotkeys: Hotkeys,
+    // what `schedule_routine` put on the scheduler for the current gremlin
+    routine: Vec<ScheduleId>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Speech {
+    pub text: String,
+    pub duration: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub title: String,
+    pub body: String,
 }
 
 pub struct LaunchArguments {
@@ -194,9 +854,33 @@ pub struct LaunchArguments {
     pub h: u32,
     pub title: String,
     pub window_flags: Vec<WindowFlags>,
+    // picked at random when not given
+    pub seed: Option<u64>,
+    // runs next to the gremlin holding the instance lock instead of handing over to it,
+    // so it stays off the command socket and doesn't touch the saved state
+    pub is_companion: bool,
+    // manifest or pack to show instead of the saved one, e.g. a visiting gremlin
+    pub gremlin_path: Option<String>,
+    pub scale: Option<f32>,
 }
 
-pub const GLOBAL_FRAMERATE: u32 = 48;
+/// how many folders deep under the manifest sheets are looked for
+pub const SHEET_SEARCH_DEPTH: u16 = 5;
+// until the user picks another, see `DesktopGremlin::set_target_framerate`
+pub const DEFAULT_FRAMERATE: u32 = 48;
+pub const MIN_FRAMERATE: u32 = 5;
+pub const MAX_FRAMERATE: u32 = 240;
+// the longest the main loop sleeps while idle, commands and hotkeys wait at most this long
+pub const MAX_IDLE_WAIT: Duration = Duration::from_millis(250);
+// how long a frame without a duration stays up at speed 1.0, what sheets are drawn for no matter
+// the framerate
+pub const FRAME_TICK: Duration = Duration::from_nanos(1_000_000_000 / DEFAULT_FRAMERATE as u64);
+pub const MAX_SPEED: f32 = 16.0;
+// slower than this is as good as stopped, and a stopped OUTRO never lets the gremlin quit
+pub const MIN_SPEED: f32 = 0.05;
+pub const MIN_SCALE: f32 = 0.25;
+pub const MAX_SCALE: f32 = 8.0;
+const NO_TRANSPARENCY_MESSAGE_DURATION: Duration = Duration::from_secs(10);
 
 impl LaunchArguments {
     pub fn _parse_from_args(args: env::Args) {
@@ -238,11 +922,27 @@ impl Default for LaunchArguments {
                 WindowFlags::NOT_FOCUSABLE,
                 WindowFlags::BORDERLESS,
             ],
+            seed: None,
+            is_companion: false,
+            gremlin_path: None,
+            scale: None,
         }
     }
 }
 impl LaunchArguments {
-    fn window_flags(&self) -> u32 {
+    fn is_transparent(&self) -> bool {
+        self.window_flags.contains(&WindowFlags::TRANSPARENT)
+    }
+
+    /// opaque from here on, the colorkey on windows and `x11_shape` on linux still hide the black
+    /// background
+    fn drop_transparency(&mut self) {
+        warn!("the gremlin's window won't be see-through");
+        self.window_flags
+            .retain(|flag| *flag != WindowFlags::TRANSPARENT);
+    }
+
+    pub fn window_flags(&self) -> u32 {
         if self.window_flags.len() == 0 {
             return 0;
         }
@@ -254,85 +954,842 @@ impl LaunchArguments {
     }
 }
 
+/// Windows only cuts the gremlin out of its window when asked to, everywhere else
+/// `WindowFlags::TRANSPARENT` is enough.
+#[allow(unused)]
+pub fn see_through(window: &Window, is_transparent: bool) {
+    #[cfg(target_os = "windows")]
+    unsafe {
+        let sdl_props = SDL_GetWindowProperties(window.raw());
+        let hwnd = SDL_GetPointerProperty(
+            sdl_props,
+            SDL_PROP_WINDOW_WIN32_HWND_POINTER,
+            std::ptr::null_mut(),
+        );
+
+        let hwnd = HWND(hwnd);
+
+        // blur behind an empty region has dwm blend the frame's own alpha, soft edges and shadows
+        // included. the colorkey is only for when that's not on offer, it cuts everything hard
+        let is_per_pixel = is_transparent && {
+            let region = CreateRectRgn(0, 0, -1, -1);
+            let blur_behind = DWM_BLURBEHIND {
+                dwFlags: DWM_BB_ENABLE | DWM_BB_BLURREGION,
+                fEnable: true.into(),
+                hRgnBlur: region,
+                fTransitionOnMaximized: false.into(),
+            };
+            let result = DwmEnableBlurBehindWindow(hwnd, &blur_behind);
+            let _ = DeleteObject(region.into());
+            result
+                .inspect_err(|err| warn!("no per-pixel alpha, falling back to a colorkey: {err}"))
+                .is_ok()
+        };
+        if !is_per_pixel {
+            let ex_style = GetWindowLongW(hwnd, GWL_EXSTYLE);
+
+            SetWindowLongW(hwnd, GWL_EXSTYLE, ex_style | (WS_EX_LAYERED.0 as i32));
+
+            let _ = SetLayeredWindowAttributes(hwnd, COLORREF(0x00000000), 255, LWA_COLORKEY);
+        }
+    }
+}
+
+/// Lets clicks fall through `window` to whatever is under it, for overlays that only show things.
+/// Windows and X11 only, elsewhere the window keeps taking clicks.
+#[allow(unused)]
+pub fn click_through(window: &Window, is_click_through: bool) {
+    #[cfg(target_os = "windows")]
+    unsafe {
+        use windows::Win32::UI::WindowsAndMessaging::{
+            GetLayeredWindowAttributes, LAYERED_WINDOW_ATTRIBUTES_FLAGS, LWA_ALPHA,
+            WS_EX_TRANSPARENT,
+        };
+
+        let sdl_props = SDL_GetWindowProperties(window.raw());
+        let hwnd = HWND(SDL_GetPointerProperty(
+            sdl_props,
+            SDL_PROP_WINDOW_WIN32_HWND_POINTER,
+            std::ptr::null_mut(),
+        ));
+        // hit testing only skips windows that are both layered and transparent. a window
+        // `see_through` already layered keeps its colorkey, the rest become fully opaque layers
+        let ex_style = GetWindowLongW(hwnd, GWL_EXSTYLE);
+        let (mut key, mut alpha, mut flags) = (COLORREF(0), 255, LWA_ALPHA);
+        if ex_style & (WS_EX_LAYERED.0 as i32) != 0 {
+            let mut layered_flags = LAYERED_WINDOW_ATTRIBUTES_FLAGS(0);
+            if GetLayeredWindowAttributes(
+                hwnd,
+                Some(&mut key),
+                Some(&mut alpha),
+                Some(&mut layered_flags),
+            )
+            .is_ok()
+            {
+                flags = layered_flags;
+            }
+        }
+        let pass_through = (WS_EX_LAYERED.0 | WS_EX_TRANSPARENT.0) as i32;
+        if is_click_through {
+            SetWindowLongW(hwnd, GWL_EXSTYLE, ex_style | pass_through);
+            let _ = SetLayeredWindowAttributes(hwnd, key, alpha, flags);
+        } else {
+            SetWindowLongW(hwnd, GWL_EXSTYLE, ex_style & !(WS_EX_TRANSPARENT.0 as i32));
+        }
+    }
+    #[cfg(target_os = "linux")]
+    {
+        use sdl3::sys::{
+            properties::SDL_GetNumberProperty, video::SDL_PROP_WINDOW_X11_WINDOW_NUMBER,
+        };
+        use x11rb::{
+            connection::RequestConnection,
+            errors::ReplyError,
+            protocol::{
+                shape::{self, ConnectionExt, SK, SO},
+                xproto::ClipOrdering,
+            },
+        };
+
+        // zero when SDL runs on wayland, which has no say in input regions here
+        let x11_window = unsafe {
+            SDL_GetNumberProperty(
+                SDL_GetWindowProperties(window.raw()),
+                SDL_PROP_WINDOW_X11_WINDOW_NUMBER,
+                0,
+            )
+        } as u32;
+        if x11_window == 0 {
+            return;
+        }
+        let Ok((connection, _)) = x11rb::connect(None) else {
+            return;
+        };
+        if !matches!(
+            connection.extension_information(shape::X11_EXTENSION_NAME),
+            Ok(Some(_))
+        ) {
+            return;
+        }
+        // an empty input region takes no clicks at all, no mask puts the whole window back
+        let shaped = if is_click_through {
+            connection
+                .shape_rectangles(
+                    SO::SET,
+                    SK::INPUT,
+                    ClipOrdering::UNSORTED,
+                    x11_window,
+                    0,
+                    0,
+                    &[],
+                )
+                .map_err(ReplyError::from)
+                .and_then(|cookie| cookie.check())
+        } else {
+            connection
+                .shape_mask(SO::SET, SK::INPUT, x11_window, 0, 0, x11rb::NONE)
+                .map_err(ReplyError::from)
+                .and_then(|cookie| cookie.check())
+        };
+        if let Err(err) = shaped {
+            warn!("couldn't change where the overlay takes clicks: {err}");
+        }
+    }
+}
+
 impl DesktopGremlin {
     pub fn new(launch_arguments: Option<LaunchArguments>) -> Result<DesktopGremlin> {
         let sdl = sdl3::init()?;
         let video = sdl.video()?;
-        let launch_arguments = launch_arguments.unwrap_or_default();
+        let mut launch_arguments = launch_arguments.unwrap_or_default();
 
-        let window = WindowBuilder::new(
-            &video,
-            &launch_arguments.title,
-            launch_arguments.w,
-            launch_arguments.h,
-        )
-        .set_window_flags(launch_arguments.window_flags())
-        .build()?;
-
-        #[cfg(target_os = "windows")]
-        unsafe {
-            let sdl_props = SDL_GetWindowProperties(window.raw());
-            let hwnd = SDL_GetPointerProperty(
-                sdl_props,
-                SDL_PROP_WINDOW_WIN32_HWND_POINTER,
-                std::ptr::null_mut(),
-            );
+        // without a compositor (a bare X11 window manager) "transparent" comes out as a black square
+        let wants_transparency = launch_arguments
+            .window_flags
+            .contains(&WindowFlags::TRANSPARENT);
+        if wants_transparency && !has_compositor() {
+            launch_arguments.drop_transparency();
+        }
+        let build_window = |launch_arguments: &LaunchArguments| {
+            WindowBuilder::new(
+                &video,
+                &launch_arguments.title,
+                launch_arguments.w,
+                launch_arguments.h,
+            )
+            .set_window_flags(launch_arguments.window_flags())
+            .build()
+        };
+        let window = match build_window(&launch_arguments) {
+            Ok(window) => window,
+            Err(err) if launch_arguments.is_transparent() => {
+                warn!("couldn't open a transparent window, trying an opaque one: {err}");
+                launch_arguments.drop_transparency();
+                build_window(&launch_arguments)?
+            }
+            Err(err) => return Err(err.into()),
+        };
 
-            let hwnd = HWND(hwnd);
+        see_through(&window, launch_arguments.is_transparent());
 
-            let ex_style = GetWindowLongW(hwnd, GWL_EXSTYLE);
+        let canvas = window.into_canvas();
 
-            SetWindowLongW(hwnd, GWL_EXSTYLE, ex_style | (WS_EX_LAYERED.0 as i32));
+        // a gremlin without sound is still a gremlin
+        let audio = GremlinAudio::new(&sdl)
+            .inspect_err(|err| warn!("audio unavailable: {err}"))
+            .ok();
 
-            let _ = SetLayeredWindowAttributes(hwnd, COLORREF(0x00000000), 255, LWA_COLORKEY);
+        let backend = SdlBackend { canvas };
+        let is_opaque = wants_transparency && !launch_arguments.is_transparent();
+        #[cfg(target_os = "linux")]
+        let (backend, is_cut_out): (Box<dyn WindowBackend>, bool) = if is_opaque {
+            match crate::backend::x11_shape::ShapedBackend::wrap(backend) {
+                Ok(shaped) => (Box::new(shaped), true),
+                Err(backend) => (Box::new(backend), false),
+            }
+        } else {
+            (Box::new(backend), false)
+        };
+        // the colorkey `see_through` set already cuts out the black
+        #[cfg(not(target_os = "linux"))]
+        let (backend, is_cut_out) = (Box::new(backend), is_opaque && cfg!(target_os = "windows"));
+
+        let mut application = DesktopGremlin {
+            sdl: Some(sdl),
+            audio,
+            ..Self::with_backend(backend)
+        };
+        if is_cut_out {
+            application.say(
+                "no compositor here, so my edges come out jagged",
+                NO_TRANSPARENCY_MESSAGE_DURATION,
+            );
+        } else if is_opaque {
+            application.say(
+                "my window can't be see-through here, is a compositor running?",
+                NO_TRANSPARENCY_MESSAGE_DURATION,
+            );
+        }
+        if let Some(seed) = launch_arguments.seed {
+            application.reseed(seed);
         }
+        Ok(application)
+    }
 
-        let canvas = window.into_canvas();
+    /// No SDL at all, window calls and drawn frames are recorded by a `HeadlessBackend`.
+    #[allow(unused)]
+    pub fn headless() -> DesktopGremlin {
+        Self::with_backend(Box::new(HeadlessBackend::default()))
+    }
+
+    /// what a `headless()` gremlin's window was asked to do so far, nothing for a real window
+    pub fn headless_calls(&self) -> &[BackendCall] {
+        (self.window.as_ref() as &dyn Any)
+            .downcast_ref::<HeadlessBackend>()
+            .map_or(&[], |backend| &backend.calls)
+    }
 
-        Ok(DesktopGremlin {
-            sdl,
+    fn with_backend(window: Box<dyn WindowBackend>) -> DesktopGremlin {
+        let base_size = window.size();
+        let seed = rand::random();
+        DesktopGremlin {
+            sdl: None,
             current_gremlin: None,
-            canvas,
+            window,
             should_exit: Arc::new(Mutex::new(false)),
-            // texture_cache: Default::default(),
+            texture_cache: Default::default(),
             task_queue: Default::default(),
+            interrupt_policy: Default::default(),
             task_channel: mpsc::channel(),
             should_check_for_action: true,
-        })
+            movement_mode: Default::default(),
+            is_chasing: false,
+            stats: Default::default(),
+            audio: None,
+            speech_queue: Default::default(),
+            notifications: Default::default(),
+            chat_prompts: Default::default(),
+            base_size,
+            launch_size: base_size,
+            scale: 1.0,
+            gremlin_path: None,
+            disabled_behaviors: Default::default(),
+            is_paused: Default::default(),
+            framerate: Arc::new(AtomicU32::new(DEFAULT_FRAMERATE)),
+            target_framerate: DEFAULT_FRAMERATE,
+            frame_ticks: 1,
+            wake_at: None,
+            speed: 1.0,
+            goto_target: None,
+            snap_distance: None,
+            spawn_requests: Default::default(),
+            ink: Default::default(),
+            accessories: Vec::new(),
+            achievements: Default::default(),
+            emitted_events: Vec::new(),
+            rng: StdRng::seed_from_u64(seed),
+            seed,
+            profile: Default::default(),
+            scheduler: Default::default(),
+            foreground_app: None,
+            hotkeys: Default::default(),
+            routine: Vec::new(),
+        }
+    }
+
+    /// restarts the rng from `seed`, same seed and same inputs give the same gremlin
+    pub fn reseed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    /// makes the current gremlin's manifest size its scale 1.0, so big sprites don't get squashed
+    /// into the launch size. The user's scale stays on top of it.
+    pub fn fit_window_to_gremlin(&mut self) {
+        let Some(gremlin) = &self.current_gremlin else {
+            return;
+        };
+        // not `base_size`, that's the last gremlin's size and switching would compound it
+        let (launch_width, launch_height) = self.launch_size;
+        let manifest_scale = gremlin.metadata.scale.unwrap_or(1.0);
+        self.base_size = (
+            ((gremlin.frame_width.unwrap_or(launch_width) as f32) * manifest_scale).round() as u32,
+            ((gremlin.frame_height.unwrap_or(launch_height) as f32) * manifest_scale).round()
+                as u32,
+        );
+        self.set_scale(self.scale);
+    }
+
+    /// resizes the gremlin window relative to `base_size`
+    pub fn set_scale(&mut self, scale: f32) {
+        self.scale = scale.clamp(MIN_SCALE, MAX_SCALE);
+        let (base_width, base_height) = self.base_size;
+        let (mut width, mut height) = (
+            ((base_width as f32) * self.scale).round() as u32,
+            ((base_height as f32) * self.scale).round() as u32,
+        );
+        // a sprite pixel has to cover a whole number of screen pixels or it smears
+        if let Some(gremlin) = &self.current_gremlin
+            && gremlin.scaling == Scaling::Pixel
+            && let (Some(frame_width), Some(frame_height)) =
+                (gremlin.frame_width, gremlin.frame_height)
+            && frame_width > 0
+        {
+            let factor = (width as f32 / frame_width as f32).round().max(1.0) as u32;
+            (width, height) = (frame_width * factor, frame_height * factor);
+        }
+        self.window.set_size(width, height);
+    }
+
+    /// raises an event for every behavior to see on the next frame
+    pub fn emit(&mut self, event: Event, data: Option<EventData>) {
+        self.emitted_events.push((event, data));
+    }
+
+    /// sends `action` every `every` until cancelled, e.g. `schedule_every(Duration::from_secs(30), Event::Custom("save".to_string()))`
+    pub fn schedule_every(
+        &mut self,
+        every: Duration,
+        action: impl Into<ScheduledAction>,
+    ) -> ScheduleId {
+        self.scheduler.every(every, action.into())
+    }
+
+    /// sends `action` once `at` has passed
+    pub fn schedule_at(&mut self, at: Instant, action: impl Into<ScheduledAction>) -> ScheduleId {
+        self.scheduler.at(at, action.into())
+    }
+
+    pub fn cancel_scheduled(&mut self, id: ScheduleId) {
+        self.scheduler.cancel(id);
+    }
+
+    /// puts the current gremlin's `[schedule]` on the scheduler, replacing the previous gremlin's
+    pub fn schedule_routine(&mut self) {
+        for id in self.routine.drain(..) {
+            self.scheduler.cancel(id);
+        }
+        let Some(gremlin) = &self.current_gremlin else {
+            return;
+        };
+        for (rule, animation_name) in &gremlin.schedule {
+            let tasks = vec![
+                GremlinTask::Play(animation_name.clone()),
+                GremlinTask::Play(gremlin.metadata.default_animation().to_string()),
+            ];
+            self.routine
+                .push(self.scheduler.by_rule(*rule, tasks.into()));
+        }
+    }
+
+    /// sends off whatever the scheduler has due, called by the runtime once a frame
+    pub fn run_scheduled(&mut self) {
+        for action in self.scheduler.due(Instant::now()) {
+            match action {
+                ScheduledAction::Task(task) => {
+                    let _ = self.task_channel.0.send(task);
+                }
+                ScheduledAction::Tasks(tasks) => {
+                    for task in tasks {
+                        let _ = self.task_channel.0.send(task);
+                    }
+                }
+                ScheduledAction::Emit(event) => self.emit(event, None),
+            }
+        }
+    }
+
+    /// how often the main loop runs, `target_framerate` unless something's saving power
+    pub fn set_framerate(&self, framerate: u32) {
+        self.framerate.store(framerate.max(1), Ordering::Relaxed);
+    }
+
+    /// the user's framerate, clamped to `MIN_FRAMERATE..=MAX_FRAMERATE`. Takes over right away
+    /// unless something's saving power, then it's what that goes back to
+    pub fn set_target_framerate(&mut self, framerate: u32) {
+        let framerate = framerate.clamp(MIN_FRAMERATE, MAX_FRAMERATE);
+        if self.framerate.load(Ordering::Relaxed) == self.target_framerate {
+            self.set_framerate(framerate);
+        }
+        self.target_framerate = framerate;
+    }
+
+    /// how long one frame is up at the current framerate
+    pub fn frame_interval(&self) -> Duration {
+        Duration::from_secs(1) / self.framerate.load(Ordering::Relaxed).max(1)
+    }
+
+    /// how much animation time this frame is worth, a whole number of `frame_interval`s.
+    /// None at all for a frame that came early, see `FrameClock`
+    pub fn frame_tick(&self) -> Duration {
+        self.frame_interval() * self.frame_ticks
+    }
+
+    /// lets the main loop skip frames for up to `duration` (at most `MAX_IDLE_WAIT`), unless
+    /// another behavior wants one sooner. Frames nobody says this on run at the full framerate.
+    pub fn idle_for(&mut self, duration: Duration) {
+        let at = Instant::now() + duration.min(MAX_IDLE_WAIT);
+        self.wake_at = Some(self.wake_at.map_or(at, |wake_at| wake_at.min(at)));
+    }
+
+    /// registers a global `hotkey` like "ctrl+alt+KeyG" and gives back the id its presses come
+    /// with as `kinds::HotKey`, has to be called from the thread running the event loop (any
+    /// `Behavior::setup` is)
+    pub fn register_hotkey(&mut self, hotkey: &str) -> Result<u32> {
+        self.hotkeys.register(hotkey)
+    }
+
+    /// keeps the next frame coming at the full framerate, for behaviors animating on their own
+    pub fn stay_awake(&mut self) {
+        self.idle_for(Duration::ZERO);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.is_paused.load(Ordering::Relaxed)
+    }
+
+    /// the current gremlin's resting animation, see `GremlinMetadata::default_animation`
+    pub fn default_animation(&self) -> &str {
+        self.current_gremlin
+            .as_ref()
+            .map_or(IDLE_ANIMATION, |gremlin| {
+                gremlin.metadata.default_animation()
+            })
+    }
+
+    /// names of the queued tasks, next up first, see `GremlinTask::name`
+    pub fn queued_task_names(&self) -> impl Iterator<Item = &str> {
+        self.task_queue.iter().map(GremlinTask::name)
+    }
+
+    /// drops every queued task called `name`, e.g. `cancel_tasks("SNEEZE")`, returns how many went
+    pub fn cancel_tasks(&mut self, name: &str) -> usize {
+        let queued = self.task_queue.len();
+        self.task_queue.retain(|task| task.name() != name);
+        queued - self.task_queue.len()
+    }
+
+    /// drops every queued `Play`/`PlayInterrupt` so nothing cuts short the animation about to
+    /// start, e.g. a looping GRAB or RUN. Custom tasks stay queued. Returns how many went
+    pub fn cancel_animations(&mut self) -> usize {
+        let queued = self.task_queue.len();
+        self.task_queue
+            .retain(|task| !matches!(task, GremlinTask::Play(_) | GremlinTask::PlayInterrupt(_)));
+        queued - self.task_queue.len()
+    }
+
+    /// collapses runs of the same task into one, e.g. three IDLEs in a row play once
+    pub fn dedup_tasks(&mut self) {
+        let mut tasks = Vec::from(std::mem::take(&mut self.task_queue));
+        tasks.dedup();
+        self.task_queue = tasks.into();
+    }
+
+    /// queues a speech bubble, shown once the ones before it are gone
+    pub fn say(&mut self, text: impl Into<String>, duration: Duration) {
+        self.speech_queue.push_back(Speech {
+            text: text.into(),
+            duration,
+        });
+    }
+
+    /// has the gremlin come on screen, play ALERT and show the message in a bubble
+    pub fn notify(&mut self, title: impl Into<String>, body: impl Into<String>) {
+        self.notifications.push_back(Notification {
+            title: title.into(),
+            body: body.into(),
+        });
+    }
+
+    /// draws a line through `points` (screen coordinates) on the overlay, it fades out by itself
+    pub fn scribble(&mut self, points: Vec<(i32, i32)>) {
+        if points.len() > 1 {
+            self.ink.push_back(points);
+        }
+    }
+
+    /// an arrow from `from` pointing at `to`, both in screen coordinates
+    pub fn draw_arrow(&mut self, from: (i32, i32), to: (i32, i32)) {
+        const HEAD_LENGTH: f32 = 18.0;
+        const HEAD_ANGLE: f32 = 0.5;
+
+        let angle = ((from.1 - to.1) as f32).atan2((from.0 - to.0) as f32);
+        let barb = |side: f32| {
+            (
+                to.0 + (HEAD_LENGTH * (angle + side * HEAD_ANGLE).cos()).round() as i32,
+                to.1 + (HEAD_LENGTH * (angle + side * HEAD_ANGLE).sin()).round() as i32,
+            )
+        };
+        self.scribble(vec![from, to, barb(1.0), to, barb(-1.0)]);
     }
 
     pub fn load_gremlin(&mut self, gremlin_txt_path: String) -> Result<Gremlin, GremlinLoadError> {
         let path = Path::new(gremlin_txt_path.as_str());
-        let gremlin_txt = fs::read_to_string(path)?;
+        if pack::is_pack(path) {
+            let manifest = pack::unpack(path)?;
+            return self.load_gremlin(manifest.to_string_lossy().into_owned());
+        }
+        let gremlin_txt = fs::read_to_string(path).map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => GremlinLoadError::MissingManifest(path.to_path_buf()),
+            _ => GremlinLoadError::Io(err),
+        })?;
         let mut gremlin = Gremlin::default();
-        for line in gremlin_txt.lines() {
-            // skip comments
-            if line.starts_with("//") {
+        let mut section = String::new();
+        // animations might be declared after their durations
+        let mut frame_durations = HashMap::new();
+        // (accessory, animation) -> anchor, same for accessories
+        let mut anchors = HashMap::new();
+        // animation -> tags, the animation might come after its tags
+        let mut tags = HashMap::new();
+        // node name -> definition, and the line it's on for when it doesn't make sense
+        let mut tree_definitions = HashMap::new();
+        let mut tree_lines = HashMap::new();
+        for (index, raw_line) in gremlin_txt.lines().enumerate() {
+            let line = raw_line.trim();
+            // skip blank lines and comments
+            if line.is_empty() || line.starts_with("//") {
                 continue;
             }
-            let split = line.split('=').collect::<Vec<&str>>();
-            if split.len() == 2 {
-                if split[0].starts_with('.') {
-                    match split[0] {
-                        ".name" => {
-                            gremlin.name = String::from(split[1]);
-                        }
-                        _ => {
-                            gremlin
-                                .metadata
-                                .insert(split[0].to_string(), split[1].to_string());
-                        }
-                    }
+            if let Some(section_name) = line
+                .strip_prefix('[')
+                .and_then(|line| line.strip_suffix(']'))
+            {
+                section = section_name.trim().to_lowercase();
+                continue;
+            }
+            let malformed = || GremlinLoadError::MalformedLine {
+                line: index + 1,
+                content: raw_line.to_string(),
+            };
+            let split = line.split('=').map(str::trim).collect::<Vec<&str>>();
+            if split.len() != 2 {
+                return Err(malformed());
+            }
+            if section == "aliases" {
+                if let Some((name, period)) = split[0].split_once('@') {
+                    let (from, to) = parse_period(period).ok_or_else(malformed)?;
+                    gremlin
+                        .timed_aliases
+                        .entry(name.trim().to_uppercase())
+                        .or_default()
+                        .push((from, to, split[1].to_uppercase()));
                     continue;
                 }
-
-                if let Ok(count) = split[1].parse::<u32>() {
-                    let animation_properties =
-                        AnimationProperties::new(split[0].to_string(), count);
+                gremlin
+                    .aliases
+                    .insert(split[0].to_uppercase(), split[1].to_uppercase());
+                continue;
+            }
+            if section == "tags" {
+                tags.insert(
+                    split[0].to_uppercase(),
+                    split[1]
+                        .split(',')
+                        .map(|tag| tag.trim().to_lowercase())
+                        .filter(|tag| !tag.is_empty())
+                        .collect::<Vec<_>>(),
+                );
+                continue;
+            }
+            if section == "variants" {
+                let variants = split[1]
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|variant| !variant.is_empty())
+                    .map(|variant| match variant.split_once(':') {
+                        Some((variant, weight)) => weight
+                            .trim()
+                            .parse::<u32>()
+                            .map(|weight| (variant.trim().to_uppercase(), weight)),
+                        None => Ok((variant.to_uppercase(), 1)),
+                    })
+                    .collect::<std::result::Result<Vec<_>, _>>()
+                    .map_err(|_| malformed())?;
+                gremlin.variants.insert(split[0].to_uppercase(), variants);
+                continue;
+            }
+            if section == "fallbacks" {
+                gremlin.fallbacks.insert(
+                    split[0].to_uppercase(),
+                    split[1]
+                        .split(',')
+                        .map(|fallback| fallback.trim().to_uppercase())
+                        .filter(|fallback| !fallback.is_empty())
+                        .collect(),
+                );
+                continue;
+            }
+            if section == "durations" {
+                let durations = split[1]
+                    .split(',')
+                    .map(|millis| millis.trim().parse::<u64>().map(Duration::from_millis))
+                    .collect::<std::result::Result<Vec<Duration>, _>>()
+                    .map_err(|_| malformed())?;
+                frame_durations.insert(split[0].to_uppercase(), durations);
+                continue;
+            }
+            if section == "speeds" {
+                let speed = split[1]
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|speed| speed.is_finite() && *speed >= MIN_SPEED)
+                    .ok_or_else(malformed)?;
+                gremlin
+                    .speeds
+                    .insert(split[0].to_uppercase(), speed.min(MAX_SPEED));
+                continue;
+            }
+            if section == "tree" {
+                let name = split[0].to_lowercase();
+                tree_lines.insert(name.clone(), (index + 1, raw_line.to_string()));
+                tree_definitions.insert(name, split[1].to_string());
+                continue;
+            }
+            if section == "utility" {
+                gremlin
+                    .utility
+                    .push(UtilityCandidate::parse(split[0], split[1]).map_err(|_| malformed())?);
+                continue;
+            }
+            if section == "personality" {
+                let value = split[1]
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|value| (0.0..=1.0).contains(value))
+                    .ok_or_else(malformed)?;
+                match split[0].to_lowercase().as_str() {
+                    "curiosity" => gremlin.personality.curiosity = value,
+                    "laziness" => gremlin.personality.laziness = value,
+                    "clinginess" => gremlin.personality.clinginess = value,
+                    _ => return Err(malformed()),
+                }
+                continue;
+            }
+            if section == "strides" {
+                let stride = split[1]
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|stride| stride.is_finite() && *stride > 0.0)
+                    .ok_or_else(malformed)?;
+                gremlin.strides.insert(split[0].to_uppercase(), stride);
+                continue;
+            }
+            if section == "gamepad" {
+                let button = split[0].parse::<GamepadButton>().map_err(|_| malformed())?;
+                gremlin.gamepad.insert(button, split[1].to_uppercase());
+                continue;
+            }
+            if section == "clicks" {
+                let button = split[0].parse::<MouseButton>().map_err(|_| malformed())?;
+                let action = split[1].parse::<ClickAction>().map_err(|_| malformed())?;
+                gremlin.clicks.insert(button, action);
+                continue;
+            }
+            if section == "schedule" {
+                let rule = split[0].parse::<ScheduleRule>().map_err(|_| malformed())?;
+                gremlin.schedule.push((rule, split[1].to_uppercase()));
+                continue;
+            }
+            if section == "layers" {
+                let mut fields = split[1].split(',').map(str::trim);
+                let count = fields
+                    .next()
+                    .and_then(|count| count.parse::<u32>().ok())
+                    .filter(|count| *count > 0)
+                    .ok_or_else(malformed)?;
+                let mode = fields
+                    .next()
+                    .and_then(|mode| mode.parse::<LayerMode>().ok())
+                    .ok_or_else(malformed)?;
+                gremlin.layers.push(Layer {
+                    properties: AnimationProperties::new(split[0].to_string(), count),
+                    mode,
+                    animations: fields
+                        .filter(|animation| !animation.is_empty())
+                        .map(str::to_uppercase)
+                        .collect(),
+                });
+                continue;
+            }
+            if section == "accessories" {
+                if let Some(parent) = path.parent() {
+                    gremlin.accessories.push(Accessory {
+                        name: split[0].to_uppercase(),
+                        sprite_path: parent.join(split[1]),
+                        anchors: HashMap::new(),
+                    });
+                }
+                continue;
+            }
+            if section == "anchors" {
+                let (accessory, animation) = split[0].split_once('.').unwrap_or((split[0], ""));
+                let anchor = split[1]
+                    .split_once(',')
+                    .and_then(|(x, y)| Some((x.trim().parse().ok()?, y.trim().parse().ok()?)))
+                    .ok_or_else(malformed)?;
+                anchors.insert((accessory.to_uppercase(), animation.to_uppercase()), anchor);
+                continue;
+            }
+            if section == "unlocks" {
+                let name = split[0].to_uppercase();
+                let id = split[1].to_lowercase();
+                // from a newer version maybe, nothing stays locked behind it
+                if achievements::find(&id).is_none() {
+                    warn!("{name} waits for {id:?}, which isn't an achievement");
+                    continue;
+                }
+                gremlin.locked.insert(name.clone());
+                gremlin.unlocks.insert(name, id);
+                continue;
+            }
+            if section == "sounds" {
+                if let Some(parent) = path.parent() {
                     gremlin
-                        .animation_map
-                        .insert(split[0].to_string(), animation_properties);
+                        .sounds
+                        .insert(split[0].to_uppercase(), parent.join(split[1]));
                 }
+                continue;
+            }
+            if split[0].starts_with('.') {
+                match split[0] {
+                    ".name" => {
+                        gremlin.name = String::from(split[1]);
+                    }
+                    ".width" => {
+                        gremlin.frame_width =
+                            Some(parse_frame_size(split[1]).ok_or_else(malformed)?);
+                    }
+                    ".height" => {
+                        gremlin.frame_height =
+                            Some(parse_frame_size(split[1]).ok_or_else(malformed)?);
+                    }
+                    ".scaling" => {
+                        gremlin.scaling = split[1].parse().map_err(|_| malformed())?;
+                    }
+                    ".trail" => {
+                        gremlin.trail = split[1]
+                            .parse()
+                            .ok()
+                            .filter(|trail| *trail <= MAX_TRAIL)
+                            .ok_or_else(malformed)?;
+                    }
+                    ".scale" => {
+                        gremlin.metadata.scale = Some(
+                            split[1]
+                                .parse::<f32>()
+                                .ok()
+                                .filter(|scale| scale.is_finite() && *scale > 0.0)
+                                .ok_or_else(malformed)?,
+                        );
+                    }
+                    ".fps" => {
+                        gremlin.metadata.fps = Some(
+                            split[1]
+                                .parse::<u32>()
+                                .ok()
+                                .filter(|fps| *fps > 0)
+                                .ok_or_else(malformed)?,
+                        );
+                    }
+                    ".colorkey" => {
+                        gremlin.metadata.colorkey =
+                            Some(parse_colorkey(split[1]).ok_or_else(malformed)?);
+                    }
+                    ".author" => {
+                        gremlin.metadata.author = Some(split[1].to_string());
+                    }
+                    ".version" => {
+                        gremlin.metadata.version = Some(split[1].to_string());
+                    }
+                    ".default" => {
+                        gremlin.metadata.default_animation = Some(split[1].to_uppercase());
+                    }
+                    key => {
+                        gremlin
+                            .metadata
+                            .extra
+                            .insert(key[1..].to_string(), split[1].to_string());
+                    }
+                }
+                continue;
+            }
+
+            let count = split[1].parse::<u32>().map_err(|_| malformed())?;
+            // uppercase like the sheets' file names and every other section's keys
+            let name = split[0].to_uppercase();
+            let animation_properties = AnimationProperties::new(name.clone(), count);
+            gremlin.animation_map.insert(name, animation_properties);
+        }
+        for ((name, animation), anchor) in anchors {
+            if let Some(accessory) = gremlin
+                .accessories
+                .iter_mut()
+                .find(|accessory| accessory.name == name)
+            {
+                accessory.anchors.insert(animation, anchor);
+            }
+        }
+        if !tree_definitions.is_empty() {
+            let tree = BehaviorTree::parse(&tree_definitions).map_err(|err| {
+                warn!("the [tree] doesn't work out, {err}");
+                let (line, content) = tree_lines
+                    .remove(&err.node)
+                    .or_else(|| tree_lines.into_values().min())
+                    .unwrap_or_default();
+                GremlinLoadError::MalformedLine { line, content }
+            })?;
+            gremlin.tree = Some(tree);
+        }
+        for (name, tags) in tags {
+            match gremlin.animation_map.get_mut(&name) {
+                Some(value) => value.tags = tags,
+                None => warn!("{name} is tagged but there's no such animation"),
+            }
+        }
+        for (name, durations) in frame_durations {
+            match gremlin.animation_map.get_mut(&name) {
+                Some(value) => value.frame_durations = durations,
+                None => warn!("{name} has [durations] but there's no such animation"),
             }
         }
         if let Some(parent) = path.parent()
@@ -340,34 +1797,323 @@ impl DesktopGremlin {
         {
             let mut png_list = HashMap::new();
             // will error out if i can't get into da directories
-            get_png_list(parent_path_str, 5, &mut png_list)?;
+            get_png_list(parent_path_str, SHEET_SEARCH_DEPTH, &mut png_list)?;
 
             // lets consume the map so we don't allocate more memory!
             for (name, path) in png_list.into_iter() {
                 if let Some(value) = gremlin.animation_map.get_mut(&name) {
+                    // the manifest's [durations] win over an aseprite export
+                    if value.frame_durations.is_empty() {
+                        value.frame_durations =
+                            aseprite_frame_durations(&path.with_extension("json"));
+                    }
                     let _ = value.sprite_path.insert(path);
+                } else if let Some(layer) = gremlin
+                    .layers
+                    .iter_mut()
+                    .find(|layer| layer.properties.animation_name == name)
+                {
+                    let _ = layer.properties.sprite_path.insert(path);
+                }
+            }
+            gremlin.layers.retain(|layer| {
+                let has_sheet = layer.properties.sprite_path.is_some();
+                if !has_sheet {
+                    warn!(
+                        "no sheet for the {} layer, leaving it off",
+                        layer.properties.animation_name
+                    );
+                }
+                has_sheet
+            });
+            for (name, value) in gremlin.animation_map.iter_mut() {
+                if value.sprite_path.is_none() {
+                    warn!("no sheet for {name}, using the built-in one");
+                    *value = AnimationProperties {
+                        tags: std::mem::take(&mut value.tags),
+                        ..AnimationProperties::fallback(name.clone())
+                    };
+                } else {
+                    value.colorkey = gremlin.metadata.colorkey;
                 }
             }
+            for layer in &mut gremlin.layers {
+                layer.properties.colorkey = gremlin.metadata.colorkey;
+            }
+            if let Some(default_animation) = &gremlin.metadata.default_animation
+                && !gremlin.animation_map.contains_key(default_animation)
+            {
+                warn!("there's no {default_animation} to play by default, sticking with IDLE");
+                gremlin.metadata.default_animation = None;
+            }
+            // sized after its sprites instead of squeezed into the launch window
+            if let Some((frame_width, frame_height)) = gremlin.sheet_frame_size() {
+                gremlin.frame_width.get_or_insert(frame_width);
+                gremlin.frame_height.get_or_insert(frame_height);
+            }
             Ok(gremlin)
         } else {
-            Err(GremlinLoadError::FsError(None))
+            Err(GremlinLoadError::MissingManifest(path.to_path_buf()))
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum GremlinTask {
     Play(String),
     PlayInterrupt(String),
+    #[allow(unused)]
+    SetMovementMode(MovementMode),
+    // starts or stops chasing the cursor, see `DesktopGremlin::is_chasing`
+    Chase(bool),
+    // freezes behaviors and the animation on its current frame until `Resume`
+    Pause,
+    Resume,
+    ToggleDebugOverlay,
+    // resizes the gremlin relative to `base_size`, clamped to `MIN_SCALE..=MAX_SCALE`
+    SetScale(f32),
+    // multiplies how fast every animation plays, clamped to `MIN_SPEED..=MAX_SPEED`
+    SetSpeed(f32),
+    // walks the window's top left corner to this screen position, see `GremlinMovement`
+    Goto(i32, i32),
+    // a mini clone of `gremlin` (a manifest or pack, the current gremlin when `None`) in its own
+    // window, `offset` away from this one, see `GremlinClones`
+    Spawn {
+        gremlin: Option<String>,
+        offset: (i32, i32),
+    },
+    // saves the frame on screen (layers and accessories too, not the debug overlay) to a png,
+    // see `GremlinRender`
+    Screenshot(PathBuf),
+    // records whatever's drawn for a while into a looping gif, see `Recording`
+    Record {
+        path: PathBuf,
+        duration: Duration,
+    },
+    // anything a behavior defines itself, see `Task`
+    Custom(CustomTask),
+}
+
+/// How much a task matters, see `InterruptPolicy`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TaskPriority {
+    /// filler that anything can cut short
+    Low,
+    #[default]
+    Normal,
+    /// the user is doing something to the gremlin
+    High,
+    /// nothing cuts these short, like OUTRO
+    Critical,
+}
+
+/// Which tasks (by `GremlinTask::name`) may cut the current animation short, and which queued
+/// task goes next. A `PlayInterrupt` only interrupts an animation of the same or lower priority,
+/// otherwise it goes to the front of the queue. Queued tasks go in order, High and Critical ones
+/// skip ahead so filler can't hold them up, but nothing below that waits on anything else forever.
+#[derive(Debug, Clone)]
+pub struct InterruptPolicy {
+    priorities: HashMap<String, TaskPriority>,
+}
+
+impl Default for InterruptPolicy {
+    fn default() -> Self {
+        Self {
+            priorities: [
+                ("GRAB", TaskPriority::High),
+                ("INTRO", TaskPriority::High),
+                ("OUTRO", TaskPriority::Critical),
+            ]
+            .into_iter()
+            .map(|(name, priority)| (name.to_string(), priority))
+            .collect(),
+        }
+    }
+}
+
+impl InterruptPolicy {
+    pub fn priority(&self, name: &str) -> TaskPriority {
+        self.priorities.get(name).copied().unwrap_or_default()
+    }
+
+    #[allow(unused)]
+    pub fn set_priority(&mut self, name: impl Into<String>, priority: TaskPriority) {
+        self.priorities.insert(name.into(), priority);
+    }
+
+    pub fn may_interrupt(&self, incoming: &str, current: &str) -> bool {
+        self.priority(incoming) >= self.priority(current)
+    }
+
+    /// where in `queue` the task to run next is, the first of the most urgent High or Critical
+    /// ones, or the front
+    pub fn next_in(&self, queue: &VecDeque<GremlinTask>) -> Option<usize> {
+        queue
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(index, task)| (index, self.priority(task.name())))
+            .filter(|(_, priority)| *priority >= TaskPriority::High)
+            .max_by_key(|(_, priority)| *priority)
+            .map(|(index, _)| index)
+            .or((!queue.is_empty()).then_some(0))
+    }
+}
+
+/// A task behaviors can define without touching `GremlinTask`, sent as `GremlinTask::custom(...)`.
+/// Waits its turn in the task queue like `Play` does unless it's immediate.
+pub trait Task: Send + Sync {
+    /// for logs and the debug overlay
+    fn name(&self) -> &str;
+
+    fn run(&self, application: &mut DesktopGremlin);
+
+    /// runs as soon as it's received like `Pause` does, instead of after the current animation
+    fn is_immediate(&self) -> bool {
+        false
+    }
+}
+
+/// Shared so tasks stay cloneable for the scheduler, two are equal when they're the same task.
+#[derive(Clone)]
+pub struct CustomTask(pub Arc<dyn Task>);
+
+impl fmt::Debug for CustomTask {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("CustomTask").field(&self.0.name()).finish()
+    }
+}
+
+impl PartialEq for CustomTask {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl GremlinTask {
+    /// the animation for `Play`s, what the task calls itself for `Custom`s, the variant otherwise
+    pub fn name(&self) -> &str {
+        match self {
+            GremlinTask::Play(animation_name) | GremlinTask::PlayInterrupt(animation_name) => {
+                animation_name
+            }
+            GremlinTask::SetMovementMode(_) => "SetMovementMode",
+            GremlinTask::Chase(_) => "Chase",
+            GremlinTask::Pause => "Pause",
+            GremlinTask::Resume => "Resume",
+            GremlinTask::ToggleDebugOverlay => "ToggleDebugOverlay",
+            GremlinTask::SetScale(_) => "SetScale",
+            GremlinTask::SetSpeed(_) => "SetSpeed",
+            GremlinTask::Goto(_, _) => "Goto",
+            GremlinTask::Spawn { .. } => "Spawn",
+            GremlinTask::Screenshot(_) => "Screenshot",
+            GremlinTask::Record { .. } => "Record",
+            GremlinTask::Custom(task) => task.0.name(),
+        }
+    }
+
+    /// wraps a behavior's own `Task` to send it like any other:
+    ///
+    /// ```no_run
+    /// use desktop_gremlin::{DesktopGremlin, GremlinTask, gremlin::Task};
+    ///
+    /// /// shrinks the gremlin back once the animations queued before it have played
+    /// struct Shrink;
+    ///
+    /// impl Task for Shrink {
+    ///     fn name(&self) -> &str {
+    ///         "Shrink"
+    ///     }
+    ///
+    ///     fn run(&self, application: &mut DesktopGremlin) {
+    ///         application.set_scale(1.0);
+    ///     }
+    /// }
+    ///
+    /// # fn send(application: &DesktopGremlin) {
+    /// let _ = application.task_channel.0.send(GremlinTask::custom(Shrink));
+    /// # }
+    /// ```
+    pub fn custom(task: impl Task + 'static) -> GremlinTask {
+        GremlinTask::Custom(CustomTask(Arc::new(task)))
+    }
 }
 
 #[derive(Debug)]
 pub enum GremlinLoadError {
-    FsError(Option<io::Error>),
+    MissingManifest(PathBuf),
+    MalformedLine {
+        line: usize,
+        content: String,
+    },
+    MissingSheet {
+        animation: String,
+    },
+    // the sheet can't be cut into `frame_count` frames laid out in DEFAULT_COLUMN_COUNT columns
+    FrameCountMismatch {
+        animation: String,
+        frame_count: u32,
+        width: u32,
+        height: u32,
+    },
+    UnsupportedPixelFormat {
+        animation: String,
+        color_type: ColorType,
+    },
+    Image {
+        animation: String,
+        source: ImageError,
+    },
+    Io(io::Error),
+}
+
+impl fmt::Display for GremlinLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingManifest(path) => write!(f, "no gremlin manifest at {}", path.display()),
+            Self::MalformedLine { line, content } => {
+                write!(f, "manifest line {line} doesn't make sense: `{content}`")
+            }
+            Self::MissingSheet { animation } => {
+                write!(f, "no sprite sheet found for {animation}")
+            }
+            Self::FrameCountMismatch {
+                animation,
+                frame_count,
+                width,
+                height,
+            } => write!(
+                f,
+                "{animation} is {width}x{height}, which can't hold {frame_count} frames in {DEFAULT_COLUMN_COUNT} columns"
+            ),
+            Self::UnsupportedPixelFormat {
+                animation,
+                color_type,
+            } => write!(
+                f,
+                "{animation} uses {color_type:?} pixels, sprite sheets need to be 8-bit RGBA"
+            ),
+            Self::Image { animation, source } => {
+                write!(f, "couldn't decode the sheet for {animation}: {source}")
+            }
+            Self::Io(err) => write!(f, "{err}"),
+        }
+    }
 }
+
+impl std::error::Error for GremlinLoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Image { source, .. } => Some(source),
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
 impl From<std::io::Error> for GremlinLoadError {
     fn from(value: std::io::Error) -> Self {
-        Self::FsError(Some(value))
+        Self::Io(value)
     }
 }
 
@@ -381,6 +2127,8 @@ pub struct Animation {
 #[derive(Default, Clone, Hash, Debug)]
 pub struct Animator {
     pub current_frame: u32,
+    // how long the current frame has been up
+    pub frame_time: Duration,
     pub texture_size: (u32, u32),
     pub sprite_size: (u32, u32),
     pub animation_properties: AnimationProperties,
@@ -398,6 +2146,7 @@ impl TryFrom<&AnimationProperties> for Animator {
         {
             return Ok(Animator {
                 current_frame: Default::default(),
+                frame_time: Default::default(),
                 texture_size: (image_data.width(), image_data.height()),
                 animation_properties: value.clone(),
                 column_count: DEFAULT_COLUMN_COUNT,
@@ -417,6 +2166,7 @@ impl From<&Animation> for Animator {
     fn from(value: &Animation) -> Self {
         Self {
             current_frame: Default::default(),
+            frame_time: Default::default(),
             texture_size: (
                 value.sprite_sheet.image.width(),
                 value.sprite_sheet.image.height(),
@@ -440,6 +2190,55 @@ impl From<&Animation> for Animator {
 }
 
 impl Animator {
+    /// back to the first frame
+    pub fn restart(&mut self) {
+        self.current_frame = 0;
+        self.frame_time = Duration::ZERO;
+    }
+
+    /// counts `elapsed` towards the current frame and moves past every frame that has been up
+    /// for its duration, true when the animation went past its last frame
+    pub fn advance(&mut self, elapsed: Duration) -> bool {
+        let sprite_count = self.animation_properties.sprite_count;
+        if sprite_count == 0 {
+            return false;
+        }
+        self.frame_time += elapsed;
+        loop {
+            let duration = self
+                .animation_properties
+                .frame_duration(self.current_frame)
+                .unwrap_or(FRAME_TICK);
+            if duration.is_zero() || self.frame_time < duration {
+                return false;
+            }
+            self.frame_time -= duration;
+            self.current_frame = (self.current_frame + 1) % sprite_count;
+            if self.current_frame == 0 {
+                return true;
+            }
+        }
+    }
+
+    /// how much longer the current frame stays up at speed 1.0, `None` when it stays for good
+    pub fn until_next_frame(&self) -> Option<Duration> {
+        if self.animation_properties.sprite_count == 0 {
+            return None;
+        }
+        let duration = self
+            .animation_properties
+            .frame_duration(self.current_frame)
+            .unwrap_or(FRAME_TICK);
+        (!duration.is_zero()).then(|| duration.saturating_sub(self.frame_time))
+    }
+
+    /// jumps to `frame` and stays there until the next `advance`, for sheets that pick their
+    /// frame instead of playing through them like `Gremlin::layers`
+    pub fn show_frame(&mut self, frame: u32) {
+        self.current_frame = frame.min(self.animation_properties.sprite_count.saturating_sub(1));
+        self.frame_time = Duration::ZERO;
+    }
+
     pub fn get_frame_rect(&self) -> Rect {
         let (sprite_width, sprite_height) = self.sprite_size;
         Rect::new(
@@ -450,3 +2249,264 @@ impl Animator {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gremlin_with(sheets: &[&str]) -> Gremlin {
+        Gremlin {
+            animation_map: sheets
+                .iter()
+                .map(|name| {
+                    (
+                        name.to_string(),
+                        AnimationProperties::new(name.to_string(), 1),
+                    )
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    /// `manifest` loaded from a `config.txt` of its own, with blank `sheets` next to it
+    fn load(name: &str, manifest: &str, sheets: &[&str]) -> Gremlin {
+        let dir =
+            std::env::temp_dir().join(format!("desktop_gremlin-{name}-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.txt");
+        fs::write(&path, manifest).unwrap();
+        for sheet in sheets {
+            image::RgbaImage::new(8, 8)
+                .save(dir.join(format!("{sheet}.png")))
+                .unwrap();
+        }
+        let gremlin = DesktopGremlin::headless().load_gremlin(path.to_string_lossy().into_owned());
+        let _ = fs::remove_dir_all(&dir);
+        gremlin.unwrap()
+    }
+
+    #[test]
+    fn parses_tags() {
+        let gremlin = load(
+            "tags",
+            "idle=4\nWave=2\n[tags]\nwave=Happy, reaction\nIDLE=calm,\nmissing=sad\n",
+            &[],
+        );
+        assert_eq!(gremlin.animation_map["WAVE"].tags, ["happy", "reaction"]);
+        assert_eq!(gremlin.animation_map["IDLE"].tags, ["calm"]);
+        assert!(!gremlin.animation_map.contains_key("MISSING"));
+    }
+
+    #[test]
+    fn parses_durations() {
+        let gremlin = load(
+            "durations",
+            "WAVE=2\nIDLE=1\n[durations]\nwave=100, 250\nmissing=50\n",
+            &["wave", "idle"],
+        );
+        assert_eq!(
+            gremlin.animation_map["WAVE"].frame_durations,
+            [Duration::from_millis(100), Duration::from_millis(250)]
+        );
+        assert!(gremlin.animation_map["IDLE"].frame_durations.is_empty());
+    }
+
+    #[test]
+    fn parses_schedules() {
+        let gremlin = load(
+            "schedule",
+            "IDLE=1\nNAP=1\n[schedule]\n12:30=nap\nevery 30m=Idle\n",
+            &["idle", "nap"],
+        );
+        assert_eq!(
+            gremlin.schedule,
+            [
+                (
+                    ScheduleRule::Daily(chrono::NaiveTime::from_hms_opt(12, 30, 0).unwrap()),
+                    "NAP".to_string()
+                ),
+                (
+                    ScheduleRule::Every(Duration::from_secs(30 * 60)),
+                    "IDLE".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_periods() {
+        assert_eq!(parse_period(" Night "), Some((22, 6)));
+        assert_eq!(parse_period("9-17"), Some((9, 17)));
+        assert_eq!(parse_period("22 - 24"), Some((22, 24)));
+        assert_eq!(parse_period("24-6"), None);
+        assert_eq!(parse_period("6-25"), None);
+        assert_eq!(parse_period("brunch"), None);
+    }
+
+    #[test]
+    fn resolves_aliases_and_fallbacks() {
+        let mut gremlin = gremlin_with(&["IDLE", "WALK", "NAP"]);
+        gremlin
+            .aliases
+            .insert("SLEEP".to_string(), "NAP".to_string());
+        gremlin.fallbacks.insert(
+            "DANCE".to_string(),
+            vec!["JUMP".to_string(), "WALK".to_string()],
+        );
+        assert_eq!(gremlin.resolve_animation("SLEEP").as_deref(), Some("NAP"));
+        assert_eq!(gremlin.resolve_animation("DANCE").as_deref(), Some("WALK"));
+        // from `DEFAULT_FALLBACKS`
+        assert_eq!(gremlin.resolve_animation("RUN").as_deref(), Some("WALK"));
+        assert_eq!(gremlin.resolve_animation("JUMP"), None);
+
+        gremlin.locked.insert("WALK".to_string());
+        assert_eq!(gremlin.resolve_animation("RUN").as_deref(), Some("IDLE"));
+    }
+
+    #[test]
+    fn fallback_cycles_end() {
+        let mut gremlin = gremlin_with(&["IDLE"]);
+        gremlin
+            .fallbacks
+            .insert("A".to_string(), vec!["B".to_string()]);
+        gremlin
+            .fallbacks
+            .insert("B".to_string(), vec!["A".to_string()]);
+        assert_eq!(gremlin.resolve_animation("A"), None);
+    }
+
+    #[test]
+    fn variants_are_picked_by_weight() {
+        let mut gremlin = gremlin_with(&["IDLE", "IDLE2", "IDLE3", "IDLE4"]);
+        gremlin.variants.insert(
+            "IDLE".to_string(),
+            vec![
+                ("IDLE2".to_string(), 1),
+                ("IDLE3".to_string(), 0),
+                ("IDLE4".to_string(), 1),
+                ("MISSING".to_string(), 100),
+            ],
+        );
+        gremlin.locked.insert("IDLE4".to_string());
+        let mut rng = StdRng::seed_from_u64(7);
+        // no weight, locked or missing never come up
+        for _ in 0..32 {
+            assert_eq!(
+                gremlin.resolve_variant("IDLE", &mut rng).as_deref(),
+                Some("IDLE2")
+            );
+        }
+        // without variants it's the animation itself
+        assert_eq!(
+            gremlin.resolve_variant("IDLE3", &mut rng).as_deref(),
+            Some("IDLE3")
+        );
+
+        gremlin.locked.clear();
+        let picks = |seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            (0..32)
+                .map(|_| gremlin.resolve_variant("IDLE", &mut rng).unwrap())
+                .collect::<Vec<_>>()
+        };
+        // the same seed picks the same, and both get picked
+        assert_eq!(picks(7), picks(7));
+        assert!(picks(7).iter().any(|pick| pick == "IDLE2"));
+        assert!(picks(7).iter().any(|pick| pick == "IDLE4"));
+    }
+
+    #[test]
+    fn interrupts_go_by_priority() {
+        let mut policy = InterruptPolicy::default();
+        assert!(policy.may_interrupt("PAT", "IDLE"));
+        assert!(policy.may_interrupt("IDLE", "PAT"));
+        assert!(policy.may_interrupt("GRAB", "INTRO"));
+        assert!(!policy.may_interrupt("PAT", "GRAB"));
+        assert!(!policy.may_interrupt("GRAB", "OUTRO"));
+
+        policy.set_priority("FIDGET", TaskPriority::Low);
+        assert!(!policy.may_interrupt("FIDGET", "IDLE"));
+        assert!(policy.may_interrupt("IDLE", "FIDGET"));
+    }
+
+    #[test]
+    fn urgent_tasks_skip_the_queue() {
+        let mut policy = InterruptPolicy::default();
+        policy.set_priority("FIDGET", TaskPriority::Low);
+        let play = |name: &str| GremlinTask::Play(name.to_string());
+
+        assert_eq!(policy.next_in(&VecDeque::new()), None);
+        // filler isn't held up by what came after it
+        let queue = VecDeque::from([play("FIDGET"), play("PAT"), play("WALK")]);
+        assert_eq!(policy.next_in(&queue), Some(0));
+        let queue = VecDeque::from([play("PAT"), play("GRAB"), play("OUTRO"), play("INTRO")]);
+        assert_eq!(policy.next_in(&queue), Some(2));
+        let queue = VecDeque::from([play("PAT"), play("GRAB"), play("INTRO")]);
+        assert_eq!(policy.next_in(&queue), Some(1));
+    }
+
+    #[test]
+    fn parses_frame_sizes() {
+        assert_eq!(parse_frame_size(" 64 "), Some(64));
+        assert_eq!(parse_frame_size("0"), None);
+        assert_eq!(parse_frame_size("-4"), None);
+    }
+
+    #[test]
+    fn fitting_the_window_doesnt_compound() {
+        let mut application = DesktopGremlin::headless();
+        let mut gremlin = gremlin_with(&["IDLE"]);
+        gremlin.frame_width = Some(100);
+        gremlin.metadata.scale = Some(2.0);
+        application.current_gremlin = Some(gremlin);
+        application.fit_window_to_gremlin();
+        assert_eq!(application.base_size, (200, 300));
+        application.fit_window_to_gremlin();
+        assert_eq!(application.base_size, (200, 300));
+        assert_eq!(application.window.size(), (200, 300));
+    }
+
+    #[test]
+    fn cancels_queued_tasks() {
+        let mut application = DesktopGremlin::headless();
+        let play = |name: &str| GremlinTask::Play(name.to_string());
+        application.task_queue = VecDeque::from([
+            play("IDLE"),
+            play("IDLE"),
+            play("WAVE"),
+            GremlinTask::SetScale(2.0),
+            play("IDLE"),
+        ]);
+        application.dedup_tasks();
+        assert_eq!(
+            application.queued_task_names().collect::<Vec<_>>(),
+            ["IDLE", "WAVE", "SetScale", "IDLE"]
+        );
+        assert_eq!(application.cancel_tasks("IDLE"), 2);
+        assert_eq!(application.cancel_animations(), 1);
+        assert_eq!(
+            application.task_queue,
+            VecDeque::from([GremlinTask::SetScale(2.0)])
+        );
+    }
+
+    #[test]
+    fn headless_windows_record_what_they_were_asked() {
+        let mut application = DesktopGremlin::headless();
+        application.window.set_position(10, 20);
+        application.window.set_size(64, 64);
+        assert_eq!(application.window.rect(), Rect::new(10, 20, 64, 64));
+        assert_eq!(
+            application.headless_calls(),
+            [
+                BackendCall::Move { x: 10, y: 20 },
+                BackendCall::Resize {
+                    width: 64,
+                    height: 64
+                },
+            ]
+        );
+        assert_eq!(application.default_animation(), IDLE_ANIMATION);
+    }
+}
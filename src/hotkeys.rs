@@ -0,0 +1,30 @@
+use global_hotkey::{GlobalHotKeyManager, hotkey::HotKey};
+
+/// The one `GlobalHotKeyManager` every behavior registers its hotkeys with through
+/// `DesktopGremlin::register_hotkey`. Presses come through as `kinds::HotKey` carrying the id
+/// `register` handed out.
+#[derive(Default)]
+pub struct Hotkeys {
+    // made on the first registration, it has to be made on the thread running the event loop
+    // and dropping it unregisters everything
+    manager: Option<GlobalHotKeyManager>,
+    registered: Vec<HotKey>,
+}
+
+impl Hotkeys {
+    /// `hotkey` is written like "ctrl+shift+KeyG" or "alt+F11". Registering the same key twice
+    /// gives the same id back, so both registrations see its presses.
+    pub fn register(&mut self, hotkey: &str) -> anyhow::Result<u32> {
+        let hotkey: HotKey = hotkey.parse()?;
+        if self.registered.contains(&hotkey) {
+            return Ok(hotkey.id());
+        }
+        let manager = match &mut self.manager {
+            Some(manager) => manager,
+            manager => manager.insert(GlobalHotKeyManager::new()?),
+        };
+        manager.register(hotkey)?;
+        self.registered.push(hotkey);
+        Ok(hotkey.id())
+    }
+}
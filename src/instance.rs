@@ -0,0 +1,31 @@
+use std::{
+    fs::{self, File, TryLockError},
+    io,
+};
+
+use crate::utils::data_dir;
+
+/// Held for the whole lifetime of the gremlin, the OS releases the lock when the process exits
+/// (even if it crashed), so a stale lock file never blocks the next launch.
+pub struct InstanceLock {
+    _file: File,
+}
+
+impl InstanceLock {
+    /// `Ok(None)` when another gremlin already holds the lock
+    pub fn acquire() -> io::Result<Option<Self>> {
+        let dir = data_dir();
+        fs::create_dir_all(&dir)?;
+        let file = File::options()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(dir.join("instance.lock"))?;
+
+        match file.try_lock() {
+            Ok(()) => Ok(Some(Self { _file: file })),
+            Err(TryLockError::WouldBlock) => Ok(None),
+            Err(TryLockError::Error(err)) => Err(err),
+        }
+    }
+}
@@ -1,15 +1,22 @@
 use std::{
-    sync::{
-        Arc, Mutex,
-        mpsc::{self, Receiver, Sender},
-    },
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    sync::mpsc::{self, Receiver, Sender},
     thread::{self, JoinHandle},
-    time::Duration,
 };
 
+use anyhow::{anyhow, bail};
+use image::{GenericImage, RgbaImage};
+use tracing::debug;
+
 use crate::{
-    gremlin::{Animation, AnimationProperties},
-    threads::ThreadPool, utils::TextureCacheItem,
+    gremlin::{
+        Animation, AnimationProperties, DEFAULT_COLUMN_COUNT, GremlinLoadError, SHEET_SEARCH_DEPTH,
+    },
+    threads::ThreadPool,
+    utils::{TextureCacheItem, get_png_list, set_manifest_value},
 };
 
 pub enum LoaderTask {
@@ -17,81 +24,40 @@ pub enum LoaderTask {
     Die,
 }
 
+pub type LoaderResult = (String, Result<Animation, GremlinLoadError>);
+
+// sheets decoding at once, more only fight over the same disk and cores
+const LOADER_THREADS: usize = 2;
+
+/// Decodes sheets off the render thread on a small pool. Every `LoaderTask::Load` comes back
+/// through `result_rx` as soon as its sheet is decoded, the threads sleep on their channels
+/// in between.
 pub struct AsyncAnimationLoader {
     thread_handle: Option<JoinHandle<()>>,
     pub task_tx: Sender<LoaderTask>,
-    pub result_rx: Receiver<(String, Animation)>,
+    pub result_rx: Receiver<LoaderResult>,
 }
 
 impl Default for AsyncAnimationLoader {
     fn default() -> Self {
         let (task_tx, task_rx): (Sender<LoaderTask>, Receiver<LoaderTask>) = mpsc::channel();
-        let (result_tx, result_rx): (Sender<(String, Animation)>, Receiver<(String, Animation)>) =
+        let (result_tx, result_rx): (Sender<LoaderResult>, Receiver<LoaderResult>) =
             mpsc::channel();
 
         Self {
             thread_handle: Some(thread::spawn(move || {
-                let handle_list: Arc<Mutex<Vec<JoinHandle<(String, Animation)>>>> =
-                    Default::default();
-                let checker_handle_list = Arc::clone(&handle_list);
-                let (checker_heartbeat_tx, checker_heartbeat_rx): (Sender<bool>, Receiver<bool>) =
-                    mpsc::channel();
-                let checker_heartbeat_tx_outer = checker_heartbeat_tx.clone();
-
-                // the checker
-                thread::spawn(move || {
-                    while let Ok(true) = checker_heartbeat_rx.recv_timeout(Duration::from_secs(1)) {
-                        let mut finished_handles: Vec<usize> = Default::default();
-                        let mut handle_list = checker_handle_list.lock().unwrap();
-                        if handle_list.len() > 0 {
-                            for (index, handle) in handle_list.iter().enumerate() {
-                                if handle.is_finished() {
-                                    finished_handles.push(index);
-                                }
-                            }
-                        }
-
-                        for handle_indx in finished_handles.iter() {
-                            if let Ok(result) = handle_list.remove(*handle_indx).join() {
-                                let _ = result_tx.send(result);
-                            }
-                        }
-
-                        finished_handles.clear();
-                    }
-                    println!("loader killed");
-                });
-
-                // the processor
-                thread::spawn(move || {
-                    while let Ok(task) = task_rx.recv() {
-                        match task {
-                            LoaderTask::Load(animation_properties) => {
-                                handle_list.lock().unwrap().push(thread::spawn(move || {
-                                    (
-                                        animation_properties.animation_name.clone(),
-                                        <&AnimationProperties as TryInto<Animation>>::try_into(
-                                            &animation_properties,
-                                        )
-                                        .unwrap(),
-                                    )
-                                }));
-                            }
-                            LoaderTask::Die => {
-                                let _ = checker_heartbeat_tx.send(false);
-                                break;
-                            }
-                        }
-                    }
-                    println!("processor killed");
-                });
-                loop {
-                    if let Ok(_) = checker_heartbeat_tx_outer.send(true) {
-                        thread::sleep(Duration::from_micros(500));
-                    } else {
-                        break;
-                    }
+                // dropped on the way out, which waits for the decodes still going
+                let pool = ThreadPool::new(LOADER_THREADS);
+                while let Ok(LoaderTask::Load(animation_properties)) = task_rx.recv() {
+                    let result_tx = result_tx.clone();
+                    pool.exec(move || {
+                        let _ = result_tx.send((
+                            animation_properties.animation_name.clone(),
+                            (&animation_properties).try_into(),
+                        ));
+                    });
                 }
+                debug!("loader killed");
             })),
             task_tx,
             result_rx,
@@ -121,7 +87,265 @@ impl Default for AsyncBlitCache {
 }
 
 impl AsyncBlitCache {
-    pub fn cache(&self, _: TextureCacheItem) {
-        
+    pub fn cache(&self, _: TextureCacheItem) {}
+}
+
+/// the number a frame file ends with, `walk_012.png` is 12
+fn frame_number(path: &Path) -> Option<u32> {
+    let stem = path.file_stem()?.to_str()?;
+    let digits = stem.trim_end_matches(|c: char| !c.is_ascii_digit());
+    let start = digits
+        .rfind(|c: char| !c.is_ascii_digit())
+        .map_or(0, |index| index + 1);
+    digits[start..].parse().ok()
+}
+
+/// Packs the numbered frame pngs in `frames_dir` into a sheet `column_count` frames across, in
+/// the order of their numbers, leaving the rest of the last row empty. Every frame has to be as
+/// big as the first. Gives back the sheet and how many frames went into it.
+pub fn assemble_sheet(frames_dir: &Path, column_count: u32) -> anyhow::Result<(RgbaImage, u32)> {
+    if column_count == 0 {
+        bail!("a sheet needs at least one column");
+    }
+    let mut frames = Vec::new();
+    for entry in fs::read_dir(frames_dir)? {
+        let path = entry?.path();
+        if !path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("png"))
+        {
+            continue;
+        }
+        let Some(number) = frame_number(&path) else {
+            bail!(
+                "{} isn't numbered, can't tell where it goes",
+                path.display()
+            );
+        };
+        frames.push((number, path));
+    }
+    frames.sort();
+    if let Some(pair) = frames.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+        bail!(
+            "{} and {} are both frame {}",
+            pair[0].1.display(),
+            pair[1].1.display(),
+            pair[0].0
+        );
+    }
+    let Some((_, first)) = frames.first() else {
+        bail!("there are no pngs in {}", frames_dir.display());
+    };
+    let (frame_width, frame_height) = image::image_dimensions(first)?;
+    let frame_count = frames.len() as u32;
+    let mut sheet = RgbaImage::new(
+        frame_width * column_count,
+        frame_height * frame_count.div_ceil(column_count),
+    );
+    for (index, (_, path)) in frames.iter().enumerate() {
+        let frame = image::open(path)
+            .map_err(|err| anyhow!("couldn't read {}: {err}", path.display()))?
+            .into_rgba8();
+        if frame.dimensions() != (frame_width, frame_height) {
+            bail!(
+                "{} is {}x{}, the frames before it are {frame_width}x{frame_height}",
+                path.display(),
+                frame.width(),
+                frame.height()
+            );
+        }
+        let index = index as u32;
+        sheet.copy_from(
+            &frame,
+            index % column_count * frame_width,
+            index / column_count * frame_height,
+        )?;
+    }
+    Ok((sheet, frame_count))
+}
+
+/// Assembles `frames_dir` into `<name>.png` next to the gremlin's `manifest_path` and sets the
+/// animation's frame count in the manifest. When the animation is already there both get
+/// replaced, its sheet wherever it is, so the loader doesn't find two sheets by that name.
+pub fn add_frame_sequence(
+    frames_dir: &Path,
+    manifest_path: &Path,
+    name: &str,
+) -> anyhow::Result<PathBuf> {
+    let manifest = fs::read_to_string(manifest_path)
+        .map_err(|err| anyhow!("couldn't read {}: {err}", manifest_path.display()))?;
+    let (sheet, frame_count) = assemble_sheet(frames_dir, DEFAULT_COLUMN_COUNT)?;
+    let gremlin_dir = manifest_path.parent().unwrap_or(Path::new("."));
+    let mut sheets = HashMap::new();
+    get_png_list(
+        &gremlin_dir.to_string_lossy(),
+        SHEET_SEARCH_DEPTH,
+        &mut sheets,
+    )?;
+    let sheet_path = sheets
+        .remove(&name.to_uppercase())
+        .unwrap_or_else(|| gremlin_dir.join(format!("{}.png", name.to_lowercase())));
+    sheet.save(&sheet_path)?;
+    fs::write(
+        manifest_path,
+        set_manifest_value(
+            &manifest,
+            "",
+            &name.to_uppercase(),
+            &frame_count.to_string(),
+        ),
+    )?;
+    Ok(sheet_path)
+}
+
+/// `desktop_gremlin --assemble <frames folder> --into <manifest> [--name <name>]` adds the frames
+/// to a gremlin as one animation, `--columns <count>` only writes the sheet to `--into` instead,
+/// for sheets the loader doesn't cut up itself
+pub fn run_assemble_cli(
+    frames_dir: &str,
+    into: Option<String>,
+    name: Option<String>,
+    columns: Option<String>,
+) -> ExitCode {
+    let frames_dir = Path::new(frames_dir);
+    let usage = "usage: desktop_gremlin --assemble <frames folder> --into <manifest> [--name <name>]\n       desktop_gremlin --assemble <frames folder> --columns <count> --into <sheet.png>";
+    let Some(into) = into.map(PathBuf::from) else {
+        eprintln!("{usage}");
+        return ExitCode::FAILURE;
+    };
+    let result = match columns {
+        Some(columns) => {
+            let Ok(column_count) = columns.parse() else {
+                eprintln!("{usage}");
+                return ExitCode::FAILURE;
+            };
+            assemble_sheet(frames_dir, column_count).and_then(|(sheet, frame_count)| {
+                sheet.save(&into)?;
+                Ok(format!(
+                    "packed {frame_count} frames into {}",
+                    into.display()
+                ))
+            })
+        }
+        None => {
+            let Some(name) = name.or_else(|| {
+                frames_dir
+                    .canonicalize()
+                    .ok()?
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+            }) else {
+                eprintln!("{usage}");
+                return ExitCode::FAILURE;
+            };
+            add_frame_sequence(frames_dir, &into, &name).map(|sheet_path| {
+                format!(
+                    "added {} to {} as {}",
+                    name.to_uppercase(),
+                    into.display(),
+                    sheet_path.display()
+                )
+            })
+        }
+    };
+    match result {
+        Ok(message) => {
+            println!("{message}");
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("couldn't assemble the frames: {err:#}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use super::*;
+
+    /// an empty directory of its own under the system's temp directory
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("desktop_gremlin-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn save_frame(dir: &Path, file_name: &str, shade: u8, size: u32) {
+        RgbaImage::from_pixel(size, size, Rgba([shade, 0, 0, 255]))
+            .save(dir.join(file_name))
+            .unwrap();
+    }
+
+    #[test]
+    fn frame_numbers_come_from_the_end() {
+        assert_eq!(frame_number(Path::new("walk_012.png")), Some(12));
+        assert_eq!(frame_number(Path::new("dir/7.png")), Some(7));
+        assert_eq!(frame_number(Path::new("frame2 copy.png")), Some(2));
+        assert_eq!(frame_number(Path::new("run2_4.png")), Some(4));
+        assert_eq!(frame_number(Path::new("walk.png")), None);
+    }
+
+    #[test]
+    fn assembles_frames_in_number_order() {
+        let dir = scratch_dir("assemble");
+        save_frame(&dir, "walk_10.png", 10, 2);
+        save_frame(&dir, "walk_2.png", 2, 2);
+        save_frame(&dir, "walk_1.png", 1, 2);
+        fs::write(dir.join("notes.txt"), "not a frame").unwrap();
+
+        let (sheet, frame_count) = assemble_sheet(&dir, 2).unwrap();
+        assert_eq!(frame_count, 3);
+        assert_eq!(sheet.dimensions(), (4, 4));
+        assert_eq!(sheet.get_pixel(0, 0)[0], 1);
+        assert_eq!(sheet.get_pixel(2, 0)[0], 2);
+        assert_eq!(sheet.get_pixel(0, 2)[0], 10);
+        // what's left of the last row stays empty
+        assert_eq!(sheet.get_pixel(2, 2)[3], 0);
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn replaces_sheets_where_they_are() {
+        let gremlin_dir = scratch_dir("replace");
+        let frames_dir = gremlin_dir.join("frames");
+        let walk_dir = gremlin_dir.join("Walk");
+        fs::create_dir_all(&frames_dir).unwrap();
+        fs::create_dir_all(&walk_dir).unwrap();
+        save_frame(&frames_dir, "1.png", 0, 2);
+        save_frame(&walk_dir, "walkleft.png", 0, 4);
+        let manifest_path = gremlin_dir.join("config.txt");
+        fs::write(&manifest_path, "WALKLEFT=1\n").unwrap();
+
+        let sheet_path = add_frame_sequence(&frames_dir, &manifest_path, "walkleft").unwrap();
+        assert_eq!(sheet_path, walk_dir.join("walkleft.png"));
+        assert!(!gremlin_dir.join("walkleft.png").exists());
+
+        let sheet_path = add_frame_sequence(&frames_dir, &manifest_path, "hop").unwrap();
+        assert_eq!(sheet_path, gremlin_dir.join("hop.png"));
+        let _ = fs::remove_dir_all(gremlin_dir);
+    }
+
+    #[test]
+    fn refuses_frames_that_dont_fit() {
+        let dir = scratch_dir("mismatch");
+        save_frame(&dir, "1.png", 0, 2);
+        save_frame(&dir, "2.png", 0, 3);
+        assert!(assemble_sheet(&dir, 2).is_err());
+
+        let _ = fs::remove_file(dir.join("2.png"));
+        save_frame(&dir, "01.png", 0, 2);
+        // two frame 1s
+        assert!(assemble_sheet(&dir, 2).is_err());
+
+        let _ = fs::remove_file(dir.join("01.png"));
+        save_frame(&dir, "cover.png", 0, 2);
+        assert!(assemble_sheet(&dir, 2).is_err());
+        assert!(assemble_sheet(&dir, 0).is_err());
+        let _ = fs::remove_dir_all(dir);
     }
 }
@@ -0,0 +1,812 @@
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    path::{Component, Path, PathBuf},
+    process::ExitCode,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use interprocess::local_socket::{
+    GenericFilePath, GenericNamespaced, ListenerNonblockingMode, ListenerOptions, Name, Stream,
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::{
+    achievements,
+    backend::WindowLayer,
+    behavior::{
+        CATCH_TOGGLE_EVENT, EDITOR_TOGGLE_EVENT, INK_TOGGLE_EVENT, POMODORO_START_EVENT,
+        POMODORO_STOP_EVENT, PRESENT_TOGGLE_EVENT, VISIT_HOME_EVENT, capture_path, screenshots_dir,
+    },
+    events::Event,
+    gremlin::{DesktopGremlin, GremlinTask},
+    profile::PhaseTiming,
+    recording::MAX_RECORDING,
+};
+
+const SOCKET_NAME: &str = "desktop_gremlin.sock";
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+// the main loop answers within a frame unless it's stuck
+const REPLY_TIMEOUT: Duration = Duration::from_secs(2);
+// longest a `say` bubble stays up
+const MAX_SPEECH: Duration = Duration::from_secs(600);
+
+/// One JSON object per line, e.g. `{"command": "play", "animation": "IDLE"}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum IpcCommand {
+    Play {
+        animation: String,
+        #[serde(default)]
+        interrupt: bool,
+    },
+    MoveTo {
+        x: i32,
+        y: i32,
+    },
+    /// walks there instead of teleporting
+    Goto {
+        x: i32,
+        y: i32,
+    },
+    SetScale {
+        scale: f32,
+    },
+    /// behind the desktop icons or back on top of everything, windows only
+    Wallpaper {
+        enabled: bool,
+    },
+    /// slides the gremlin onto a screen edge it's dropped within `distance` pixels of, never
+    /// without a distance
+    Snap {
+        distance: Option<u32>,
+    },
+    SetSpeed {
+        speed: f32,
+    },
+    /// answers with the framerate it was clamped to
+    SetFramerate {
+        framerate: u32,
+    },
+    /// how loud animation sounds play, 0 to 1
+    SetVolume {
+        volume: f32,
+    },
+    /// answers with whether it's muted now
+    Mute {
+        muted: bool,
+    },
+    Say {
+        text: String,
+        #[serde(default = "default_say_seconds")]
+        seconds: f32,
+    },
+    Notify {
+        title: String,
+        #[serde(default)]
+        body: String,
+    },
+    /// the gremlin answers in a speech bubble, needs the `chat` feature
+    Chat {
+        text: String,
+    },
+    Pause,
+    Resume,
+    ToggleDebugOverlay,
+    /// saves the frame on screen as a png, named `path` or after the time, always into
+    /// `screenshots_dir()`. Answers with where it goes
+    Screenshot {
+        #[serde(default)]
+        path: Option<PathBuf>,
+    },
+    /// records the next few seconds into a looping gif, named `path` or after the time, always
+    /// into `screenshots_dir()`. Answers with where it goes
+    Record {
+        #[serde(default = "default_record_seconds")]
+        seconds: f32,
+        #[serde(default)]
+        path: Option<PathBuf>,
+    },
+    /// starts the pomodoro timer over, see `GremlinPomodoro`
+    PomodoroStart,
+    PomodoroStop,
+    /// turns presentation mode on or off, see `GremlinPresenter`
+    Present,
+    /// lets the user draw on the screen or stops it, see `GremlinInk`
+    Ink,
+    /// opens the animation editor or closes it, see `GremlinEditor`
+    Editor,
+    /// starts a round of catch or ends it early, see `GremlinCatch`
+    Catch,
+    /// puts the accessory on or takes it off, everything comes off without one.
+    /// Answers with what's worn now
+    Wear {
+        #[serde(default)]
+        accessory: Option<String>,
+    },
+    /// answers with every achievement and whether it's been earned
+    Achievements,
+    /// answers with the rolling average time of every frame phase, slowest first
+    Profile,
+    /// answers with the texture cache's hit/miss/eviction counts and size
+    CacheStats,
+    /// answers with the names of the queued tasks, next up first
+    Tasks,
+    /// sends every gremlin visiting from the LAN home, see `GremlinVisits`
+    SendHome,
+    /// another copy of the gremlin was started without anything to forward
+    Launched,
+    Quit,
+}
+
+fn default_say_seconds() -> f32 {
+    5.0
+}
+
+fn default_record_seconds() -> f32 {
+    5.0
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IpcResponse {
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    // whatever a query command answers with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct AchievementStatus {
+    id: &'static str,
+    title: &'static str,
+    description: &'static str,
+    earned: bool,
+}
+
+impl IpcResponse {
+    fn ok() -> Self {
+        Self {
+            ok: true,
+            error: None,
+            data: None,
+        }
+    }
+
+    fn error(error: impl ToString) -> Self {
+        Self {
+            ok: false,
+            error: Some(error.to_string()),
+            data: None,
+        }
+    }
+
+    fn with_data(data: impl Serialize) -> Self {
+        match serde_json::to_value(data) {
+            Ok(data) => Self {
+                data: Some(data),
+                ..Self::ok()
+            },
+            Err(err) => Self::error(err),
+        }
+    }
+}
+
+impl IpcCommand {
+    /// runs the command against the application, called from the main loop
+    pub fn apply(self, application: &mut DesktopGremlin) -> IpcResponse {
+        let task_tx = &application.task_channel.0;
+        match self {
+            IpcCommand::Play {
+                animation,
+                interrupt,
+            } => {
+                let animation = animation.to_uppercase();
+                if interrupt {
+                    let default_animation = application.default_animation().to_string();
+                    application.cancel_tasks(&default_animation);
+                    let task_tx = &application.task_channel.0;
+                    let _ = task_tx.send(GremlinTask::PlayInterrupt(animation));
+                    let _ = task_tx.send(GremlinTask::Play(default_animation));
+                } else {
+                    let _ = task_tx.send(GremlinTask::Play(animation));
+                }
+            }
+            IpcCommand::MoveTo { x, y } => {
+                application.window.set_position(x, y);
+            }
+            IpcCommand::Goto { x, y } => {
+                let _ = task_tx.send(GremlinTask::Goto(x, y));
+            }
+            IpcCommand::SetScale { scale } => {
+                let _ = task_tx.send(GremlinTask::SetScale(scale));
+            }
+            IpcCommand::Snap { distance } => {
+                application.snap_distance = distance;
+            }
+            IpcCommand::Wallpaper { enabled } => {
+                let layer = if enabled {
+                    WindowLayer::Wallpaper
+                } else {
+                    WindowLayer::OnTop
+                };
+                if let Err(err) = application.window.set_layer(layer) {
+                    return IpcResponse::error(format!("{err:#}"));
+                }
+            }
+            IpcCommand::SetSpeed { speed } => {
+                let _ = task_tx.send(GremlinTask::SetSpeed(speed));
+            }
+            IpcCommand::SetFramerate { framerate } => {
+                application.set_target_framerate(framerate);
+                return IpcResponse::with_data(application.target_framerate);
+            }
+            IpcCommand::SetVolume { volume } => {
+                let Some(audio) = &mut application.audio else {
+                    return IpcResponse::error("there's no audio device to play sounds on");
+                };
+                if !(0.0..=1.0).contains(&volume) {
+                    return IpcResponse::error(format!("volume goes from 0 to 1, not {volume}"));
+                }
+                audio.set_volume(volume);
+            }
+            IpcCommand::Mute { muted } => {
+                let Some(audio) = &mut application.audio else {
+                    return IpcResponse::error("there's no audio device to play sounds on");
+                };
+                audio.set_muted(muted);
+                return IpcResponse::with_data(audio.is_muted());
+            }
+            IpcCommand::Say { text, seconds } => {
+                let Ok(duration) = Duration::try_from_secs_f32(seconds) else {
+                    return IpcResponse::error(format!(
+                        "can't say something for {seconds} seconds"
+                    ));
+                };
+                application.say(text, duration.min(MAX_SPEECH));
+            }
+            IpcCommand::Notify { title, body } => {
+                application.notify(title, body);
+            }
+            IpcCommand::Chat { text } => {
+                if !cfg!(feature = "chat") {
+                    return IpcResponse::error("this gremlin was built without the chat feature");
+                }
+                application.chat_prompts.push_back(text);
+            }
+            IpcCommand::Pause => {
+                let _ = task_tx.send(GremlinTask::Pause);
+            }
+            IpcCommand::Resume => {
+                let _ = task_tx.send(GremlinTask::Resume);
+            }
+            IpcCommand::ToggleDebugOverlay => {
+                let _ = task_tx.send(GremlinTask::ToggleDebugOverlay);
+            }
+            IpcCommand::Screenshot { path } => {
+                let path = match path {
+                    Some(path) => match capture_target(&path) {
+                        Ok(path) => path,
+                        Err(err) => return IpcResponse::error(err),
+                    },
+                    None => capture_path(application, "png"),
+                };
+                let _ = task_tx.send(GremlinTask::Screenshot(path.clone()));
+                return IpcResponse::with_data(path);
+            }
+            IpcCommand::Record { seconds, path } => {
+                let path = match path {
+                    Some(path) => match capture_target(&path) {
+                        Ok(path) => path,
+                        Err(err) => return IpcResponse::error(err),
+                    },
+                    None => capture_path(application, "gif"),
+                };
+                let _ = task_tx.send(GremlinTask::Record {
+                    path: path.clone(),
+                    duration: Duration::from_secs_f32(
+                        seconds.clamp(0.0, MAX_RECORDING.as_secs_f32()),
+                    ),
+                });
+                return IpcResponse::with_data(path);
+            }
+            IpcCommand::PomodoroStart => {
+                application.emit(Event::Custom(POMODORO_START_EVENT.to_string()), None);
+            }
+            IpcCommand::PomodoroStop => {
+                application.emit(Event::Custom(POMODORO_STOP_EVENT.to_string()), None);
+            }
+            IpcCommand::Present => {
+                application.emit(Event::Custom(PRESENT_TOGGLE_EVENT.to_string()), None);
+            }
+            IpcCommand::Ink => {
+                application.emit(Event::Custom(INK_TOGGLE_EVENT.to_string()), None);
+            }
+            IpcCommand::Editor => {
+                application.emit(Event::Custom(EDITOR_TOGGLE_EVENT.to_string()), None);
+            }
+            IpcCommand::Catch => {
+                application.emit(Event::Custom(CATCH_TOGGLE_EVENT.to_string()), None);
+            }
+            IpcCommand::Wear { accessory } => {
+                let Some(accessory) = accessory.map(|accessory| accessory.to_uppercase()) else {
+                    application.accessories.clear();
+                    return IpcResponse::with_data(&application.accessories);
+                };
+                if let Some(index) = application
+                    .accessories
+                    .iter()
+                    .position(|worn| *worn == accessory)
+                {
+                    application.accessories.remove(index);
+                } else {
+                    let Some(gremlin) = &application.current_gremlin else {
+                        return IpcResponse::error("there's no gremlin to put it on");
+                    };
+                    if gremlin.locked.contains(&accessory)
+                        && let Some(achievement) = gremlin
+                            .unlocks
+                            .get(&accessory)
+                            .and_then(|id| achievements::find(id))
+                    {
+                        return IpcResponse::error(format!(
+                            "{accessory} is locked until \"{}\": {}",
+                            achievement.title, achievement.description
+                        ));
+                    }
+                    if !gremlin
+                        .accessories
+                        .iter()
+                        .any(|available| available.name == accessory)
+                    {
+                        let available: Vec<&str> = gremlin
+                            .accessories
+                            .iter()
+                            .map(|available| available.name.as_str())
+                            .collect();
+                        return IpcResponse::error(format!(
+                            "{} has no {accessory}, it has: {}",
+                            gremlin.name,
+                            available.join(", ")
+                        ));
+                    }
+                    application.accessories.push(accessory);
+                }
+                return IpcResponse::with_data(&application.accessories);
+            }
+            IpcCommand::Achievements => {
+                let listed: Vec<AchievementStatus> = achievements::ACHIEVEMENTS
+                    .iter()
+                    .map(|achievement| AchievementStatus {
+                        id: achievement.id,
+                        title: achievement.title,
+                        description: achievement.description,
+                        earned: application.achievements.has(achievement.id),
+                    })
+                    .collect();
+                return IpcResponse::with_data(listed);
+            }
+            IpcCommand::Profile => {
+                let timings: Vec<PhaseTiming> = application.profile.slowest(usize::MAX);
+                return IpcResponse::with_data(timings);
+            }
+            IpcCommand::Tasks => {
+                let names: Vec<&str> = application.queued_task_names().collect();
+                return IpcResponse::with_data(names);
+            }
+            IpcCommand::CacheStats => {
+                return match application.texture_cache.lock() {
+                    Ok(cache) => IpcResponse::with_data(cache.stats()),
+                    Err(_) => IpcResponse::error("texture cache is poisoned"),
+                };
+            }
+            IpcCommand::SendHome => {
+                application.emit(Event::Custom(VISIT_HOME_EVENT.to_string()), None);
+            }
+            IpcCommand::Launched => {
+                application.say("i'm already here!", Duration::from_secs(3));
+            }
+            // like closing the window, every behavior gets to wrap up
+            IpcCommand::Quit => {
+                application.emit(Event::Quit, None);
+            }
+        }
+        IpcResponse::ok()
+    }
+
+    /// parses the shell-friendly form, e.g. `play IDLE`, `say "hello there"`, `move 100 200`,
+    /// anything starting with `{` is taken as a raw json command
+    pub fn from_args(args: &[String]) -> Result<IpcCommand, String> {
+        let Some(command) = args.first() else {
+            return Err(USAGE.to_string());
+        };
+        if command.trim_start().starts_with('{') {
+            return serde_json::from_str(&args.join(" ")).map_err(|err| err.to_string());
+        }
+
+        let rest = &args[1..];
+        let parse_number = |index: usize, what: &str| -> Result<f32, String> {
+            rest.get(index)
+                .ok_or(format!("missing {what}"))?
+                .parse::<f32>()
+                .map_err(|_| format!("{what} should be a number"))
+        };
+        match command.as_str() {
+            "play" => Ok(IpcCommand::Play {
+                animation: rest.first().ok_or("missing animation name")?.clone(),
+                interrupt: rest.iter().any(|arg| arg == "--interrupt" || arg == "-i"),
+            }),
+            "move" => Ok(IpcCommand::MoveTo {
+                x: parse_number(0, "x")? as i32,
+                y: parse_number(1, "y")? as i32,
+            }),
+            "goto" => Ok(IpcCommand::Goto {
+                x: parse_number(0, "x")? as i32,
+                y: parse_number(1, "y")? as i32,
+            }),
+            "scale" => Ok(IpcCommand::SetScale {
+                scale: parse_number(0, "scale")?,
+            }),
+            "wallpaper" => match rest.first().map(String::as_str) {
+                Some("on") => Ok(IpcCommand::Wallpaper { enabled: true }),
+                Some("off") => Ok(IpcCommand::Wallpaper { enabled: false }),
+                _ => Err("wallpaper takes on or off".to_string()),
+            },
+            "snap" => match rest.first().map(String::as_str) {
+                Some("off") => Ok(IpcCommand::Snap { distance: None }),
+                Some(distance) => Ok(IpcCommand::Snap {
+                    distance: Some(
+                        distance
+                            .parse()
+                            .map_err(|_| "snap takes a distance in pixels or off")?,
+                    ),
+                }),
+                None => Err("snap takes a distance in pixels or off".to_string()),
+            },
+            "speed" => Ok(IpcCommand::SetSpeed {
+                speed: parse_number(0, "speed")?,
+            }),
+            "fps" => Ok(IpcCommand::SetFramerate {
+                framerate: parse_number(0, "framerate")?.round().max(0.0) as u32,
+            }),
+            "volume" => Ok(IpcCommand::SetVolume {
+                volume: parse_number(0, "volume")?,
+            }),
+            "mute" => Ok(IpcCommand::Mute { muted: true }),
+            "unmute" => Ok(IpcCommand::Mute { muted: false }),
+            "say" if !rest.is_empty() => Ok(IpcCommand::Say {
+                text: rest.join(" "),
+                seconds: default_say_seconds(),
+            }),
+            "notify" => Ok(IpcCommand::Notify {
+                title: rest.first().ok_or("missing title")?.clone(),
+                body: rest[1..].join(" "),
+            }),
+            "chat" if !rest.is_empty() => Ok(IpcCommand::Chat {
+                text: rest.join(" "),
+            }),
+            "pause" => Ok(IpcCommand::Pause),
+            "resume" => Ok(IpcCommand::Resume),
+            "debug" => Ok(IpcCommand::ToggleDebugOverlay),
+            // a file name in `screenshots_dir()`, see `capture_target`
+            "screenshot" => Ok(IpcCommand::Screenshot {
+                path: rest.first().map(PathBuf::from),
+            }),
+            "record" => Ok(IpcCommand::Record {
+                seconds: rest
+                    .first()
+                    .map(|_| parse_number(0, "seconds"))
+                    .transpose()?
+                    .unwrap_or_else(default_record_seconds),
+                path: rest.get(1).map(PathBuf::from),
+            }),
+            "pomodoro" => match rest.first().map(String::as_str) {
+                Some("start") => Ok(IpcCommand::PomodoroStart),
+                Some("stop") => Ok(IpcCommand::PomodoroStop),
+                _ => Err("pomodoro takes start or stop".to_string()),
+            },
+            "present" => Ok(IpcCommand::Present),
+            "ink" => Ok(IpcCommand::Ink),
+            "editor" => Ok(IpcCommand::Editor),
+            "catch" => Ok(IpcCommand::Catch),
+            "wear" => Ok(IpcCommand::Wear {
+                accessory: rest.first().filter(|name| *name != "off").cloned(),
+            }),
+            "achievements" => Ok(IpcCommand::Achievements),
+            "profile" => Ok(IpcCommand::Profile),
+            "cache" => Ok(IpcCommand::CacheStats),
+            "tasks" => Ok(IpcCommand::Tasks),
+            "home" => Ok(IpcCommand::SendHome),
+            "quit" => Ok(IpcCommand::Quit),
+            _ => Err(USAGE.to_string()),
+        }
+    }
+}
+
+const USAGE: &str = "usage: desktop_gremlin send <command>
+commands:
+    play <ANIMATION> [--interrupt]
+    move <x> <y>
+    goto <x> <y>
+    scale <factor>
+    speed <factor>
+    fps <frames per second>
+    volume <0-1>
+    mute
+    unmute
+    wallpaper on|off
+    snap <pixels>|off
+    say <text...>
+    notify <title> [body...]
+    chat <text...>
+    pause
+    resume
+    debug
+    screenshot [name.png]
+    record [seconds] [name.gif]
+    pomodoro start|stop
+    present
+    ink
+    editor
+    catch
+    wear <ACCESSORY>|off
+    achievements
+    profile
+    cache
+    tasks
+    home
+    quit
+    '{\"command\": ...}'       raw json";
+
+/// sends a single command to the running gremlin and waits for its answer
+pub fn send(command: &IpcCommand) -> io::Result<IpcResponse> {
+    let stream = Stream::connect(socket_name()?)?;
+    let mut reader = BufReader::new(stream);
+
+    let mut request = serde_json::to_string(command).map_err(io::Error::other)?;
+    request.push('\n');
+    reader.get_mut().write_all(request.as_bytes())?;
+
+    let mut response = String::new();
+    reader.read_line(&mut response)?;
+    serde_json::from_str(&response).map_err(io::Error::other)
+}
+
+/// entry point for `desktop_gremlin send ...`
+pub fn run_client(args: &[String]) -> ExitCode {
+    match IpcCommand::from_args(args) {
+        Ok(command) => run_command(&command),
+        Err(err) => {
+            eprintln!("{err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// a second copy was started, hand its `--scale`/`--fps` and the rest of its args over to the one
+/// already running
+pub fn forward_launch(args: &[String], scale: Option<f32>, framerate: Option<u32>) -> ExitCode {
+    let mut commands = Vec::new();
+    if let Some(scale) = scale {
+        commands.push(IpcCommand::SetScale { scale });
+    }
+    if let Some(framerate) = framerate {
+        commands.push(IpcCommand::SetFramerate { framerate });
+    }
+    if commands.is_empty() && args.is_empty() {
+        return run_command(&IpcCommand::Launched);
+    }
+    let mut exit_code = ExitCode::SUCCESS;
+    for command in &commands {
+        if run_command(command) != ExitCode::SUCCESS {
+            exit_code = ExitCode::FAILURE;
+        }
+    }
+    if !args.is_empty() && run_client(args) != ExitCode::SUCCESS {
+        exit_code = ExitCode::FAILURE;
+    }
+    exit_code
+}
+
+fn run_command(command: &IpcCommand) -> ExitCode {
+    match send(command) {
+        Ok(IpcResponse { ok: true, data, .. }) => {
+            if let Some(data) = data {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&data).unwrap_or_default()
+                );
+            }
+            ExitCode::SUCCESS
+        }
+        Ok(IpcResponse { error, .. }) => {
+            eprintln!("the gremlin refused: {}", error.unwrap_or_default());
+            ExitCode::FAILURE
+        }
+        Err(err) => {
+            eprintln!("couldn't reach the gremlin, is it running? ({err})");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// a named pipe on windows, elsewhere a socket file in a directory only this user can get into,
+/// linux's abstract sockets would let every user on the machine drive the gremlin
+pub fn socket_name() -> io::Result<Name<'static>> {
+    if cfg!(target_os = "windows") {
+        SOCKET_NAME.to_ns_name::<GenericNamespaced>()
+    } else {
+        socket_dir()?
+            .join(SOCKET_NAME)
+            .to_fs_name::<GenericFilePath>()
+    }
+}
+
+/// `XDG_RUNTIME_DIR`, which is the user's own already, or a 0700 directory in the temp directory
+#[cfg(unix)]
+fn socket_dir() -> io::Result<PathBuf> {
+    use std::{
+        fs::{self, DirBuilder, Permissions},
+        os::unix::fs::{DirBuilderExt, PermissionsExt},
+    };
+
+    if let Some(dir) = std::env::var_os("XDG_RUNTIME_DIR").filter(|dir| !dir.is_empty()) {
+        return Ok(dir.into());
+    }
+    let user = std::env::var("USER").unwrap_or_default();
+    let dir = std::env::temp_dir().join(format!("desktop_gremlin-{user}"));
+    match DirBuilder::new().mode(0o700).create(&dir) {
+        Err(err) if err.kind() != io::ErrorKind::AlreadyExists => return Err(err),
+        _ => {}
+    }
+    // someone else could have made it first, only its owner gets to change its permissions
+    if !fs::symlink_metadata(&dir)?.is_dir() {
+        return Err(io::Error::other(format!(
+            "{} isn't a directory",
+            dir.display()
+        )));
+    }
+    fs::set_permissions(&dir, Permissions::from_mode(0o700))?;
+    Ok(dir)
+}
+
+#[cfg(not(unix))]
+fn socket_dir() -> io::Result<PathBuf> {
+    Ok(std::env::temp_dir())
+}
+
+/// where a capture asked for over ipc goes, somewhere in `screenshots_dir()` so a caller can't
+/// have the gremlin write over files elsewhere
+fn capture_target(path: &Path) -> Result<PathBuf, String> {
+    let dir = screenshots_dir();
+    let relative = path.strip_prefix(&dir).unwrap_or(path);
+    if relative.as_os_str().is_empty()
+        || !relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+    {
+        return Err(format!(
+            "captures go in {}, give a file name in there",
+            dir.display()
+        ));
+    }
+    Ok(dir.join(relative))
+}
+
+/// Local command server, accepts connections on its own thread and forwards parsed
+/// commands to the main loop, which answers them in `handle_pending()`.
+pub struct IpcServer {
+    thread_handle: Option<JoinHandle<()>>,
+    should_stop: Arc<AtomicBool>,
+    command_rx: Receiver<(IpcCommand, Sender<IpcResponse>)>,
+}
+
+impl IpcServer {
+    pub fn start() -> io::Result<Self> {
+        let listener = ListenerOptions::new()
+            .name(socket_name()?)
+            // we hold the instance lock, so a leftover socket file can only be from a crashed gremlin
+            .try_overwrite(true)
+            .nonblocking(ListenerNonblockingMode::Accept)
+            .create_sync()?;
+        let (command_tx, command_rx) = mpsc::channel();
+        let should_stop = Arc::new(AtomicBool::new(false));
+        let thread_should_stop = Arc::clone(&should_stop);
+
+        let thread_handle = thread::spawn(move || {
+            while !thread_should_stop.load(Ordering::Relaxed) {
+                match listener.accept() {
+                    Ok(stream) => {
+                        let command_tx = command_tx.clone();
+                        thread::spawn(move || handle_connection(stream, command_tx));
+                    }
+                    Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                        thread::sleep(ACCEPT_POLL_INTERVAL);
+                    }
+                    Err(err) => {
+                        warn!("ipc connection failed: {err}");
+                    }
+                }
+            }
+        });
+
+        Ok(Self {
+            thread_handle: Some(thread_handle),
+            should_stop,
+            command_rx,
+        })
+    }
+
+    /// applies every command received since the last call and answers them
+    pub fn handle_pending(&self, application: &mut DesktopGremlin) {
+        while let Ok((command, reply_tx)) = self.command_rx.try_recv() {
+            let _ = reply_tx.send(command.apply(application));
+        }
+    }
+}
+
+impl Drop for IpcServer {
+    fn drop(&mut self) {
+        self.should_stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.thread_handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn handle_connection(stream: Stream, command_tx: Sender<(IpcCommand, Sender<IpcResponse>)>) {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    while let Ok(read) = reader.read_line(&mut line) {
+        if read == 0 {
+            break;
+        }
+        let response = match serde_json::from_str::<IpcCommand>(line.trim()) {
+            Ok(command) => {
+                let (reply_tx, reply_rx) = mpsc::channel();
+                match command_tx.send((command, reply_tx)) {
+                    Ok(_) => reply_rx
+                        .recv_timeout(REPLY_TIMEOUT)
+                        .unwrap_or_else(|_| IpcResponse::error("the gremlin didn't answer")),
+                    Err(_) => IpcResponse::error("the gremlin is shutting down"),
+                }
+            }
+            Err(err) => IpcResponse::error(err),
+        };
+        line.clear();
+
+        let Ok(mut response) = serde_json::to_string(&response) else {
+            break;
+        };
+        response.push('\n');
+        if reader.get_mut().write_all(response.as_bytes()).is_err() {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn captures_stay_in_the_screenshots_dir() {
+        let dir = screenshots_dir();
+        assert_eq!(capture_target(Path::new("a.png")), Ok(dir.join("a.png")));
+        assert_eq!(
+            capture_target(&dir.join("clips/b.gif")),
+            Ok(dir.join("clips/b.gif"))
+        );
+        for outside in ["../a.png", "clips/../../a.png", "", "/etc/passwd"] {
+            assert!(capture_target(Path::new(outside)).is_err(), "{outside}");
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use std::{
+    sync::mpsc::{self, Receiver, RecvTimeoutError, Sender},
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+/// Counts key presses anywhere on the desktop on its own thread and sends how many there were
+/// since the last poll, whenever there were any. Only ever counts: which keys were pressed never
+/// leaves the probe. Sends nothing where presses can't be counted (wayland).
+pub struct KeystrokeMonitor {
+    thread_handle: Option<JoinHandle<()>>,
+    stop_tx: Sender<()>,
+}
+
+impl KeystrokeMonitor {
+    pub fn spawn(interval: Duration) -> (Self, Receiver<u32>) {
+        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+        let (count_tx, count_rx) = mpsc::channel();
+
+        let thread_handle = thread::spawn(move || {
+            let Some(mut probe) = KeystrokeProbe::connect() else {
+                return;
+            };
+            while let Err(RecvTimeoutError::Timeout) = stop_rx.recv_timeout(interval) {
+                let presses = probe.presses();
+                if presses > 0 && count_tx.send(presses).is_err() {
+                    break;
+                }
+            }
+        });
+
+        (
+            Self {
+                thread_handle: Some(thread_handle),
+                stop_tx,
+            },
+            count_rx,
+        )
+    }
+}
+
+impl Drop for KeystrokeMonitor {
+    fn drop(&mut self) {
+        let _ = self.stop_tx.send(());
+        if let Some(handle) = self.thread_handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// how many keys went down between two snapshots of which keys are down, one bit per key
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+fn newly_down(last: &[u8], now: &[u8]) -> u32 {
+    last.iter()
+        .zip(now)
+        .map(|(last, now)| (now & !last).count_ones())
+        .sum()
+}
+
+#[cfg(target_os = "windows")]
+struct KeystrokeProbe {
+    down: [u8; 32],
+}
+
+#[cfg(target_os = "windows")]
+impl KeystrokeProbe {
+    fn connect() -> Option<Self> {
+        Some(Self { down: [0; 32] })
+    }
+
+    fn presses(&mut self) -> u32 {
+        use windows::Win32::UI::Input::KeyboardAndMouse::GetAsyncKeyState;
+
+        // 0x01 to 0x06 are mouse buttons
+        const FIRST_KEY: i32 = 0x08;
+        let mut down = [0u8; 32];
+        for key in FIRST_KEY..=0xfe {
+            // the high bit is set while the key is down
+            if unsafe { GetAsyncKeyState(key) } < 0 {
+                down[key as usize / 8] |= 1 << (key % 8);
+            }
+        }
+        let presses = newly_down(&self.down, &down);
+        self.down = down;
+        presses
+    }
+}
+
+#[cfg(target_os = "linux")]
+struct KeystrokeProbe {
+    connection: x11rb::rust_connection::RustConnection,
+    down: Vec<u8>,
+}
+
+#[cfg(target_os = "linux")]
+impl KeystrokeProbe {
+    /// only works on X11, wayland doesn't tell clients about keys pressed in other windows
+    fn connect() -> Option<Self> {
+        let (connection, _) = x11rb::connect(None)
+            .inspect_err(|err| tracing::debug!("no X11 display to count keystrokes on: {err}"))
+            .ok()?;
+        Some(Self {
+            connection,
+            down: vec![0; 32],
+        })
+    }
+
+    fn presses(&mut self) -> u32 {
+        use x11rb::protocol::xproto::ConnectionExt;
+
+        let Some(reply) = self
+            .connection
+            .query_keymap()
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+        else {
+            return 0;
+        };
+        let presses = newly_down(&self.down, &reply.keys);
+        self.down = reply.keys.to_vec();
+        presses
+    }
+}
+
+#[cfg(target_os = "macos")]
+#[link(name = "CoreGraphics", kind = "framework")]
+unsafe extern "C" {
+    fn CGEventSourceCounterForEventType(state_id: i32, event_type: u32) -> u32;
+}
+
+#[cfg(target_os = "macos")]
+struct KeystrokeProbe {
+    counter: u32,
+}
+
+#[cfg(target_os = "macos")]
+impl KeystrokeProbe {
+    // kCGEventSourceStateHIDSystemState and kCGEventKeyDown
+    const HID_SYSTEM_STATE: i32 = 1;
+    const KEY_DOWN: u32 = 10;
+
+    fn counter() -> u32 {
+        unsafe { CGEventSourceCounterForEventType(Self::HID_SYSTEM_STATE, Self::KEY_DOWN) }
+    }
+
+    /// the system keeps a running count of key downs, no accessibility permission needed to read it
+    fn connect() -> Option<Self> {
+        Some(Self {
+            counter: Self::counter(),
+        })
+    }
+
+    fn presses(&mut self) -> u32 {
+        let counter = Self::counter();
+        let presses = counter.wrapping_sub(self.counter);
+        self.counter = counter;
+        presses
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+struct KeystrokeProbe;
+
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+impl KeystrokeProbe {
+    fn connect() -> Option<Self> {
+        None
+    }
+
+    fn presses(&mut self) -> u32 {
+        0
+    }
+}
@@ -0,0 +1,81 @@
+//! A desktop pet that lives in a borderless window on top of everything else.
+//!
+//! The `desktop_gremlin` binary is a thin wrapper around this crate, other projects can embed
+//! a gremlin the same way or bring their own behaviors along:
+//!
+//! ```no_run
+//! use desktop_gremlin::{Behavior, ContextData, DGRuntime, DesktopGremlin, GremlinTask};
+//!
+//! /// waves whenever the gremlin gets clicked
+//! #[derive(Default)]
+//! struct Wave;
+//!
+//! impl Behavior for Wave {
+//!     fn setup(&mut self, _application: &mut DesktopGremlin) {}
+//!
+//!     fn update(&mut self, application: &mut DesktopGremlin, context: &ContextData) {
+//!         context.on::<desktop_gremlin::events::kinds::Click>(|_| {
+//!             let _ = application
+//!                 .task_channel
+//!                 .0
+//!                 .send(GremlinTask::Play("WAVE".to_string()));
+//!         });
+//!     }
+//! }
+//!
+//! let mut rt = DGRuntime::default();
+//! let mut behaviors = desktop_gremlin::behavior::default_behaviors();
+//! behaviors.push(Box::new(Wave));
+//! rt.register_behaviors(behaviors);
+//! rt.go().expect("no window to run in");
+//! ```
+//!
+//! `Behavior` is where out-of-tree code plugs in, `GremlinTask` is how it asks the gremlin to
+//! do things, `DGRuntime` drives both and `ui` draws on top of the gremlin.
+
+mod achievements;
+mod audio;
+mod autostart;
+pub mod backend;
+pub mod behavior;
+mod bench;
+#[cfg(feature = "chat")]
+mod brain;
+pub mod cli;
+mod clock;
+pub mod events;
+mod fetch;
+mod foreground;
+mod gamepad;
+pub mod gremlin;
+mod hotkeys;
+mod instance;
+pub mod io;
+mod ipc;
+mod keyboard;
+mod logging;
+mod media;
+mod monitor;
+mod neighbors;
+mod pack;
+mod profile;
+mod recording;
+pub mod runtime;
+mod scheduler;
+mod signals;
+mod state;
+mod stats;
+mod stream;
+mod threads;
+mod tree;
+pub mod ui;
+mod utility;
+mod utils;
+mod visit;
+mod weather;
+mod wizard;
+
+pub use behavior::{Behavior, ContextData};
+pub use events::{Event, EventData};
+pub use gremlin::{DesktopGremlin, GremlinTask, LaunchArguments};
+pub use runtime::DGRuntime;
@@ -0,0 +1,46 @@
+use std::{
+    env,
+    fs::{self, File},
+    path::Path,
+    sync::Mutex,
+};
+
+use tracing_subscriber::{EnvFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt};
+
+// quiet dependencies, chatty gremlin
+const DEFAULT_DIRECTIVES: &str = "warn,desktop_gremlin=info";
+
+/// Sets up logging to stdout and optionally a file.
+/// `directives` are `EnvFilter` directives like `info,desktop_gremlin::ui=debug`,
+/// falling back to `GREMLIN_LOG`, the log file falls back to `GREMLIN_LOG_FILE`.
+pub fn init(directives: Option<String>, log_file: Option<String>) {
+    let directives = directives
+        .or_else(|| env::var("GREMLIN_LOG").ok())
+        .unwrap_or_else(|| DEFAULT_DIRECTIVES.to_string());
+    let filter = EnvFilter::try_new(&directives).unwrap_or_else(|err| {
+        eprintln!("bad log directives {directives:?}, using the defaults: {err}");
+        EnvFilter::new(DEFAULT_DIRECTIVES)
+    });
+
+    let file_layer = log_file
+        .or_else(|| env::var("GREMLIN_LOG_FILE").ok())
+        .and_then(|path| {
+            open_log_file(Path::new(&path))
+                .inspect_err(|err| eprintln!("couldn't open log file {path}: {err}"))
+                .ok()
+        })
+        .map(|file| fmt::layer().with_ansi(false).with_writer(Mutex::new(file)));
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer())
+        .with(file_layer)
+        .init();
+}
+
+fn open_log_file(path: &Path) -> std::io::Result<File> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    File::options().create(true).append(true).open(path)
+}
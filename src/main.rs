@@ -1,25 +1,119 @@
-use crate::{behavior::*, runtime::DGRuntime};
-
-mod behavior;
-mod events;
-mod gremlin;
-pub mod io;
-mod runtime;
-pub mod ui;
-mod utils;
-mod threads;
-
-fn main() {
-    let mut rt = DGRuntime::default();
-
-    let behaviors: Vec<Box<dyn Behavior>> = vec![
-        CommonBehavior::new(),
-        GremlinDrag::new(),
-        GremlinMovement::new(),
-        GremlinRender::new(),
-        GremlinClick::new(),
-    ];
-
-    rt.register_behaviors(behaviors);
-    rt.go();
+use std::{env, process::ExitCode};
+
+use tracing::{error, warn};
+
+use desktop_gremlin::{DGRuntime, behavior::default_behaviors, cli};
+
+fn main() -> ExitCode {
+    let mut args = env::args().collect::<Vec<String>>();
+    cli::init_logging(
+        take_flag(&mut args, "--log"),
+        take_flag(&mut args, "--log-file"),
+    );
+    match args.get(1).map(String::as_str) {
+        Some("send") => return cli::send(&args[2..]),
+        Some("autostart") => return cli::autostart(&args[2..]),
+        Some("fetch") => return cli::fetch(&args[2..]),
+        Some("visit") => return cli::visit(&args[2..]),
+        _ => {}
+    }
+
+    let seed = take_flag(&mut args, "--seed").and_then(|seed| {
+        seed.parse()
+            .inspect_err(|_| warn!("--seed needs a number, ignoring {seed}"))
+            .ok()
+    });
+
+    let gremlin_path = take_flag(&mut args, "--gremlin");
+    let scale = take_flag(&mut args, "--scale").and_then(|scale| {
+        scale
+            .parse()
+            .ok()
+            .filter(|scale: &f32| scale.is_finite())
+            .or_else(|| {
+                warn!("--scale needs a number, ignoring {scale}");
+                None
+            })
+    });
+    let framerate = take_flag(&mut args, "--fps").and_then(|framerate| {
+        framerate
+            .parse()
+            .inspect_err(|_| warn!("--fps needs a whole number, ignoring {framerate}"))
+            .ok()
+    });
+    // `--bench <seconds>` times the sprite pipeline in a hidden window and exits
+    if let Some(seconds) = take_flag(&mut args, "--bench") {
+        return cli::bench(&seconds, gremlin_path);
+    }
+    // `--new-gremlin <folder>` builds a gremlin out of a folder of sheets and exits
+    if let Some(folder) = take_flag(&mut args, "--new-gremlin") {
+        return cli::new_gremlin(&folder, take_flag(&mut args, "--name"));
+    }
+    // `--assemble <folder>` packs numbered frame pngs into a sheet and exits
+    if let Some(folder) = take_flag(&mut args, "--assemble") {
+        return cli::assemble(
+            &folder,
+            take_flag(&mut args, "--into"),
+            take_flag(&mut args, "--name"),
+            take_flag(&mut args, "--columns"),
+        );
+    }
+    // `--companion` starts another gremlin next to the running one instead of poking it
+    let is_companion = take_switch(&mut args, "--companion");
+    let _instance_lock = match cli::InstanceLock::acquire() {
+        _ if is_companion => None,
+        Ok(Some(lock)) => Some(lock),
+        Ok(None) => {
+            // the running gremlin can change its size and framerate but not who it is
+            if gremlin_path.is_some() || seed.is_some() {
+                warn!("already running, --gremlin and --seed only apply with --companion");
+            }
+            return cli::forward_launch(&args[1..], scale, framerate);
+        }
+        Err(err) => {
+            warn!("couldn't take the instance lock, running anyway: {err}");
+            None
+        }
+    };
+
+    let mut builder = DGRuntime::builder()
+        .companion(is_companion)
+        .behaviors(default_behaviors());
+    if let Some(gremlin_path) = gremlin_path {
+        builder = builder.gremlin(gremlin_path);
+    }
+    if let Some(scale) = scale {
+        builder = builder.scale(scale);
+    }
+    if let Some(seed) = seed {
+        builder = builder.seed(seed);
+    }
+    if let Some(framerate) = framerate {
+        builder = builder.framerate(framerate);
+    }
+    let mut rt = builder.build();
+    if let Err(err) = rt.go() {
+        error!("couldn't start the gremlin: {err:#}");
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
+
+/// pulls `<flag> <value>` out of the args, the rest still get forwarded to a running gremlin
+fn take_flag(args: &mut Vec<String>, flag: &str) -> Option<String> {
+    let index = args.iter().position(|arg| arg == flag)?;
+    let value = args.get(index + 1).cloned();
+    if value.is_none() {
+        warn!("{flag} needs a value, ignoring it");
+    }
+    args.drain(index..(index + 2).min(args.len()));
+    value
+}
+
+fn take_switch(args: &mut Vec<String>, switch: &str) -> bool {
+    let index = args.iter().position(|arg| arg == switch);
+    if let Some(index) = index {
+        args.remove(index);
+    }
+    index.is_some()
 }
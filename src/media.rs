@@ -0,0 +1,147 @@
+use std::{
+    sync::mpsc::{self, RecvTimeoutError, Sender},
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use crate::events::{Event, EventSource};
+
+/// Polls the system's media sessions (SMTC on windows, MPRIS on linux) on its own thread
+/// and reports `media_playing` / `media_paused` custom events whenever that flips.
+/// Never reports anything on platforms without a supported media session api.
+pub struct MediaMonitor {
+    thread_handle: Option<JoinHandle<()>>,
+    stop_tx: Sender<()>,
+}
+
+impl MediaMonitor {
+    pub fn spawn(interval: Duration) -> (Self, EventSource) {
+        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+        let (event_tx, event_rx) = mpsc::channel();
+
+        let thread_handle = thread::spawn(move || {
+            let Some(sessions) = MediaSessions::connect() else {
+                return;
+            };
+            let mut was_playing = false;
+            while let Err(RecvTimeoutError::Timeout) = stop_rx.recv_timeout(interval) {
+                let is_playing = sessions.is_playing();
+                if is_playing != was_playing {
+                    was_playing = is_playing;
+                    let name = if is_playing {
+                        "media_playing"
+                    } else {
+                        "media_paused"
+                    };
+                    let _ = event_tx.send((Event::Custom(name.to_string()), None));
+                }
+            }
+        });
+
+        (
+            Self {
+                thread_handle: Some(thread_handle),
+                stop_tx,
+            },
+            event_rx,
+        )
+    }
+}
+
+impl Drop for MediaMonitor {
+    fn drop(&mut self) {
+        let _ = self.stop_tx.send(());
+        if let Some(handle) = self.thread_handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+struct MediaSessions {
+    manager: windows::Media::Control::GlobalSystemMediaTransportControlsSessionManager,
+}
+
+#[cfg(target_os = "windows")]
+impl MediaSessions {
+    fn connect() -> Option<Self> {
+        use windows::{
+            Media::Control::GlobalSystemMediaTransportControlsSessionManager,
+            Win32::System::WinRT::{RO_INIT_MULTITHREADED, RoInitialize},
+        };
+
+        unsafe { RoInitialize(RO_INIT_MULTITHREADED) }.ok()?;
+        let manager = GlobalSystemMediaTransportControlsSessionManager::RequestAsync()
+            .and_then(|request| request.join())
+            .inspect_err(|err| tracing::warn!("no media session manager: {err}"))
+            .ok()?;
+        Some(Self { manager })
+    }
+
+    /// only looks at the session windows would show in its media flyout
+    fn is_playing(&self) -> bool {
+        use windows::Media::Control::GlobalSystemMediaTransportControlsSessionPlaybackStatus;
+
+        self.manager
+            .GetCurrentSession()
+            .and_then(|session| session.GetPlaybackInfo())
+            .and_then(|info| info.PlaybackStatus())
+            .is_ok_and(|status| {
+                status == GlobalSystemMediaTransportControlsSessionPlaybackStatus::Playing
+            })
+    }
+}
+
+#[cfg(target_os = "linux")]
+struct MediaSessions {
+    connection: zbus::blocking::Connection,
+}
+
+#[cfg(target_os = "linux")]
+impl MediaSessions {
+    const MPRIS_PREFIX: &str = "org.mpris.MediaPlayer2.";
+
+    fn connect() -> Option<Self> {
+        let connection = zbus::blocking::Connection::session()
+            .inspect_err(|err| tracing::warn!("no session bus for media players: {err}"))
+            .ok()?;
+        Some(Self { connection })
+    }
+
+    /// any MPRIS player on the session bus playing counts
+    fn is_playing(&self) -> bool {
+        let Some(names) = zbus::blocking::fdo::DBusProxy::new(&self.connection)
+            .ok()
+            .and_then(|dbus| dbus.list_names().ok())
+        else {
+            return false;
+        };
+        names
+            .iter()
+            .filter(|name| name.starts_with(Self::MPRIS_PREFIX))
+            .any(|name| {
+                zbus::blocking::Proxy::new(
+                    &self.connection,
+                    name.as_str(),
+                    "/org/mpris/MediaPlayer2",
+                    "org.mpris.MediaPlayer2.Player",
+                )
+                .and_then(|player| player.get_property::<String>("PlaybackStatus"))
+                .is_ok_and(|status| status == "Playing")
+            })
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+struct MediaSessions;
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+impl MediaSessions {
+    fn connect() -> Option<Self> {
+        None
+    }
+
+    fn is_playing(&self) -> bool {
+        false
+    }
+}
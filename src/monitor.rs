@@ -0,0 +1,339 @@
+use std::{
+    sync::mpsc::{self, Receiver, RecvTimeoutError, Sender},
+    thread::{self, JoinHandle},
+    time::{Duration, SystemTime},
+};
+
+use sdl3::sys::power::{SDL_GetPowerInfo, SDL_PowerState};
+
+use crate::events::{Event, EventData};
+
+const BATTERY_LOW_THRESHOLD: f32 = 20.0;
+const CPU_HIGH_THRESHOLD: f32 = 85.0;
+// the wall clock getting this much further ahead than the poll interval means the machine slept
+const RESUME_GAP: Duration = Duration::from_secs(30);
+// how long suspending is held off after `system_sleep` so the gremlin gets to wind down
+const SLEEP_GRACE: Duration = Duration::from_secs(1);
+
+/// Polls battery and cpu load on its own thread and reports them as custom events:
+///
+/// - `battery` / `cpu` with the current percentage on every poll
+/// - `battery_low` / `battery_ok` and `cpu_high` / `cpu_normal` when a threshold is crossed
+/// - `on_battery` / `on_ac` when the machine gets unplugged or plugged back in
+/// - `system_sleep` when the machine is about to be suspended (where that's announced, logind and
+///   windows), and `system_resume` once it's back
+pub struct SystemMonitor {
+    thread_handle: Option<JoinHandle<()>>,
+    stop_tx: Sender<()>,
+}
+
+impl SystemMonitor {
+    pub fn spawn(interval: Duration) -> (Self, Receiver<(Event, Option<EventData>)>) {
+        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+        let (event_tx, event_rx) = mpsc::channel();
+
+        let thread_handle = thread::spawn(move || {
+            let mut cpu_sampler = CpuSampler::default();
+            let mut is_battery_low = false;
+            let mut is_on_battery = false;
+            let mut is_cpu_high = false;
+            let sleep_watch = SleepWatch::start(event_tx.clone());
+            let mut last_poll = SystemTime::now();
+            // first cpu sample only primes the sampler
+            cpu_sampler.sample();
+
+            while let Err(RecvTimeoutError::Timeout) = stop_rx.recv_timeout(interval) {
+                // a suspended machine doesn't poll, so the time between polls gives resuming away.
+                // setting the clock forward looks the same, which is harmless
+                let now = SystemTime::now();
+                let gap = now.duration_since(last_poll).unwrap_or_default();
+                last_poll = now;
+                if sleep_watch.is_none() && gap > interval + RESUME_GAP {
+                    let _ = event_tx.send((Event::Custom("system_resume".to_string()), None));
+                }
+
+                let percent = battery_percent();
+                if percent.is_some() != is_on_battery {
+                    is_on_battery = !is_on_battery;
+                    let name = if is_on_battery { "on_battery" } else { "on_ac" };
+                    let _ = event_tx.send((Event::Custom(name.to_string()), None));
+                }
+                if let Some(percent) = percent {
+                    let _ = event_tx.send((
+                        Event::Custom("battery".to_string()),
+                        Some(EventData::Value(percent)),
+                    ));
+                    if (percent < BATTERY_LOW_THRESHOLD) != is_battery_low {
+                        is_battery_low = !is_battery_low;
+                        let name = if is_battery_low {
+                            "battery_low"
+                        } else {
+                            "battery_ok"
+                        };
+                        let _ = event_tx.send((Event::Custom(name.to_string()), None));
+                    }
+                }
+
+                if let Some(load) = cpu_sampler.sample() {
+                    let _ = event_tx.send((
+                        Event::Custom("cpu".to_string()),
+                        Some(EventData::Value(load)),
+                    ));
+                    if (load > CPU_HIGH_THRESHOLD) != is_cpu_high {
+                        is_cpu_high = !is_cpu_high;
+                        let name = if is_cpu_high {
+                            "cpu_high"
+                        } else {
+                            "cpu_normal"
+                        };
+                        let _ = event_tx.send((Event::Custom(name.to_string()), None));
+                    }
+                }
+            }
+        });
+
+        (
+            Self {
+                thread_handle: Some(thread_handle),
+                stop_tx,
+            },
+            event_rx,
+        )
+    }
+}
+
+impl Drop for SystemMonitor {
+    fn drop(&mut self) {
+        let _ = self.stop_tx.send(());
+        if let Some(handle) = self.thread_handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// `None` when running off a machine without a battery, or while charging
+pub fn battery_percent() -> Option<f32> {
+    let mut percent: i32 = -1;
+    let state = unsafe { SDL_GetPowerInfo(std::ptr::null_mut(), &mut percent) };
+    match state {
+        SDL_PowerState::ON_BATTERY if percent >= 0 => Some(percent as f32),
+        _ => None,
+    }
+}
+
+/// Hears about suspending from logind, holding a delay inhibitor so there's time to wind down
+/// before it goes ahead. `PrepareForSleep` comes in on its own thread, which is left to end with
+/// the next one after the monitor's gone.
+#[cfg(target_os = "linux")]
+struct SleepWatch;
+
+#[cfg(target_os = "linux")]
+impl SleepWatch {
+    fn start(event_tx: Sender<(Event, Option<EventData>)>) -> Option<Self> {
+        let connection = zbus::blocking::Connection::system()
+            .inspect_err(|err| tracing::debug!("no system bus to hear about suspending on: {err}"))
+            .ok()?;
+        let manager = zbus::blocking::Proxy::new(
+            &connection,
+            "org.freedesktop.login1",
+            "/org/freedesktop/login1",
+            "org.freedesktop.login1.Manager",
+        )
+        .ok()?;
+        let signals = manager
+            .receive_signal("PrepareForSleep")
+            .inspect_err(|err| tracing::debug!("couldn't listen for suspending: {err}"))
+            .ok()?;
+
+        thread::spawn(move || {
+            // suspending waits until this is closed, or for logind's InhibitDelayMaxSec
+            let mut inhibitor = Self::inhibit(&manager);
+            for message in signals {
+                let Ok(is_going_to_sleep) = message.body().deserialize::<bool>() else {
+                    continue;
+                };
+                let name = if is_going_to_sleep {
+                    "system_sleep"
+                } else {
+                    "system_resume"
+                };
+                if event_tx
+                    .send((Event::Custom(name.to_string()), None))
+                    .is_err()
+                {
+                    break;
+                }
+                if is_going_to_sleep {
+                    thread::sleep(SLEEP_GRACE);
+                    inhibitor = None;
+                } else {
+                    inhibitor = Self::inhibit(&manager);
+                }
+            }
+            drop(inhibitor);
+        });
+        Some(Self)
+    }
+
+    fn inhibit(manager: &zbus::blocking::Proxy) -> Option<zbus::zvariant::OwnedFd> {
+        manager
+            .call(
+                "Inhibit",
+                &("sleep", "desktop_gremlin", "winding down first", "delay"),
+            )
+            .inspect_err(|err| tracing::debug!("couldn't hold off suspending: {err}"))
+            .ok()
+    }
+}
+
+/// Hears about suspending from windows, which waits for the callback before going ahead.
+#[cfg(target_os = "windows")]
+struct SleepWatch {
+    registration: windows::Win32::System::Power::HPOWERNOTIFY,
+    // what the callback is handed, freed once it's unregistered
+    parameters: *mut windows::Win32::System::Power::DEVICE_NOTIFY_SUBSCRIBE_PARAMETERS,
+}
+
+#[cfg(target_os = "windows")]
+impl SleepWatch {
+    fn start(event_tx: Sender<(Event, Option<EventData>)>) -> Option<Self> {
+        use windows::Win32::{
+            Foundation::{ERROR_SUCCESS, HANDLE},
+            System::Power::{
+                DEVICE_NOTIFY_SUBSCRIBE_PARAMETERS, HPOWERNOTIFY,
+                PowerRegisterSuspendResumeNotification,
+            },
+            UI::WindowsAndMessaging::DEVICE_NOTIFY_CALLBACK,
+        };
+
+        let parameters = Box::into_raw(Box::new(DEVICE_NOTIFY_SUBSCRIBE_PARAMETERS {
+            Callback: Some(Self::on_power_event),
+            Context: Box::into_raw(Box::new(event_tx)).cast(),
+        }));
+        let mut registration = std::ptr::null_mut();
+        let result = unsafe {
+            PowerRegisterSuspendResumeNotification(
+                DEVICE_NOTIFY_CALLBACK,
+                HANDLE(parameters.cast()),
+                &mut registration,
+            )
+        };
+        let watch = Self {
+            registration: HPOWERNOTIFY(registration as isize),
+            parameters,
+        };
+        if result != ERROR_SUCCESS {
+            tracing::debug!("couldn't listen for suspending: {result:?}");
+            return None;
+        }
+        Some(watch)
+    }
+
+    unsafe extern "system" fn on_power_event(
+        context: *const std::ffi::c_void,
+        kind: u32,
+        _: *const std::ffi::c_void,
+    ) -> u32 {
+        use windows::Win32::UI::WindowsAndMessaging::{PBT_APMRESUMEAUTOMATIC, PBT_APMSUSPEND};
+
+        let event_tx = unsafe { &*context.cast::<Sender<(Event, Option<EventData>)>>() };
+        let name = match kind {
+            PBT_APMSUSPEND => "system_sleep",
+            PBT_APMRESUMEAUTOMATIC => "system_resume",
+            _ => return 0,
+        };
+        if event_tx
+            .send((Event::Custom(name.to_string()), None))
+            .is_ok()
+            && kind == PBT_APMSUSPEND
+        {
+            thread::sleep(SLEEP_GRACE);
+        }
+        0
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl Drop for SleepWatch {
+    fn drop(&mut self) {
+        use windows::Win32::System::Power::PowerUnregisterSuspendResumeNotification;
+
+        if !self.registration.is_invalid() {
+            unsafe { PowerUnregisterSuspendResumeNotification(self.registration) };
+        }
+        let parameters = unsafe { Box::from_raw(self.parameters) };
+        drop(unsafe {
+            Box::from_raw(
+                parameters
+                    .Context
+                    .cast::<Sender<(Event, Option<EventData>)>>(),
+            )
+        });
+    }
+}
+
+/// Elsewhere suspending only shows afterwards, as a gap between polls.
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+struct SleepWatch;
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+impl SleepWatch {
+    fn start(_: Sender<(Event, Option<EventData>)>) -> Option<Self> {
+        None
+    }
+}
+
+/// cpu load is the busy share of cpu time between two samples
+#[derive(Default)]
+struct CpuSampler {
+    // (idle, total)
+    last: Option<(u64, u64)>,
+}
+
+impl CpuSampler {
+    fn sample(&mut self) -> Option<f32> {
+        let (idle, total) = cpu_times()?;
+        let load = self.last.and_then(|(last_idle, last_total)| {
+            let total_delta = total.saturating_sub(last_total);
+            let idle_delta = idle.saturating_sub(last_idle);
+            (total_delta > 0).then(|| 100.0 * (1.0 - (idle_delta as f32) / (total_delta as f32)))
+        });
+        self.last = Some((idle, total));
+        load
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn cpu_times() -> Option<(u64, u64)> {
+    let stat = std::fs::read_to_string("/proc/stat").ok()?;
+    // cpu  user nice system idle iowait irq softirq steal ...
+    let times = stat
+        .lines()
+        .next()?
+        .split_whitespace()
+        .skip(1)
+        .filter_map(|value| value.parse::<u64>().ok())
+        .collect::<Vec<u64>>();
+    let idle = times.get(3)? + times.get(4).unwrap_or(&0);
+    Some((idle, times.iter().sum()))
+}
+
+#[cfg(target_os = "windows")]
+fn cpu_times() -> Option<(u64, u64)> {
+    use windows::Win32::{Foundation::FILETIME, System::Threading::GetSystemTimes};
+
+    let (mut idle, mut kernel, mut user) = (
+        FILETIME::default(),
+        FILETIME::default(),
+        FILETIME::default(),
+    );
+    unsafe { GetSystemTimes(Some(&mut idle), Some(&mut kernel), Some(&mut user)) }.ok()?;
+    let as_u64 = |time: FILETIME| ((time.dwHighDateTime as u64) << 32) | time.dwLowDateTime as u64;
+    // kernel time already includes idle time
+    Some((as_u64(idle), as_u64(kernel) + as_u64(user)))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+fn cpu_times() -> Option<(u64, u64)> {
+    None
+}
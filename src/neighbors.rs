@@ -0,0 +1,130 @@
+use std::{
+    io,
+    net::{Ipv4Addr, UdpSocket},
+    ops::RangeInclusive,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver},
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+/// Every gremlin on this machine binds the first free port in here and talks to the rest of the range,
+/// so the port doubles as the gremlin's id.
+pub const NEIGHBOR_PORTS: RangeInclusive<u16> = 7480..=7495;
+const READ_TIMEOUT: Duration = Duration::from_millis(100);
+const MAX_MESSAGE_SIZE: usize = 2048;
+
+/// Sent as one JSON datagram, e.g. `{"kind": "gesture", "animation": "WAVE"}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum NeighborMessage {
+    /// where the sender is and what it's doing, sent every so often
+    State {
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        animation: Option<String>,
+        is_idle: bool,
+    },
+    /// asks the receiver to play `animation` together with the sender
+    Gesture { animation: String },
+    /// the sender is going away
+    Bye,
+}
+
+/// (port of the gremlin that sent it, message)
+pub type Envelope = (u16, NeighborMessage);
+
+/// Finds the other gremlins running on this machine over localhost UDP and swaps messages with them.
+/// Listens on its own thread, received messages come out of the receiver `start()` hands back.
+pub struct NeighborLink {
+    socket: UdpSocket,
+    port: u16,
+    thread_handle: Option<JoinHandle<()>>,
+    should_stop: Arc<AtomicBool>,
+}
+
+impl NeighborLink {
+    pub fn start() -> io::Result<(Self, Receiver<Envelope>)> {
+        let socket = NEIGHBOR_PORTS
+            .clone()
+            .find_map(|port| UdpSocket::bind((Ipv4Addr::LOCALHOST, port)).ok())
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::AddrInUse, "every neighbor port is taken")
+            })?;
+        let port = socket.local_addr()?.port();
+        let listener = socket.try_clone()?;
+        listener.set_read_timeout(Some(READ_TIMEOUT))?;
+        let (message_tx, message_rx) = mpsc::channel();
+        let should_stop = Arc::new(AtomicBool::new(false));
+        let thread_should_stop = Arc::clone(&should_stop);
+
+        let thread_handle = thread::spawn(move || {
+            let mut buffer = [0u8; MAX_MESSAGE_SIZE];
+            while !thread_should_stop.load(Ordering::Relaxed) {
+                match listener.recv_from(&mut buffer) {
+                    Ok((length, sender)) => {
+                        let Ok(message) = serde_json::from_slice(&buffer[..length]) else {
+                            continue;
+                        };
+                        if message_tx.send((sender.port(), message)).is_err() {
+                            break;
+                        }
+                    }
+                    Err(err)
+                        if matches!(
+                            err.kind(),
+                            io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                        ) => {}
+                    // windows reports an earlier send to a port nobody listens on this way
+                    Err(err) if err.kind() == io::ErrorKind::ConnectionReset => {}
+                    Err(err) => {
+                        warn!("stopped listening for neighbors: {err}");
+                        break;
+                    }
+                }
+            }
+        });
+
+        Ok((
+            Self {
+                socket,
+                port,
+                thread_handle: Some(thread_handle),
+                should_stop,
+            },
+            message_rx,
+        ))
+    }
+
+    /// to every other port in the range, nobody listening on most of them is fine
+    pub fn broadcast(&self, message: &NeighborMessage) {
+        for port in NEIGHBOR_PORTS.clone().filter(|port| *port != self.port) {
+            self.send_to(port, message);
+        }
+    }
+
+    pub fn send_to(&self, port: u16, message: &NeighborMessage) {
+        let Ok(payload) = serde_json::to_vec(message) else {
+            return;
+        };
+        let _ = self.socket.send_to(&payload, (Ipv4Addr::LOCALHOST, port));
+    }
+}
+
+impl Drop for NeighborLink {
+    fn drop(&mut self) {
+        self.broadcast(&NeighborMessage::Bye);
+        self.should_stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.thread_handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
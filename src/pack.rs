@@ -0,0 +1,215 @@
+use std::{
+    fs::{self, File},
+    io::{self, Cursor, Read, Seek, Write},
+    path::{Path, PathBuf},
+};
+
+use zip::{ZipArchive, ZipWriter, read::root_dir_common_filter, write::SimpleFileOptions};
+
+use crate::utils::data_dir;
+
+pub const PACK_EXTENSION: &str = "gremlin";
+const MANIFEST_NAME: &str = "config.txt";
+// written after a successful extraction, extracted files may keep the archive's timestamps
+const EXTRACTED_MARKER: &str = ".extracted";
+// packs come in from LAN peers too, see `visit.rs`, anything bigger than a generous sprite pack
+// is turned away before a byte of it is written
+pub const MAX_UNPACKED_SIZE: u64 = 64 * 1024 * 1024;
+const MAX_ENTRIES: usize = 2048;
+
+pub fn is_pack(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case(PACK_EXTENSION))
+}
+
+/// Unpacks a `.gremlin` archive (a zip of the usual gremlin folder) into the data directory
+/// and returns the path to its manifest. Packs are only extracted again when the archive changes.
+pub fn unpack(archive_path: &Path) -> io::Result<PathBuf> {
+    let target = extracted_dir(archive_path)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "pack has no file name"))?;
+    let manifest = target.join(MANIFEST_NAME);
+
+    let archive_modified = fs::metadata(archive_path)?.modified()?;
+    let is_up_to_date = fs::metadata(target.join(EXTRACTED_MARKER))
+        .and_then(|metadata| metadata.modified())
+        .is_ok_and(|extracted| extracted >= archive_modified);
+    if is_up_to_date {
+        return Ok(manifest);
+    }
+
+    if target.exists() {
+        fs::remove_dir_all(&target)?;
+    }
+    let mut archive = ZipArchive::new(File::open(archive_path)?).map_err(io::Error::other)?;
+    if let Err(err) = extract(&mut archive, &target) {
+        let _ = fs::remove_dir_all(&target);
+        return Err(err);
+    }
+
+    if manifest.exists() {
+        fs::write(target.join(EXTRACTED_MARKER), [])?;
+        Ok(manifest)
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{} has no {MANIFEST_NAME}", archive_path.display()),
+        ))
+    }
+}
+
+/// Extracts `archive` into `target`, refusing archives with more than `MAX_ENTRIES` entries,
+/// more than `MAX_UNPACKED_SIZE` bytes in all, symlinks or paths leading out of `target`.
+/// Packs zipped from a folder have everything under one directory, that gets flattened.
+fn extract<R: Read + Seek>(archive: &mut ZipArchive<R>, target: &Path) -> io::Result<()> {
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+    if archive.len() > MAX_ENTRIES {
+        return Err(invalid(format!(
+            "the pack has {} files, at most {MAX_ENTRIES} are unpacked",
+            archive.len()
+        )));
+    }
+    let root = archive
+        .root_dir(root_dir_common_filter)
+        .map_err(io::Error::other)?;
+    // what each entry says it unpacks to, checked against what it actually does while writing
+    let mut entries = Vec::new();
+    let mut total = 0u64;
+    for index in 0..archive.len() {
+        let file = archive.by_index_raw(index).map_err(io::Error::other)?;
+        let raw_name = String::from_utf8_lossy(file.name_raw()).into_owned();
+        if file.is_symlink() {
+            return Err(invalid(format!("{raw_name} is a symlink")));
+        }
+        let Some(name) = file.enclosed_name() else {
+            return Err(invalid(format!("{raw_name} points outside the pack")));
+        };
+        if !root_dir_common_filter(&name) {
+            continue;
+        }
+        total = total.saturating_add(file.size());
+        if total > MAX_UNPACKED_SIZE {
+            return Err(invalid(format!(
+                "the pack unpacks to more than {} MB",
+                MAX_UNPACKED_SIZE / 1024 / 1024
+            )));
+        }
+        let name = match &root {
+            Some(root) => name.strip_prefix(root).unwrap_or(&name).to_path_buf(),
+            None => name,
+        };
+        entries.push((index, target.join(name), file.is_dir()));
+    }
+
+    fs::create_dir_all(target)?;
+    let mut remaining = MAX_UNPACKED_SIZE;
+    for (index, path, is_dir) in entries {
+        if is_dir {
+            fs::create_dir_all(&path)?;
+            continue;
+        }
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = archive.by_index(index).map_err(io::Error::other)?;
+        // the sizes in the archive can lie, one byte past what's left gives it away
+        let written = io::copy(&mut file.take(remaining + 1), &mut File::create(&path)?)?;
+        remaining = remaining
+            .checked_sub(written)
+            .ok_or_else(|| invalid("the pack unpacks to more than it says".to_string()))?;
+    }
+    Ok(())
+}
+
+/// where `unpack` puts the contents of the archive at `archive_path`
+pub fn extracted_dir(archive_path: &Path) -> Option<PathBuf> {
+    archive_path
+        .file_stem()
+        .map(|name| data_dir().join("packs").join(name))
+}
+
+/// The gremlin `gremlin_path` points at as `.gremlin` archive bytes, zipping up its folder
+/// when it isn't a pack already.
+pub fn pack_bytes(gremlin_path: &Path) -> io::Result<Vec<u8>> {
+    if is_pack(gremlin_path) {
+        return fs::read(gremlin_path);
+    }
+    let dir = gremlin_path
+        .parent()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "manifest has no folder"))?;
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    zip_dir(&mut writer, dir, dir)?;
+    Ok(writer.finish().map_err(io::Error::other)?.into_inner())
+}
+
+fn zip_dir(writer: &mut ZipWriter<Cursor<Vec<u8>>>, root: &Path, dir: &Path) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            zip_dir(writer, root, &path)?;
+            continue;
+        }
+        let Ok(name) = path.strip_prefix(root) else {
+            continue;
+        };
+        // zip paths use forward slashes everywhere
+        let name = name.to_string_lossy().replace('\\', "/");
+        writer
+            .start_file(name, SimpleFileOptions::default())
+            .map_err(io::Error::other)?;
+        writer.write_all(&fs::read(&path)?)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn archive(build: impl FnOnce(&mut ZipWriter<Cursor<Vec<u8>>>)) -> ZipArchive<Cursor<Vec<u8>>> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        build(&mut writer);
+        ZipArchive::new(writer.finish().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn unpacks_within_limits() {
+        let target =
+            std::env::temp_dir().join(format!("desktop_gremlin-pack-{}", std::process::id()));
+        let options = SimpleFileOptions::default();
+        let mut pack = archive(|writer| {
+            writer.start_file("gremlin/config.txt", options).unwrap();
+            writer.write_all(b"IDLE=1").unwrap();
+            writer
+                .start_file("gremlin/sheets/idle.png", options)
+                .unwrap();
+        });
+        extract(&mut pack, &target).unwrap();
+        assert_eq!(
+            fs::read_to_string(target.join(MANIFEST_NAME)).unwrap(),
+            "IDLE=1"
+        );
+        assert!(target.join("sheets/idle.png").exists());
+        let _ = fs::remove_dir_all(&target);
+
+        let mut bomb = archive(|writer| {
+            writer.start_file("bomb", options).unwrap();
+            writer
+                .write_all(&vec![0; MAX_UNPACKED_SIZE as usize + 1])
+                .unwrap();
+        });
+        assert!(extract(&mut bomb, &target).is_err());
+        let mut symlink = archive(|writer| {
+            writer
+                .add_symlink("config.txt", "/etc/passwd", options)
+                .unwrap();
+        });
+        assert!(extract(&mut symlink, &target).is_err());
+        let mut crowded = archive(|writer| {
+            for index in 0..=MAX_ENTRIES {
+                writer.start_file(index.to_string(), options).unwrap();
+            }
+        });
+        assert!(extract(&mut crowded, &target).is_err());
+        assert!(!target.exists());
+    }
+}
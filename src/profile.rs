@@ -0,0 +1,84 @@
+use std::{collections::VecDeque, time::Duration};
+
+use serde::Serialize;
+
+// weight of the newest sample, about the last 20 frames matter
+const SMOOTHING: f64 = 0.05;
+// how many of the last frames the frame statistics cover, a few seconds worth
+const FRAME_WINDOW: usize = 240;
+
+/// Rolling average of how long each frame phase takes, fed by the runtime every frame.
+/// Phases are the behaviors' `update()`s (by `Behavior::name()`) and the event pump.
+/// Also keeps how long the last `FRAME_WINDOW` frames took and how late they came.
+#[derive(Debug, Clone, Default)]
+pub struct Profile {
+    // insertion order, which is the order the phases run in
+    timings: Vec<(String, f64)>,
+    // (ms the frame took, ms it came late), oldest first
+    frames: VecDeque<(f64, f64)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PhaseTiming {
+    pub name: String,
+    pub average_ms: f64,
+}
+
+impl Profile {
+    pub fn record(&mut self, name: &str, elapsed: Duration) {
+        let millis = elapsed.as_secs_f64() * 1000.0;
+        match self.timings.iter_mut().find(|(phase, _)| phase == name) {
+            Some((_, average)) => *average += (millis - *average) * SMOOTHING,
+            None => self.timings.push((name.to_string(), millis)),
+        }
+    }
+
+    pub fn timings(&self) -> Vec<PhaseTiming> {
+        self.timings
+            .iter()
+            .map(|(name, average_ms)| PhaseTiming {
+                name: name.clone(),
+                average_ms: *average_ms,
+            })
+            .collect()
+    }
+
+    /// slowest phases first
+    pub fn slowest(&self, count: usize) -> Vec<PhaseTiming> {
+        let mut timings = self.timings();
+        timings.sort_by(|a, b| b.average_ms.total_cmp(&a.average_ms));
+        timings.truncate(count);
+        timings
+    }
+
+    /// summed up averages of every phase
+    pub fn frame_ms(&self) -> f64 {
+        self.timings.iter().map(|(_, average)| average).sum()
+    }
+
+    pub fn record_frame(&mut self, took: Duration, lateness: Duration) {
+        if self.frames.len() >= FRAME_WINDOW {
+            self.frames.pop_front();
+        }
+        self.frames
+            .push_back((took.as_secs_f64() * 1000.0, lateness.as_secs_f64() * 1000.0));
+    }
+
+    /// 95 out of 100 recent frames took at most this long
+    pub fn frame_p95_ms(&self) -> f64 {
+        let mut took: Vec<f64> = self.frames.iter().map(|(took, _)| *took).collect();
+        if took.is_empty() {
+            return 0.0;
+        }
+        took.sort_by(f64::total_cmp);
+        took[(took.len() * 95 / 100).min(took.len() - 1)]
+    }
+
+    /// how late recent frames came on average
+    pub fn jitter_ms(&self) -> f64 {
+        if self.frames.is_empty() {
+            return 0.0;
+        }
+        self.frames.iter().map(|(_, late)| late).sum::<f64>() / self.frames.len() as f64
+    }
+}
@@ -0,0 +1,108 @@
+use std::{
+    fs::{self, File},
+    io::BufWriter,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use image::{
+    Delay, Frame, RgbaImage,
+    codecs::gif::{GifEncoder, Repeat},
+};
+use tracing::{info, warn};
+
+// every frame is kept in memory until it's encoded
+pub const MAX_RECORDING: Duration = Duration::from_secs(20);
+// browsers play anything shorter than 2 centiseconds a frame at 10, way too slow
+const MIN_FRAME_GAP: Duration = Duration::from_millis(20);
+// how hard the encoder works on each frame's palette, 1 is best and slowest, 30 fastest
+const GIF_SPEED: i32 = 10;
+
+/// The frames `GremlinRender` drew over a few seconds, see `GremlinTask::Record`. Frames are only
+/// captured when something changed, each one stays up until the next, so a gremlin sitting still
+/// costs a single frame.
+pub struct Recording {
+    path: PathBuf,
+    until: Instant,
+    frames: Vec<(Instant, RgbaImage)>,
+}
+
+impl Recording {
+    /// `duration` is cut down to `MAX_RECORDING`
+    pub fn start(path: PathBuf, duration: Duration) -> Self {
+        Self {
+            path,
+            until: Instant::now() + duration.min(MAX_RECORDING),
+            frames: Vec::new(),
+        }
+    }
+
+    pub fn is_done(&self, now: Instant) -> bool {
+        now >= self.until
+    }
+
+    /// wants the frame on screen now, always the first one, after that only what's newly drawn
+    /// and not too soon after the last
+    pub fn wants_frame(&self, now: Instant, is_new: bool) -> bool {
+        match self.frames.last() {
+            None => true,
+            Some((captured_at, _)) => is_new && now - *captured_at >= MIN_FRAME_GAP,
+        }
+    }
+
+    pub fn capture(&mut self, now: Instant, image: RgbaImage) {
+        // moving the window around redraws the same picture
+        if self.frames.last().is_some_and(|(_, last)| *last == image) {
+            return;
+        }
+        self.frames.push((now, image));
+    }
+
+    /// encodes a looping gif on its own thread, the main loop shouldn't wait for it
+    pub fn finish(self) {
+        let Self {
+            path,
+            until,
+            frames,
+        } = self;
+        if frames.is_empty() {
+            warn!("nothing was drawn while recording {}", path.display());
+            return;
+        }
+        let mut ends = frames
+            .iter()
+            .skip(1)
+            .map(|(captured_at, _)| *captured_at)
+            .collect::<Vec<_>>();
+        ends.push(until);
+        let frames = frames
+            .into_iter()
+            .zip(ends)
+            .map(|((captured_at, image), end)| (image, end.saturating_duration_since(captured_at)))
+            .collect();
+        thread::spawn(move || match encode_gif(&path, frames) {
+            Ok(()) => info!("saved a recording to {}", path.display()),
+            Err(err) => warn!("couldn't save a recording to {}: {err:#}", path.display()),
+        });
+    }
+}
+
+fn encode_gif(path: &Path, frames: Vec<(RgbaImage, Duration)>) -> anyhow::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).context("couldn't make its folder")?;
+    }
+    let file = File::create(path)?;
+    let mut encoder = GifEncoder::new_with_speed(BufWriter::new(file), GIF_SPEED);
+    encoder.set_repeat(Repeat::Infinite)?;
+    encoder.encode_frames(frames.into_iter().map(|(image, shown_for)| {
+        Frame::from_parts(
+            image,
+            0,
+            0,
+            Delay::from_saturating_duration(shown_for.max(MIN_FRAME_GAP)),
+        )
+    }))?;
+    Ok(())
+}
@@ -1,17 +1,125 @@
-use std::{sync::mpsc, thread, time::Duration};
+use std::{
+    rc::Rc,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use global_hotkey::{GlobalHotKeyEvent, HotKeyState};
+use sdl3::sys::events::SDL_WaitEventTimeout;
+use tracing::{info, warn};
 
 use crate::{
-    behavior::{Behavior, ContextData},
-    events::EventMediator,
-    gremlin::{DesktopGremlin, GLOBAL_FRAMERATE},
+    behavior::{Behavior, ContextData, EventHistory},
+    clock::FrameClock,
+    events::{Event, EventMediator},
+    foreground::ForegroundWatcher,
+    gamepad::Gamepads,
+    gremlin::{DesktopGremlin, LaunchArguments},
+    ipc::IpcServer,
+    monitor::SystemMonitor,
+    signals,
+    state::AppState,
 };
 
+const SYSTEM_POLL_INTERVAL: Duration = Duration::from_secs(5);
+const FOREGROUND_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Puts a `DGRuntime` together in one go, e.g.
+///
+/// ```no_run
+/// # use desktop_gremlin::{DGRuntime, behavior::GremlinDrag};
+/// let mut rt = DGRuntime::builder()
+///     .gremlin("gremlins/mambo/manifest.txt")
+///     .framerate(60)
+///     .behavior(GremlinDrag::new())
+///     .build();
+/// ```
+///
+/// Anything left out falls back to what `go()` restores from the saved state, then the defaults.
+#[derive(Default)]
+pub struct DGRuntimeBuilder {
+    behaviors: Vec<Box<dyn Behavior>>,
+    launch_arguments: LaunchArguments,
+    framerate: Option<u32>,
+}
+
+impl DGRuntimeBuilder {
+    /// replaces the launch arguments set so far, including `gremlin()`, `scale()`, `seed()`
+    /// and `companion()`, so call it first
+    pub fn launch_args(mut self, launch_arguments: LaunchArguments) -> Self {
+        self.launch_arguments = launch_arguments;
+        self
+    }
+
+    /// manifest or `.gremlin` pack to show instead of the saved one
+    pub fn gremlin(mut self, gremlin_path: impl Into<String>) -> Self {
+        self.launch_arguments.gremlin_path = Some(gremlin_path.into());
+        self
+    }
+
+    pub fn scale(mut self, scale: f32) -> Self {
+        self.launch_arguments.scale = Some(scale);
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.launch_arguments.seed = Some(seed);
+        self
+    }
+
+    /// see `LaunchArguments::is_companion`
+    pub fn companion(mut self, is_companion: bool) -> Self {
+        self.launch_arguments.is_companion = is_companion;
+        self
+    }
+
+    /// see `DGRuntime::set_framerate`
+    pub fn framerate(mut self, framerate: u32) -> Self {
+        self.framerate = Some(framerate);
+        self
+    }
+
+    /// runs after the behaviors added before it
+    pub fn behavior(mut self, behavior: Box<dyn Behavior>) -> Self {
+        self.behaviors.push(behavior);
+        self
+    }
+
+    pub fn behaviors(mut self, behaviors: Vec<Box<dyn Behavior>>) -> Self {
+        self.behaviors.extend(behaviors);
+        self
+    }
+
+    pub fn build(self) -> DGRuntime {
+        DGRuntime {
+            behaviors: self.behaviors,
+            is_paused: Default::default(),
+            launch_arguments: Some(self.launch_arguments),
+            framerate: self.framerate,
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct DGRuntime {
     behaviors: Vec<Box<dyn Behavior>>,
+    is_paused: Arc<AtomicBool>,
+    launch_arguments: Option<LaunchArguments>,
+    // overrides the saved framerate, see `set_framerate`
+    framerate: Option<u32>,
 }
 
 impl DGRuntime {
+    /// Starts from the default window with no behaviors, see `DGRuntimeBuilder`.
+    pub fn builder() -> DGRuntimeBuilder {
+        DGRuntimeBuilder::default()
+    }
+
     pub fn _register_behavior(&mut self, behavior: Box<dyn Behavior>) {
         self.behaviors.push(behavior);
     }
@@ -20,40 +128,342 @@ impl DGRuntime {
         self.behaviors.append(&mut behavior);
     }
 
-    pub fn go(&mut self) {
-        let (heartbeat_tx, heartbeat_rx) = mpsc::sync_channel::<()>(1);
+    /// used for the window `go()` opens, defaults otherwise
+    pub fn set_launch_arguments(&mut self, launch_arguments: LaunchArguments) {
+        self.launch_arguments = Some(launch_arguments);
+    }
 
-        let heartbeat = thread::spawn(move || {
-            while let Ok(_) = heartbeat_tx.send(()) {
-                thread::sleep(Duration::from_secs_f64(1.0 / (GLOBAL_FRAMERATE as f64)));
-            }
-            println!("Heartbeat stopped, someone get the zapper!");
-        });
+    /// Frames per second to run at instead of the saved or default one, clamped to
+    /// `MIN_FRAMERATE..=MAX_FRAMERATE`. Changes while running go through
+    /// `DesktopGremlin::set_target_framerate`.
+    pub fn set_framerate(&mut self, framerate: u32) {
+        self.framerate = Some(framerate);
+    }
+
+    /// Freezes behavior updates and the animation, the gremlin stays on screen.
+    /// Same as sending `GremlinTask::Pause`.
+    #[allow(unused)]
+    pub fn pause(&self) {
+        self.is_paused.store(true, Ordering::Relaxed);
+    }
+
+    #[allow(unused)]
+    pub fn resume(&self) {
+        self.is_paused.store(false, Ordering::Relaxed);
+    }
+
+    #[allow(unused)]
+    pub fn is_paused(&self) -> bool {
+        self.is_paused.load(Ordering::Relaxed)
+    }
 
-        if let Ok(mut application) = DesktopGremlin::new(
-            None) {
-            let mut event_pump = application.sdl.event_pump().unwrap();
-            let mut event_mediator = EventMediator::default();
+    /// Hooks the application up to the runtime and sets up every behavior, what `go()` does
+    /// before the first frame. Together with `step()` this drives any `DesktopGremlin`,
+    /// including a `DesktopGremlin::headless()` one.
+    pub fn setup(&mut self, application: &mut DesktopGremlin) {
+        application.is_paused = Arc::clone(&self.is_paused);
+        for behavior in self.behaviors.iter_mut() {
+            behavior.setup(application);
+        }
+    }
 
-            for behavior in self.behaviors.iter_mut() {
-                behavior.setup(&mut application);
+    /// Runs a single frame with the given events, returns `false` once the gremlin wants to exit.
+    pub fn step(&mut self, application: &mut DesktopGremlin, context: &ContextData) -> bool {
+        application.run_scheduled();
+        let is_paused = application.is_paused();
+        for behavior in self.behaviors.iter_mut() {
+            if application.disabled_behaviors.contains(behavior.name())
+                || (is_paused && !behavior.runs_while_paused())
+            {
+                continue;
             }
+            let started = Instant::now();
+            behavior.update(application, context);
+            application
+                .profile
+                .record(behavior.name(), started.elapsed());
+        }
 
-            while let Ok(_) = heartbeat_rx.recv() {
-                let events = event_mediator.pump_events(&mut event_pump);
-                let context = ContextData { events: events };
-                for behavior in self.behaviors.iter_mut() {
-                    behavior.update(&mut application, &context);
-                }
+        !application
+            .should_exit
+            .lock()
+            .is_ok_and(|should_exit| *should_exit)
+    }
+
+    /// Opens the window and runs until the gremlin exits. Fails when there's no window
+    /// to run in, e.g. SDL couldn't start or there's no display.
+    pub fn go(&mut self) -> anyhow::Result<()> {
+        let mut launch_arguments = self.launch_arguments.take();
+        let is_companion = launch_arguments
+            .as_ref()
+            .is_some_and(|launch_arguments| launch_arguments.is_companion);
+        // applied over the restored state below
+        let (gremlin_path, scale) = launch_arguments
+            .as_mut()
+            .map(|launch_arguments| (launch_arguments.gremlin_path.take(), launch_arguments.scale))
+            .unwrap_or_default();
+        let mut application = DesktopGremlin::new(launch_arguments)?;
+        let mut event_pump = application
+            .sdl
+            .as_ref()
+            .context("no SDL context to pump events from")?
+            .event_pump()?;
+
+        // pass it back with --seed to replay this run
+        info!("rng seed: {}", application.seed);
+        let mut event_mediator = EventMediator::default();
+        if let Some(canvas) = application.window.canvas() {
+            event_mediator.set_window(canvas.window().id());
+        }
+        let (_system_monitor, system_events) = SystemMonitor::spawn(SYSTEM_POLL_INTERVAL);
+        event_mediator.attach_source(system_events);
+        let (_foreground_watcher, foreground_apps) =
+            ForegroundWatcher::spawn(FOREGROUND_POLL_INTERVAL);
+        match signals::quit_events() {
+            Ok(quit_events) => event_mediator.attach_source(quit_events),
+            Err(err) => warn!("couldn't listen for quit signals: {err}"),
+        }
+        // the socket belongs to the gremlin holding the instance lock
+        let ipc_server = (!is_companion)
+            .then(|| {
+                IpcServer::start()
+                    .inspect_err(|err| warn!("ipc server unavailable: {err}"))
+                    .ok()
+            })
+            .flatten();
 
-                if let Ok(should_exit_lock) = application.should_exit.lock()
-                    && *should_exit_lock == true
-                {
-                    break;
+        // like the socket, two gremlins walking off together on one stick isn't much fun
+        let mut gamepads = application
+            .sdl
+            .as_ref()
+            .filter(|_| !is_companion)
+            .and_then(|sdl| {
+                Gamepads::start(sdl)
+                    .inspect_err(|err| warn!("gamepads unavailable: {err}"))
+                    .ok()
+            });
+
+        if !is_companion {
+            AppState::load().restore(&mut application);
+        }
+        if gremlin_path.is_some() {
+            application.gremlin_path = gremlin_path;
+        }
+        if let Some(scale) = scale {
+            application.set_scale(scale);
+        }
+        if let Some(framerate) = self.framerate {
+            application.set_target_framerate(framerate);
+        }
+        self.setup(&mut application);
+
+        let mut clock = FrameClock::default();
+        let mut history = Rc::new(EventHistory::default());
+        loop {
+            let started = Instant::now();
+            application.frame_ticks = clock.tick(started, application.frame_interval());
+            for foreground_app in foreground_apps.try_iter() {
+                application.foreground_app = foreground_app;
+                event_mediator.inject(Event::Custom("foreground_changed".to_string()), None);
+            }
+            // one receiver for every registered hotkey, behaviors pick theirs out by id
+            for hotkey in GlobalHotKeyEvent::receiver().try_iter() {
+                if hotkey.state() == HotKeyState::Pressed {
+                    event_mediator.inject(Event::HotKey(hotkey.id()), None);
                 }
             }
+            for (event, data) in application.emitted_events.drain(..) {
+                event_mediator.inject(event, data);
+            }
+            let events = event_mediator.pump_events(&mut event_pump);
+            application.profile.record("event pump", started.elapsed());
+            if let Some(gamepads) = &mut gamepads {
+                gamepads.handle(&events);
+            }
+            // nothing else holds on to it by now, so this doesn't copy the history
+            Rc::make_mut(&mut history).push(started, &events);
+            let context = ContextData::new(events, Rc::clone(&history));
+            if let Some(ipc_server) = &ipc_server {
+                ipc_server.handle_pending(&mut application);
+            }
+            if !self.step(&mut application, &context) {
+                break;
+            }
+            application
+                .profile
+                .record_frame(started.elapsed(), clock.lateness());
+            clock.plan(Self::wait_for_next_frame(&mut application, started));
+        }
+
+        if !is_companion {
+            let state = AppState::capture(
+                &application,
+                self.behaviors.iter().map(|behavior| behavior.name()),
+            );
+            if let Err(err) = state.save() {
+                warn!("couldn't save state: {err}");
+            }
+        }
+        Ok(())
+    }
+
+    /// Sleeps until the next frame, one frame interval after `started` (see
+    /// `DesktopGremlin::set_framerate`) unless every behavior is fine waiting longer, see
+    /// `DesktopGremlin::idle_for`. The longer waits block on SDL's event queue, so input still
+    /// wakes the loop right away while an idle gremlin costs next to no cpu.
+    /// Returns when the next frame was meant to come.
+    fn wait_for_next_frame(application: &mut DesktopGremlin, started: Instant) -> Instant {
+        let next_frame = started + application.frame_interval();
+        let mut wake_at = application
+            .wake_at
+            .take()
+            .map_or(next_frame, |wake_at| wake_at.max(next_frame));
+        if let Some(due) = application.scheduler.next_due() {
+            wake_at = wake_at.min(due.max(next_frame));
+        }
+        // everyone sees these next frame, which shouldn't keep them waiting
+        if !application.emitted_events.is_empty() {
+            wake_at = next_frame;
+        }
+
+        let now = Instant::now();
+        if next_frame > now {
+            thread::sleep(next_frame - now);
+        }
+        let now = Instant::now();
+        if wake_at > now {
+            let timeout = (wake_at - now).as_millis().min(i32::MAX as u128) as i32;
+            // SAFETY: SDL is up while the loop runs, and without an event to fill in the one
+            // that woke it stays queued for the pump
+            unsafe { SDL_WaitEventTimeout(std::ptr::null_mut(), timeout) };
+        }
+        wake_at
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+    use crate::{
+        backend::BackendCall,
+        events::{EventData, MouseButton, kinds::Click},
+        gremlin::GremlinTask,
+    };
+
+    /// moves the window 10 pixels right every frame and keeps a log of what it saw
+    struct Walker {
+        log: Rc<RefCell<Vec<String>>>,
+        runs_while_paused: bool,
+    }
+
+    impl Behavior for Walker {
+        fn setup(&mut self, application: &mut DesktopGremlin) {
+            application.window.set_position(0, 0);
+            self.log.borrow_mut().push("setup".to_string());
+        }
+
+        fn update(&mut self, application: &mut DesktopGremlin, context: &ContextData) {
+            let (x, y) = application.window.position();
+            application.window.set_position(x + 10, y);
+            context.on::<Click>(|_| self.log.borrow_mut().push("click".to_string()));
+            self.log.borrow_mut().push("update".to_string());
+        }
+
+        fn runs_while_paused(&self) -> bool {
+            self.runs_while_paused
         }
-        drop(heartbeat_rx);
-        let _ = heartbeat.join();
+    }
+
+    fn new_walker(runs_while_paused: bool) -> (Box<Walker>, Rc<RefCell<Vec<String>>>) {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let walker = Walker {
+            log: Rc::clone(&log),
+            runs_while_paused,
+        };
+        (Box::new(walker), log)
+    }
+
+    fn frame(sequence: Vec<(Event, Option<EventData>)>) -> ContextData {
+        ContextData::new(sequence, Default::default())
+    }
+
+    #[test]
+    fn steps_run_every_behavior() {
+        let (walker, log) = new_walker(false);
+        let mut rt = DGRuntime::builder().behavior(walker).build();
+        let mut application = DesktopGremlin::headless();
+        rt.setup(&mut application);
+        assert!(rt.step(&mut application, &frame(Vec::new())));
+        let click = (
+            Event::Click {
+                mouse_btn: MouseButton::Left,
+            },
+            Some(EventData::FCoordinate { x: 1.0, y: 2.0 }),
+        );
+        assert!(rt.step(&mut application, &frame(vec![click])));
+
+        assert_eq!(*log.borrow(), ["setup", "update", "click", "update"]);
+        assert_eq!(application.window.position(), (20, 0));
+        assert_eq!(
+            application.headless_calls().last(),
+            Some(&BackendCall::Move { x: 20, y: 0 })
+        );
+        assert!(
+            application
+                .profile
+                .timings()
+                .iter()
+                .any(|timing| timing.name == "Walker")
+        );
+    }
+
+    #[test]
+    fn pausing_skips_behaviors_that_dont_run_while_paused() {
+        let (walker, walker_log) = new_walker(false);
+        let (keeper, keeper_log) = new_walker(true);
+        let mut rt = DGRuntime::builder()
+            .behavior(walker)
+            .behavior(keeper)
+            .build();
+        let mut application = DesktopGremlin::headless();
+        rt.setup(&mut application);
+        rt.pause();
+        assert!(application.is_paused());
+        rt.step(&mut application, &frame(Vec::new()));
+        assert_eq!(*walker_log.borrow(), ["setup"]);
+        assert_eq!(*keeper_log.borrow(), ["setup", "update"]);
+
+        rt.resume();
+        rt.step(&mut application, &frame(Vec::new()));
+        assert_eq!(*walker_log.borrow(), ["setup", "update"]);
+    }
+
+    #[test]
+    fn disabled_behaviors_are_skipped() {
+        let (walker, log) = new_walker(false);
+        let mut rt = DGRuntime::builder().behavior(walker).build();
+        let mut application = DesktopGremlin::headless();
+        rt.setup(&mut application);
+        application.disabled_behaviors.insert("Walker".to_string());
+        rt.step(&mut application, &frame(Vec::new()));
+        assert_eq!(*log.borrow(), ["setup"]);
+    }
+
+    #[test]
+    fn steps_run_due_schedules_and_stop_on_exit() {
+        let mut rt = DGRuntime::default();
+        let mut application = DesktopGremlin::headless();
+        rt.setup(&mut application);
+        application.schedule_at(Instant::now(), GremlinTask::Pause);
+        assert!(rt.step(&mut application, &frame(Vec::new())));
+        assert_eq!(
+            application.task_channel.1.try_recv().ok(),
+            Some(GremlinTask::Pause)
+        );
+
+        *application.should_exit.lock().unwrap() = true;
+        assert!(!rt.step(&mut application, &frame(Vec::new())));
     }
 }
@@ -0,0 +1,306 @@
+use std::{
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Days, Local, NaiveTime, TimeDelta};
+
+use crate::{events::Event, gremlin::GremlinTask};
+
+// longer than anyone keeps a gremlin running, and short enough to add to an `Instant`
+const MAX_EVERY: Duration = Duration::from_secs(365 * 24 * 60 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ScheduleId(u64);
+
+/// What a schedule does once it's due.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScheduledAction {
+    /// sent down the task channel
+    Task(GremlinTask),
+    /// sent down the task channel one after the other
+    Tasks(Vec<GremlinTask>),
+    /// raised like `DesktopGremlin::emit`, for behaviors that need to run code on a timer
+    Emit(Event),
+}
+
+impl From<GremlinTask> for ScheduledAction {
+    fn from(task: GremlinTask) -> Self {
+        Self::Task(task)
+    }
+}
+
+impl From<Vec<GremlinTask>> for ScheduledAction {
+    fn from(tasks: Vec<GremlinTask>) -> Self {
+        Self::Tasks(tasks)
+    }
+}
+
+impl From<Event> for ScheduledAction {
+    fn from(event: Event) -> Self {
+        Self::Emit(event)
+    }
+}
+
+/// When a manifest's `[schedule]` line fires, written as `12:00` or `every 30m`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScheduleRule {
+    /// every day at this local time
+    Daily(NaiveTime),
+    Every(Duration),
+}
+
+impl FromStr for ScheduleRule {
+    type Err = ();
+
+    fn from_str(rule: &str) -> Result<Self, Self::Err> {
+        let rule = rule.trim();
+        let Some(interval) = rule.strip_prefix("every") else {
+            return NaiveTime::parse_from_str(rule, "%H:%M")
+                .map(Self::Daily)
+                .map_err(|_| ());
+        };
+        // `30s`, `30m` or `2h`, `every30m` isn't a thing
+        if !interval.starts_with(char::is_whitespace) {
+            return Err(());
+        }
+        let interval = interval.trim();
+        let unit = match interval.chars().last() {
+            Some('s') => 1,
+            Some('m') => 60,
+            Some('h') => 60 * 60,
+            _ => return Err(()),
+        };
+        let count = interval[..interval.len() - 1]
+            .trim()
+            .parse::<u64>()
+            .map_err(|_| ())?;
+        count
+            .checked_mul(unit)
+            .map(Duration::from_secs)
+            .filter(|every| !every.is_zero() && *every <= MAX_EVERY)
+            .map(Self::Every)
+            .ok_or(())
+    }
+}
+
+#[derive(Debug)]
+enum Repeat {
+    Every(Duration),
+    // the local time it fires at and when the clock next reads it. Monotonic time stands still
+    // while the machine's suspended, so these go by the clock rather than `due_at`
+    Daily(NaiveTime, DateTime<Local>),
+}
+
+#[derive(Debug)]
+struct Job {
+    id: ScheduleId,
+    due_at: Instant,
+    // `None` fires once
+    repeat: Option<Repeat>,
+    action: ScheduledAction,
+}
+
+/// Timers for the main loop, see `DesktopGremlin::schedule_every` and `DesktopGremlin::schedule_at`.
+#[derive(Debug, Default)]
+pub struct Scheduler {
+    jobs: Vec<Job>,
+    next_id: u64,
+}
+
+impl Scheduler {
+    /// fires `action` every `every`, the first time one `every` from now. Anything over a year
+    /// is taken as a year.
+    pub fn every(&mut self, every: Duration, action: ScheduledAction) -> ScheduleId {
+        let every = every.min(MAX_EVERY);
+        self.push(Instant::now() + every, Some(Repeat::Every(every)), action)
+    }
+
+    /// fires `action` once, on the first frame at or after `at`
+    pub fn at(&mut self, at: Instant, action: ScheduledAction) -> ScheduleId {
+        self.push(at, None, action)
+    }
+
+    /// fires `action` whenever the local clock reads `time`. The next one's looked up on the
+    /// clock every time it fires, so it keeps to it across suspends and daylight saving changes.
+    pub fn daily(&mut self, time: NaiveTime, action: ScheduledAction) -> ScheduleId {
+        let next = next_daily(time);
+        self.push(until(next), Some(Repeat::Daily(time, next)), action)
+    }
+
+    /// fires `action` according to `rule`
+    pub fn by_rule(&mut self, rule: ScheduleRule, action: ScheduledAction) -> ScheduleId {
+        match rule {
+            ScheduleRule::Daily(time) => self.daily(time, action),
+            ScheduleRule::Every(every) => self.every(every, action),
+        }
+    }
+
+    pub fn cancel(&mut self, id: ScheduleId) {
+        self.jobs.retain(|job| job.id != id);
+    }
+
+    /// when the next job comes due, the main loop doesn't sleep past it
+    pub fn next_due(&self) -> Option<Instant> {
+        self.jobs.iter().map(|job| job.due_at).min()
+    }
+
+    /// takes the actions due by `now`, in the order they came due. Repeating jobs are pushed
+    /// back from `now`, so a stalled loop doesn't fire a burst of catch-up actions.
+    pub fn due(&mut self, now: Instant) -> Vec<ScheduledAction> {
+        let mut due: Vec<(Instant, ScheduledAction)> = Vec::new();
+        self.jobs.retain_mut(|job| {
+            let is_due = match job.repeat {
+                Some(Repeat::Daily(_, next)) => Local::now() >= next,
+                _ => job.due_at <= now,
+            };
+            if !is_due {
+                return true;
+            }
+            due.push((job.due_at, job.action.clone()));
+            match &mut job.repeat {
+                Some(Repeat::Every(every)) => job.due_at = now + *every,
+                Some(Repeat::Daily(time, next)) => {
+                    *next = next_daily(*time);
+                    job.due_at = until(*next);
+                }
+                None => return false,
+            }
+            true
+        });
+        due.sort_by_key(|(due_at, _)| *due_at);
+        due.into_iter().map(|(_, action)| action).collect()
+    }
+
+    fn push(
+        &mut self,
+        due_at: Instant,
+        repeat: Option<Repeat>,
+        action: ScheduledAction,
+    ) -> ScheduleId {
+        let id = ScheduleId(self.next_id);
+        self.next_id += 1;
+        self.jobs.push(Job {
+            id,
+            due_at,
+            repeat,
+            action,
+        });
+        id
+    }
+}
+
+/// the next time the local clock reads `time`, from today on
+fn next_daily(time: NaiveTime) -> DateTime<Local> {
+    let now = Local::now();
+    // a daylight saving change can skip `time` on a day, so there's a day after to fall back on
+    (0..3)
+        .filter_map(|days| now.date_naive().checked_add_days(Days::new(days)))
+        .filter_map(|day| day.and_time(time).and_local_timezone(Local).earliest())
+        .find(|at| *at > now)
+        .unwrap_or(now + TimeDelta::days(1))
+}
+
+/// `at` on the monotonic clock, as it stands now
+fn until(at: DateTime<Local>) -> Instant {
+    Instant::now() + (at - Local::now()).to_std().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_rules() {
+        assert_eq!(
+            "12:30".parse(),
+            Ok(ScheduleRule::Daily(
+                NaiveTime::from_hms_opt(12, 30, 0).unwrap()
+            ))
+        );
+        assert_eq!(
+            " every 30m ".parse(),
+            Ok(ScheduleRule::Every(Duration::from_secs(30 * 60)))
+        );
+        assert_eq!(
+            "every 2 h".parse(),
+            Ok(ScheduleRule::Every(Duration::from_secs(2 * 60 * 60)))
+        );
+        assert_eq!(
+            "every 45s".parse(),
+            Ok(ScheduleRule::Every(Duration::from_secs(45)))
+        );
+        for rule in [
+            "25:00",
+            "noon",
+            "every30m",
+            "everyday",
+            "every 0m",
+            "every 30",
+            "every m",
+            "every -5m",
+            "every 99999999999999999h",
+            "every 400000h",
+        ] {
+            assert_eq!(rule.parse::<ScheduleRule>(), Err(()), "{rule}");
+        }
+    }
+
+    #[test]
+    fn takes_due_actions_in_order() {
+        let mut scheduler = Scheduler::default();
+        let now = Instant::now();
+        scheduler.at(now + Duration::from_secs(2), Event::Quit.into());
+        scheduler.at(now + Duration::from_secs(1), GremlinTask::Pause.into());
+        scheduler.at(now + Duration::from_secs(10), GremlinTask::Resume.into());
+        assert_eq!(scheduler.next_due(), Some(now + Duration::from_secs(1)));
+
+        assert!(scheduler.due(now).is_empty());
+        assert_eq!(
+            scheduler.due(now + Duration::from_secs(5)),
+            vec![GremlinTask::Pause.into(), Event::Quit.into()]
+        );
+        // fired once, gone
+        assert!(scheduler.due(now + Duration::from_secs(5)).is_empty());
+        assert_eq!(scheduler.next_due(), Some(now + Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn repeats_from_when_it_fired() {
+        let mut scheduler = Scheduler::default();
+        let every = Duration::from_secs(60);
+        let id = scheduler.every(every, GremlinTask::Pause.into());
+        let first = scheduler.next_due().unwrap();
+
+        // a stalled loop gets one action, not one for every minute it missed
+        let late = first + every * 5;
+        assert_eq!(scheduler.due(late).len(), 1);
+        assert_eq!(scheduler.next_due(), Some(late + every));
+
+        scheduler.cancel(id);
+        assert_eq!(scheduler.next_due(), None);
+    }
+
+    #[test]
+    fn daily_goes_by_the_clock() {
+        let time = NaiveTime::from_hms_opt(3, 30, 0).unwrap();
+        let next = next_daily(time);
+        assert!(next > Local::now());
+        assert!(next <= Local::now() + TimeDelta::days(2));
+
+        let mut scheduler = Scheduler::default();
+        scheduler.daily(time, GremlinTask::Pause.into());
+        // not due until the clock says so, however far along monotonic time is
+        assert!(scheduler.due(Instant::now() + MAX_EVERY).is_empty());
+        assert!(scheduler.next_due().unwrap() <= until(next) + Duration::from_secs(1));
+    }
+
+    #[test]
+    fn caps_intervals() {
+        let mut scheduler = Scheduler::default();
+        let before = Instant::now();
+        scheduler.every(Duration::MAX, GremlinTask::Pause.into());
+        assert!(scheduler.next_due().unwrap() <= Instant::now() + MAX_EVERY);
+        assert!(scheduler.next_due().unwrap() >= before + MAX_EVERY);
+    }
+}
@@ -0,0 +1,27 @@
+use std::{
+    process,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc,
+    },
+};
+
+use tracing::warn;
+
+use crate::events::{Event, EventSource};
+
+/// Turns SIGINT/SIGTERM/SIGHUP (ctrl-c, ctrl-break and closing the console on windows) into
+/// `Event::Quit`, so the gremlin still plays its goodbye and shuts down like when the window is closed.
+/// A second signal exits right away.
+pub fn quit_events() -> Result<EventSource, ctrlc::Error> {
+    let (event_tx, event_rx) = mpsc::channel();
+    let is_quitting = AtomicBool::new(false);
+    ctrlc::set_handler(move || {
+        if is_quitting.swap(true, Ordering::Relaxed) {
+            warn!("asked to quit again, skipping the goodbye");
+            process::exit(130);
+        }
+        let _ = event_tx.send((Event::Quit, None));
+    })?;
+    Ok(event_rx)
+}
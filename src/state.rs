@@ -0,0 +1,122 @@
+use std::{collections::HashMap, fs, io, path::PathBuf};
+
+use sdl3::rect::Point;
+use serde::{Deserialize, Serialize};
+
+use crate::{achievements::Achievements, gremlin::DesktopGremlin, utils::data_dir};
+
+/// Where the gremlin was and what it was doing when it last exited, restored on the next launch.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppState {
+    pub position: Option<(i32, i32)>,
+    pub scale: f32,
+    pub gremlin_name: Option<String>,
+    pub gremlin_path: Option<String>,
+    // behavior name -> enabled, behaviors missing from here are enabled
+    pub behaviors: HashMap<String, bool>,
+    // the accessories being worn, see `DesktopGremlin::accessories`
+    pub accessories: Vec<String>,
+    pub achievements: Achievements,
+    // `DesktopGremlin::target_framerate`, the default when `None`
+    pub framerate: Option<u32>,
+    pub snap_distance: Option<u32>,
+}
+
+impl Default for AppState {
+    fn default() -> Self {
+        Self {
+            position: None,
+            scale: 1.0,
+            gremlin_name: None,
+            gremlin_path: None,
+            behaviors: HashMap::new(),
+            accessories: Vec::new(),
+            achievements: Achievements::default(),
+            framerate: None,
+            snap_distance: None,
+        }
+    }
+}
+
+impl AppState {
+    pub fn path() -> PathBuf {
+        data_dir().join("state.json")
+    }
+
+    /// falls back to a fresh state if there's no save yet or it can't be read
+    pub fn load() -> Self {
+        fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, json)
+    }
+
+    pub fn capture<'a>(
+        application: &DesktopGremlin,
+        behavior_names: impl IntoIterator<Item = &'a str>,
+    ) -> Self {
+        Self {
+            position: Some(application.window.position()),
+            scale: application.scale,
+            gremlin_name: application
+                .current_gremlin
+                .as_ref()
+                .map(|gremlin| gremlin.name.clone()),
+            gremlin_path: application.gremlin_path.clone(),
+            behaviors: behavior_names
+                .into_iter()
+                .map(|name| {
+                    (
+                        name.to_string(),
+                        !application.disabled_behaviors.contains(name),
+                    )
+                })
+                .collect(),
+            accessories: application.accessories.clone(),
+            achievements: application.achievements.clone(),
+            framerate: Some(application.target_framerate),
+            snap_distance: application.snap_distance,
+        }
+    }
+
+    /// should run before the behaviors are set up so they start from the restored state
+    pub fn restore(&self, application: &mut DesktopGremlin) {
+        application.set_scale(self.scale);
+        if self.gremlin_path.is_some() {
+            application.gremlin_path = self.gremlin_path.clone();
+        }
+        application.disabled_behaviors = self
+            .behaviors
+            .iter()
+            .filter(|(_, is_enabled)| !**is_enabled)
+            .map(|(name, _)| name.clone())
+            .collect();
+        application.accessories = self.accessories.clone();
+        application.achievements = self.achievements.clone();
+        if let Some(framerate) = self.framerate {
+            application.set_target_framerate(framerate);
+        }
+        application.snap_distance = self.snap_distance;
+
+        // a monitor might have been unplugged since, don't put the gremlin somewhere nobody can see it
+        if let Some((x, y)) = self.position
+            && application
+                .window
+                .all_usable_bounds()
+                .iter()
+                .any(|bounds| bounds.contains_point(Point::new(x, y)))
+        {
+            application.window.set_position(x, y);
+        }
+    }
+}
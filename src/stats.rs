@@ -0,0 +1,61 @@
+use std::{fs, io, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::utils::data_dir;
+
+pub const STAT_MAX: f32 = 100.0;
+
+/// The gremlin's mood, every stat lives in `[0, STAT_MAX]`.
+/// Hunger goes *up* over time, happiness and energy go *down*.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PetStats {
+    pub hunger: f32,
+    pub happiness: f32,
+    pub energy: f32,
+}
+
+impl Default for PetStats {
+    fn default() -> Self {
+        Self {
+            hunger: 0.0,
+            happiness: STAT_MAX / 2.0,
+            energy: STAT_MAX,
+        }
+    }
+}
+
+impl PetStats {
+    pub fn add_hunger(&mut self, amount: f32) {
+        self.hunger = (self.hunger + amount).clamp(0.0, STAT_MAX);
+    }
+
+    pub fn add_happiness(&mut self, amount: f32) {
+        self.happiness = (self.happiness + amount).clamp(0.0, STAT_MAX);
+    }
+
+    pub fn add_energy(&mut self, amount: f32) {
+        self.energy = (self.energy + amount).clamp(0.0, STAT_MAX);
+    }
+
+    pub fn path() -> PathBuf {
+        data_dir().join("stats.json")
+    }
+
+    /// falls back to the default mood if there's no save yet or it can't be read
+    pub fn load() -> Self {
+        fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, json)
+    }
+}
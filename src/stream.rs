@@ -0,0 +1,120 @@
+use std::{
+    io,
+    net::{Ipv4Addr, TcpListener, TcpStream},
+    sync::mpsc::{self, RecvTimeoutError, Sender},
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use serde::Serialize;
+use tracing::warn;
+use tungstenite::{Message, WebSocket};
+
+use crate::events::MouseButton;
+
+pub const DEFAULT_STREAM_PORT: u16 = 7474;
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+// a client taking longer than this over its handshake or a write gets dropped, or it holds up
+// everyone else
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Sent to every client as a JSON text frame, e.g. `{"event": "animation", "name": "IDLE"}`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum StreamEvent {
+    Animation { name: String },
+    Position { x: i32, y: i32 },
+    Click { button: MouseButton },
+    DragStart { button: MouseButton },
+    DragEnd { button: MouseButton },
+}
+
+/// Read-only websocket feed of what the gremlin is doing, for dashboards and overlays.
+/// Clients are accepted and written to on a separate thread so a slow client never stalls a frame.
+pub struct EventStream {
+    thread_handle: Option<JoinHandle<()>>,
+    event_tx: Option<Sender<StreamEvent>>,
+}
+
+impl EventStream {
+    /// listens on localhost only, nobody outside this machine needs to watch the gremlin
+    pub fn start(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+        listener.set_nonblocking(true)?;
+        let (event_tx, event_rx) = mpsc::channel::<StreamEvent>();
+
+        let thread_handle = thread::spawn(move || {
+            let mut clients: Vec<WebSocket<TcpStream>> = Vec::new();
+            // newcomers get the latest animation and position right away instead of waiting for a change
+            let mut latest_animation: Option<String> = None;
+            let mut latest_position: Option<String> = None;
+            loop {
+                while let Ok((stream, _)) = listener.accept() {
+                    // the handshake is easier to do blocking, it's a single request
+                    let _ = stream.set_nonblocking(false);
+                    if let Err(err) = stream
+                        .set_read_timeout(Some(CLIENT_TIMEOUT))
+                        .and_then(|_| stream.set_write_timeout(Some(CLIENT_TIMEOUT)))
+                    {
+                        warn!("couldn't set a timeout on a websocket client, dropping it: {err}");
+                        continue;
+                    }
+                    match tungstenite::accept(stream) {
+                        Ok(mut client) => {
+                            let is_alive = [&latest_animation, &latest_position]
+                                .into_iter()
+                                .flatten()
+                                .all(|payload| client.send(Message::text(payload.clone())).is_ok());
+                            if is_alive {
+                                clients.push(client);
+                            }
+                        }
+                        Err(err) => warn!("websocket handshake failed: {err}"),
+                    }
+                }
+
+                let event = match event_rx.recv_timeout(POLL_INTERVAL) {
+                    Ok(event) => event,
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    Err(RecvTimeoutError::Disconnected) => break,
+                };
+                let Ok(payload) = serde_json::to_string(&event) else {
+                    continue;
+                };
+                match event {
+                    StreamEvent::Animation { .. } => latest_animation = Some(payload.clone()),
+                    StreamEvent::Position { .. } => latest_position = Some(payload.clone()),
+                    _ => {}
+                }
+                // a failed or timed out write leaves the frame half sent, that client is done
+                clients.retain_mut(|client| client.send(Message::text(payload.clone())).is_ok());
+            }
+
+            for client in clients.iter_mut() {
+                let _ = client.close(None);
+                let _ = client.flush();
+            }
+        });
+
+        Ok(Self {
+            thread_handle: Some(thread_handle),
+            event_tx: Some(event_tx),
+        })
+    }
+
+    pub fn publish(&self, event: StreamEvent) {
+        if let Some(event_tx) = &self.event_tx {
+            let _ = event_tx.send(event);
+        }
+    }
+}
+
+impl Drop for EventStream {
+    fn drop(&mut self) {
+        // dropping the sender is what tells the thread to stop
+        self.event_tx = None;
+        if let Some(handle) = self.thread_handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
@@ -29,7 +29,6 @@ impl ThreadPool {
             work_sender: Some(tx),
         }
     }
-    #[allow(unused)]
     pub fn exec<Fn>(&self, work: Fn)
     where
         Fn: FnOnce() + Send + 'static,
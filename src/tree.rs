@@ -0,0 +1,336 @@
+use std::{collections::HashMap, fmt, str::FromStr, time::Duration};
+
+// deep enough for any tree someone writes by hand, a cycle gets caught long before
+const MAX_DEPTH: usize = 32;
+
+/// Something the gremlin checks before it decides, see `Blackboard::check`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    /// `toggled`, flips every time the gremlin is clicked
+    Toggled,
+    /// `idle`, nothing but the default animation going on
+    Idle,
+    /// `idle_for(30)`, idle for at least that many seconds
+    IdleFor(Duration),
+    /// `cursor_near(200)`, the cursor is at most that many pixels from the gremlin's middle
+    CursorNear(f32),
+    /// `chance(0.1)`, true that often
+    Chance(f32),
+    /// `hours(22-6)`, the local hour is in there, the end not included, wrapping past midnight
+    Hours(u32, u32),
+}
+
+/// Something the gremlin does once it decided to, see `Blackboard::act`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    /// `follow`, chases the cursor until the tree decides on something else
+    Follow,
+    /// `wander`, walks to somewhere on its display
+    Wander,
+    /// `sleep`, plays SLEEP
+    Sleep,
+    /// `play(DANCE)`, plays the animation
+    Play(String),
+    /// `play_tagged(happy)`, plays one of the animations with that tag at random
+    PlayTagged(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+    /// `select:a,b,c`, the first child that succeeds
+    Select(Vec<Node>),
+    /// `sequence:a,b,c`, every child in order, up to the first that fails
+    Sequence(Vec<Node>),
+    /// `not:a`
+    Not(Box<Node>),
+    Condition(Condition),
+    Action(Action),
+}
+
+/// Checks the conditions and carries out the actions of a `BehaviorTree`.
+pub trait Blackboard {
+    fn check(&mut self, condition: &Condition) -> bool;
+    /// false when the gremlin can't do it, e.g. it has no such animation
+    fn act(&mut self, action: &Action) -> bool;
+}
+
+impl Node {
+    /// runs the node against `blackboard`, true when it succeeded
+    pub fn tick(&self, blackboard: &mut impl Blackboard) -> bool {
+        match self {
+            Self::Select(children) => children.iter().any(|child| child.tick(blackboard)),
+            Self::Sequence(children) => children.iter().all(|child| child.tick(blackboard)),
+            Self::Not(child) => !child.tick(blackboard),
+            Self::Condition(condition) => blackboard.check(condition),
+            Self::Action(action) => blackboard.act(action),
+        }
+    }
+}
+
+/// A leaf, `name` or `name(argument)`
+impl FromStr for Node {
+    type Err = ();
+
+    fn from_str(leaf: &str) -> Result<Self, Self::Err> {
+        let leaf = leaf.trim();
+        let (name, argument) = match leaf.strip_suffix(')').and_then(|leaf| leaf.split_once('(')) {
+            Some((name, argument)) => (name.trim(), Some(argument.trim())),
+            None => (leaf, None),
+        };
+        let number = || argument.and_then(|argument| argument.parse::<f32>().ok());
+        Ok(match (name.to_lowercase().as_str(), argument) {
+            ("toggled", None) => Self::Condition(Condition::Toggled),
+            ("idle", None) => Self::Condition(Condition::Idle),
+            ("idle_for", Some(_)) => Self::Condition(Condition::IdleFor(
+                number()
+                    .and_then(|seconds| Duration::try_from_secs_f32(seconds).ok())
+                    .ok_or(())?,
+            )),
+            ("cursor_near", Some(_)) => Self::Condition(Condition::CursorNear(
+                number().filter(|pixels| *pixels >= 0.0).ok_or(())?,
+            )),
+            ("chance", Some(_)) => Self::Condition(Condition::Chance(
+                number()
+                    .filter(|chance| (0.0..=1.0).contains(chance))
+                    .ok_or(())?,
+            )),
+            ("hours", Some(hours)) => {
+                let (from, to) = hours.split_once('-').ok_or(())?;
+                let (from, to) = (
+                    from.trim().parse::<u32>().map_err(|_| ())?,
+                    to.trim().parse::<u32>().map_err(|_| ())?,
+                );
+                if from > 23 || to > 24 {
+                    return Err(());
+                }
+                Self::Condition(Condition::Hours(from, to))
+            }
+            ("follow", None) => Self::Action(Action::Follow),
+            ("wander", None) => Self::Action(Action::Wander),
+            ("sleep", None) => Self::Action(Action::Sleep),
+            ("play", Some(animation)) if !animation.is_empty() => {
+                Self::Action(Action::Play(animation.to_uppercase()))
+            }
+            ("play_tagged", Some(tag)) if !tag.is_empty() => {
+                Self::Action(Action::PlayTagged(tag.to_lowercase()))
+            }
+            _ => return Err(()),
+        })
+    }
+}
+
+/// What's wrong with a `[tree]`, and in which node.
+#[derive(Debug)]
+pub struct TreeError {
+    pub node: String,
+    pub reason: String,
+}
+
+impl fmt::Display for TreeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.node, self.reason)
+    }
+}
+
+/// The gremlin's decisions from the manifest's `[tree]`, one node per line starting at `root`.
+/// A node is `select:`, `sequence:` or `not:` followed by its children, or a single leaf, and
+/// children are other nodes by name or leaves written out, e.g.
+///
+/// ```text
+/// [tree]
+/// root=select:chase,nap,bored
+/// chase=sequence:toggled,follow
+/// nap=sequence:hours(23-7),idle_for(60),sleep
+/// bored=sequence:idle_for(20),chance(0.3),wander
+/// ```
+///
+/// See `Condition` and `Action` for the leaves, `GremlinTree` runs it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BehaviorTree {
+    pub root: Node,
+}
+
+impl BehaviorTree {
+    /// builds the tree from the `[tree]` lines, node name -> definition
+    pub fn parse(definitions: &HashMap<String, String>) -> Result<Self, TreeError> {
+        if !definitions.contains_key("root") {
+            return Err(TreeError {
+                node: "root".to_string(),
+                reason: "there's no root to start from".to_string(),
+            });
+        }
+        Ok(Self {
+            root: build("root", definitions, &mut Vec::new())?,
+        })
+    }
+
+    pub fn tick(&self, blackboard: &mut impl Blackboard) -> bool {
+        self.root.tick(blackboard)
+    }
+}
+
+fn build<'a>(
+    name: &'a str,
+    definitions: &'a HashMap<String, String>,
+    path: &mut Vec<&'a str>,
+) -> Result<Node, TreeError> {
+    let error = |reason: String| TreeError {
+        node: name.to_string(),
+        reason,
+    };
+    if path.contains(&name) || path.len() >= MAX_DEPTH {
+        return Err(error(format!(
+            "goes round in circles through {}",
+            path.join(" > ")
+        )));
+    }
+    path.push(name);
+    let definition = definitions[name].trim();
+    let node = match definition.split_once(':') {
+        Some((kind, children)) => {
+            let children = children
+                .split(',')
+                .map(str::trim)
+                .filter(|child| !child.is_empty())
+                .map(
+                    |child| match definitions.get_key_value(&child.to_lowercase()) {
+                        Some((child, _)) => build(child, definitions, path),
+                        None => child
+                            .parse::<Node>()
+                            .map_err(|_| error(format!("{child} isn't a node or a leaf"))),
+                    },
+                )
+                .collect::<Result<Vec<_>, _>>()?;
+            match (kind.trim().to_lowercase().as_str(), children.len()) {
+                (_, 0) => return Err(error("has no children".to_string())),
+                ("select", _) => Node::Select(children),
+                ("sequence", _) => Node::Sequence(children),
+                ("not", 1) => Node::Not(Box::new(children.into_iter().next().unwrap())),
+                ("not", _) => return Err(error("not takes a single child".to_string())),
+                (kind, _) => return Err(error(format!("{kind} isn't select, sequence or not"))),
+            }
+        }
+        None => definition
+            .parse::<Node>()
+            .map_err(|_| error(format!("{definition} isn't a leaf")))?,
+    };
+    path.pop();
+    Ok(node)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree(lines: &[(&str, &str)]) -> Result<BehaviorTree, TreeError> {
+        BehaviorTree::parse(
+            &lines
+                .iter()
+                .map(|(name, definition)| (name.to_string(), definition.to_string()))
+                .collect(),
+        )
+    }
+
+    fn tree_ok(lines: &[(&str, &str)]) -> BehaviorTree {
+        tree(lines).unwrap()
+    }
+
+    #[test]
+    fn parses_nodes_by_name_and_leaves_inline() {
+        let tree = tree_ok(&[
+            ("root", "select:chase,wander"),
+            ("chase", "sequence:toggled,calm,play(dance)"),
+            ("calm", "not:idle_for(2.5)"),
+        ]);
+        assert_eq!(
+            tree.root,
+            Node::Select(vec![
+                Node::Sequence(vec![
+                    Node::Condition(Condition::Toggled),
+                    Node::Not(Box::new(Node::Condition(Condition::IdleFor(
+                        Duration::from_millis(2500)
+                    )))),
+                    Node::Action(Action::Play("DANCE".to_string())),
+                ]),
+                Node::Action(Action::Wander),
+            ])
+        );
+    }
+
+    #[test]
+    fn needs_a_root() {
+        let err = tree(&[("start", "wander")]).unwrap_err();
+        assert_eq!(err.node, "root");
+    }
+
+    #[test]
+    fn rejects_cycles() {
+        let err = tree(&[("root", "select:a,wander"), ("a", "sequence:idle,root")]).unwrap_err();
+        assert_eq!(err.node, "root");
+        assert!(tree(&[("root", "select:root")]).is_err());
+    }
+
+    #[test]
+    fn shared_subtrees_are_not_cycles() {
+        let tree = tree_ok(&[("root", "select:a,a"), ("a", "sleep")]);
+        assert_eq!(
+            tree.root,
+            Node::Select(vec![
+                Node::Action(Action::Sleep),
+                Node::Action(Action::Sleep)
+            ])
+        );
+    }
+
+    #[test]
+    fn rejects_trees_too_deep() {
+        let mut lines: Vec<(String, String)> = (0..MAX_DEPTH)
+            .map(|depth| (format!("n{depth}"), format!("select:n{}", depth + 1)))
+            .collect();
+        lines.push((format!("n{MAX_DEPTH}"), "sleep".to_string()));
+        lines.push(("root".to_string(), "select:n0".to_string()));
+        let definitions = lines.into_iter().collect();
+        assert!(BehaviorTree::parse(&definitions).is_err());
+    }
+
+    #[test]
+    fn rejects_bad_nodes() {
+        assert!(tree(&[("root", "select:")]).is_err());
+        assert!(tree(&[("root", "not:idle,sleep")]).is_err());
+        assert!(tree(&[("root", "maybe:idle")]).is_err());
+        assert!(tree(&[("root", "select:idle,dance")]).is_err());
+        // only named nodes have children, not a leaf written out
+        assert!(tree(&[("root", "sequence:toggled,not:idle")]).is_err());
+    }
+
+    #[test]
+    fn parses_leaves() {
+        assert_eq!("IDLE".parse(), Ok(Node::Condition(Condition::Idle)));
+        assert_eq!(
+            "hours(22 - 6)".parse(),
+            Ok(Node::Condition(Condition::Hours(22, 6)))
+        );
+        assert_eq!(
+            "play_tagged(Happy)".parse(),
+            Ok(Node::Action(Action::PlayTagged("happy".to_string())))
+        );
+        assert_eq!(
+            "chance(0.5)".parse(),
+            Ok(Node::Condition(Condition::Chance(0.5)))
+        );
+        for leaf in [
+            "chance(2)",
+            "cursor_near(-1)",
+            "idle_for(-1)",
+            "idle_for(1e30)",
+            "idle_for(nan)",
+            "hours(24-6)",
+            "hours(6)",
+            "play()",
+            "idle(3)",
+            "follow(3)",
+        ] {
+            assert_eq!(leaf.parse::<Node>(), Err(()), "{leaf}");
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use std::time::{Duration, Instant};
+
+use sdl3::{
+    VideoSubsystem,
+    pixels::Color,
+    rect::Rect,
+    render::{Canvas, FRect},
+    video::{Window, WindowFlags, WindowPos},
+};
+
+use crate::ui::{Render, text::Text};
+
+const PADDING: u32 = 8;
+const MAX_TEXT_WIDTH: f32 = 220.0;
+const GAP: i32 = 4;
+
+/// A small borderless window with a message, floating above (or below) whatever it follows.
+pub struct SpeechBubble {
+    canvas: Canvas<Window>,
+    text: Text,
+    expires_at: Instant,
+}
+
+impl SpeechBubble {
+    pub fn new(video: &VideoSubsystem, message: &str, duration: Duration) -> anyhow::Result<Self> {
+        let text = Text::new(message).max_width(MAX_TEXT_WIDTH);
+        let (text_width, text_height) = text
+            .rasterize()
+            .map(|(width, height, _)| (width, height))
+            .unwrap_or((1, 1));
+
+        let window = video
+            .window(
+                "gremlin says",
+                text_width + PADDING * 2,
+                text_height + PADDING * 2,
+            )
+            .set_window_flags(
+                (WindowFlags::ALWAYS_ON_TOP
+                    | WindowFlags::NOT_FOCUSABLE
+                    | WindowFlags::BORDERLESS
+                    | WindowFlags::UTILITY)
+                    .as_u32(),
+            )
+            .build()?;
+
+        let mut bubble = Self {
+            canvas: window.into_canvas(),
+            text,
+            expires_at: Instant::now() + duration,
+        };
+        bubble.draw()?;
+        Ok(bubble)
+    }
+
+    pub fn is_expired(&self) -> bool {
+        Instant::now() >= self.expires_at
+    }
+
+    /// centers the bubble above `anchor`, flipping below it when there's no room at the top
+    pub fn follow(&mut self, anchor: Rect) {
+        let (width, height) = self.canvas.window().size();
+        let x = anchor.x + (anchor.w - width as i32) / 2;
+        let mut y = anchor.y - height as i32 - GAP;
+        if y < 0 {
+            y = anchor.y + anchor.h + GAP;
+        }
+        if self.canvas.window().position() != (x, y) {
+            self.canvas
+                .window_mut()
+                .set_position(WindowPos::Positioned(x), WindowPos::Positioned(y));
+        }
+    }
+
+    fn draw(&mut self) -> anyhow::Result<()> {
+        let (width, height) = self.canvas.window().size();
+        self.canvas.set_draw_color(Color::RGB(0xFA, 0xF7, 0xF0));
+        self.canvas.clear();
+        self.canvas.set_draw_color(Color::RGB(0x40, 0x40, 0x40));
+        self.canvas
+            .draw_rect(FRect::new(0.0, 0.0, width as f32, height as f32))?;
+        self.text.render_canvas(
+            &mut self.canvas,
+            Some(FRect::new(
+                PADDING as f32,
+                PADDING as f32,
+                (width - PADDING * 2) as f32,
+                (height - PADDING * 2) as f32,
+            )),
+        )?;
+        self.canvas.present();
+        Ok(())
+    }
+}
@@ -7,6 +7,10 @@ use sdl3::{
     render::{Canvas, FRect, Texture},
     video::Window,
 };
+use tracing::{debug, trace};
+
+pub mod bubble;
+pub mod text;
 pub mod widgets;
 
 use crate::{
@@ -139,7 +143,7 @@ impl Render for Div {
                 match style {
                     RenderStyle::BackgroundColor(color) => {
                         background_color = *color;
-                        println!("{:?}", color);
+                        trace!(?color, "background color");
                     }
                     RenderStyle::Position(position) => match position {
                         Position::Relative(size_unit, size_unit1) => {
@@ -249,7 +253,7 @@ fn render_tree(
         (component.preferred_size.0, component.preferred_size.1),
     );
 
-    println!("{:?}", render_rect_size);
+    trace!(?render_rect_size);
     let render_rect = {
         Rect::new(
             /*offsets in the future maybe*/ 0,
@@ -278,7 +282,7 @@ fn render_tree_canvas(
         (component.preferred_size.0, component.preferred_size.1),
     );
 
-    println!("{:?}", render_rect_size);
+    trace!(?render_rect_size);
     let render_rect = { Rect::new(0, 0, render_rect_size.0, render_rect_size.1) };
     component
         .rendered_by
@@ -361,7 +365,7 @@ impl Notify for Button {
             ComponentEvent::OnMouseDown {
                 global_pointer_location,
             } => {
-                println!("{:?}", global_pointer_location);
+                debug!(?global_pointer_location, "button pressed");
             }
             _ => {}
         }
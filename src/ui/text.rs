@@ -0,0 +1,170 @@
+use std::{fs, sync::OnceLock};
+
+use fontdue::{
+    Font, FontSettings,
+    layout::{CoordinateSystem, Layout, LayoutSettings, TextStyle},
+};
+use sdl3::{
+    pixels::Color,
+    render::{BlendMode, Canvas, FRect, Texture},
+    video::Window,
+};
+
+use crate::{
+    gremlin::GLOBAL_PIXEL_FORMAT,
+    ui::{ComponentEvent, Composable, Notify, Render},
+    utils::into_opt_rect,
+};
+
+const FONT_CANDIDATES: &[&str] = &[
+    r"C:\Windows\Fonts\segoeui.ttf",
+    r"C:\Windows\Fonts\arial.ttf",
+    "/System/Library/Fonts/Supplemental/Arial.ttf",
+    "/Library/Fonts/Arial.ttf",
+    "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/TTF/DejaVuSans.ttf",
+    "/usr/share/fonts/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/truetype/liberation/LiberationSans-Regular.ttf",
+    "/usr/share/fonts/noto/NotoSans-Regular.ttf",
+];
+
+/// first usable font found on the system, loaded once
+pub fn system_font() -> Option<&'static Font> {
+    static FONT: OnceLock<Option<Font>> = OnceLock::new();
+    FONT.get_or_init(|| {
+        FONT_CANDIDATES.iter().find_map(|path| {
+            let bytes = fs::read(path).ok()?;
+            Font::from_bytes(bytes, FontSettings::default()).ok()
+        })
+    })
+    .as_ref()
+}
+
+#[derive(Debug, Clone)]
+pub struct Text {
+    pub content: String,
+    pub size: f32,
+    pub color: Color,
+    pub max_width: Option<f32>,
+}
+
+impl Text {
+    pub fn new(content: impl Into<String>) -> Self {
+        Self {
+            content: content.into(),
+            size: 14.0,
+            // pure black gets colorkeyed away on windows
+            color: Color::RGB(0x20, 0x20, 0x20),
+            max_width: None,
+        }
+    }
+
+    pub fn size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn max_width(mut self, max_width: f32) -> Self {
+        self.max_width = Some(max_width);
+        self
+    }
+
+    /// lays out and rasterizes the text into a tightly sized RGBA buffer,
+    /// `None` when there's no font available on the system
+    pub fn rasterize(&self) -> Option<(u32, u32, Vec<u8>)> {
+        let font = system_font()?;
+        let mut layout = Layout::new(CoordinateSystem::PositiveYDown);
+        layout.reset(&LayoutSettings {
+            max_width: self.max_width,
+            ..Default::default()
+        });
+        layout.append(&[font], &TextStyle::new(&self.content, self.size, 0));
+
+        let width = layout
+            .glyphs()
+            .iter()
+            .map(|glyph| (glyph.x + glyph.width as f32).ceil() as u32)
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        let height = (layout.height().ceil() as u32).max(1);
+
+        let bytes_per_pixel = GLOBAL_PIXEL_FORMAT.bytes_per_pixel();
+        let mut buffer = vec![0u8; (width * height) as usize * bytes_per_pixel];
+        for glyph in layout.glyphs() {
+            if glyph.width == 0 || glyph.height == 0 {
+                continue;
+            }
+            let (_, coverage) = font.rasterize_config(glyph.key);
+            for row in 0..glyph.height {
+                for column in 0..glyph.width {
+                    let (x, y) = (glyph.x as i64 + column as i64, glyph.y as i64 + row as i64);
+                    if x < 0 || y < 0 || x >= width as i64 || y >= height as i64 {
+                        continue;
+                    }
+                    let alpha = coverage[row * glyph.width + column];
+                    let index = (y as usize * width as usize + x as usize) * bytes_per_pixel;
+                    // overlapping glyphs keep the strongest coverage
+                    if alpha > buffer[index + 3] {
+                        buffer[index] = self.color.r;
+                        buffer[index + 1] = self.color.g;
+                        buffer[index + 2] = self.color.b;
+                        buffer[index + 3] = alpha;
+                    }
+                }
+            }
+        }
+        Some((width, height, buffer))
+    }
+}
+
+impl Render for Text {
+    fn render(&self, texture: &mut Texture, rect: Option<FRect>) -> anyhow::Result<()> {
+        let Some((width, _, buffer)) = self.rasterize() else {
+            return Ok(());
+        };
+        let row_length = width as usize * GLOBAL_PIXEL_FORMAT.bytes_per_pixel();
+        texture.with_lock(into_opt_rect(rect), |target, stride| {
+            for (row, line) in buffer.chunks(row_length).enumerate() {
+                let start = row * stride;
+                if start >= target.len() {
+                    break;
+                }
+                let end = (start + line.len()).min(target.len()).min(start + stride);
+                target[start..end].copy_from_slice(&line[..end - start]);
+            }
+        })?;
+        Ok(())
+    }
+
+    fn render_canvas(
+        &self,
+        canvas: &mut Canvas<Window>,
+        rect: Option<FRect>,
+    ) -> anyhow::Result<()> {
+        let Some((width, height, buffer)) = self.rasterize() else {
+            return Ok(());
+        };
+        let texture_creator = canvas.texture_creator();
+        let mut texture =
+            texture_creator.create_texture_static(GLOBAL_PIXEL_FORMAT, width, height)?;
+        texture.update(
+            None,
+            &buffer,
+            width as usize * GLOBAL_PIXEL_FORMAT.bytes_per_pixel(),
+        )?;
+        texture.set_blend_mode(BlendMode::Blend);
+
+        // keep the text at its natural size, anchored to the top left of the rect
+        let target = rect.map(|rect| FRect::new(rect.x, rect.y, width as f32, height as f32));
+        canvas.copy(&texture, None, target)?;
+        unsafe { texture.destroy() };
+        Ok(())
+    }
+}
+
+impl Notify for Text {
+    fn notify(&self, _: ComponentEvent) {}
+}
+
+impl Composable for Text {}
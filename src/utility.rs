@@ -0,0 +1,238 @@
+use std::{f32::consts::PI, str::FromStr};
+
+use crate::tree::{Action, Node};
+
+/// What a consideration looks at, every one between 0 and 1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Input {
+    /// `hunger`, `happiness`, `energy`, the stat over `STAT_MAX`
+    Hunger,
+    Happiness,
+    Energy,
+    /// `hour`, how far into the local day it is, 0.5 is noon
+    Hour,
+    /// `idle`, how long nothing but the default animation went on, full after a minute
+    Idle,
+    /// `clicked`, 1 right after the gremlin got clicked, down to 0 over a minute
+    Clicked,
+}
+
+impl FromStr for Input {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        Ok(match input.trim().to_lowercase().as_str() {
+            "hunger" => Self::Hunger,
+            "happiness" => Self::Happiness,
+            "energy" => Self::Energy,
+            "hour" => Self::Hour,
+            "idle" => Self::Idle,
+            "clicked" => Self::Clicked,
+            _ => return Err(()),
+        })
+    }
+}
+
+/// How an input turns into a score.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Curve {
+    /// `linear`, the input as it is
+    Linear,
+    /// `inverse`, 1 minus the input
+    Inverse,
+    /// `square`, barely anything until the input gets high
+    Square,
+    /// `step(0.7)`, 1 from there on, 0 below
+    Step(f32),
+    /// `bell(0.95,0.1)`, 1 at the center, down to 0 that far to either side. Wraps around
+    /// for `hour` so late evening and early morning are close
+    Bell(f32, f32),
+}
+
+impl Curve {
+    fn apply(&self, value: f32, is_cyclic: bool) -> f32 {
+        match *self {
+            Self::Linear => value,
+            Self::Inverse => 1.0 - value,
+            Self::Square => value * value,
+            Self::Step(threshold) => {
+                if value >= threshold {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            Self::Bell(center, width) => {
+                let mut distance = (value - center).abs();
+                if is_cyclic {
+                    distance = distance.min(1.0 - distance);
+                }
+                if distance >= width {
+                    0.0
+                } else {
+                    // smooth at the top and the edges
+                    0.5 + 0.5 * (distance / width * PI).cos()
+                }
+            }
+        }
+    }
+}
+
+impl FromStr for Curve {
+    type Err = ();
+
+    fn from_str(curve: &str) -> Result<Self, Self::Err> {
+        let curve = curve.trim();
+        let (name, arguments) = match curve
+            .strip_suffix(')')
+            .and_then(|curve| curve.split_once('('))
+        {
+            Some((name, arguments)) => (
+                name.trim(),
+                arguments
+                    .split(',')
+                    .map(|argument| argument.trim().parse::<f32>().map_err(|_| ()))
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+            None => (curve, Vec::new()),
+        };
+        Ok(match (name.to_lowercase().as_str(), arguments.as_slice()) {
+            ("linear", []) => Self::Linear,
+            ("inverse", []) => Self::Inverse,
+            ("square", []) => Self::Square,
+            ("step", [threshold]) => Self::Step(*threshold),
+            ("bell", [center, width]) if *width > 0.0 => Self::Bell(*center, *width),
+            _ => return Err(()),
+        })
+    }
+}
+
+/// `input:curve`, `linear` when there's no curve.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Consideration {
+    pub input: Input,
+    pub curve: Curve,
+}
+
+/// An action the gremlin might take from the manifest's `[utility]`, scored by multiplying its
+/// weight and considerations, written as the action and what it's scored by, joined by `*`, e.g.
+///
+/// ```text
+/// [utility]
+/// sleep=energy:inverse*hour:bell(0.95,0.15)
+/// wander=0.4*idle*happiness:square
+/// play(DANCE)=0.3*clicked*happiness:step(0.6)
+/// follow=0.8*clicked:square
+/// ```
+///
+/// Actions are the `[tree]`'s, see `Action`. `GremlinUtility` scores every one of them each
+/// second and goes with the best.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UtilityCandidate {
+    pub action: Action,
+    pub weight: f32,
+    pub considerations: Vec<Consideration>,
+}
+
+impl UtilityCandidate {
+    pub fn parse(action: &str, score: &str) -> Result<Self, ()> {
+        let Ok(Node::Action(action)) = action.parse::<Node>() else {
+            return Err(());
+        };
+        let mut candidate = Self {
+            action,
+            weight: 1.0,
+            considerations: Vec::new(),
+        };
+        for factor in score.split('*').map(str::trim) {
+            if let Ok(weight) = factor.parse::<f32>() {
+                if !weight.is_finite() || weight < 0.0 {
+                    return Err(());
+                }
+                candidate.weight *= weight;
+                continue;
+            }
+            let (input, curve) = factor.split_once(':').unwrap_or((factor, "linear"));
+            candidate.considerations.push(Consideration {
+                input: input.parse()?,
+                curve: curve.parse()?,
+            });
+        }
+        Ok(candidate)
+    }
+
+    /// `value` is what each input is at right now
+    pub fn score(&self, value: impl Fn(Input) -> f32) -> f32 {
+        self.considerations
+            .iter()
+            .map(|consideration| {
+                consideration.curve.apply(
+                    value(consideration.input).clamp(0.0, 1.0),
+                    consideration.input == Input::Hour,
+                )
+            })
+            .product::<f32>()
+            * self.weight
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-5
+    }
+
+    #[test]
+    fn curves_shape_the_input() {
+        assert_eq!(Curve::Linear.apply(0.3, false), 0.3);
+        assert!(close(Curve::Inverse.apply(0.3, false), 0.7));
+        assert!(close(Curve::Square.apply(0.5, false), 0.25));
+        assert_eq!(Curve::Step(0.7).apply(0.69, false), 0.0);
+        assert_eq!(Curve::Step(0.7).apply(0.7, false), 1.0);
+
+        let bell = Curve::Bell(0.5, 0.2);
+        assert!(close(bell.apply(0.5, false), 1.0));
+        assert!(close(bell.apply(0.6, false), 0.5));
+        assert_eq!(bell.apply(0.75, false), 0.0);
+        assert_eq!(bell.apply(0.1, false), 0.0);
+    }
+
+    #[test]
+    fn bells_wrap_around_for_cyclic_inputs() {
+        // just before midnight is close to just after it
+        let night = Curve::Bell(0.95, 0.1);
+        assert_eq!(night.apply(0.02, false), 0.0);
+        assert!(night.apply(0.02, true) > 0.0);
+        assert!(close(night.apply(0.95, true), 1.0));
+    }
+
+    #[test]
+    fn parses_curves() {
+        assert_eq!("Square".parse(), Ok(Curve::Square));
+        assert_eq!("step(0.6)".parse(), Ok(Curve::Step(0.6)));
+        assert_eq!("bell( 0.95 , 0.1 )".parse(), Ok(Curve::Bell(0.95, 0.1)));
+        for curve in ["bell(0.5)", "bell(0.5,0)", "step", "step(x)", "cubic"] {
+            assert_eq!(curve.parse::<Curve>(), Err(()), "{curve}");
+        }
+    }
+
+    #[test]
+    fn scores_candidates() {
+        let candidate = UtilityCandidate::parse("sleep", "0.5*energy:inverse*2").unwrap();
+        assert_eq!(candidate.action, Action::Sleep);
+        assert_eq!(candidate.weight, 1.0);
+        let score = candidate.score(|input| match input {
+            Input::Energy => 0.25,
+            _ => 1.0,
+        });
+        assert!(close(score, 0.75));
+        // inputs are kept in range before they're shaped
+        assert!(close(candidate.score(|_| 3.0), 0.0));
+
+        assert!(UtilityCandidate::parse("idle", "1").is_err());
+        assert!(UtilityCandidate::parse("sleep", "-1*energy").is_err());
+        assert!(UtilityCandidate::parse("sleep", "energy:cubic").is_err());
+    }
+}
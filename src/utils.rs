@@ -1,5 +1,6 @@
 use std::{
     collections::{HashMap, VecDeque},
+    env,
     fs::read_dir,
     io,
     path::PathBuf,
@@ -10,15 +11,17 @@ use image::{DynamicImage, EncodableLayout};
 use sdl3::{
     pixels::PixelFormat,
     rect::{Point, Rect},
-    render::{Canvas, FRect, Texture},
+    render::{Canvas, FRect, ScaleMode, Texture},
     surface::Surface,
     sys::{mouse::SDL_GetGlobalMouseState, surface::SDL_ScaleMode},
     video::Window,
 };
+use serde::Serialize;
+use tracing::debug;
 
 use crate::{
     events::MouseButton,
-    gremlin::{Animator, GLOBAL_PIXEL_FORMAT, SpriteError},
+    gremlin::{Animator, GLOBAL_PIXEL_FORMAT, Scaling, SpriteError},
     ui::widgets::SizeUnit,
 };
 
@@ -31,6 +34,20 @@ pub fn _inflate(point: Point, x: u32, y: u32) -> Rect {
     )
 }
 
+/// per-user directory for things the gremlin wants to remember between sessions
+pub fn data_dir() -> PathBuf {
+    let base = if cfg!(target_os = "windows") {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+    } else {
+        env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+    };
+    base.unwrap_or_else(env::temp_dir).join("desktop_gremlin")
+}
+
 pub fn get_png_list(
     dir: &str,
     max_depth: u16,
@@ -65,9 +82,56 @@ pub fn get_png_list(
     Ok(())
 }
 
+/// `manifest` with `key=value` in `section` (`""` for the animations at the top), replacing the
+/// key's line when it's already there so comments and the order stay as the author left them
+pub fn set_manifest_value(manifest: &str, section: &str, key: &str, value: &str) -> String {
+    let mut lines = manifest.lines().map(str::to_string).collect::<Vec<_>>();
+    let mut current = String::new();
+    // right after the section's last entry, or its header when it has none
+    let mut insert_at = section.is_empty().then_some(0);
+    for index in 0..lines.len() {
+        let line = lines[index].trim();
+        if let Some(name) = line
+            .strip_prefix('[')
+            .and_then(|line| line.strip_suffix(']'))
+        {
+            current = name.trim().to_lowercase();
+            if current == section {
+                insert_at = Some(index + 1);
+            }
+            continue;
+        }
+        if current != section || line.is_empty() || line.starts_with("//") {
+            continue;
+        }
+        insert_at = Some(index + 1);
+        if let Some((existing, _)) = line.split_once('=')
+            && existing.trim().eq_ignore_ascii_case(key)
+        {
+            lines[index] = format!("{}={value}", existing.trim());
+            return joined(lines);
+        }
+    }
+    match insert_at {
+        Some(index) => lines.insert(index, format!("{key}={value}")),
+        None => {
+            lines.push(format!("[{section}]"));
+            lines.push(format!("{key}={value}"));
+        }
+    }
+    joined(lines)
+}
+
+fn joined(lines: Vec<String>) -> String {
+    let mut manifest = lines.join("\n");
+    manifest.push('\n');
+    manifest
+}
+
 pub fn sdl_resize(
     image: &DynamicImage,
     target: (u32, u32),
+    scaling: Scaling,
     canvas: &'_ mut Canvas<Window>,
 ) -> anyhow::Result<Texture> {
     let mut binding = img_get_bytes_global(&image).unwrap();
@@ -82,8 +146,14 @@ pub fn sdl_resize(
 
     let mut res = Surface::new(target.0, target.1, GLOBAL_PIXEL_FORMAT)?;
 
-    original.blit_scaled(None, &mut res, None, SDL_ScaleMode::LINEAR)?;
-    let res = canvas.create_texture_from_surface(res)?;
+    let (blit_mode, texture_mode) = match scaling {
+        Scaling::Smooth => (SDL_ScaleMode::LINEAR, ScaleMode::Linear),
+        Scaling::Pixel => (SDL_ScaleMode::NEAREST, ScaleMode::Nearest),
+    };
+    original.blit_scaled(None, &mut res, None, blit_mode)?;
+    let mut res = canvas.create_texture_from_surface(res)?;
+    // the window can still end up a bit off the sheet's size, see `DesktopGremlin::set_scale`
+    res.set_scale_mode(texture_mode);
     Ok(res)
 }
 
@@ -133,6 +203,15 @@ pub fn _get_writer<T: Fn(&mut (u8, u8, u8, u8))>(a: T) -> impl Fn(&mut [u8], usi
         }
     }
 }
+/// whether `hour` is in `from..to`, wrapping past midnight when `from` is later than `to`
+pub fn is_hour_between(hour: u32, from: u32, to: u32) -> bool {
+    if from <= to {
+        (from..to).contains(&hour)
+    } else {
+        hour >= from || hour < to
+    }
+}
+
 /// *SAFETY*: Only use this function when the Sdl context is still in scope and available.
 pub fn get_cursor_position() -> (f32, f32) {
     unsafe {
@@ -166,6 +245,62 @@ pub fn get_move_direction(cursor_position: Point, gremlin_rect: Rect) -> (Direct
     (dir_x, dir_y)
 }
 
+// points checked along each leg of a path
+const PATH_SAMPLES: i32 = 32;
+
+/// Waypoints from `from` to `to`: straight there when the whole way is on some display,
+/// otherwise around whichever corner keeps it on screen, so the gremlin doesn't cut through
+/// the gap between two monitors.
+pub fn plan_path(from: Point, to: Point, displays: &[Rect]) -> Vec<Point> {
+    let is_on_screen = |point: Point| {
+        displays.is_empty() || displays.iter().any(|display| display.contains_point(point))
+    };
+    // worked out in i64, far apart displays would overflow `end - start` in i32. The result
+    // lies between `start` and `end` so it fits back
+    let lerp = |start: i32, end: i32, sample: i32| {
+        (start as i64 + (end as i64 - start as i64) * sample as i64 / PATH_SAMPLES as i64) as i32
+    };
+    let is_clear = |start: Point, end: Point| {
+        (0..=PATH_SAMPLES).all(|sample| {
+            is_on_screen(Point::new(
+                lerp(start.x, end.x, sample),
+                lerp(start.y, end.y, sample),
+            ))
+        })
+    };
+
+    if is_clear(from, to) {
+        return vec![to];
+    }
+    for corner in [Point::new(to.x, from.y), Point::new(from.x, to.y)] {
+        if is_clear(from, corner) && is_clear(corner, to) {
+            return vec![corner, to];
+        }
+    }
+    vec![to]
+}
+
+/// RUNLEFT, RUNUP, UPLEFT... naming used by gremlin sheets for directional movement
+pub fn directional_animation_name(dir_x: DirectionX, dir_y: DirectionY) -> String {
+    let x_anim = match dir_x {
+        DirectionX::None => "",
+        DirectionX::Left => "LEFT",
+        DirectionX::Right => "RIGHT",
+    };
+    let y_anim = match dir_y {
+        DirectionY::None => "",
+        DirectionY::Up => "UP",
+        DirectionY::Down => "DOWN",
+    };
+
+    match (dir_x, dir_y) {
+        (DirectionX::None, DirectionY::None) => "RUNIDLE".to_string(),
+        (DirectionX::None, _) => "RUN".to_string() + y_anim,
+        (_, DirectionY::None) => "RUN".to_string() + x_anim,
+        (_, _) => y_anim.to_string() + x_anim,
+    }
+}
+
 #[derive(Clone, Copy, Debug, Hash)]
 pub enum DirectionX {
     None,
@@ -179,6 +314,29 @@ pub enum DirectionY {
     Down,
 }
 
+/// Easing curves mapping a normalized progress `t` in `[0, 1]` to a factor in `[0, 1]`
+#[allow(unused)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Easing {
+    #[default]
+    Linear,
+    EaseIn,
+    EaseOut,
+    SmoothStep,
+}
+
+impl Easing {
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Easing::SmoothStep => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
 // impl Into<Rect> for FRect {
 pub fn into_rect(f_rect: FRect) -> Rect {
     Rect::new(
@@ -249,6 +407,21 @@ pub fn win_to_rect(window: &Window) -> Rect {
 #[derive(Default)]
 pub struct TextureCache {
     pub data: VecDeque<(String, TextureCacheItem)>,
+    stats: CacheStats,
+    // (animation, the sheet it was last played with) never picked for eviction, so switching
+    // back to these never has to rebuild the sheet. Oldest first, at most `MAX_PINNED`
+    pinned: VecDeque<(String, String)>,
+}
+
+/// How well the texture cache is doing, shown in the debug overlay and answered over ipc.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    // what the cached sheets take up in video memory, give or take
+    pub bytes: u64,
+    pub entries: usize,
 }
 
 pub type TextureCacheItem = (Animator, Rc<Texture>);
@@ -261,45 +434,179 @@ impl TextureCache {
         }
     }
 
-    pub fn print(&self) {
-        let mut res = String::new();
-        for (name, rc) in &self.data {
-            res += format!(
-                "| {} strong:{} weak:{}",
-                name,
-                Rc::strong_count(&rc.1),
-                Rc::weak_count(&rc.1)
-            )
-            .as_str();
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            bytes: self
+                .data
+                .iter()
+                .map(|(_, (_, texture))| texture_bytes(texture))
+                .sum(),
+            entries: self.data.len(),
+            ..self.stats
+        }
+    }
+
+    /// keeps the `sheet` that `animation` was played with around once it's cached, however
+    /// long it goes unused. An animation holds on to one pin whichever sheet (variant, alias)
+    /// it comes out as, the latest. Pinned sheets still count towards `CACHE_CAPACITY`, past
+    /// `MAX_PINNED` animations the oldest pin goes
+    pub fn pin(&mut self, animation: impl Into<String>, sheet: impl Into<String>) {
+        let (animation, sheet) = (animation.into(), sheet.into());
+        if let Some((_, pinned)) = self.pinned.iter_mut().find(|(name, _)| *name == animation) {
+            *pinned = sheet;
+            return;
+        }
+        if self.pinned.len() >= MAX_PINNED {
+            self.pinned.pop_front();
         }
-        println!("{}", (res))
+        self.pinned.push_back((animation, sheet));
     }
+
+    #[allow(unused)]
+    pub fn unpin(&mut self, animation: &str) {
+        self.pinned.retain(|(name, _)| name != animation);
+    }
+
+    fn is_pinned(&self, sheet: &str) -> bool {
+        self.pinned.iter().any(|(_, pinned)| pinned == sheet)
+    }
+
     pub fn cache(&mut self, name: String, texture: TextureCacheItem) {
-        match &self.data.len() {
-            CACHE_CAPACITY.. => {
-                if let Some(val) = self.data.pop_front() {
-                    let tex = val.1.1;
-                    if let Some(tex) = Rc::into_inner(tex) {
-                        unsafe { tex.destroy() };
-                        println!("destroyed tex {}", val.0);
-                    }
+        if self.data.len() >= CACHE_CAPACITY {
+            // least recently used first, going over capacity if everything is pinned
+            let evict_index = self.data.iter().position(|(name, _)| !self.is_pinned(name));
+            if let Some(val) = evict_index.and_then(|index| self.data.remove(index)) {
+                self.stats.evictions += 1;
+                let tex = val.1.1;
+                if let Some(tex) = Rc::into_inner(tex) {
+                    unsafe { tex.destroy() };
+                    debug!("destroyed tex {}", val.0);
                 }
             }
-            _ => {}
-        };
-        self.print();
-
+        }
         self.data.push_back((name, texture));
     }
 
-    pub fn lookup(&self, name: String) -> Option<(usize, TextureCacheItem)> {
-        self.data
+    /// destroys every texture nothing else holds on to
+    pub fn clear(&mut self) {
+        for (name, (_, texture)) in self.data.drain(..) {
+            if let Some(texture) = Rc::into_inner(texture) {
+                unsafe { texture.destroy() };
+                debug!("destroyed tex {name}");
+            }
+        }
+    }
+
+    pub fn lookup(&mut self, name: String) -> Option<(usize, TextureCacheItem)> {
+        let found = self
+            .data
             .iter()
             .enumerate()
             .rev()
             .find(|a| a.1.0 == name)
-            .map(|a| (a.0, a.1.1.clone()))
+            .map(|a| (a.0, a.1.1.clone()));
+        if found.is_some() {
+            self.stats.hits += 1;
+        } else {
+            self.stats.misses += 1;
+        }
+        found
     }
 }
 
-const CACHE_CAPACITY: usize = 10;
+/// sheets are uploaded as `GLOBAL_PIXEL_FORMAT`, 4 bytes a pixel
+fn texture_bytes(texture: &Texture) -> u64 {
+    texture.width() as u64 * texture.height() as u64 * 4
+}
+
+pub const CACHE_CAPACITY: usize = 16;
+// the default animation and every directional one, the rest of the cache is left for whatever
+// else the gremlin gets up to
+pub const MAX_PINNED: usize = 10;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hours_wrap_past_midnight() {
+        assert!(is_hour_between(9, 6, 12));
+        assert!(!is_hour_between(12, 6, 12));
+        assert!(!is_hour_between(5, 6, 12));
+        assert!(is_hour_between(23, 22, 6));
+        assert!(is_hour_between(0, 22, 6));
+        assert!(!is_hour_between(6, 22, 6));
+        assert!(!is_hour_between(12, 22, 6));
+        // an empty range, not the whole day
+        assert!(!is_hour_between(3, 5, 5));
+    }
+
+    #[test]
+    fn paths_go_straight_on_one_display() {
+        let displays = [Rect::new(0, 0, 1920, 1080)];
+        let to = Point::new(1500, 900);
+        assert_eq!(plan_path(Point::new(10, 10), to, &displays), vec![to]);
+        // nothing to stay on, nothing to go around
+        assert_eq!(plan_path(Point::new(10, 10), to, &[]), vec![to]);
+    }
+
+    #[test]
+    fn paths_go_around_the_gap_between_displays() {
+        // a tall display on the left, a short one on the right lined up at the top
+        let displays = [Rect::new(0, 0, 1000, 1000), Rect::new(1000, 0, 1000, 500)];
+        let from = Point::new(500, 900);
+        let to = Point::new(1500, 100);
+        // straight across would go through the empty corner bottom right
+        assert_eq!(
+            plan_path(from, to, &displays),
+            vec![Point::new(from.x, to.y), to]
+        );
+    }
+
+    #[test]
+    fn paths_between_far_off_points_dont_overflow() {
+        let from = Point::new(i32::MIN / 2 - 10, 0);
+        let to = Point::new(i32::MAX / 2 + 10, 0);
+        assert_eq!(plan_path(from, to, &[]), vec![to]);
+    }
+
+    #[test]
+    fn pins_go_by_animation() {
+        let mut cache = TextureCache::default();
+        cache.pin("IDLE", "IDLE");
+        // variants of it don't push it out, they take over its pin
+        for variant in ["IDLE3", "IDLE2"].repeat(MAX_PINNED) {
+            cache.pin("IDLE", variant);
+        }
+        cache.pin("RUNLEFT", "RUNLEFT");
+        assert!(cache.is_pinned("IDLE2") && cache.is_pinned("RUNLEFT"));
+        assert!(!cache.is_pinned("IDLE") && !cache.is_pinned("IDLE3"));
+
+        // every directional animation fits along with the default one
+        for x in [DirectionX::None, DirectionX::Left, DirectionX::Right] {
+            for y in [DirectionY::None, DirectionY::Up, DirectionY::Down] {
+                let name = directional_animation_name(x, y);
+                cache.pin(name.clone(), name);
+            }
+        }
+        assert!(cache.is_pinned("IDLE2"));
+        cache.pin("NAP", "NAP");
+        assert!(!cache.is_pinned("IDLE2"));
+    }
+
+    #[test]
+    fn easing_stays_in_range() {
+        for easing in [
+            Easing::Linear,
+            Easing::EaseIn,
+            Easing::EaseOut,
+            Easing::SmoothStep,
+        ] {
+            assert_eq!(easing.apply(-1.0), 0.0);
+            assert_eq!(easing.apply(0.0), 0.0);
+            assert_eq!(easing.apply(1.0), 1.0);
+            assert_eq!(easing.apply(2.0), 1.0);
+        }
+        assert_eq!(Easing::SmoothStep.apply(0.5), 0.5);
+    }
+}
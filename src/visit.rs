@@ -0,0 +1,326 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{IpAddr, Ipv4Addr, TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    process::ExitCode,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc::{self, Receiver, Sender},
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use anyhow::{Context, anyhow, bail};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::{
+    fetch::is_safe_file_name,
+    pack::{self, PACK_EXTENSION},
+    state::AppState,
+    utils::data_dir,
+};
+
+pub const DEFAULT_VISIT_PORT: u16 = 7476;
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+// a pack over a slow LAN, not a connection someone forgot about
+const TRANSFER_TIMEOUT: Duration = Duration::from_secs(60);
+// visits being received at once, anyone knocking past that gets the door shut
+const MAX_CONNECTIONS: usize = 4;
+// a name, a scale and a size, anything longer isn't a visit
+const MAX_REQUEST_LEN: u64 = 1024;
+// visitors with the same name that can be over at once
+const MAX_NAMESAKES: usize = 8;
+// a sprite pack, anyone on the LAN can knock so this is far below what `fetch` allows
+const MAX_VISITOR_SIZE: u64 = 32 * 1024 * 1024;
+
+/// First line of a visit, as JSON. The pack's bytes follow right after it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VisitRequest {
+    pub name: String,
+    pub scale: f32,
+    pub pack_size: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct VisitReply {
+    ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// A gremlin that arrived and is waiting to be let in, its pack is already on disk.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Visitor {
+    pub name: String,
+    pub scale: f32,
+    pub pack_path: PathBuf,
+}
+
+impl Visitor {
+    /// throws away the visitor's pack and what got unpacked from it, once it went home
+    pub fn clean_up(&self) {
+        if let Some(extracted) = pack::extracted_dir(&self.pack_path) {
+            let _ = fs::remove_dir_all(extracted);
+        }
+        let _ = fs::remove_file(&self.pack_path);
+    }
+}
+
+/// where visiting gremlins' packs are kept until they go home
+pub fn visitors_dir() -> PathBuf {
+    data_dir().join("visitors")
+}
+
+/// only gremlins from this machine or the local network get to visit
+fn is_neighbor(address: IpAddr) -> bool {
+    match address {
+        IpAddr::V4(address) => {
+            address.is_loopback() || address.is_private() || address.is_link_local()
+        }
+        IpAddr::V6(address) => address.is_loopback(),
+    }
+}
+
+/// Sends the gremlin at `gremlin_path` (a manifest or a `.gremlin` pack) over to `address`
+/// ("host" or "host:port"), returns once the other side has the whole pack.
+pub fn send_visit(address: &str, gremlin_path: &Path, scale: f32) -> anyhow::Result<()> {
+    let bytes = pack::pack_bytes(gremlin_path)
+        .with_context(|| format!("couldn't pack up {}", gremlin_path.display()))?;
+    // a manifest is named config.txt, the gremlin is named after its folder
+    let name = if pack::is_pack(gremlin_path) {
+        gremlin_path.file_stem()
+    } else {
+        gremlin_path.parent().and_then(Path::file_name)
+    };
+    let name = name
+        .map(|name| name.to_string_lossy().into_owned())
+        .ok_or_else(|| anyhow!("can't tell what {} is called", gremlin_path.display()))?;
+
+    // the other side would only turn it away after it's all been sent
+    if bytes.len() as u64 > MAX_VISITOR_SIZE {
+        bail!(
+            "{name} is too big to visit, packs go up to {} MB",
+            MAX_VISITOR_SIZE / 1024 / 1024
+        );
+    }
+    let address = if address.contains(':') {
+        address.to_string()
+    } else {
+        format!("{address}:{DEFAULT_VISIT_PORT}")
+    };
+    let stream =
+        TcpStream::connect(&address).with_context(|| format!("couldn't reach {address}"))?;
+    stream.set_read_timeout(Some(TRANSFER_TIMEOUT))?;
+    let mut reader = BufReader::new(stream);
+
+    let mut request = serde_json::to_string(&VisitRequest {
+        name,
+        scale,
+        pack_size: bytes.len() as u64,
+    })?;
+    request.push('\n');
+    reader.get_mut().write_all(request.as_bytes())?;
+    reader.get_mut().write_all(&bytes)?;
+
+    let mut reply = String::new();
+    reader.read_line(&mut reply)?;
+    match serde_json::from_str(&reply)? {
+        VisitReply { ok: true, .. } => Ok(()),
+        VisitReply { error, .. } => bail!("{}", error.unwrap_or_default()),
+    }
+}
+
+/// entry point for `desktop_gremlin visit <host[:port]> [gremlin]`, sends the current gremlin
+/// when none is given
+pub fn run_cli(args: &[String]) -> ExitCode {
+    let state = AppState::load();
+    let gremlin_path = args.get(1).cloned().or(state.gremlin_path);
+    let (Some(address), Some(gremlin_path)) = (args.first(), gremlin_path) else {
+        eprintln!("usage: desktop_gremlin visit <host[:port]> [gremlin]");
+        return ExitCode::FAILURE;
+    };
+    match send_visit(address, Path::new(&gremlin_path), state.scale) {
+        Ok(()) => {
+            println!("your gremlin is over at {address}");
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("your gremlin couldn't visit: {err:#}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Takes in visiting gremlins from the LAN on its own thread. Only runs when a behavior asks for it,
+/// nobody gets a gremlin pushed onto their desktop without opting in.
+pub struct VisitListener {
+    thread_handle: Option<JoinHandle<()>>,
+    should_stop: Arc<AtomicBool>,
+    has_room: Arc<AtomicBool>,
+}
+
+impl VisitListener {
+    pub fn start(port: u16) -> io::Result<(Self, Receiver<Visitor>)> {
+        let listener = TcpListener::bind((Ipv4Addr::UNSPECIFIED, port))?;
+        listener.set_nonblocking(true)?;
+        let (visitor_tx, visitor_rx) = mpsc::channel();
+        let should_stop = Arc::new(AtomicBool::new(false));
+        let thread_should_stop = Arc::clone(&should_stop);
+        let has_room = Arc::new(AtomicBool::new(true));
+        let thread_has_room = Arc::clone(&has_room);
+        let connections = Arc::new(AtomicUsize::new(0));
+
+        let thread_handle = thread::spawn(move || {
+            while !thread_should_stop.load(Ordering::Relaxed) {
+                match listener.accept() {
+                    Ok((_, peer)) if !is_neighbor(peer.ip()) => {
+                        warn!("{peer} isn't on the local network, not letting it visit");
+                    }
+                    Ok((_, peer)) if connections.load(Ordering::Relaxed) >= MAX_CONNECTIONS => {
+                        warn!("too many visits at once, turned {peer} away");
+                    }
+                    Ok((stream, _)) => {
+                        // only this thread adds connections, the check above can't go stale
+                        connections.fetch_add(1, Ordering::Relaxed);
+                        let connections = Arc::clone(&connections);
+                        let has_room = Arc::clone(&thread_has_room);
+                        let visitor_tx = visitor_tx.clone();
+                        thread::spawn(move || {
+                            handle_visit(stream, &has_room, visitor_tx);
+                            connections.fetch_sub(1, Ordering::Relaxed);
+                        });
+                    }
+                    Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                        thread::sleep(ACCEPT_POLL_INTERVAL);
+                    }
+                    Err(err) => warn!("visit connection failed: {err}"),
+                }
+            }
+        });
+
+        Ok((
+            Self {
+                thread_handle: Some(thread_handle),
+                should_stop,
+                has_room,
+            },
+            visitor_rx,
+        ))
+    }
+
+    /// whether another visitor would fit, visits are turned away before their pack gets sent
+    /// when it wouldn't
+    pub fn set_has_room(&self, has_room: bool) {
+        self.has_room.store(has_room, Ordering::Relaxed);
+    }
+}
+
+impl Drop for VisitListener {
+    fn drop(&mut self) {
+        self.should_stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.thread_handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn handle_visit(stream: TcpStream, has_room: &AtomicBool, visitor_tx: Sender<Visitor>) {
+    // accepted sockets can inherit the listener's non-blocking mode
+    let _ = stream.set_nonblocking(false);
+    let _ = stream.set_read_timeout(Some(TRANSFER_TIMEOUT));
+    let mut reader = BufReader::new(stream);
+    let reply = match receive_visitor(&mut reader, has_room) {
+        Ok(visitor) => {
+            let _ = visitor_tx.send(visitor);
+            VisitReply {
+                ok: true,
+                error: None,
+            }
+        }
+        Err(err) => {
+            warn!("turned a visitor away: {err:#}");
+            VisitReply {
+                ok: false,
+                error: Some(format!("{err:#}")),
+            }
+        }
+    };
+    if let Ok(mut reply) = serde_json::to_string(&reply) {
+        reply.push('\n');
+        let _ = reader.get_mut().write_all(reply.as_bytes());
+    }
+}
+
+fn receive_visitor(
+    reader: &mut BufReader<TcpStream>,
+    has_room: &AtomicBool,
+) -> anyhow::Result<Visitor> {
+    let mut request = String::new();
+    reader
+        .by_ref()
+        .take(MAX_REQUEST_LEN)
+        .read_line(&mut request)?;
+    if !request.ends_with('\n') {
+        bail!("that's not how a visit starts");
+    }
+    let request: VisitRequest = serde_json::from_str(&request)?;
+    if !is_safe_file_name(&request.name) {
+        bail!("{:?} isn't a name a gremlin can have", request.name);
+    }
+    if request.pack_size > MAX_VISITOR_SIZE {
+        bail!("{} is too big to visit", request.name);
+    }
+    if !has_room.load(Ordering::Relaxed) {
+        bail!("no room for {} right now", request.name);
+    }
+
+    let dir = visitors_dir();
+    fs::create_dir_all(&dir)?;
+    // kept apart from our own gremlins, which might have the same name, and from other
+    // visitors with the same name that are still over
+    let (pack_path, mut file) = (1..=MAX_NAMESAKES)
+        .find_map(|slot| {
+            let file_name = match slot {
+                1 => format!("visiting-{}.{PACK_EXTENSION}", request.name),
+                _ => format!("visiting-{}-{slot}.{PACK_EXTENSION}", request.name),
+            };
+            let pack_path = dir.join(file_name);
+            let file = OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&pack_path);
+            match file {
+                Ok(file) => Some(Ok((pack_path, file))),
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => None,
+                Err(err) => Some(Err(err)),
+            }
+        })
+        .ok_or_else(|| anyhow!("too many gremlins called {} are over already", request.name))??;
+    let visitor = Visitor {
+        name: request.name,
+        scale: request.scale,
+        pack_path,
+    };
+    // straight to disk, nothing the size of a pack is held in memory
+    let received = io::copy(&mut reader.take(request.pack_size), &mut file)
+        .map_err(anyhow::Error::from)
+        .and_then(|written| {
+            if written != request.pack_size {
+                bail!("{} got lost on the way", visitor.name);
+            }
+            Ok(())
+        });
+    drop(file);
+    // unpacking checks it's really a gremlin before anyone tries to show it
+    if let Err(err) =
+        received.and_then(|()| pack::unpack(&visitor.pack_path).map_err(anyhow::Error::from))
+    {
+        visitor.clean_up();
+        return Err(err);
+    }
+    Ok(visitor)
+}
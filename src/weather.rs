@@ -0,0 +1,175 @@
+use std::{
+    fs,
+    path::PathBuf,
+    sync::mpsc::{self, Receiver, RecvTimeoutError, Sender},
+    thread::{self, JoinHandle},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::utils::data_dir;
+
+const FORECAST_URL: &str = "https://api.open-meteo.com/v1/forecast";
+// quitting waits for the poller thread, a hanging request mustn't hold that up for long
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WeatherCondition {
+    Clear,
+    Cloudy,
+    Fog,
+    Rain,
+    Snow,
+    Storm,
+}
+
+impl WeatherCondition {
+    /// from a WMO weather interpretation code, the ones open-meteo reports
+    fn from_wmo_code(code: u32) -> Self {
+        match code {
+            0 | 1 => Self::Clear,
+            2 | 3 => Self::Cloudy,
+            45 | 48 => Self::Fog,
+            71..=77 | 85 | 86 => Self::Snow,
+            95..=99 => Self::Storm,
+            _ => Self::Rain,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WeatherReport {
+    pub condition: WeatherCondition,
+    pub temperature: f32,
+    // unix seconds
+    pub fetched_at: u64,
+}
+
+impl WeatherReport {
+    pub fn path() -> PathBuf {
+        data_dir().join("weather.json")
+    }
+
+    /// the last report fetched, if it was for this location
+    pub fn load_cached(location: (f64, f64)) -> Option<Self> {
+        let json = fs::read_to_string(Self::path()).ok()?;
+        let cached: CachedReport = serde_json::from_str(&json).ok()?;
+        (cached.location == location).then_some(cached.report)
+    }
+
+    pub fn save(&self, location: (f64, f64)) -> anyhow::Result<()> {
+        fs::create_dir_all(data_dir())?;
+        let cached = CachedReport {
+            location,
+            report: *self,
+        };
+        fs::write(Self::path(), serde_json::to_string_pretty(&cached)?)?;
+        Ok(())
+    }
+
+    pub fn age(&self) -> Duration {
+        Duration::from_secs(unix_now().saturating_sub(self.fetched_at))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedReport {
+    // (latitude, longitude)
+    location: (f64, f64),
+    report: WeatherReport,
+}
+
+#[derive(Deserialize)]
+struct ForecastResponse {
+    current: CurrentWeather,
+}
+
+#[derive(Deserialize)]
+struct CurrentWeather {
+    temperature_2m: f32,
+    weather_code: u32,
+}
+
+/// current weather at (latitude, longitude) from open-meteo, no api key needed
+pub fn fetch_weather((latitude, longitude): (f64, f64)) -> anyhow::Result<WeatherReport> {
+    let url = format!(
+        "{FORECAST_URL}?latitude={latitude}&longitude={longitude}&current=temperature_2m,weather_code"
+    );
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(FETCH_TIMEOUT))
+        .build()
+        .into();
+    let json = agent.get(&url).call()?.body_mut().read_to_string()?;
+    let response: ForecastResponse = serde_json::from_str(&json)?;
+    Ok(WeatherReport {
+        condition: WeatherCondition::from_wmo_code(response.current.weather_code),
+        temperature: response.current.temperature_2m,
+        fetched_at: unix_now(),
+    })
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.as_secs())
+}
+
+/// Fetches the weather on its own thread every `interval`, caching each report to disk.
+/// Starts off with the cached report when it's still fresh instead of fetching right away.
+pub struct WeatherPoller {
+    thread_handle: Option<JoinHandle<()>>,
+    stop_tx: Sender<()>,
+}
+
+impl WeatherPoller {
+    pub fn spawn(location: (f64, f64), interval: Duration) -> (Self, Receiver<WeatherReport>) {
+        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+        let (report_tx, report_rx) = mpsc::channel();
+
+        let thread_handle = thread::spawn(move || {
+            let mut wait = Duration::ZERO;
+            if let Some(cached) = WeatherReport::load_cached(location)
+                && cached.age() < interval
+            {
+                let _ = report_tx.send(cached);
+                // it's a little older by now, maybe past `interval`
+                wait = interval.saturating_sub(cached.age());
+            }
+
+            while let Err(RecvTimeoutError::Timeout) = stop_rx.recv_timeout(wait) {
+                wait = interval;
+                match fetch_weather(location) {
+                    Ok(report) => {
+                        if let Err(err) = report.save(location) {
+                            warn!("couldn't cache the weather: {err}");
+                        }
+                        if report_tx.send(report).is_err() {
+                            break;
+                        }
+                    }
+                    Err(err) => warn!("couldn't fetch the weather: {err}"),
+                }
+            }
+        });
+
+        (
+            Self {
+                thread_handle: Some(thread_handle),
+                stop_tx,
+            },
+            report_rx,
+        )
+    }
+}
+
+impl Drop for WeatherPoller {
+    fn drop(&mut self) {
+        let _ = self.stop_tx.send(());
+        if let Some(handle) = self.thread_handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
@@ -0,0 +1,263 @@
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    fs,
+    io::{self, BufRead, IsTerminal, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use anyhow::{anyhow, bail};
+use image::{GenericImageView, RgbaImage};
+
+use crate::{
+    fetch::{gremlins_dir, is_safe_file_name},
+    gremlin::DEFAULT_COLUMN_COUNT,
+    utils::get_png_list,
+};
+
+// how deep to look for sheets in the folder
+const MAX_DEPTH: u16 = 5;
+// what the runtime plays on its own, a gremlin without them falls back or skips them
+const EXPECTED: &[&str] = &[
+    "IDLE",
+    "INTRO",
+    "OUTRO",
+    "CLICK",
+    "GRAB",
+    "WALKLEFT",
+    "WALKRIGHT",
+];
+
+/// One sheet going into the new gremlin.
+struct Sheet {
+    name: String,
+    image: RgbaImage,
+    frame_count: u32,
+}
+
+impl Sheet {
+    /// the folder it goes into, laid out like the bundled gremlins
+    fn group(&self) -> &'static str {
+        let name = self.name.as_str();
+        if name.starts_with("WALK") {
+            "Walk"
+        } else if name.starts_with("RUN") && name != "RUNIDLE"
+            || ["UPLEFT", "UPRIGHT", "DOWNLEFT", "DOWNRIGHT"].contains(&name)
+        {
+            "Run"
+        } else if name.starts_with("EMOTE") {
+            "Emotes"
+        } else {
+            "Actions"
+        }
+    }
+
+    fn frame_size(&self, frame_count: u32) -> (u32, u32) {
+        let rows = frame_count.div_ceil(DEFAULT_COLUMN_COUNT).max(1);
+        (
+            self.image.width() / DEFAULT_COLUMN_COUNT,
+            self.image.height() / rows,
+        )
+    }
+
+    /// whether the sheet splits evenly into `frame_count` frames
+    fn fits(&self, frame_count: u32) -> bool {
+        let rows = frame_count.div_ceil(DEFAULT_COLUMN_COUNT).max(1);
+        frame_count > 0 && self.image.height().is_multiple_of(rows)
+    }
+}
+
+/// Guesses the frame count from which cells of the sheet have anything in them: frames are as
+/// high as they're wide when that splits the sheet evenly, a single row otherwise, and the
+/// count runs up to the last cell that isn't fully transparent.
+fn infer_frame_count(image: &RgbaImage) -> u32 {
+    let frame_width = image.width() / DEFAULT_COLUMN_COUNT;
+    let rows = if frame_width > 0 && image.height().is_multiple_of(frame_width) {
+        image.height() / frame_width
+    } else {
+        1
+    };
+    let frame_height = image.height() / rows.max(1);
+    let cells = rows * DEFAULT_COLUMN_COUNT;
+    (0..cells)
+        .rev()
+        .find(|cell| {
+            let (x, y) = (
+                cell % DEFAULT_COLUMN_COUNT * frame_width,
+                cell / DEFAULT_COLUMN_COUNT * frame_height,
+            );
+            image
+                .view(x, y, frame_width, frame_height)
+                .pixels()
+                .any(|(_, _, pixel)| pixel.0[3] > 0)
+        })
+        .map_or(cells, |cell| cell + 1)
+}
+
+fn load_sheets(folder: &Path) -> anyhow::Result<Vec<Sheet>> {
+    let mut png_list = HashMap::new();
+    let folder_str = folder
+        .to_str()
+        .ok_or_else(|| anyhow!("{} isn't a path this can read", folder.display()))?;
+    get_png_list(folder_str, MAX_DEPTH, &mut png_list)?;
+    let mut sheets = png_list
+        .into_iter()
+        .map(|(name, path)| {
+            let image = image::open(&path)
+                .map_err(|err| anyhow!("couldn't read {}: {err}", path.display()))?
+                // the loader only takes 8 bit rgba
+                .into_rgba8();
+            if image.width() % DEFAULT_COLUMN_COUNT != 0 {
+                bail!(
+                    "{} is {}px wide, sheets have to be {DEFAULT_COLUMN_COUNT} frames across",
+                    path.display(),
+                    image.width()
+                );
+            }
+            let frame_count = infer_frame_count(&image);
+            Ok(Sheet {
+                name,
+                image,
+                frame_count,
+            })
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    sheets.sort_by(|a, b| (a.group(), &a.name).cmp(&(b.group(), &b.name)));
+    Ok(sheets)
+}
+
+/// asks for every sheet's frame count, with the guess as the answer when there's none
+fn confirm_frame_counts(sheets: &mut [Sheet]) -> anyhow::Result<()> {
+    let mut lines = io::stdin().lock().lines();
+    for sheet in sheets {
+        loop {
+            print!(
+                "{} ({}x{}): frames [{}] ",
+                sheet.name,
+                sheet.image.width(),
+                sheet.image.height(),
+                sheet.frame_count
+            );
+            io::stdout().flush()?;
+            let Some(line) = lines.next().transpose()? else {
+                return Ok(());
+            };
+            let line = line.trim();
+            if line.is_empty() {
+                break;
+            }
+            match line.parse::<u32>() {
+                Ok(frame_count) if sheet.fits(frame_count) => {
+                    sheet.frame_count = frame_count;
+                    break;
+                }
+                Ok(frame_count) => eprintln!(
+                    "{frame_count} frames don't split {}px into even rows",
+                    sheet.image.height()
+                ),
+                Err(_) => eprintln!("that's not a number"),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn manifest(name: &str, sheets: &[Sheet]) -> String {
+    let mut manifest = format!(".name={name}\n");
+    // the window is sized after IDLE, or whatever comes first without one
+    if let Some(sheet) = sheets
+        .iter()
+        .find(|sheet| sheet.name == "IDLE")
+        .or(sheets.first())
+    {
+        let (width, height) = sheet.frame_size(sheet.frame_count);
+        let _ = writeln!(manifest, ".width={width}\n.height={height}");
+    }
+    let mut group = "";
+    for sheet in sheets {
+        if sheet.group() != group {
+            group = sheet.group();
+            let _ = writeln!(manifest, "//{group}");
+        }
+        let _ = writeln!(manifest, "{}={}", sheet.name, sheet.frame_count);
+    }
+    manifest
+}
+
+/// Builds a gremlin out of a folder of sheets into `gremlins_dir()`: every png in the folder or
+/// below it becomes the animation it's named after, converted to what the loader takes and sorted
+/// into folders like the bundled gremlins, next to a manifest with every sheet's frame count.
+pub fn create(folder: &Path, name: &str, is_interactive: bool) -> anyhow::Result<PathBuf> {
+    if !is_safe_file_name(name) {
+        bail!("can't name a gremlin {name:?}, stick to letters, numbers, spaces, - and _");
+    }
+    let dir = gremlins_dir().join(name);
+    if dir.exists() {
+        bail!("there's already a gremlin at {}", dir.display());
+    }
+    let mut sheets = load_sheets(folder)?;
+    if sheets.is_empty() {
+        bail!("there are no pngs in {}", folder.display());
+    }
+    if !sheets.iter().any(|sheet| sheet.name == "IDLE") {
+        bail!("there's no idle.png, every gremlin needs one to stand around with");
+    }
+    if is_interactive {
+        println!("how many frames each sheet has, enter keeps the guess");
+        confirm_frame_counts(&mut sheets)?;
+    }
+    for expected in EXPECTED {
+        if !sheets.iter().any(|sheet| sheet.name == *expected) {
+            eprintln!("no {expected} sheet, it'll fall back to another or skip it");
+        }
+    }
+
+    for sheet in &sheets {
+        if !sheet.fits(sheet.frame_count) {
+            bail!(
+                "{} frames don't split {}'s {}px into even rows",
+                sheet.frame_count,
+                sheet.name,
+                sheet.image.height()
+            );
+        }
+        let group = dir.join(sheet.group());
+        fs::create_dir_all(&group)?;
+        sheet
+            .image
+            .save(group.join(format!("{}.png", sheet.name.to_lowercase())))?;
+    }
+    let manifest_path = dir.join("config.txt");
+    fs::write(&manifest_path, manifest(name, &sheets))?;
+    Ok(manifest_path)
+}
+
+/// `desktop_gremlin --new-gremlin <folder> [--name <name>]`, asks for frame counts when run
+/// from a terminal and goes with the guesses otherwise
+pub fn run_cli(folder: &str, name: Option<String>) -> ExitCode {
+    let folder = Path::new(folder);
+    let Some(name) = name.or_else(|| {
+        folder
+            .canonicalize()
+            .ok()?
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+    }) else {
+        eprintln!("usage: desktop_gremlin --new-gremlin <folder of sheets> [--name <name>]");
+        return ExitCode::FAILURE;
+    };
+    match create(folder, &name, io::stdin().is_terminal()) {
+        Ok(manifest_path) => {
+            println!(
+                "made {name}, start it with --gremlin \"{}\"",
+                manifest_path.display()
+            );
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("couldn't make the gremlin: {err:#}");
+            ExitCode::FAILURE
+        }
+    }
+}